@@ -6,7 +6,10 @@ use revme::cmd::{
 
 fn evm(c: &mut Criterion) {
     for &bench_name in BenchName::ALL {
-        let cmd = MainCmd::Bench(bench::Cmd { name: bench_name });
+        let cmd = MainCmd::Bench(bench::Cmd {
+            name: bench_name,
+            profile: None,
+        });
         c.bench_function(bench_name.as_str(), |b| {
             b.iter(|| cmd.run().unwrap());
         });
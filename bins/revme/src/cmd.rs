@@ -1,7 +1,9 @@
 pub mod bench;
 pub mod bytecode;
+pub mod debug;
 pub mod eofvalidation;
 pub mod evmrunner;
+pub mod serve;
 pub mod statetest;
 
 use clap::Parser;
@@ -16,10 +18,14 @@ pub enum MainCmd {
     EofValidation(eofvalidation::Cmd),
     /// Run arbitrary EVM bytecode.
     Evm(evmrunner::Cmd),
+    /// Debug EVM bytecode interactively.
+    Debug(debug::Cmd),
     /// Print the structure of an EVM bytecode.
     Bytecode(bytecode::Cmd),
     /// Run bench from specified list.
     Bench(bench::Cmd),
+    /// Serve JSON-RPC calls against an in-memory or forked state.
+    Serve(serve::Cmd),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -28,6 +34,12 @@ pub enum Error {
     Statetest(#[from] statetest::Error),
     #[error(transparent)]
     EvmRunnerErrors(#[from] evmrunner::Errors),
+    #[error(transparent)]
+    Debug(#[from] debug::Errors),
+    #[error(transparent)]
+    Serve(#[from] serve::Errors),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
     #[error("Eof validation failed: {:?}/{total_tests}", total_tests-failed_test)]
     EofValidation {
         failed_test: usize,
@@ -43,14 +55,13 @@ impl MainCmd {
             Self::Statetest(cmd) => cmd.run().map_err(Into::into),
             Self::EofValidation(cmd) => cmd.run().map_err(Into::into),
             Self::Evm(cmd) => cmd.run().map_err(Into::into),
+            Self::Debug(cmd) => cmd.run().map_err(Into::into),
+            Self::Serve(cmd) => cmd.run().map_err(Into::into),
             Self::Bytecode(cmd) => {
                 cmd.run();
                 Ok(())
             }
-            Self::Bench(cmd) => {
-                cmd.run();
-                Ok(())
-            }
+            Self::Bench(cmd) => cmd.run().map_err(Into::into),
         }
     }
 }
@@ -4,6 +4,14 @@ pub mod snailtracer;
 pub mod transfer;
 
 use clap::{Parser, ValueEnum};
+use inspector::{
+    inspector_context::InspectorContext,
+    inspector_handler,
+    inspectors::{ProfileMetric, Profiler},
+    InspectorMainEvm,
+};
+use revm::{Database, EthContext, EvmExec};
+use std::{fs, io, path::Path, path::PathBuf};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum BenchName {
@@ -36,16 +44,55 @@ impl BenchName {
 pub struct Cmd {
     #[arg(value_enum)]
     pub name: BenchName,
+    /// Runs the benchmark once under the profiler and writes the folded stacks to the directory
+    ///
+    /// `<name>.gas.folded` and `<name>.time.folded` can be rendered with `inferno-flamegraph`
+    /// or `flamegraph.pl`, the per-opcode histogram is printed to the standard output.
+    #[arg(long, value_name = "DIR")]
+    pub profile: Option<PathBuf>,
 }
 
 impl Cmd {
     /// Runs bench command.
-    pub fn run(&self) {
+    pub fn run(&self) -> Result<(), io::Error> {
+        if let Some(dir) = &self.profile {
+            let name = self.name.as_str();
+            return match self.name {
+                BenchName::Analysis => profile(name, analysis::context(), dir),
+                BenchName::Burntpix => profile(name, burntpix::context(), dir),
+                BenchName::Snailtracer => {
+                    profile(name, snailtracer::context(snailtracer::bytecode()), dir)
+                }
+                BenchName::Transfer => profile(name, transfer::context(), dir),
+            };
+        }
         match self.name {
             BenchName::Analysis => analysis::run(),
             BenchName::Burntpix => burntpix::run(),
             BenchName::Snailtracer => snailtracer::run(),
             BenchName::Transfer => transfer::run(),
         }
+        Ok(())
+    }
+}
+
+/// Executes the transaction of the benchmark under the [`Profiler`].
+fn profile<DB: Database>(name: &str, context: EthContext<DB>, dir: &Path) -> Result<(), io::Error> {
+    let mut evm = InspectorMainEvm::new(
+        InspectorContext::new(context, Profiler::new()),
+        inspector_handler(),
+    );
+    if evm.exec().is_err() {
+        return Err(io::Error::other("transaction execution failed"));
+    }
+    let profiler = &evm.context.inspector;
+
+    fs::create_dir_all(dir)?;
+    for (metric, extension) in [(ProfileMetric::Gas, "gas"), (ProfileMetric::Time, "time")] {
+        let path = dir.join(format!("{name}.{extension}.folded"));
+        profiler.write_folded(metric, io::BufWriter::new(fs::File::create(&path)?))?;
+        println!("Written {}", path.display());
     }
+    print!("{}", profiler.histogram());
+    Ok(())
 }
@@ -3,16 +3,16 @@ use revm::{
     bytecode::Bytecode,
     handler::EthHandler,
     primitives::{address, bytes, hex, Bytes, TxKind},
-    Context, MainEvm,
+    Context, EthContext, MainEvm,
 };
 
 const BYTES: &str = include_str!("analysis.hex");
 
-pub fn run() {
+pub fn context() -> EthContext<BenchmarkDB> {
     let bytecode = Bytecode::new_raw(Bytes::from(hex::decode(BYTES).unwrap()));
 
     // BenchmarkDB is dummy state that implements Database trait.
-    let context = Context::builder()
+    Context::builder()
         .with_db(BenchmarkDB::new_bytecode(bytecode))
         .modify_tx_chained(|tx| {
             // Execution globals block hash/gas_limit/coinbase/timestamp..
@@ -20,7 +20,10 @@ pub fn run() {
             tx.kind = TxKind::Call(address!("0000000000000000000000000000000000000000"));
             //evm.env.tx.data = Bytes::from(hex::decode("30627b7c").unwrap());
             tx.data = bytes!("8035F0CE");
-        });
-    let mut evm = MainEvm::new(context, EthHandler::default());
+        })
+}
+
+pub fn run() {
+    let mut evm = MainEvm::new(context(), EthHandler::default());
     let _ = evm.transact().unwrap();
 }
@@ -15,7 +15,7 @@ use revm::{
     handler::EthHandler,
     primitives::{address, hex, keccak256, Address, Bytes, TxKind, B256, U256},
     state::{AccountInfo, Bytecode},
-    Context, MainEvm,
+    Context, EthContext, MainEvm,
 };
 
 use std::fs::File;
@@ -30,20 +30,25 @@ sol! {
     }
 }
 
-pub fn run() {
+/// Context of the `run` call with the seed and iterations from the `SEED` and `ITERATIONS` env vars.
+pub fn context() -> EthContext<CacheDB<EmptyDB>> {
     let (seed, iterations) = try_init_env_vars().expect("Failed to parse env vars");
 
     let run_call_data = IBURNTPIX::runCall { seed, iterations }.abi_encode();
 
     let db = init_db();
 
-    let context = Context::builder().with_db(db).modify_tx_chained(|tx| {
+    Context::builder().with_db(db).modify_tx_chained(|tx| {
         tx.caller = address!("1000000000000000000000000000000000000000");
         tx.kind = TxKind::Call(BURNTPIX_MAIN_ADDRESS);
         tx.data = run_call_data.clone().into();
         tx.gas_limit = u64::MAX;
-    });
-    let mut evm = MainEvm::new(context, EthHandler::default());
+    })
+}
+
+pub fn run() {
+    let (seed, iterations) = try_init_env_vars().expect("Failed to parse env vars");
+    let mut evm = MainEvm::new(context(), EthHandler::default());
 
     let started = Instant::now();
     let tx_result = evm.transact().unwrap().result;
@@ -3,11 +3,11 @@ use revm::{
     bytecode::Bytecode,
     handler::EthHandler,
     primitives::{address, bytes, hex, Bytes, TxKind},
-    Context, MainEvm,
+    Context, EthContext, MainEvm,
 };
 
-pub fn simple_example(bytecode: Bytecode) {
-    let context = Context::builder()
+pub fn context(bytecode: Bytecode) -> EthContext<BenchmarkDB> {
+    Context::builder()
         .with_db(BenchmarkDB::new_bytecode(bytecode.clone()))
         .modify_tx_chained(|tx| {
             // Execution globals block hash/gas_limit/coinbase/timestamp..
@@ -15,14 +15,21 @@ pub fn simple_example(bytecode: Bytecode) {
             tx.kind = TxKind::Call(address!("0000000000000000000000000000000000000000"));
             tx.data = bytes!("30627b7c");
             tx.gas_limit = 1_000_000_000;
-        });
-    let mut evm = MainEvm::new(context, EthHandler::default());
+        })
+}
+
+pub fn simple_example(bytecode: Bytecode) {
+    let mut evm = MainEvm::new(context(bytecode), EthHandler::default());
     let _ = evm.transact().unwrap();
 }
 
+pub fn bytecode() -> Bytecode {
+    Bytecode::new_raw(Bytes::from(hex::decode(BYTES).unwrap()))
+}
+
 pub fn run() {
     println!("Running snailtracer example!");
-    let bytecode = Bytecode::new_raw(Bytes::from(hex::decode(BYTES).unwrap()));
+    let bytecode = bytecode();
     let start = std::time::Instant::now();
     simple_example(bytecode);
     let elapsed = start.elapsed();
@@ -3,11 +3,11 @@ use revm::{
     bytecode::Bytecode,
     handler::EthHandler,
     primitives::{TxKind, U256},
-    Context, MainEvm,
+    Context, EthContext, MainEvm,
 };
 
-pub fn run() {
-    let context = Context::builder()
+pub fn context() -> EthContext<BenchmarkDB> {
+    Context::builder()
         .with_db(BenchmarkDB::new_bytecode(Bytecode::new()))
         .modify_tx_chained(|tx| {
             // Execution globals block hash/gas_limit/coinbase/timestamp..
@@ -20,8 +20,11 @@ pub fn run() {
                     .parse()
                     .unwrap(),
             );
-        });
-    let mut evm = MainEvm::new(context, EthHandler::default());
+        })
+}
+
+pub fn run() {
+    let mut evm = MainEvm::new(context(), EthHandler::default());
 
     let _ = evm.transact().unwrap();
 }
@@ -0,0 +1,245 @@
+mod recorder;
+mod session;
+
+pub use recorder::{
+    Checkpoint, MemoryDiff, Recorder, StackDiff, Step, StorageWrite, CHECKPOINT_INTERVAL,
+};
+pub use session::{Breakpoint, Session};
+
+use clap::Parser;
+use database::BenchmarkDB;
+use inspector::{inspector_context::InspectorContext, inspector_handler, InspectorMainEvm};
+use revm::{
+    bytecode::{Bytecode, BytecodeDecodeError},
+    primitives::{address, hex, Address, Bytes, TxKind},
+    Context, Database, EvmExec,
+};
+use std::io::{BufRead, Error as IoError, Write};
+use std::path::PathBuf;
+use std::{borrow::Cow, fs};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Errors {
+    #[error("The specified path does not exist")]
+    PathNotExists,
+    #[error("Invalid bytecode")]
+    InvalidBytecode,
+    #[error("Invalid input")]
+    InvalidInput,
+    #[error("EVM Error")]
+    EVMError,
+    #[error(transparent)]
+    Io(#[from] IoError),
+    #[error(transparent)]
+    BytecodeDecodeError(#[from] BytecodeDecodeError),
+}
+
+/// Interactive debugger of the EVM bytecode
+///
+/// The bytecode is executed once while recording every step, the debugger then moves over
+/// the recorded execution in both directions. Commands are read from the standard input,
+/// type `help` to list them.
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// Hex-encoded EVM bytecode to be debugged
+    #[arg(required_unless_present = "path")]
+    bytecode: Option<String>,
+    /// Path to a file containing the hex-encoded EVM bytecode to be debugged
+    ///
+    /// Overrides the positional `bytecode` argument.
+    #[arg(long)]
+    path: Option<PathBuf>,
+    /// Hex-encoded input/calldata bytes
+    #[arg(long, default_value = "")]
+    input: String,
+}
+
+impl Cmd {
+    /// Runs debug command.
+    pub fn run(&self) -> Result<(), Errors> {
+        let bytecode_str: Cow<'_, str> = if let Some(path) = &self.path {
+            // Check if path exists.
+            if !path.exists() {
+                return Err(Errors::PathNotExists);
+            }
+            fs::read_to_string(path)?.into()
+        } else if let Some(bytecode) = &self.bytecode {
+            bytecode.as_str().into()
+        } else {
+            unreachable!()
+        };
+
+        let bytecode = hex::decode(bytecode_str.trim()).map_err(|_| Errors::InvalidBytecode)?;
+        let input = hex::decode(self.input.trim())
+            .map_err(|_| Errors::InvalidInput)?
+            .into();
+        let mut session = record(Bytecode::new_raw_checked(bytecode.into())?, input)?;
+
+        let mut stdout = std::io::stdout();
+        writeln!(stdout, "{}", session.execute("where").unwrap_or_default())?;
+        for line in std::io::stdin().lock().lines() {
+            let Some(output) = session.execute(&line?) else {
+                break;
+            };
+            writeln!(stdout, "{output}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Executes the bytecode deployed at the zero address and records the execution.
+pub fn record(bytecode: Bytecode, input: Bytes) -> Result<Session, Errors> {
+    const CALLER: Address = address!("0000000000000000000000000000000000000001");
+
+    let mut db = BenchmarkDB::new_bytecode(bytecode);
+    let nonce = db.basic(CALLER).unwrap().map_or(0, |account| account.nonce);
+
+    let ctx = Context::builder().with_db(db).modify_tx_chained(|tx| {
+        tx.caller = CALLER;
+        tx.kind = TxKind::Call(Address::ZERO);
+        tx.data = input;
+        tx.nonce = nonce;
+    });
+    let mut evm = InspectorMainEvm::new(
+        InspectorContext::new(ctx, Recorder::default()),
+        inspector_handler(),
+    );
+    let output = evm.exec().map_err(|_| Errors::EVMError)?;
+    let recorder = std::mem::take(&mut evm.context.inspector);
+    Ok(Session::new(recorder, &output.state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::bytecode::opcode::*;
+
+    #[test]
+    fn breakpoints_and_step_back() {
+        // SSTORE(0, 1), SSTORE(0, 2), MSTORE(0, 3)
+        let code = vec![
+            PUSH1, 0x01, PUSH1, 0x00, SSTORE, PUSH1, 0x02, PUSH1, 0x00, SSTORE, PUSH1, 0x03, PUSH1,
+            0x00, MSTORE, STOP,
+        ];
+        let mut session = record(Bytecode::new_raw(code.into()), Bytes::new()).unwrap();
+        let mut run = |command: &str| session.execute(command).unwrap();
+
+        assert_eq!(run("break op sstore"), "breakpoint 0: op SSTORE");
+        assert!(run("continue").starts_with("#2 depth 1"));
+        assert_eq!(run("storage"), "0x0: 0x0");
+        assert!(run("stack").starts_with("   0: 0x0000"));
+
+        assert!(run("c").contains("pc 0x9 SSTORE"));
+        assert_eq!(run("storage"), "0x0: 0x1");
+        assert!(run("s 4").contains("STOP"));
+        assert_eq!(run("storage"), "0x0: 0x2");
+        assert!(run("memory").ends_with("03"));
+
+        // Stepping back replays the writes up to the step.
+        assert!(run("back").contains("MSTORE"));
+        assert_eq!(run("memory"), "empty");
+        assert!(run("back 3").contains("SSTORE"));
+        assert_eq!(run("storage"), "0x0: 0x1");
+        assert!(run("reverse").contains("pc 0x4 SSTORE"));
+        assert_eq!(run("storage"), "0x0: 0x0");
+
+        assert!(run("c").contains("pc 0x9"));
+        assert!(run("c").starts_with("end of execution"));
+        assert!(run("where").contains("STOP"));
+        assert_eq!(run("delete 0"), "deleted op SSTORE");
+        assert!(run("break op 0xzz").starts_with("error"));
+        assert!(session.execute("quit").is_none());
+    }
+
+    #[test]
+    fn reverted_call() {
+        // Calls itself with one byte of calldata, the inner call writes a slot and reverts.
+        let code = vec![
+            CALLDATASIZE,
+            PUSH1,
+            19,
+            JUMPI,
+            PUSH1,
+            0,
+            PUSH1,
+            0,
+            PUSH1,
+            1,
+            PUSH1,
+            0,
+            PUSH1,
+            0,
+            PUSH1,
+            0,
+            GAS,
+            CALL,
+            STOP,
+            JUMPDEST,
+            PUSH1,
+            5,
+            PUSH1,
+            0,
+            SSTORE,
+            PUSH1,
+            0,
+            PUSH1,
+            0,
+            REVERT,
+        ];
+        let mut session = record(Bytecode::new_raw(code.into()), Bytes::new()).unwrap();
+        let mut run = |command: &str| session.execute(command).unwrap();
+
+        run("break op REVERT");
+        assert!(run("c").contains("depth 2"));
+        assert_eq!(run("storage"), "0x0: 0x5");
+        assert!(run("out").contains("depth 1"));
+        assert!(run("where").contains("STOP"));
+        assert_eq!(run("storage"), "0x0: 0x0");
+        assert!(run("stack").ends_with(&format!("0x{:064x}", 0)));
+        assert!(run("rc").contains("REVERT"));
+        assert_eq!(run("storage"), "0x0: 0x5");
+    }
+
+    #[test]
+    fn checkpoints() {
+        // Stores the counter in memory at its value, until it reaches 1000.
+        let code = vec![
+            PUSH0, JUMPDEST, PUSH1, 0x01, ADD, DUP1, DUP1, MSTORE8, DUP1, PUSH2, 0x03, 0xe8, GT,
+            PUSH1, 0x01, JUMPI, STOP,
+        ];
+        let mut session = record(Bytecode::new_raw(code.into()), Bytes::new()).unwrap();
+        let mut run = |command: &str| session.execute(command).unwrap();
+
+        // Every iteration takes 11 steps, the first iteration starts at step 1. Counter 93 is
+        // at the checkpoint of step 1024, counter 94 is rebuilt from it.
+        for counter in [0usize, 1, 93, 94, 500, 999] {
+            let step = 1 + counter * 11;
+            assert!(run(&format!("goto {step}")).contains("JUMPDEST"));
+            assert_eq!(run("stack"), format!("   0: 0x{counter:064x}"));
+            let memory = run("memory");
+            if counter == 0 {
+                assert_eq!(memory, "empty");
+            } else {
+                let bytes = hex::decode(
+                    memory
+                        .lines()
+                        .flat_map(|line| line.split_once(": "))
+                        .map(|(_, word)| word)
+                        .collect::<String>(),
+                )
+                .unwrap();
+                assert_eq!(bytes.len(), (counter + 32) / 32 * 32);
+                for (i, byte) in bytes.iter().enumerate() {
+                    let expected = if (1..=counter).contains(&i) {
+                        i as u8
+                    } else {
+                        0
+                    };
+                    assert_eq!(*byte, expected, "counter {counter} byte {i}");
+                }
+            }
+        }
+        assert!(run("goto 100000").starts_with("end of execution"));
+        assert_eq!(run("stack"), format!("   0: 0x{:064x}", 1000));
+    }
+}
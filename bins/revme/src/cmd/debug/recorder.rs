@@ -0,0 +1,238 @@
+use inspector::{
+    journal::{JournalExt, JournalExtGetter},
+    Inspector,
+};
+use revm::{
+    context_interface::{Journal, JournalGetter},
+    interpreter::{
+        interpreter::EthInterpreter,
+        interpreter_types::{InputsTrait, Jumps, LoopControl, MemoryTrait, ReturnData},
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter,
+    },
+    primitives::{Address, Bytes, U256},
+    JournalEntry,
+};
+
+/// Number of steps between the recorded copies of the stack and the memory.
+pub const CHECKPOINT_INTERVAL: usize = 1024;
+
+/// Snapshot of the interpreter taken before executing the instruction.
+///
+/// Stack and memory are recorded as the changes from the previous step, see [Checkpoint].
+#[derive(Clone, Debug)]
+pub struct Step {
+    pub pc: usize,
+    pub opcode: u8,
+    pub depth: usize,
+    pub address: Address,
+    pub gas_remaining: u64,
+    pub stack: StackDiff,
+    pub memory: MemoryDiff,
+    /// Return data, shared with the previous step if it did not change.
+    pub return_data: Bytes,
+    /// Number of the recorded storage writes applied before this step.
+    pub writes: usize,
+}
+
+/// Change of the stack from the previous step.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StackDiff {
+    /// Number of the items of the previous stack that are kept.
+    pub keep: usize,
+    /// Items pushed on top of the kept items.
+    pub push: Vec<U256>,
+}
+
+/// Change of the memory from the previous step.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryDiff {
+    /// Size of the memory, it is expanded with zeros.
+    pub len: usize,
+    /// Offset of the changed bytes.
+    pub offset: usize,
+    /// Changed bytes.
+    pub data: Bytes,
+}
+
+impl MemoryDiff {
+    /// Returns the change from the old to the new memory.
+    fn new(old: &[u8], new: &[u8]) -> Self {
+        let len = new.len();
+        // Bytes past the old memory are compared to the zeros of the expansion.
+        let changed = |(i, byte): (usize, &u8)| *byte != old.get(i).copied().unwrap_or_default();
+        let Some(offset) = new.iter().enumerate().position(changed) else {
+            return Self {
+                len,
+                offset: len,
+                data: Bytes::new(),
+            };
+        };
+        let end = new.iter().enumerate().rposition(changed).unwrap_or(offset) + 1;
+        Self {
+            len,
+            offset,
+            data: Bytes::copy_from_slice(&new[offset..end]),
+        }
+    }
+}
+
+/// Stack and memory at a step.
+///
+/// A checkpoint is recorded every [CHECKPOINT_INTERVAL] steps, the state at the other steps is
+/// rebuilt by applying the changes of the following steps to the previous checkpoint.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub stack: Vec<U256>,
+    pub memory: Vec<u8>,
+}
+
+impl Checkpoint {
+    /// Applies the changes of the step.
+    pub fn apply(&mut self, step: &Step) {
+        self.stack.truncate(step.stack.keep);
+        self.stack.extend_from_slice(&step.stack.push);
+        let MemoryDiff { len, offset, data } = &step.memory;
+        self.memory.resize(*len, 0);
+        self.memory[*offset..*offset + data.len()].copy_from_slice(data);
+    }
+}
+
+/// Storage write taken from the [`JournalEntry::StorageChanged`] entry.
+///
+/// Reverted calls are recorded as the inverse writes, so the storage at any step is the
+/// original storage with the writes up to the step applied in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StorageWrite {
+    pub address: Address,
+    pub key: U256,
+    pub old: U256,
+    pub new: U256,
+}
+
+/// Inspector recording every step of the execution together with the storage writes.
+#[derive(Debug, Default)]
+pub struct Recorder {
+    pub steps: Vec<Step>,
+    pub writes: Vec<StorageWrite>,
+    /// State at every [CHECKPOINT_INTERVAL] steps, starting with the first step.
+    pub checkpoints: Vec<Checkpoint>,
+    /// State at the last step.
+    last: Checkpoint,
+    /// Number of the writes at the start of every active call.
+    frames: Vec<usize>,
+    /// Number of the entries already read from the last journal.
+    seen: usize,
+}
+
+impl Recorder {
+    /// Records the storage writes journaled since the last call.
+    fn read_journal<CTX: JournalExtGetter>(&mut self, context: &CTX) {
+        let journal = context.journal_ext();
+        let entries = journal.last_journal();
+        for entry in entries.iter().skip(self.seen) {
+            if let JournalEntry::StorageChanged {
+                address,
+                key,
+                had_value,
+            } = *entry
+            {
+                let new = journal.evm_state()[&address].storage[&key].present_value;
+                self.writes.push(StorageWrite {
+                    address,
+                    key,
+                    old: had_value,
+                    new,
+                });
+            }
+        }
+        self.seen = entries.len();
+    }
+
+    /// Records the end of the call, the writes of the reverted call are undone.
+    fn frame_end<CTX: JournalExtGetter>(&mut self, context: &CTX, success: bool) {
+        let start = self.frames.pop().unwrap_or_default();
+        if success {
+            self.read_journal(context);
+        } else {
+            let undo = self.writes[start..]
+                .iter()
+                .rev()
+                .map(|write| StorageWrite {
+                    old: write.new,
+                    new: write.old,
+                    ..*write
+                })
+                .collect::<Vec<_>>();
+            self.writes.extend(undo);
+        }
+        // Reverted journals are truncated, continue with the journal of the parent.
+        self.seen = context.journal_ext().last_journal().len();
+    }
+}
+
+impl<CTX> Inspector<CTX, EthInterpreter> for Recorder
+where
+    CTX: JournalGetter + JournalExtGetter,
+{
+    fn initialize_interp(&mut self, _: &mut Interpreter<EthInterpreter>, context: &mut CTX) {
+        self.seen = context.journal_ext().last_journal().len();
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, context: &mut CTX) {
+        self.read_journal(context);
+        let stack = interp.stack.data();
+        let keep = stack
+            .iter()
+            .zip(&self.last.stack)
+            .take_while(|(new, old)| new == old)
+            .count();
+        let stack = StackDiff {
+            keep,
+            push: stack[keep..].to_vec(),
+        };
+        let memory = MemoryDiff::new(
+            &self.last.memory,
+            &interp.memory.slice(0..interp.memory.size()),
+        );
+        let return_data = match self.steps.last() {
+            Some(last) if last.return_data[..] == *interp.return_data.buffer() => {
+                last.return_data.clone()
+            }
+            _ => Bytes::copy_from_slice(interp.return_data.buffer()),
+        };
+        let step = Step {
+            pc: interp.bytecode.pc(),
+            opcode: interp.bytecode.opcode(),
+            depth: context.journal().depth(),
+            address: interp.input.target_address(),
+            gas_remaining: interp.control.gas().remaining(),
+            stack,
+            memory,
+            return_data,
+            writes: self.writes.len(),
+        };
+        self.last.apply(&step);
+        if self.steps.len() == self.checkpoints.len() * CHECKPOINT_INTERVAL {
+            self.checkpoints.push(self.last.clone());
+        }
+        self.steps.push(step);
+    }
+
+    fn call(&mut self, _: &mut CTX, _: &mut CallInputs) -> Option<CallOutcome> {
+        self.frames.push(self.writes.len());
+        None
+    }
+
+    fn call_end(&mut self, context: &mut CTX, _: &CallInputs, outcome: &mut CallOutcome) {
+        self.frame_end(context, outcome.result.is_ok());
+    }
+
+    fn create(&mut self, _: &mut CTX, _: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.frames.push(self.writes.len());
+        None
+    }
+
+    fn create_end(&mut self, context: &mut CTX, _: &CreateInputs, outcome: &mut CreateOutcome) {
+        self.frame_end(context, outcome.result.is_ok());
+    }
+}
@@ -0,0 +1,299 @@
+use super::recorder::{Checkpoint, Recorder, Step, StorageWrite, CHECKPOINT_INTERVAL};
+use revm::{
+    bytecode::opcode::OpCode,
+    primitives::{hex, Address, HashMap, U256},
+    state::EvmState,
+};
+use std::{collections::BTreeMap, fmt::Write};
+
+const HELP: &str = "\
+s, step [n]          step into the next instruction
+n, next              step over the call
+o, out               step out of the call
+c, continue          run to the next breakpoint
+b, back [n]          step back
+rc, reverse          run back to the previous breakpoint
+goto <step>          jump to the step
+break pc <pc>        break at the program counter
+break op <opcode>    break at the opcode, by name or value
+break depth <depth>  break at the call depth
+breakpoints          list the breakpoints
+delete <index>       delete the breakpoint
+w, where             print the current step
+stack                print the stack
+memory               print the memory
+storage              print the storage of the current contract
+returndata           print the return data of the last call
+q, quit              exit the debugger";
+
+/// Condition on which the execution is paused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Breakpoint {
+    Pc(usize),
+    Opcode(u8),
+    Depth(usize),
+}
+
+impl Breakpoint {
+    fn parse(kind: &str, value: &str) -> Result<Self, String> {
+        match kind {
+            "pc" => parse_number(value).map(Self::Pc),
+            "op" | "opcode" => match OpCode::parse(&value.to_uppercase()) {
+                Some(opcode) => Ok(Self::Opcode(opcode.get())),
+                None => parse_number(value)
+                    .ok()
+                    .and_then(|opcode| u8::try_from(opcode).ok())
+                    .map(Self::Opcode)
+                    .ok_or_else(|| format!("unknown opcode {value}")),
+            },
+            "depth" => parse_number(value).map(Self::Depth),
+            _ => Err(format!(
+                "unknown breakpoint {kind}, expected pc, op or depth"
+            )),
+        }
+    }
+
+    fn matches(&self, step: &Step) -> bool {
+        match *self {
+            Self::Pc(pc) => step.pc == pc,
+            Self::Opcode(opcode) => step.opcode == opcode,
+            Self::Depth(depth) => step.depth == depth,
+        }
+    }
+}
+
+impl std::fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Pc(pc) => write!(f, "pc 0x{pc:x}"),
+            Self::Opcode(opcode) => write!(f, "op {}", opcode_name(opcode)),
+            Self::Depth(depth) => write!(f, "depth {depth}"),
+        }
+    }
+}
+
+/// Debugging session over the recorded execution.
+///
+/// Moving backwards is done by replaying the recorded storage writes from the start of the
+/// transaction, and the stack and memory changes from the previous [Checkpoint].
+#[derive(Debug)]
+pub struct Session {
+    steps: Vec<Step>,
+    writes: Vec<StorageWrite>,
+    checkpoints: Vec<Checkpoint>,
+    /// Storage values at the start of the transaction.
+    original: HashMap<(Address, U256), U256>,
+    breakpoints: Vec<Breakpoint>,
+    cursor: usize,
+}
+
+impl Session {
+    /// Creates the session from the recorded execution and the final state of the transaction.
+    pub fn new(recorder: Recorder, state: &EvmState) -> Self {
+        let original = state
+            .iter()
+            .flat_map(|(address, account)| {
+                account
+                    .storage
+                    .iter()
+                    .map(|(key, slot)| ((*address, *key), slot.original_value))
+            })
+            .collect();
+        Self {
+            steps: recorder.steps,
+            writes: recorder.writes,
+            checkpoints: recorder.checkpoints,
+            original,
+            breakpoints: Vec::new(),
+            cursor: 0,
+        }
+    }
+
+    /// Executes the command, returns `None` if the session should end.
+    pub fn execute(&mut self, line: &str) -> Option<String> {
+        let args = line.split_whitespace().collect::<Vec<_>>();
+        let Some((&command, args)) = args.split_first() else {
+            return Some(String::new());
+        };
+        if matches!(command, "q" | "quit" | "exit") {
+            return None;
+        }
+        Some(
+            self.command(command, args)
+                .unwrap_or_else(|e| format!("error: {e}")),
+        )
+    }
+
+    fn command(&mut self, command: &str, args: &[&str]) -> Result<String, String> {
+        if matches!(command, "h" | "help") {
+            return Ok(HELP.into());
+        }
+        if self.steps.is_empty() {
+            return Err("no instructions were executed".into());
+        }
+        let count = || args.first().map_or(Ok(1), |n| parse_number(n));
+        let depth = self.steps[self.cursor].depth;
+        match command {
+            "s" | "step" => {
+                let count = count()?;
+                self.seek(self.cursor.saturating_add(count))
+            }
+            "n" | "next" => self.forward(|step| step.depth <= depth),
+            "o" | "out" | "finish" => self.forward(|step| step.depth < depth),
+            "c" | "continue" => {
+                let breakpoints = self.breakpoints.clone();
+                self.forward(|step| breakpoints.iter().any(|b| b.matches(step)))
+            }
+            "b" | "back" => {
+                let count = count()?;
+                self.seek(self.cursor.saturating_sub(count))
+            }
+            "rc" | "reverse" => {
+                let target = self.steps[..self.cursor]
+                    .iter()
+                    .rposition(|step| self.breakpoints.iter().any(|b| b.matches(step)))
+                    .unwrap_or_default();
+                self.seek(target)
+            }
+            "goto" => {
+                let target = args.first().ok_or("expected the step")?;
+                self.seek(parse_number(target)?)
+            }
+            "break" => {
+                let [kind, value] = args else {
+                    return Err("expected `break <pc|op|depth> <value>`".into());
+                };
+                let breakpoint = Breakpoint::parse(kind, value)?;
+                self.breakpoints.push(breakpoint);
+                Ok(format!(
+                    "breakpoint {}: {breakpoint}",
+                    self.breakpoints.len() - 1
+                ))
+            }
+            "breakpoints" => Ok(self
+                .breakpoints
+                .iter()
+                .enumerate()
+                .map(|(i, breakpoint)| format!("{i}: {breakpoint}"))
+                .collect::<Vec<_>>()
+                .join("\n")),
+            "delete" => {
+                let index = parse_number(args.first().ok_or("expected the breakpoint")?)?;
+                if index >= self.breakpoints.len() {
+                    return Err(format!("no breakpoint {index}"));
+                }
+                Ok(format!("deleted {}", self.breakpoints.remove(index)))
+            }
+            "w" | "where" => Ok(self.location()),
+            "stack" => Ok(self.stack()),
+            "memory" => Ok(self.memory()),
+            "storage" => Ok(self.storage()),
+            "returndata" => Ok(hex::encode_prefixed(&self.steps[self.cursor].return_data)),
+            _ => Err(format!("unknown command {command}, see `help`")),
+        }
+    }
+
+    /// Moves to the next step matching the condition, or past the last step.
+    fn forward(&mut self, condition: impl Fn(&Step) -> bool) -> Result<String, String> {
+        let target = self.steps[self.cursor + 1..]
+            .iter()
+            .position(condition)
+            .map_or(self.steps.len(), |i| self.cursor + 1 + i);
+        self.seek(target)
+    }
+
+    fn seek(&mut self, target: usize) -> Result<String, String> {
+        if target >= self.steps.len() {
+            self.cursor = self.steps.len() - 1;
+            return Ok(format!("end of execution\n{}", self.location()));
+        }
+        self.cursor = target;
+        Ok(self.location())
+    }
+
+    fn location(&self) -> String {
+        let step = &self.steps[self.cursor];
+        format!(
+            "#{} depth {} {} pc 0x{:x} {} gas {}",
+            self.cursor,
+            step.depth,
+            step.address,
+            step.pc,
+            opcode_name(step.opcode),
+            step.gas_remaining
+        )
+    }
+
+    /// Replays the stack and memory changes up to the current step on top of the checkpoint.
+    fn checkpoint(&self) -> Checkpoint {
+        let base = self.cursor / CHECKPOINT_INTERVAL;
+        let mut checkpoint = self.checkpoints[base].clone();
+        for step in &self.steps[base * CHECKPOINT_INTERVAL + 1..=self.cursor] {
+            checkpoint.apply(step);
+        }
+        checkpoint
+    }
+
+    fn stack(&self) -> String {
+        let stack = self.checkpoint().stack;
+        if stack.is_empty() {
+            return "empty".into();
+        }
+        stack
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, value)| format!("{i:>4}: 0x{value:064x}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn memory(&self) -> String {
+        let memory = self.checkpoint().memory;
+        if memory.is_empty() {
+            return "empty".into();
+        }
+        let mut out = String::new();
+        for (i, word) in memory.chunks(32).enumerate() {
+            let _ = writeln!(out, "0x{:04x}: {}", i * 32, hex::encode(word));
+        }
+        out.pop();
+        out
+    }
+
+    /// Replays the storage writes up to the current step on top of the original storage.
+    fn storage(&self) -> String {
+        let step = &self.steps[self.cursor];
+        let mut storage = self
+            .original
+            .iter()
+            .filter(|((address, _), _)| *address == step.address)
+            .map(|((_, key), value)| (*key, *value))
+            .collect::<BTreeMap<_, _>>();
+        for write in &self.writes[..step.writes] {
+            if write.address == step.address {
+                storage.insert(write.key, write.new);
+            }
+        }
+        if storage.is_empty() {
+            return "empty".into();
+        }
+        storage
+            .iter()
+            .map(|(key, value)| format!("0x{key:x}: 0x{value:x}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn opcode_name(opcode: u8) -> String {
+    OpCode::new(opcode).map_or_else(|| format!("0x{opcode:02x}"), |op| op.as_str().into())
+}
+
+fn parse_number(value: &str) -> Result<usize, String> {
+    match value.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => value.parse(),
+    }
+    .map_err(|_| format!("invalid number {value}"))
+}
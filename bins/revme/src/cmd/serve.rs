@@ -0,0 +1,266 @@
+pub mod rpc;
+
+use alloy_provider::{network::primitives::BlockTransactionsKind, Provider, ProviderBuilder};
+use clap::Parser;
+use database::{AlloyDB, BlockId, InMemoryDB, PersistentCacheDB};
+use revm::{
+    context::{BlockEnv, CfgEnv, OverrideDB},
+    database_interface::WrapDatabaseAsync,
+    DatabaseRef,
+};
+use rpc::{state_override, Rpc, RpcError, StateOverride};
+use serde_json::Value;
+use std::{
+    fs,
+    io::{BufRead, BufReader, Error as IoError, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    path::PathBuf,
+    time::Duration,
+};
+use tokio::runtime::Runtime;
+
+/// Largest request body accepted by the server, like the default of geth.
+const MAX_BODY_SIZE: usize = 5 * 1024 * 1024;
+
+/// Largest size of the request line and the headers.
+const MAX_HEADER_SIZE: u64 = 64 * 1024;
+
+/// Time a client has to send the request or read the response, requests are served one at a
+/// time so an idle client delays all the others.
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, thiserror::Error)]
+pub enum Errors {
+    #[error("Invalid fork URL")]
+    InvalidForkUrl,
+    #[error("Fork block not found")]
+    ForkBlockNotFound,
+    #[error("Fork provider error: {0}")]
+    Provider(String),
+    #[error("Invalid alloc: {0}")]
+    InvalidAlloc(String),
+    #[error(transparent)]
+    Io(#[from] IoError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Serve command hosts an HTTP JSON-RPC endpoint executing calls against the state
+///
+/// Supports `eth_call`, `eth_estimateGas`, `eth_createAccessList` and `debug_traceCall`
+/// with the `callTracer` and `prestateTracer`. The state is in-memory, optionally seeded
+/// with `--alloc`, or forked from another node with `--fork-url`.
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// Address the server listens on
+    #[arg(long, default_value = "127.0.0.1:8545")]
+    addr: SocketAddr,
+    /// Chain id of the in-memory state, forks use the chain id of the provider
+    #[arg(long, default_value_t = 1)]
+    chain_id: u64,
+    /// Path to a JSON file with the accounts in the geth genesis `alloc` format
+    #[arg(long)]
+    alloc: Option<PathBuf>,
+    /// URL of the JSON-RPC node to fork the state from
+    #[arg(long)]
+    fork_url: Option<String>,
+    /// Number of the block to fork, defaults to the latest block
+    #[arg(long, requires = "fork_url")]
+    fork_block: Option<u64>,
+    /// Directory to persist the state fetched from the fork in
+    #[arg(long, requires = "fork_url")]
+    cache_dir: Option<PathBuf>,
+}
+
+impl Cmd {
+    /// Run serve command.
+    pub fn run(&self) -> Result<(), Errors> {
+        let alloc: StateOverride = match &self.alloc {
+            Some(path) => serde_json::from_slice(&fs::read(path)?)?,
+            None => StateOverride::default(),
+        };
+        let alloc = state_override(alloc).map_err(|e| Errors::InvalidAlloc(e.message))?;
+        let mut cfg = CfgEnv::default();
+        cfg.chain_id = self.chain_id;
+
+        let Some(fork_url) = &self.fork_url else {
+            let db = OverrideDB::new(InMemoryDB::default(), alloc);
+            return self.serve(Rpc::new(db, BlockEnv::default(), cfg), |_| Ok(()));
+        };
+
+        let runtime = Runtime::new()?;
+        let provider =
+            ProviderBuilder::new().on_http(fork_url.parse().map_err(|_| Errors::InvalidForkUrl)?);
+        let (chain_id, block) = runtime
+            .block_on(async {
+                let chain_id = provider.get_chain_id().await?;
+                let number = match self.fork_block {
+                    Some(number) => number,
+                    None => provider.get_block_number().await?,
+                };
+                let block = provider
+                    .get_block_by_number(number.into(), BlockTransactionsKind::Hashes)
+                    .await?;
+                Ok::<_, alloy_transport::TransportError>((chain_id, block))
+            })
+            .map_err(|e| Errors::Provider(e.to_string()))?;
+        let header = block.ok_or(Errors::ForkBlockNotFound)?.header;
+
+        // Calls execute on top of the state of the block, in its environment, like in geth.
+        let block = BlockEnv {
+            number: header.number,
+            beneficiary: header.beneficiary,
+            timestamp: header.timestamp,
+            gas_limit: header.gas_limit,
+            basefee: header.base_fee_per_gas.unwrap_or_default(),
+            difficulty: header.difficulty,
+            prevrandao: Some(header.mix_hash),
+            ..Default::default()
+        };
+        cfg.chain_id = chain_id;
+        println!("Forked block {} of chain {chain_id}", block.number);
+
+        let remote = WrapDatabaseAsync::with_runtime(
+            AlloyDB::new(provider, BlockId::number(block.number)),
+            runtime,
+        );
+        match &self.cache_dir {
+            Some(dir) => {
+                fs::create_dir_all(dir)?;
+                let remote = PersistentCacheDB::new(remote, dir, chain_id, block.number)?;
+                let db = OverrideDB::new(remote, alloc);
+                // The server runs until it is killed, so the cache is written after every request.
+                self.serve(Rpc::new(db, block, cfg), |db| db.db.flush())
+            }
+            None => {
+                let db = OverrideDB::new(remote, alloc);
+                self.serve(Rpc::new(db, block, cfg), |_| Ok(()))
+            }
+        }
+    }
+
+    fn serve<DB: DatabaseRef>(
+        &self,
+        rpc: Rpc<DB>,
+        flush: impl Fn(&DB) -> Result<(), IoError>,
+    ) -> Result<(), Errors> {
+        let listener = TcpListener::bind(self.addr)?;
+        println!("Listening on http://{}", listener.local_addr()?);
+        for stream in listener.incoming() {
+            let result = stream.and_then(|stream| handle_connection(&rpc, stream));
+            if let Err(e) = result {
+                eprintln!("Connection error: {e}");
+            }
+            flush(rpc.db())?;
+        }
+        Ok(())
+    }
+}
+
+/// Serves a single HTTP request and closes the connection.
+///
+/// Bodies larger than [MAX_BODY_SIZE] and headers larger than [MAX_HEADER_SIZE] are rejected.
+fn handle_connection<DB: DatabaseRef>(rpc: &Rpc<DB>, stream: TcpStream) -> Result<(), IoError> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut headers = (&mut reader).take(MAX_HEADER_SIZE);
+    let mut line = String::new();
+    headers.read_line(&mut line)?;
+    let method = line
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+
+    let mut content_length = 0;
+    let mut headers_complete = false;
+    loop {
+        line.clear();
+        if headers.read_line(&mut line)? == 0 {
+            break;
+        }
+        if line.trim().is_empty() {
+            headers_complete = true;
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or_default();
+            }
+        }
+    }
+
+    let (status, body) = match method.as_str() {
+        _ if !headers_complete && headers.limit() == 0 => {
+            ("431 Request Header Fields Too Large", Vec::new())
+        }
+        // CORS preflight of the browser clients.
+        "OPTIONS" => ("204 No Content", Vec::new()),
+        "POST" if content_length > MAX_BODY_SIZE => ("413 Payload Too Large", Vec::new()),
+        "POST" => {
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body)?;
+            let response = match serde_json::from_slice::<Value>(&body) {
+                Ok(request) => rpc.handle(request),
+                Err(e) => RpcError::parse_error(e).response(Value::Null),
+            };
+            ("200 OK", serde_json::to_vec(&response)?)
+        }
+        _ => ("405 Method Not Allowed", Vec::new()),
+    };
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Methods: POST, OPTIONS\r\n\
+         Access-Control-Allow-Headers: Content-Type\r\n\
+         Connection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    /// Sends the request to a server handling a single connection and returns the response.
+    fn request(request: &'static [u8]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(request).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        });
+        let rpc = Rpc::new(
+            InMemoryDB::default(),
+            BlockEnv::default(),
+            CfgEnv::default(),
+        );
+        let (stream, _) = listener.accept().unwrap();
+        handle_connection(&rpc, stream).unwrap();
+        client.join().unwrap()
+    }
+
+    #[test]
+    fn http_limits() {
+        let response = request(
+            b"POST / HTTP/1.1\r\nContent-Length: 47\r\n\r\n{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"eth_chainId\"}",
+        );
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+        assert!(response.ends_with(r#""result":"0x1"}"#), "{response}");
+
+        let response = request(b"POST / HTTP/1.1\r\nContent-Length: 1000000000\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 413"), "{response}");
+    }
+}
@@ -0,0 +1,611 @@
+//! JSON-RPC methods served by the `serve` command.
+use database::CacheDB;
+use inspector::{
+    inspector_context::InspectorContext,
+    inspector_handler,
+    inspectors::{
+        decode_revert_reason, CallTracer, CallTracerConfig, PrestateTracer, PrestateTracerConfig,
+    },
+    InspectorMainEvm,
+};
+use revm::{
+    bytecode::Bytecode,
+    context::{
+        overrides::{self, OverrideDB, StorageOverride},
+        BlockEnv, CfgEnv, TxEnv,
+    },
+    context_interface::{result::ExecutionResult, transaction::TransactionType},
+    handler::EthHandler,
+    primitives::{self, alloy_primitives::U64, Address, Bytes, TxKind, B256, U256},
+    Context, Database, DatabaseRef, EstimateGasError, EthContext, EvmExec, MainEvm,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{collections::HashMap, fmt::Display};
+
+/// Transaction of `eth_call` and the related methods.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallRequest {
+    pub from: Option<Address>,
+    pub to: Option<Address>,
+    pub gas: Option<U64>,
+    pub gas_price: Option<U256>,
+    pub max_fee_per_gas: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub value: Option<U256>,
+    #[serde(alias = "data")]
+    pub input: Option<Bytes>,
+    pub nonce: Option<U64>,
+    pub access_list: Option<Vec<AccessListItem>>,
+}
+
+/// Entry of the access list.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<B256>,
+}
+
+/// Override of the account state, also used for the accounts of the genesis `alloc`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+    pub balance: Option<U256>,
+    pub nonce: Option<U64>,
+    pub code: Option<Bytes>,
+    /// Replaces the whole storage of the account.
+    #[serde(alias = "storage")]
+    pub state: Option<HashMap<B256, B256>>,
+    /// Overrides the given storage slots of the account.
+    pub state_diff: Option<HashMap<B256, B256>>,
+    pub move_precompile_to_address: Option<Address>,
+}
+
+/// Overrides of the account states by their address.
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
+/// Overrides of the block environment.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockOverrides {
+    pub number: Option<U64>,
+    pub time: Option<U64>,
+    pub gas_limit: Option<U64>,
+    #[serde(alias = "coinbase")]
+    pub fee_recipient: Option<Address>,
+    #[serde(alias = "baseFee")]
+    pub base_fee_per_gas: Option<U64>,
+    #[serde(alias = "random")]
+    pub prev_randao: Option<B256>,
+    pub difficulty: Option<U256>,
+}
+
+/// Options of `debug_traceCall`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceCallOptions {
+    pub tracer: Option<String>,
+    pub tracer_config: Option<Value>,
+    pub state_overrides: Option<StateOverride>,
+    pub block_overrides: Option<BlockOverrides>,
+}
+
+/// Error object of the JSON-RPC response.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Display) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            data: None,
+        }
+    }
+
+    /// Request body is not a valid JSON.
+    pub fn parse_error(error: impl Display) -> Self {
+        Self::new(-32700, format!("parse error: {error}"))
+    }
+
+    fn invalid_request() -> Self {
+        Self::new(-32600, "invalid request")
+    }
+
+    fn method_not_found(method: &str) -> Self {
+        Self::new(-32601, format!("the method {method} does not exist"))
+    }
+
+    fn invalid_params(message: impl Display) -> Self {
+        Self::new(-32602, message)
+    }
+
+    fn internal(message: impl Display) -> Self {
+        Self::new(-32000, message)
+    }
+
+    /// Execution reverted, the output is returned as the error data like in geth.
+    fn reverted(output: Bytes) -> Self {
+        let message = match decode_revert_reason(&output) {
+            Some(reason) => format!("execution reverted: {reason}"),
+            None => "execution reverted".to_string(),
+        };
+        Self {
+            data: Some(json!(output)),
+            ..Self::new(3, message)
+        }
+    }
+
+    /// Response to the request with the given id.
+    pub fn response(self, id: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "error": self })
+    }
+}
+
+/// Converts the state overrides to the ones applied by the [OverrideDB].
+pub fn state_override(state: StateOverride) -> Result<overrides::StateOverride, RpcError> {
+    state
+        .into_iter()
+        .map(|(address, account)| {
+            let storage = match (account.state, account.state_diff) {
+                (Some(_), Some(_)) => {
+                    return Err(RpcError::invalid_params(format!(
+                        "account {address} has both 'state' and 'stateDiff'"
+                    )))
+                }
+                (Some(state), None) => Some(StorageOverride::Replace(storage_slots(state))),
+                (None, Some(diff)) => Some(StorageOverride::Diff(storage_slots(diff))),
+                (None, None) => None,
+            };
+            let code = account
+                .code
+                .map(Bytecode::new_raw_checked)
+                .transpose()
+                .map_err(RpcError::invalid_params)?;
+            let account = overrides::AccountOverride {
+                balance: account.balance,
+                nonce: account.nonce.map(|nonce| nonce.to()),
+                code,
+                storage,
+                move_precompile_to: account.move_precompile_to_address,
+            };
+            Ok((address, account))
+        })
+        .collect()
+}
+
+fn storage_slots(storage: HashMap<B256, B256>) -> primitives::HashMap<U256, U256> {
+    storage
+        .into_iter()
+        .map(|(key, value)| (key.into(), value.into()))
+        .collect()
+}
+
+impl From<BlockOverrides> for overrides::BlockOverride {
+    fn from(block: BlockOverrides) -> Self {
+        Self {
+            number: block.number.map(|number| number.to()),
+            beneficiary: block.fee_recipient,
+            timestamp: block.time.map(|time| time.to()),
+            gas_limit: block.gas_limit.map(|gas_limit| gas_limit.to()),
+            basefee: block.base_fee_per_gas.map(|basefee| basefee.to()),
+            difficulty: block.difficulty,
+            prevrandao: block.prev_randao,
+        }
+    }
+}
+
+/// Serves the JSON-RPC methods on top of the database.
+///
+/// Every call executes on a fresh [CacheDB] with the [OverrideDB] on top, so calls never
+/// change the state.
+#[derive(Debug)]
+pub struct Rpc<DB> {
+    db: DB,
+    block: BlockEnv,
+    cfg: CfgEnv,
+}
+
+impl<DB: DatabaseRef> Rpc<DB> {
+    /// Creates a new server executing in the given block environment.
+    pub fn new(db: DB, block: BlockEnv, cfg: CfgEnv) -> Self {
+        Self { db, block, cfg }
+    }
+
+    /// Returns the database.
+    pub fn db(&self) -> &DB {
+        &self.db
+    }
+
+    /// Handles the JSON-RPC request or the batch of requests.
+    pub fn handle(&self, request: Value) -> Value {
+        match request {
+            Value::Array(batch) if batch.is_empty() => {
+                RpcError::invalid_request().response(Value::Null)
+            }
+            Value::Array(batch) => batch.into_iter().map(|r| self.handle_one(r)).collect(),
+            request => self.handle_one(request),
+        }
+    }
+
+    fn handle_one(&self, request: Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            return RpcError::invalid_request().response(id);
+        };
+        let params = match request.get("params") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(params)) => params.clone(),
+            Some(_) => {
+                return RpcError::invalid_params("params must be an array").response(id);
+            }
+        };
+        match self.call_method(method, &params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(e) => e.response(id),
+        }
+    }
+
+    fn call_method(&self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        match method {
+            "eth_chainId" => Ok(json!(U64::from(self.cfg.chain_id))),
+            "eth_blockNumber" => Ok(json!(U64::from(self.block.number))),
+            "eth_call" | "eth_estimateGas" | "eth_createAccessList" | "debug_traceCall" => {
+                self.check_block(optional(params, 1)?)?;
+                match method {
+                    "eth_call" => self.call(
+                        required(params, 0)?,
+                        optional(params, 2)?,
+                        optional(params, 3)?,
+                    ),
+                    "eth_estimateGas" => self.estimate_gas(
+                        required(params, 0)?,
+                        optional(params, 2)?,
+                        optional(params, 3)?,
+                    ),
+                    "eth_createAccessList" => {
+                        self.create_access_list(required(params, 0)?, optional(params, 2)?)
+                    }
+                    _ => self.trace_call(
+                        required(params, 0)?,
+                        optional(params, 2)?.unwrap_or_default(),
+                    ),
+                }
+            }
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+
+    /// Checks that the block parameter selects the served block, the state of other blocks is
+    /// not available.
+    fn check_block(&self, block: Option<Value>) -> Result<(), RpcError> {
+        let number = match &block {
+            None => return Ok(()),
+            Some(Value::String(tag)) if tag == "latest" || tag == "pending" => return Ok(()),
+            Some(Value::String(number)) => number.parse::<U64>().ok(),
+            Some(Value::Object(block)) => block
+                .get("blockNumber")
+                .and_then(|number| serde_json::from_value::<U64>(number.clone()).ok()),
+            Some(_) => None,
+        };
+        if number == Some(U64::from(self.block.number)) {
+            return Ok(());
+        }
+        Err(RpcError::invalid_params(format!(
+            "block {} is not available, only the block {} is served",
+            block.unwrap_or_default(),
+            self.block.number
+        )))
+    }
+
+    fn call(
+        &self,
+        request: CallRequest,
+        state: Option<StateOverride>,
+        block: Option<BlockOverrides>,
+    ) -> Result<Value, RpcError> {
+        let mut evm = MainEvm::new(self.context(request, state, block)?, EthHandler::default());
+        match evm.exec().map_err(RpcError::internal)?.result {
+            ExecutionResult::Success { output, .. } => Ok(json!(output.into_data())),
+            ExecutionResult::Revert { output, .. } => Err(RpcError::reverted(output)),
+            ExecutionResult::Halt { reason, .. } => {
+                Err(RpcError::internal(format!("execution halted: {reason:?}")))
+            }
+        }
+    }
+
+    fn estimate_gas(
+        &self,
+        request: CallRequest,
+        state: Option<StateOverride>,
+        block: Option<BlockOverrides>,
+    ) -> Result<Value, RpcError> {
+        let mut evm = MainEvm::new(self.context(request, state, block)?, EthHandler::default());
+        match evm.estimate_gas() {
+            Ok(gas) => Ok(json!(U64::from(gas))),
+            Err(EstimateGasError::Revert { output, .. }) => Err(RpcError::reverted(output)),
+            Err(e) => Err(RpcError::internal(e)),
+        }
+    }
+
+    fn create_access_list(
+        &self,
+        request: CallRequest,
+        state: Option<StateOverride>,
+    ) -> Result<Value, RpcError> {
+        let mut evm = MainEvm::new(self.context(request, state, None)?, EthHandler::default());
+        let output = evm.create_access_list().map_err(RpcError::internal)?;
+        let access_list: Vec<AccessListItem> = output
+            .access_list
+            .into_iter()
+            .map(|(address, storage_keys)| AccessListItem {
+                address,
+                storage_keys,
+            })
+            .collect();
+        let mut result = json!({
+            "accessList": access_list,
+            "gasUsed": U64::from(output.gas_used),
+        });
+        match output.result {
+            ExecutionResult::Success { .. } => {}
+            ExecutionResult::Revert { output, .. } => {
+                result["error"] = json!(RpcError::reverted(output).message);
+            }
+            ExecutionResult::Halt { reason, .. } => {
+                result["error"] = json!(format!("execution halted: {reason:?}"));
+            }
+        }
+        Ok(result)
+    }
+
+    fn trace_call(
+        &self,
+        request: CallRequest,
+        options: TraceCallOptions,
+    ) -> Result<Value, RpcError> {
+        let context = self.context(request, options.state_overrides, options.block_overrides)?;
+        let config = options.tracer_config.unwrap_or(Value::Null);
+        match options.tracer.as_deref() {
+            Some("callTracer") => {
+                let tracer = CallTracer::new(tracer_config::<CallTracerConfig>(config)?);
+                let mut evm = InspectorMainEvm::new(
+                    InspectorContext::new(context, tracer),
+                    inspector_handler(),
+                );
+                let output = evm.exec().map_err(RpcError::internal)?;
+                let tracer = &mut evm.context.inspector;
+                tracer.set_gas_used(output.result.gas_used());
+                Ok(json!(tracer.take_call_frame()))
+            }
+            Some("prestateTracer") => {
+                let tracer = PrestateTracer::new(tracer_config::<PrestateTracerConfig>(config)?);
+                let mut evm = InspectorMainEvm::new(
+                    InspectorContext::new(context, tracer),
+                    inspector_handler(),
+                );
+                let output = evm.exec().map_err(RpcError::internal)?;
+                Ok(json!(evm.context.inspector.geth_trace(&output.state)))
+            }
+            Some(tracer) => Err(RpcError::invalid_params(format!(
+                "unsupported tracer: {tracer}"
+            ))),
+            None => Err(RpcError::invalid_params(
+                "struct logger is not supported, use callTracer or prestateTracer",
+            )),
+        }
+    }
+
+    /// Builds the execution context of the call on top of the overridden state and block.
+    fn context(
+        &self,
+        request: CallRequest,
+        state: Option<StateOverride>,
+        block: Option<BlockOverrides>,
+    ) -> Result<EthContext<OverrideDB<CacheDB<&DB>>>, RpcError> {
+        let mut db = OverrideDB::new(
+            CacheDB::new(&self.db),
+            state_override(state.unwrap_or_default())?,
+        );
+
+        let mut block_env = self.block.clone();
+        if let Some(block) = block {
+            block_env.apply_override(&block.into());
+        }
+
+        let caller = request.from.unwrap_or_default();
+        let nonce = match request.nonce {
+            Some(nonce) => nonce.to(),
+            None => db
+                .basic(caller)
+                .map_err(RpcError::internal)?
+                .map_or(0, |info| info.nonce),
+        };
+        let access_list: Vec<(Address, Vec<B256>)> = request
+            .access_list
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| (item.address, item.storage_keys))
+            .collect();
+        let tx_type = if request.max_fee_per_gas.is_some() {
+            TransactionType::Eip1559
+        } else if !access_list.is_empty() {
+            TransactionType::Eip2930
+        } else {
+            TransactionType::Legacy
+        };
+        let gas_price = request
+            .max_fee_per_gas
+            .or(request.gas_price)
+            .unwrap_or_default();
+        let tx = TxEnv {
+            tx_type: tx_type as u8,
+            caller,
+            gas_limit: request.gas.map_or(block_env.gas_limit, |gas| gas.to()),
+            gas_price: gas_price.saturating_to(),
+            kind: request.to.map_or(TxKind::Create, TxKind::Call),
+            value: request.value.unwrap_or_default(),
+            data: request.input.unwrap_or_default(),
+            nonce,
+            chain_id: Some(self.cfg.chain_id),
+            access_list,
+            gas_priority_fee: request
+                .max_priority_fee_per_gas
+                .map(|fee| fee.saturating_to()),
+            ..Default::default()
+        };
+
+        let mut cfg = self.cfg.clone();
+        // Calls without the gas price are free like in geth.
+        cfg.disable_base_fee = tx.gas_price == 0;
+
+        cfg.precompile_moves = db.overrides().precompile_moves();
+        Ok(Context::builder()
+            .with_db(db)
+            .with_block(block_env)
+            .with_tx(tx)
+            .with_cfg(cfg))
+    }
+}
+
+/// Parses the `tracerConfig`, missing config is the default one.
+fn tracer_config<T: DeserializeOwned + Default>(config: Value) -> Result<T, RpcError> {
+    if config.is_null() {
+        return Ok(T::default());
+    }
+    serde_json::from_value(config).map_err(RpcError::invalid_params)
+}
+
+fn optional<T: DeserializeOwned>(params: &[Value], index: usize) -> Result<Option<T>, RpcError> {
+    match params.get(index) {
+        None | Some(Value::Null) => Ok(None),
+        Some(param) => serde_json::from_value(param.clone())
+            .map(Some)
+            .map_err(|e| RpcError::invalid_params(format!("invalid param {index}: {e}"))),
+    }
+}
+
+fn required<T: DeserializeOwned>(params: &[Value], index: usize) -> Result<T, RpcError> {
+    optional(params, index)?
+        .ok_or_else(|| RpcError::invalid_params(format!("missing param {index}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::InMemoryDB;
+    use revm::{bytecode::opcode, primitives::hex};
+
+    #[test]
+    fn rpc_methods() {
+        let rpc = Rpc::new(
+            InMemoryDB::default(),
+            BlockEnv::default(),
+            CfgEnv::default(),
+        );
+        // Returns the storage slot 0.
+        let code = [
+            opcode::PUSH0,
+            opcode::SLOAD,
+            opcode::PUSH0,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            0x20,
+            opcode::PUSH0,
+            opcode::RETURN,
+        ];
+        let state = json!({
+            "0x00000000000000000000000000000000000000aa": {
+                "code": hex::encode_prefixed(code),
+                "stateDiff": { format!("{}", B256::ZERO): format!("{}", B256::with_last_byte(42)) },
+            },
+            "0x00000000000000000000000000000000000000bb": {
+                "code": hex::encode_prefixed([opcode::PUSH0, opcode::PUSH0, opcode::REVERT]),
+            },
+        });
+        let tx = json!({ "from": "0x0000000000000000000000000000000000000001", "to": "0x00000000000000000000000000000000000000aa" });
+        let reverting = json!({ "to": "0x00000000000000000000000000000000000000bb" });
+        // Identity precompile moved to 0xcc.
+        let identity =
+            json!({ "to": "0x00000000000000000000000000000000000000cc", "input": "0x1234" });
+        let moved = json!({
+            "0x0000000000000000000000000000000000000004": {
+                "movePrecompileToAddress": "0x00000000000000000000000000000000000000cc",
+            },
+        });
+
+        let response = rpc.handle(json!([
+            { "jsonrpc": "2.0", "id": 1, "method": "eth_call", "params": [tx, "latest", state] },
+            { "jsonrpc": "2.0", "id": 2, "method": "eth_call", "params": [reverting, "latest", state] },
+            { "jsonrpc": "2.0", "id": 3, "method": "eth_estimateGas", "params": [tx, "latest", state] },
+            { "jsonrpc": "2.0", "id": 4, "method": "debug_traceCall", "params": [tx, "latest", { "tracer": "callTracer", "stateOverrides": state }] },
+            { "jsonrpc": "2.0", "id": 5, "method": "eth_createAccessList", "params": [tx] },
+            { "jsonrpc": "2.0", "id": 6, "method": "eth_sendTransaction", "params": [tx] },
+            { "jsonrpc": "2.0", "id": 7, "method": "eth_call", "params": [identity, "latest", moved] },
+        ]));
+
+        assert_eq!(
+            response[0]["result"],
+            json!(format!("{}", B256::with_last_byte(42)))
+        );
+        assert_eq!(response[1]["error"]["code"], json!(3));
+        assert_eq!(response[1]["error"]["data"], json!("0x"));
+        let gas: U64 = serde_json::from_value(response[2]["result"].clone()).unwrap();
+        assert!(gas > U64::from(21_000));
+        assert_eq!(response[3]["result"]["type"], json!("CALL"));
+        assert_eq!(response[3]["result"]["output"], response[0]["result"]);
+        // Overrides of previous calls are not persisted.
+        assert_eq!(response[4]["result"]["accessList"], json!([]));
+        assert_eq!(response[4]["result"]["gasUsed"], json!("0x5208"));
+        assert_eq!(response[5]["error"]["code"], json!(-32601));
+        assert_eq!(response[6]["result"], json!("0x1234"));
+    }
+
+    #[test]
+    fn rpc_params() {
+        let rpc = Rpc::new(
+            InMemoryDB::default(),
+            BlockEnv::default(),
+            CfgEnv::default(),
+        );
+        // Stores the block number in the slot 0.
+        let code = [opcode::NUMBER, opcode::PUSH0, opcode::SSTORE];
+        let state = json!({
+            "0x00000000000000000000000000000000000000aa": { "code": hex::encode_prefixed(code) },
+        });
+        let tx = json!({ "from": "0x0000000000000000000000000000000000000001", "to": "0x00000000000000000000000000000000000000aa" });
+
+        let response = rpc.handle(json!([
+            { "jsonrpc": "2.0", "id": 1, "method": "eth_call", "params": [tx, "0x0"] },
+            { "jsonrpc": "2.0", "id": 2, "method": "eth_call", "params": [tx, { "blockNumber": "0x0" }] },
+            { "jsonrpc": "2.0", "id": 3, "method": "eth_call", "params": [tx, "0x1"] },
+            { "jsonrpc": "2.0", "id": 4, "method": "eth_estimateGas", "params": [tx, "finalized"] },
+            { "jsonrpc": "2.0", "id": 5, "method": "eth_createAccessList", "params": [tx, "latest", state] },
+            { "jsonrpc": "2.0", "id": 6, "method": "eth_estimateGas", "params": [tx, "latest", state] },
+            { "jsonrpc": "2.0", "id": 7, "method": "eth_estimateGas", "params": [tx, "latest", state, { "number": "0x1" }] },
+        ]));
+
+        assert_eq!(response[0]["result"], json!("0x"));
+        assert_eq!(response[1]["result"], json!("0x"));
+        assert_eq!(response[2]["error"]["code"], json!(-32602));
+        assert_eq!(response[3]["error"]["code"], json!(-32602));
+        // State overrides are applied to the access list.
+        assert_eq!(
+            response[4]["result"]["accessList"],
+            json!([{
+                "address": "0x00000000000000000000000000000000000000aa",
+                "storageKeys": [format!("{}", B256::ZERO)],
+            }])
+        );
+        // Storing a non-zero block number costs more than clearing the slot.
+        let gas = |response: &Value| serde_json::from_value::<U64>(response["result"].clone());
+        assert!(gas(&response[6]).unwrap() > gas(&response[5]).unwrap());
+    }
+}
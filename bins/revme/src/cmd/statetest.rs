@@ -1,4 +1,3 @@
-pub mod merkle_trie;
 mod runner;
 pub mod utils;
 
@@ -1,7 +1,4 @@
-use super::{
-    merkle_trie::{log_rlp_hash, state_merkle_trie_root},
-    utils::recover_address,
-};
+use super::utils::recover_address;
 use database::State;
 use indicatif::{ProgressBar, ProgressDrawTarget};
 use inspector::{
@@ -146,8 +143,8 @@ fn check_evm_execution(
     spec: SpecId,
     print_json_outcome: bool,
 ) -> Result<(), TestErrorKind> {
-    let logs_root = log_rlp_hash(exec_result.as_ref().map(|r| r.logs()).unwrap_or_default());
-    let state_root = state_merkle_trie_root(db.cache.trie_account());
+    let logs_root = trie::logs_rlp_hash(exec_result.as_ref().map(|r| r.logs()).unwrap_or_default());
+    let state_root = trie::state_root(db.cache.trie_account());
 
     let print_json_output = |error: Option<String>| {
         if print_json_outcome {
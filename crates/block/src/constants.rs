@@ -0,0 +1,45 @@
+//! Addresses and parameters of the system contracts.
+use revm::primitives::{address, b256, Address, B256};
+
+pub use revm::primitives::BLOCKHASH_STORAGE_ADDRESS;
+
+/// Caller of the system calls.
+pub const SYSTEM_ADDRESS: Address = address!("fffffffffffffffffffffffffffffffffffffffe");
+
+/// Gas limit of the system calls, it does not count against the block gas limit.
+pub const SYSTEM_CALL_GAS_LIMIT: u64 = 30_000_000;
+
+/// EIP-4788: Beacon block root in the EVM
+///
+/// The address of the contract storing the parent beacon block roots.
+pub const BEACON_ROOTS_ADDRESS: Address = address!("000F3df6D732807Ef1319fB7B8bB8522d0Beac02");
+
+/// EIP-7002: Execution layer triggerable withdrawals
+///
+/// The address of the withdrawal request predeploy.
+pub const WITHDRAWAL_REQUEST_ADDRESS: Address =
+    address!("00000961Ef480Eb55e80D19ad83579A64c007002");
+
+/// EIP-7251: Increase the MAX_EFFECTIVE_BALANCE
+///
+/// The address of the consolidation request predeploy.
+pub const CONSOLIDATION_REQUEST_ADDRESS: Address =
+    address!("0000BBdDc7CE488642fb579F8B00f3a590007251");
+
+/// EIP-6110: Supply validator deposits on chain
+///
+/// Topic of the `DepositEvent(bytes,bytes,bytes,bytes,bytes)` log of the deposit contract.
+pub const DEPOSIT_EVENT_TOPIC: B256 =
+    b256!("649bbc62d0e31342afea4e5cd82d4049e7e1ee912fc0889aa790803be39038c5");
+
+/// EIP-7685 type of the deposit requests.
+pub const DEPOSIT_REQUEST_TYPE: u8 = 0x00;
+
+/// EIP-7685 type of the withdrawal requests.
+pub const WITHDRAWAL_REQUEST_TYPE: u8 = 0x01;
+
+/// EIP-7685 type of the consolidation requests.
+pub const CONSOLIDATION_REQUEST_TYPE: u8 = 0x02;
+
+/// Wei in one gwei, the unit of the withdrawal amounts.
+pub const GWEI_TO_WEI: u128 = 1_000_000_000;
@@ -0,0 +1,545 @@
+//! Sequential block executor.
+use crate::{
+    constants::{
+        BEACON_ROOTS_ADDRESS, BLOCKHASH_STORAGE_ADDRESS, CONSOLIDATION_REQUEST_ADDRESS,
+        CONSOLIDATION_REQUEST_TYPE, DEPOSIT_EVENT_TOPIC, DEPOSIT_REQUEST_TYPE, GWEI_TO_WEI,
+        SYSTEM_ADDRESS, SYSTEM_CALL_GAS_LIMIT, WITHDRAWAL_REQUEST_ADDRESS, WITHDRAWAL_REQUEST_TYPE,
+    },
+    requests::{deposit_request, push_request, requests_hash},
+};
+use core::fmt;
+use database::{states::bundle_state::BundleRetention, BundleState, State};
+use revm::{
+    context::{block::BlockEnv, tx::TxEnv, CfgEnv, Context},
+    context_interface::result::{EVMError, ExecutionResult, HaltReason, InvalidTransaction},
+    handler::EthHandler,
+    primitives::{alloy_primitives::Bloom, Address, Bytes, TxKind, B256},
+    specification::hardfork::SpecId,
+    Database, DatabaseCommit, EvmExec, MainEvm,
+};
+use std::vec::Vec;
+use trie::Receipt;
+
+/// Header fields of the block needed for its execution.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockHeader {
+    /// Environment the transactions are executed in.
+    pub env: BlockEnv,
+    /// Hash of the parent block, stored by EIP-2935.
+    pub parent_hash: B256,
+    /// Root of the parent beacon block, stored by EIP-4788.
+    pub parent_beacon_block_root: Option<B256>,
+}
+
+/// Withdrawal from the beacon chain, see EIP-4895.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Withdrawal {
+    /// Index of the withdrawal.
+    pub index: u64,
+    /// Index of the withdrawn validator.
+    pub validator_index: u64,
+    /// Recipient of the withdrawal.
+    pub address: Address,
+    /// Withdrawn amount in gwei.
+    pub amount: u64,
+}
+
+/// Output of the block execution.
+#[derive(Debug)]
+pub struct BlockOutput {
+    /// Receipts of the transactions in the block order.
+    pub receipts: Vec<Receipt>,
+    /// Results of the transactions in the block order.
+    pub results: Vec<ExecutionResult<HaltReason>>,
+    /// Gas used by all transactions of the block.
+    pub gas_used: u64,
+    /// EIP-7685 requests of the block, `None` before Prague.
+    pub requests: Option<Vec<Bytes>>,
+    /// Commitment to the requests, `None` before Prague.
+    pub requests_hash: Option<B256>,
+    /// State changes of the whole block.
+    pub bundle: BundleState,
+}
+
+impl BlockOutput {
+    /// Returns the bloom filter of the logs of all transactions.
+    pub fn logs_bloom(&self) -> Bloom {
+        let mut bloom = Bloom::ZERO;
+        for receipt in &self.receipts {
+            bloom.accrue_bloom(&receipt.logs_bloom);
+        }
+        bloom
+    }
+
+    /// Returns the receipts root of the block.
+    pub fn receipts_root(&self) -> B256 {
+        trie::receipts_root(&self.receipts)
+    }
+}
+
+/// Error of the block execution.
+#[derive(Debug, PartialEq, Eq)]
+pub enum BlockExecutionError<E> {
+    /// Transaction is invalid or the database failed while executing it.
+    Transaction {
+        /// Index of the failed transaction.
+        tx_index: usize,
+        /// Error of the transaction.
+        error: EVMError<E, InvalidTransaction>,
+    },
+    /// Gas limit of the transaction is higher than the gas left in the block.
+    BlockGasLimitExceeded {
+        /// Index of the transaction.
+        tx_index: usize,
+        /// Gas limit of the transaction.
+        gas_limit: u64,
+        /// Gas left in the block.
+        gas_left: u64,
+    },
+    /// The database failed while executing the system call.
+    SystemCall {
+        /// Called system contract.
+        address: Address,
+        /// Error of the system call.
+        error: EVMError<E, InvalidTransaction>,
+    },
+    /// The system call reverted or halted.
+    SystemCallFailed {
+        /// Called system contract.
+        address: Address,
+        /// Result of the system call.
+        result: ExecutionResult<HaltReason>,
+    },
+    /// The system contract required by the spec has no code.
+    MissingSystemContract(Address),
+    /// Log of the deposit contract is not a valid `DepositEvent`.
+    InvalidDepositLog,
+    /// The database failed outside of the transactions.
+    Database(E),
+}
+
+impl<E: fmt::Display> fmt::Display for BlockExecutionError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transaction { tx_index, error } => {
+                write!(f, "transaction {tx_index} failed: {error}")
+            }
+            Self::BlockGasLimitExceeded {
+                tx_index,
+                gas_limit,
+                gas_left,
+            } => write!(
+                f,
+                "transaction {tx_index} gas limit {gas_limit} exceeds the block gas left {gas_left}"
+            ),
+            Self::SystemCall { address, error } => {
+                write!(f, "system call to {address} failed: {error}")
+            }
+            Self::SystemCallFailed { address, result } => {
+                write!(f, "system call to {address} failed: {result:?}")
+            }
+            Self::MissingSystemContract(address) => {
+                write!(f, "system contract {address} has no code")
+            }
+            Self::InvalidDepositLog => f.write_str("invalid deposit log"),
+            Self::Database(e) => write!(f, "database error: {e}"),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for BlockExecutionError<E> {}
+
+/// Executor of whole blocks on top of the [`State`].
+///
+/// Applies the EIP-4788 and EIP-2935 system calls before the transactions, executes the
+/// transactions with cumulative gas accounting, collects the EIP-6110, EIP-7002 and
+/// EIP-7251 requests and finally processes the EIP-4895 withdrawals, in the order of geth.
+///
+/// Blocks can be executed one after another, every [`BlockOutput`] contains the changes of
+/// its block only.
+#[derive(Debug)]
+pub struct BlockExecutor<DB> {
+    state: State<DB>,
+    cfg: CfgEnv,
+    deposit_contract: Option<Address>,
+}
+
+impl<DB: Database> BlockExecutor<DB> {
+    /// Creates the executor on top of the database.
+    pub fn new(db: DB, cfg: CfgEnv) -> Self {
+        let mut state = State::builder()
+            .with_database(db)
+            .with_bundle_update()
+            .build();
+        state.set_state_clear_flag(cfg.spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+        Self {
+            state,
+            cfg,
+            deposit_contract: None,
+        }
+    }
+
+    /// Sets the deposit contract whose logs are collected as EIP-6110 deposit requests.
+    pub fn with_deposit_contract(self, deposit_contract: Address) -> Self {
+        Self {
+            deposit_contract: Some(deposit_contract),
+            ..self
+        }
+    }
+
+    /// Returns the state.
+    pub fn state(&self) -> &State<DB> {
+        &self.state
+    }
+
+    /// Consumes the executor and returns the state.
+    pub fn into_state(self) -> State<DB> {
+        self.state
+    }
+
+    /// Executes the block.
+    pub fn execute_block(
+        &mut self,
+        header: &BlockHeader,
+        txs: &[TxEnv],
+        withdrawals: &[Withdrawal],
+    ) -> Result<BlockOutput, BlockExecutionError<DB::Error>> {
+        self.apply_pre_block_calls(header)?;
+
+        let mut receipts = Vec::with_capacity(txs.len());
+        let mut results = Vec::with_capacity(txs.len());
+        let mut gas_used = 0;
+        for (tx_index, tx) in txs.iter().enumerate() {
+            let gas_left = header.env.gas_limit.saturating_sub(gas_used);
+            if tx.gas_limit > gas_left {
+                return Err(BlockExecutionError::BlockGasLimitExceeded {
+                    tx_index,
+                    gas_limit: tx.gas_limit,
+                    gas_left,
+                });
+            }
+            let output = self
+                .evm(header.env.clone(), tx.clone())
+                .exec()
+                .map_err(|error| BlockExecutionError::Transaction { tx_index, error })?;
+            self.state.commit(output.state);
+
+            gas_used += output.result.gas_used();
+            receipts.push(Receipt::new(tx.tx_type, &output.result, gas_used));
+            results.push(output.result);
+        }
+
+        let requests = if self.cfg.spec.is_enabled_in(SpecId::PRAGUE) {
+            Some(self.collect_requests(&header.env, &receipts)?)
+        } else {
+            None
+        };
+
+        self.state
+            .increment_balances(
+                withdrawals
+                    .iter()
+                    .filter(|withdrawal| withdrawal.amount > 0)
+                    .map(|withdrawal| {
+                        (withdrawal.address, withdrawal.amount as u128 * GWEI_TO_WEI)
+                    }),
+            )
+            .map_err(BlockExecutionError::Database)?;
+
+        self.state.merge_transitions(BundleRetention::Reverts);
+        Ok(BlockOutput {
+            receipts,
+            results,
+            gas_used,
+            requests_hash: requests.as_deref().map(requests_hash),
+            requests,
+            bundle: self.state.take_bundle(),
+        })
+    }
+
+    /// Stores the parent beacon block root (EIP-4788) and the parent hash (EIP-2935).
+    ///
+    /// The calls are skipped in the genesis block and if the contracts are not deployed.
+    fn apply_pre_block_calls(
+        &mut self,
+        header: &BlockHeader,
+    ) -> Result<(), BlockExecutionError<DB::Error>> {
+        if header.env.number == 0 {
+            return Ok(());
+        }
+        if let Some(root) = header.parent_beacon_block_root {
+            if self.cfg.spec.is_enabled_in(SpecId::CANCUN) {
+                self.system_call(&header.env, BEACON_ROOTS_ADDRESS, root.into())?;
+            }
+        }
+        if self.cfg.spec.is_enabled_in(SpecId::PRAGUE) {
+            self.system_call(
+                &header.env,
+                BLOCKHASH_STORAGE_ADDRESS,
+                header.parent_hash.into(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Collects the deposit requests from the receipts and reads the withdrawal and
+    /// consolidation request queues.
+    fn collect_requests(
+        &mut self,
+        block: &BlockEnv,
+        receipts: &[Receipt],
+    ) -> Result<Vec<Bytes>, BlockExecutionError<DB::Error>> {
+        let mut requests = Vec::new();
+        if let Some(deposit_contract) = self.deposit_contract {
+            let mut deposits = Vec::new();
+            let logs = receipts
+                .iter()
+                .flat_map(|receipt| &receipt.logs)
+                .filter(|log| {
+                    log.address == deposit_contract
+                        && log.topics().first() == Some(&DEPOSIT_EVENT_TOPIC)
+                });
+            for log in logs {
+                let request = deposit_request(&log.data.data)
+                    .ok_or(BlockExecutionError::InvalidDepositLog)?;
+                deposits.extend_from_slice(&request);
+            }
+            push_request(&mut requests, DEPOSIT_REQUEST_TYPE, &deposits);
+        }
+
+        for (request_type, address) in [
+            (WITHDRAWAL_REQUEST_TYPE, WITHDRAWAL_REQUEST_ADDRESS),
+            (CONSOLIDATION_REQUEST_TYPE, CONSOLIDATION_REQUEST_ADDRESS),
+        ] {
+            let output = self
+                .system_call(block, address, Bytes::new())?
+                .ok_or(BlockExecutionError::MissingSystemContract(address))?;
+            push_request(&mut requests, request_type, &output);
+        }
+        Ok(requests)
+    }
+
+    /// Calls the system contract from the [`SYSTEM_ADDRESS`] and commits its changes.
+    ///
+    /// The call is free, does not count against the block gas limit and leaves the system
+    /// address and the beneficiary untouched. Returns `None` if the contract has no code.
+    fn system_call(
+        &mut self,
+        block: &BlockEnv,
+        address: Address,
+        data: Bytes,
+    ) -> Result<Option<Bytes>, BlockExecutionError<DB::Error>> {
+        let has_code = self
+            .state
+            .basic(address)
+            .map_err(BlockExecutionError::Database)?
+            .is_some_and(|info| !info.is_empty_code_hash());
+        if !has_code {
+            return Ok(None);
+        }
+        let nonce = self
+            .state
+            .basic(SYSTEM_ADDRESS)
+            .map_err(BlockExecutionError::Database)?
+            .map_or(0, |info| info.nonce);
+
+        // Fees are paid to the system address, which is dropped from the changes.
+        let block = BlockEnv {
+            beneficiary: SYSTEM_ADDRESS,
+            basefee: 0,
+            gas_limit: block.gas_limit.max(SYSTEM_CALL_GAS_LIMIT),
+            ..block.clone()
+        };
+        let tx = TxEnv {
+            caller: SYSTEM_ADDRESS,
+            gas_limit: SYSTEM_CALL_GAS_LIMIT,
+            gas_price: 0,
+            kind: TxKind::Call(address),
+            data,
+            nonce,
+            chain_id: Some(self.cfg.chain_id),
+            ..Default::default()
+        };
+        let mut output = self
+            .evm(block, tx)
+            .exec()
+            .map_err(|error| BlockExecutionError::SystemCall { address, error })?;
+        output.state.remove(&SYSTEM_ADDRESS);
+        self.state.commit(output.state);
+
+        match output.result {
+            ExecutionResult::Success { output, .. } => Ok(Some(output.into_data())),
+            result => Err(BlockExecutionError::SystemCallFailed { address, result }),
+        }
+    }
+
+    fn evm(
+        &mut self,
+        block: BlockEnv,
+        tx: TxEnv,
+    ) -> MainEvm<&mut State<DB>, BlockEnv, TxEnv, CfgEnv> {
+        MainEvm::new(
+            Context::builder()
+                .with_db(&mut self.state)
+                .with_block(block)
+                .with_tx(tx)
+                .with_cfg(self.cfg.clone()),
+            EthHandler::default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::InMemoryDB;
+    use revm::{
+        primitives::{address, b256, U256},
+        state::{AccountInfo, Bytecode},
+    };
+
+    const ALICE: Address = address!("1000000000000000000000000000000000000001");
+    const BOB: Address = address!("2000000000000000000000000000000000000002");
+
+    fn db(contracts: &[(Address, &[u8])]) -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            ALICE,
+            AccountInfo::from_balance(U256::from(1_000_000_000_000_000u64)),
+        );
+        for (address, code) in contracts {
+            let code = Bytecode::new_raw(Bytes::copy_from_slice(code));
+            db.insert_account_info(*address, AccountInfo::from_bytecode(code));
+        }
+        db
+    }
+
+    fn cfg(spec: SpecId) -> CfgEnv {
+        let mut cfg = CfgEnv::default();
+        cfg.spec = spec;
+        cfg
+    }
+
+    fn header(number: u64) -> BlockHeader {
+        BlockHeader {
+            env: BlockEnv {
+                number,
+                timestamp: 12,
+                gas_limit: 100_000,
+                ..Default::default()
+            },
+            parent_hash: B256::repeat_byte(0x11),
+            parent_beacon_block_root: Some(B256::repeat_byte(0x22)),
+        }
+    }
+
+    fn transfer(nonce: u64) -> TxEnv {
+        TxEnv {
+            caller: ALICE,
+            kind: TxKind::Call(BOB),
+            value: U256::from(1),
+            gas_limit: 21_000,
+            nonce,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn transactions_and_withdrawals() {
+        let mut executor = BlockExecutor::new(db(&[]), cfg(SpecId::SHANGHAI));
+        let withdrawal = Withdrawal {
+            address: BOB,
+            amount: 2,
+            ..Default::default()
+        };
+        let output = executor
+            .execute_block(&header(1), &[transfer(0), transfer(1)], &[withdrawal])
+            .unwrap();
+
+        assert_eq!(output.gas_used, 42_000);
+        assert_eq!(output.receipts.len(), 2);
+        assert_eq!(output.receipts[1].cumulative_gas_used, 42_000);
+        assert!(output.receipts.iter().all(|receipt| receipt.success));
+        assert_eq!(output.logs_bloom(), Bloom::ZERO);
+        assert_ne!(output.receipts_root(), trie::EMPTY_ROOT_HASH);
+        assert_eq!(output.requests_hash, None);
+        assert_eq!(
+            output
+                .bundle
+                .account(&BOB)
+                .unwrap()
+                .info
+                .as_ref()
+                .unwrap()
+                .balance,
+            U256::from(2 + 2 * GWEI_TO_WEI)
+        );
+
+        let err = executor
+            .execute_block(&header(2), &[2, 3, 4, 5, 6].map(transfer), &[])
+            .unwrap_err();
+        assert_eq!(
+            err,
+            BlockExecutionError::BlockGasLimitExceeded {
+                tx_index: 4,
+                gas_limit: 21_000,
+                gas_left: 16_000,
+            }
+        );
+    }
+
+    #[test]
+    fn system_calls_and_requests() {
+        // Stores the calldata at the slot of the block timestamp.
+        let store = [0x60, 0x00, 0x35, 0x42, 0x55, 0x00];
+        // Returns the single byte 0xab.
+        let ret = [0x60, 0xab, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3];
+        // Returns nothing.
+        let stop = [0x00];
+        let mut executor = BlockExecutor::new(
+            db(&[
+                (BEACON_ROOTS_ADDRESS, &store),
+                (BLOCKHASH_STORAGE_ADDRESS, &store),
+                (WITHDRAWAL_REQUEST_ADDRESS, &ret),
+                (CONSOLIDATION_REQUEST_ADDRESS, &stop),
+            ]),
+            cfg(SpecId::PRAGUE),
+        );
+        let output = executor
+            .execute_block(&header(1), &[transfer(0)], &[])
+            .unwrap();
+
+        let slot = |address| {
+            output.bundle.account(&address).unwrap().storage[&U256::from(12)].present_value
+        };
+        assert_eq!(slot(BEACON_ROOTS_ADDRESS), U256::from_be_bytes([0x22; 32]));
+        assert_eq!(
+            slot(BLOCKHASH_STORAGE_ADDRESS),
+            U256::from_be_bytes([0x11; 32])
+        );
+        assert!(output.bundle.account(&SYSTEM_ADDRESS).is_none());
+        assert_eq!(output.gas_used, 21_000);
+
+        let requests = output.requests.unwrap();
+        assert_eq!(
+            requests,
+            [Bytes::from_static(&[WITHDRAWAL_REQUEST_TYPE, 0xab])]
+        );
+        assert_eq!(output.requests_hash, Some(requests_hash(&requests)));
+        assert_ne!(
+            output.requests_hash,
+            Some(b256!(
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+            ))
+        );
+    }
+
+    #[test]
+    fn missing_request_contract() {
+        let mut executor = BlockExecutor::new(db(&[]), cfg(SpecId::PRAGUE));
+        assert_eq!(
+            executor.execute_block(&header(1), &[], &[]).unwrap_err(),
+            BlockExecutionError::MissingSystemContract(WITHDRAWAL_REQUEST_ADDRESS)
+        );
+    }
+}
@@ -0,0 +1,15 @@
+//! Block execution.
+//!
+//! [`BlockExecutor`] executes whole blocks on top of [`State`][database::State]: it applies the
+//! pre-block system calls, executes the transactions with cumulative gas accounting, collects
+//! the EIP-7685 requests, processes the withdrawals and returns the receipts together with the
+//! [`BundleState`][database::BundleState] of the block.
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+
+pub mod constants;
+pub mod executor;
+pub mod requests;
+
+pub use executor::{BlockExecutionError, BlockExecutor, BlockHeader, BlockOutput, Withdrawal};
+pub use requests::requests_hash;
+pub use trie::Receipt;
@@ -0,0 +1,86 @@
+//! EIP-7685: General purpose execution layer requests
+use revm::primitives::{Bytes, B256, U256};
+use sha2::{Digest, Sha256};
+use std::vec::Vec;
+
+/// Offsets and sizes of the `pubkey`, `withdrawal_credentials`, `amount`, `signature` and
+/// `index` fields in the ABI-encoded data of the deposit log.
+const DEPOSIT_LOG_FIELDS: [(usize, usize); 5] =
+    [(160, 48), (256, 32), (320, 8), (384, 96), (512, 8)];
+
+/// Size of the ABI-encoded data of the deposit log.
+const DEPOSIT_LOG_SIZE: usize = 576;
+
+/// Returns the commitment to the requests of the block.
+///
+/// Requests are the request type followed by the request data, requests without data are
+/// expected to be left out.
+pub fn requests_hash(requests: &[Bytes]) -> B256 {
+    let mut hasher = Sha256::new();
+    for request in requests {
+        hasher.update(Sha256::digest(request));
+    }
+    B256::from_slice(&hasher.finalize())
+}
+
+/// Appends the request of the given type, unless its data is empty.
+pub fn push_request(requests: &mut Vec<Bytes>, request_type: u8, data: &[u8]) {
+    if data.is_empty() {
+        return;
+    }
+    let mut request = Vec::with_capacity(data.len() + 1);
+    request.push(request_type);
+    request.extend_from_slice(data);
+    requests.push(request.into());
+}
+
+/// Converts the data of the deposit contract log to the EIP-6110 deposit request.
+///
+/// Returns `None` if the log data is not a valid ABI-encoded `DepositEvent`.
+pub fn deposit_request(data: &[u8]) -> Option<Vec<u8>> {
+    if data.len() != DEPOSIT_LOG_SIZE {
+        return None;
+    }
+    let word = |at: usize| U256::from_be_slice(&data[at..at + 32]);
+    let mut request = Vec::with_capacity(192);
+    for (i, (offset, size)) in DEPOSIT_LOG_FIELDS.into_iter().enumerate() {
+        if word(i * 32) != U256::from(offset) || word(offset) != U256::from(size) {
+            return None;
+        }
+        request.extend_from_slice(&data[offset + 32..offset + 32 + size]);
+    }
+    Some(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::b256;
+
+    #[test]
+    fn empty_requests_hash() {
+        assert_eq!(
+            requests_hash(&[]),
+            b256!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+    }
+
+    #[test]
+    fn deposit_log() {
+        let mut data = vec![0u8; DEPOSIT_LOG_SIZE];
+        for (i, (offset, size)) in DEPOSIT_LOG_FIELDS.into_iter().enumerate() {
+            data[i * 32 + 31] = offset as u8;
+            data[i * 32 + 30] = (offset >> 8) as u8;
+            data[offset + 31] = size as u8;
+            data[offset + 32..offset + 32 + size].fill(i as u8 + 1);
+        }
+        let request = deposit_request(&data).unwrap();
+        assert_eq!(request.len(), 192);
+        assert_eq!(&request[..48], &[1; 48]);
+        assert_eq!(&request[184..], &[5; 8]);
+
+        data[31] = 0;
+        assert_eq!(deposit_request(&data), None);
+        assert_eq!(deposit_request(&data[..64]), None);
+    }
+}
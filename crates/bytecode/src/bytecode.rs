@@ -1,5 +1,6 @@
 use crate::{
     eip7702::{Eip7702Bytecode, EIP7702_MAGIC_BYTES},
+    stylus::StylusBytecode,
     BytecodeDecodeError, Eof, JumpTable, LegacyAnalyzedBytecode, LegacyRawBytecode,
     EOF_MAGIC_BYTES,
 };
@@ -17,6 +18,8 @@ pub enum Bytecode {
     Eof(Arc<Eof>),
     /// EIP-7702 delegated bytecode
     Eip7702(Eip7702Bytecode),
+    /// Stylus WASM program
+    Stylus(StylusBytecode),
 }
 
 impl Default for Bytecode {
@@ -71,6 +74,21 @@ impl Bytecode {
         matches!(self, Self::Eip7702(_))
     }
 
+    /// Returns reference to the Stylus program if bytecode is Stylus.
+    #[inline]
+    pub const fn stylus(&self) -> Option<&StylusBytecode> {
+        match self {
+            Self::Stylus(stylus) => Some(stylus),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if bytecode is a Stylus program.
+    #[inline]
+    pub const fn is_stylus(&self) -> bool {
+        matches!(self, Self::Stylus(_))
+    }
+
     /// Creates a new legacy [`Bytecode`].
     #[inline]
     pub fn new_legacy(raw: Bytes) -> Self {
@@ -95,9 +113,14 @@ impl Bytecode {
 
     /// Creates a new raw [`Bytecode`].
     ///
-    /// Returns an error on incorrect bytecode format.
+    /// Returns an error on incorrect bytecode format. Bytes with the Stylus prefix are decoded
+    /// as a Stylus program only if the `stylus` feature is enabled.
     #[inline]
     pub fn new_raw_checked(bytes: Bytes) -> Result<Self, BytecodeDecodeError> {
+        #[cfg(feature = "stylus")]
+        if bytes.starts_with(&crate::STYLUS_MAGIC_BYTES) {
+            return Ok(Self::Stylus(StylusBytecode::new_raw(bytes)?));
+        }
         let prefix = bytes.get(..2);
         match prefix {
             Some(prefix) if prefix == &EOF_MAGIC_BYTES => {
@@ -139,6 +162,7 @@ impl Bytecode {
             Self::LegacyAnalyzed(analyzed) => analyzed.bytecode(),
             Self::Eof(eof) => &eof.body.code,
             Self::Eip7702(code) => code.raw(),
+            Self::Stylus(code) => code.raw(),
         }
     }
 
@@ -167,6 +191,7 @@ impl Bytecode {
             Self::LegacyAnalyzed(analyzed) => analyzed.original_bytes(),
             Self::Eof(eof) => eof.raw().clone(),
             Self::Eip7702(eip7702) => eip7702.raw().clone(),
+            Self::Stylus(stylus) => stylus.raw().clone(),
         }
     }
 
@@ -177,6 +202,7 @@ impl Bytecode {
             Self::LegacyAnalyzed(analyzed) => analyzed.original_byte_slice(),
             Self::Eof(eof) => eof.raw(),
             Self::Eip7702(eip7702) => eip7702.raw(),
+            Self::Stylus(stylus) => stylus.raw(),
         }
     }
 
@@ -196,8 +222,50 @@ impl Bytecode {
 #[cfg(test)]
 mod tests {
     use super::{Bytecode, Eof};
+    use primitives::bytes;
     use std::sync::Arc;
 
+    #[test]
+    #[cfg(feature = "stylus")]
+    fn stylus_prefix() {
+        use crate::StylusDictionary;
+
+        let bytecode = Bytecode::new_raw(bytes!("eff00000aabb"));
+        let stylus = bytecode.stylus().expect("Stylus bytecode");
+        assert_eq!(stylus.dictionary, StylusDictionary::Empty);
+        assert_eq!(stylus.compressed_wasm(), &[0xaa, 0xbb]);
+        assert_eq!(bytecode.original_byte_slice(), &bytes!("eff00000aabb")[..]);
+    }
+
+    #[test]
+    #[cfg(feature = "stylus")]
+    fn invalid_stylus_prefix() {
+        use crate::{BytecodeDecodeError, StylusDecodeError};
+
+        assert_eq!(
+            Bytecode::new_raw_checked(bytes!("eff000ff")),
+            Err(BytecodeDecodeError::Stylus(
+                StylusDecodeError::UnknownDictionary
+            ))
+        );
+        assert_eq!(
+            Bytecode::new_raw_checked(bytes!("eff000")),
+            Err(BytecodeDecodeError::Stylus(
+                StylusDecodeError::InvalidLength
+            ))
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "stylus"))]
+    fn stylus_prefix_is_legacy() {
+        for raw in [bytes!("eff00000aabb"), bytes!("eff000ff")] {
+            let bytecode = Bytecode::new_raw(raw.clone());
+            assert!(matches!(bytecode, Bytecode::LegacyAnalyzed(_)), "{raw}");
+            assert_eq!(bytecode.original_byte_slice(), &raw[..]);
+        }
+    }
+
     #[test]
     fn eof_arc_clone() {
         let eof = Arc::new(Eof::default());
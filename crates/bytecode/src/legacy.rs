@@ -1,7 +1,11 @@
 mod analyzed;
+pub mod blocks;
 mod jump_map;
 mod raw;
 
 pub use analyzed::LegacyAnalyzedBytecode;
+pub use blocks::{BasicBlock, BlockAnalysis, Step};
+#[cfg(feature = "std")]
+pub use blocks::{BlockAnalysisCache, DEFAULT_BLOCK_ANALYSIS_CACHE_SIZE};
 pub use jump_map::JumpTable;
 pub use raw::{analyze_legacy, LegacyRawBytecode};
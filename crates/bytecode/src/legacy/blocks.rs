@@ -0,0 +1,471 @@
+//! Basic block analysis of legacy bytecode.
+//!
+//! The analysis splits the bytecode into basic blocks and sums the static gas costs of their
+//! instructions, so that the interpreter can charge the gas once per block. Common instruction
+//! sequences are fused into superinstructions that are executed with a single dispatch.
+use super::LegacyAnalyzedBytecode;
+use crate::opcode::{self, OpCode};
+use core::ops::Range;
+use std::{boxed::Box, vec, vec::Vec};
+
+#[cfg(feature = "std")]
+use core::num::NonZeroUsize;
+#[cfg(feature = "std")]
+use lru::LruCache;
+#[cfg(feature = "std")]
+use primitives::{keccak256, Bytes, B256};
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+/// Step of a [`BasicBlock`], a single instruction or a superinstruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Step {
+    /// Single instruction, executed with the instruction table.
+    Instruction,
+    /// `PUSH1`-`PUSH32` followed by `JUMP`.
+    ///
+    /// The target is `None` if the pushed value is not a valid jump destination.
+    PushJump(Option<u32>),
+    /// `PUSH1`-`PUSH32` followed by `JUMPI`.
+    ///
+    /// The target is `None` if the pushed value is not a valid jump destination.
+    PushJumpi(Option<u32>),
+    /// `DUP1`-`DUP16` followed by `SWAP1`-`SWAP16`.
+    DupSwap,
+}
+
+/// Sequence of instructions that is only entered at its first instruction and only left after
+/// its last instruction.
+///
+/// Blocks start at `JUMPDEST` and after the instructions that end a block, see
+/// [`ends_block`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BasicBlock {
+    /// Program counter of the first instruction.
+    pub start: usize,
+    /// Sum of the static gas costs of the instructions.
+    pub static_gas: u64,
+    /// Range of the block in [`BlockAnalysis::steps`].
+    pub steps: Range<usize>,
+}
+
+/// Basic blocks and superinstructions of legacy bytecode.
+///
+/// The static gas costs of the blocks are computed with the costs the analysis was created
+/// with, see [`BlockAnalysis::static_costs`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BlockAnalysis {
+    /// Static gas costs of the instructions, indexed by opcode.
+    static_costs: Box<[u64; 256]>,
+    /// Index of the block starting at the program counter, [`u32::MAX`] if no block starts there.
+    block_starts: Box<[u32]>,
+    /// Basic blocks, ordered by their start.
+    blocks: Box<[BasicBlock]>,
+    /// Steps of all blocks.
+    steps: Box<[Step]>,
+}
+
+impl BlockAnalysis {
+    /// Analyzes the bytecode with the given static gas costs of the instructions.
+    ///
+    /// The padding of the bytecode is analyzed as well, so that every instruction the
+    /// interpreter can reach belongs to a block.
+    pub fn new(bytecode: &LegacyAnalyzedBytecode, static_costs: &[u64; 256]) -> Self {
+        let code = bytecode.bytecode().as_ref();
+        let jump_table = bytecode.jump_table();
+        let mut block_starts = vec![u32::MAX; code.len()].into_boxed_slice();
+        let mut blocks = Vec::new();
+        let mut steps = Vec::new();
+        let mut block: Option<BasicBlock> = None;
+
+        let mut pc = 0;
+        while pc < code.len() {
+            let op = code[pc];
+            if op == opcode::JUMPDEST {
+                if let Some(mut block) = block.take() {
+                    block.steps.end = steps.len();
+                    blocks.push(block);
+                }
+            }
+            let current = block.get_or_insert_with(|| {
+                block_starts[pc] = blocks.len() as u32;
+                BasicBlock {
+                    start: pc,
+                    static_gas: 0,
+                    steps: steps.len()..steps.len(),
+                }
+            });
+
+            let immediate = immediate_len(op);
+            let next_pc = pc + 1 + immediate;
+            let next = code.get(next_pc).copied().unwrap_or(opcode::STOP);
+            let step = match (op, next) {
+                (opcode::PUSH1..=opcode::PUSH32, opcode::JUMP | opcode::JUMPI) => {
+                    let target = code
+                        .get(pc + 1..next_pc)
+                        .and_then(|immediate| {
+                            immediate.iter().try_fold(0u32, |target, &byte| {
+                                target.checked_mul(256)?.checked_add(byte as u32)
+                            })
+                        })
+                        .filter(|&target| jump_table.is_valid(target as usize));
+                    if next == opcode::JUMP {
+                        Some(Step::PushJump(target))
+                    } else {
+                        Some(Step::PushJumpi(target))
+                    }
+                }
+                (opcode::DUP1..=opcode::DUP16, opcode::SWAP1..=opcode::SWAP16) => {
+                    Some(Step::DupSwap)
+                }
+                _ => None,
+            };
+
+            let last = match step {
+                Some(step) => {
+                    steps.push(step);
+                    current.static_gas = current
+                        .static_gas
+                        .saturating_add(static_costs[op as usize])
+                        .saturating_add(static_costs[next as usize]);
+                    pc = next_pc + 1;
+                    next
+                }
+                None => {
+                    steps.push(Step::Instruction);
+                    current.static_gas =
+                        current.static_gas.saturating_add(static_costs[op as usize]);
+                    pc = next_pc;
+                    op
+                }
+            };
+            if ends_block(last) {
+                if let Some(mut block) = block.take() {
+                    block.steps.end = steps.len();
+                    blocks.push(block);
+                }
+            }
+        }
+        if let Some(mut block) = block.take() {
+            block.steps.end = steps.len();
+            blocks.push(block);
+        }
+
+        Self {
+            static_costs: Box::new(*static_costs),
+            block_starts,
+            blocks: blocks.into_boxed_slice(),
+            steps: steps.into_boxed_slice(),
+        }
+    }
+
+    /// Returns the static gas costs the analysis was created with.
+    #[inline]
+    pub fn static_costs(&self) -> &[u64; 256] {
+        &self.static_costs
+    }
+
+    /// Returns the block starting at the program counter.
+    #[inline]
+    pub fn block_at(&self, pc: usize) -> Option<&BasicBlock> {
+        let index = *self.block_starts.get(pc)?;
+        self.blocks.get(index as usize)
+    }
+
+    /// Returns the basic blocks, ordered by their start.
+    #[inline]
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+
+    /// Returns the steps of all blocks, see [`BasicBlock::steps`].
+    #[inline]
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+}
+
+/// Returns `true` if the instruction ends a basic block.
+///
+/// Besides jumps and terminating instructions, blocks end after the instructions that read the
+/// remaining gas, as the static gas of the following instructions is already charged.
+#[inline]
+pub const fn ends_block(opcode: u8) -> bool {
+    match OpCode::info_by_op(opcode) {
+        Some(info) if info.is_terminating() => true,
+        Some(_) => matches!(
+            opcode,
+            opcode::JUMP
+                | opcode::JUMPI
+                | opcode::GAS
+                | opcode::SSTORE
+                | opcode::CREATE
+                | opcode::CALL
+                | opcode::CALLCODE
+                | opcode::DELEGATECALL
+                | opcode::CREATE2
+                | opcode::STATICCALL
+        ),
+        None => true,
+    }
+}
+
+/// Returns the number of immediate bytes of the legacy instruction.
+#[inline]
+const fn immediate_len(opcode: u8) -> usize {
+    match opcode {
+        opcode::PUSH1..=opcode::PUSH32 => (opcode - opcode::PUSH0) as usize,
+        _ => 0,
+    }
+}
+
+/// Default number of analyses held by the [`BlockAnalysisCache`].
+#[cfg(feature = "std")]
+pub const DEFAULT_BLOCK_ANALYSIS_CACHE_SIZE: usize = 4096;
+
+/// Cache of block analyses keyed by code hash.
+///
+/// An analysis is only returned for the bytecode it was created from and with the requested
+/// static gas costs, and only inserted if the code hash is the hash of the bytecode. The cache
+/// holds a bounded number of analyses, the least recently used analysis is evicted first.
+/// Use [`BlockAnalysisCache::global`] to share analyses between EVM instances.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct BlockAnalysisCache {
+    analyses: Mutex<LruCache<B256, CachedAnalysis>>,
+}
+
+/// Analysis with the padded bytecode it was created from.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct CachedAnalysis {
+    bytecode: Bytes,
+    analysis: Arc<BlockAnalysis>,
+}
+
+#[cfg(feature = "std")]
+impl Default for BlockAnalysisCache {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_BLOCK_ANALYSIS_CACHE_SIZE)
+    }
+}
+
+#[cfg(feature = "std")]
+impl BlockAnalysisCache {
+    /// Creates an empty cache with the [`DEFAULT_BLOCK_ANALYSIS_CACHE_SIZE`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty cache holding at most `capacity` analyses, at least one.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            analyses: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Returns the cache shared by all EVM instances.
+    pub fn global() -> &'static Self {
+        static INSTANCE: OnceLock<BlockAnalysisCache> = OnceLock::new();
+        INSTANCE.get_or_init(Self::new)
+    }
+
+    /// Returns the analysis if it was created from the bytecode with the given static gas costs.
+    pub fn get(
+        &self,
+        code_hash: B256,
+        bytecode: &LegacyAnalyzedBytecode,
+        static_costs: &[u64; 256],
+    ) -> Option<Arc<BlockAnalysis>> {
+        self.analyses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&code_hash)
+            .filter(|cached| {
+                cached.bytecode == *bytecode.bytecode()
+                    && cached.analysis.static_costs() == static_costs
+            })
+            .map(|cached| cached.analysis.clone())
+    }
+
+    /// Inserts the analysis of the bytecode and returns it, replaces the analysis with other
+    /// static gas costs.
+    ///
+    /// The analysis is not cached if the code hash is not the hash of the bytecode.
+    pub fn insert(
+        &self,
+        code_hash: B256,
+        bytecode: &LegacyAnalyzedBytecode,
+        analysis: BlockAnalysis,
+    ) -> Arc<BlockAnalysis> {
+        let analysis = Arc::new(analysis);
+        if keccak256(bytecode.original_byte_slice()) == code_hash {
+            self.analyses
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .put(
+                    code_hash,
+                    CachedAnalysis {
+                        bytecode: bytecode.bytecode().clone(),
+                        analysis: analysis.clone(),
+                    },
+                );
+        }
+        analysis
+    }
+
+    /// Returns the analysis, analyzes the bytecode if it is not cached.
+    pub fn get_or_analyze(
+        &self,
+        code_hash: B256,
+        bytecode: &LegacyAnalyzedBytecode,
+        static_costs: &[u64; 256],
+    ) -> Arc<BlockAnalysis> {
+        if let Some(analysis) = self.get(code_hash, bytecode, static_costs) {
+            return analysis;
+        }
+        self.insert(
+            code_hash,
+            bytecode,
+            BlockAnalysis::new(bytecode, static_costs),
+        )
+    }
+
+    /// Returns the number of cached analyses.
+    pub fn len(&self) -> usize {
+        self.analyses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns `true` if no analysis is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached analyses.
+    pub fn clear(&self) {
+        self.analyses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{opcode::*, LegacyRawBytecode};
+
+    fn analyze(code: &[u8]) -> BlockAnalysis {
+        let costs = core::array::from_fn(|op| op as u64);
+        BlockAnalysis::new(
+            &LegacyRawBytecode(code.to_vec().into()).into_analyzed(),
+            &costs,
+        )
+    }
+
+    #[test]
+    fn basic_blocks() {
+        let analysis = analyze(&[
+            PUSH1, 0x01, ADD, JUMPDEST, // Block starts at jumpdest.
+            CALLER, GAS, // Block ends after GAS.
+            PUSH0, PUSH1, 0x08, JUMPI, // Jump into push data is invalid.
+            PUSH2, 0x00, 0x03, JUMP, STOP,
+        ]);
+        let blocks = analysis.blocks();
+        assert_eq!(blocks[0].start, 0);
+        assert_eq!(blocks[0].static_gas, (PUSH1 + ADD) as u64);
+        assert_eq!(blocks[1].start, 3);
+        assert_eq!(
+            blocks[1].static_gas,
+            (JUMPDEST + CALLER) as u64 + GAS as u64
+        );
+        assert_eq!(blocks[2].start, 6);
+        assert_eq!(
+            analysis.steps()[blocks[2].steps.clone()],
+            [Step::Instruction, Step::PushJumpi(None)]
+        );
+        assert_eq!(blocks[2].static_gas, (PUSH0 + PUSH1) as u64 + JUMPI as u64);
+        assert_eq!(
+            analysis.steps()[blocks[3].steps.clone()],
+            [Step::PushJump(Some(3))]
+        );
+        assert_eq!(blocks[4].start, 14);
+
+        assert_eq!(analysis.block_at(3), Some(&blocks[1]));
+        assert_eq!(analysis.block_at(4), None);
+        // Padding is analyzed as well.
+        assert!(analysis.block_at(15).is_some());
+    }
+
+    #[test]
+    fn dup_swap() {
+        let analysis = analyze(&[DUP2, SWAP1, DUP1, DUP3, SWAP2]);
+        assert_eq!(
+            analysis.steps()[analysis.blocks()[0].steps.clone()],
+            [
+                Step::DupSwap,
+                Step::Instruction,
+                Step::DupSwap,
+                Step::Instruction
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn cache() {
+        let cache = BlockAnalysisCache::new();
+        let code = [PUSH0, SLOAD];
+        let hash = keccak256(code);
+        let bytecode = LegacyRawBytecode(code.into()).into_analyzed();
+        let costs = [1; 256];
+        let analysis = cache.get_or_analyze(hash, &bytecode, &costs);
+        // Block ends at the `STOP` of the padding.
+        assert_eq!(analysis.blocks()[0].static_gas, 3);
+        assert!(Arc::ptr_eq(
+            &analysis,
+            &cache.get_or_analyze(hash, &bytecode, &costs)
+        ));
+        // Analyses with other costs are replaced.
+        assert!(cache.get(hash, &bytecode, &[2; 256]).is_none());
+        let analysis = cache.get_or_analyze(hash, &bytecode, &[2; 256]);
+        assert_eq!(analysis.blocks()[0].static_gas, 6);
+        assert_eq!(cache.len(), 1);
+
+        // Other bytecode under the hash is analyzed, but not cached.
+        let other = LegacyRawBytecode([PUSH0, PUSH0, SLOAD].into()).into_analyzed();
+        assert!(cache.get(hash, &other, &[2; 256]).is_none());
+        let analysis = cache.get_or_analyze(hash, &other, &[2; 256]);
+        assert_eq!(analysis.blocks()[0].static_gas, 8);
+        assert!(cache.get(hash, &bytecode, &[2; 256]).is_some());
+        cache.get_or_analyze(B256::ZERO, &other, &[2; 256]);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn cache_evicts_least_recently_used() {
+        let cache = BlockAnalysisCache::with_capacity(2);
+        let costs = [1; 256];
+        let [a, b, c] = [ADD, MUL, SUB].map(|op| {
+            let code = [PUSH0, PUSH0, op];
+            (
+                keccak256(code),
+                LegacyRawBytecode(code.into()).into_analyzed(),
+            )
+        });
+        cache.get_or_analyze(a.0, &a.1, &costs);
+        cache.get_or_analyze(b.0, &b.1, &costs);
+        // Use `a`, so that `b` is evicted.
+        assert!(cache.get(a.0, &a.1, &costs).is_some());
+        cache.get_or_analyze(c.0, &c.1, &costs);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(a.0, &a.1, &costs).is_some());
+        assert!(cache.get(b.0, &b.1, &costs).is_none());
+        assert!(cache.get(c.0, &c.1, &costs).is_some());
+    }
+}
@@ -11,6 +11,7 @@ pub mod eip7702;
 pub mod eof;
 pub mod legacy;
 pub mod opcode;
+pub mod stylus;
 pub mod utils;
 
 pub use bitvec;
@@ -24,3 +25,4 @@ pub use eof::{
     Eof, EOF_MAGIC, EOF_MAGIC_BYTES, EOF_MAGIC_HASH,
 };
 pub use legacy::{JumpTable, LegacyAnalyzedBytecode, LegacyRawBytecode};
+pub use stylus::{StylusBytecode, StylusDecodeError, StylusDictionary, STYLUS_MAGIC_BYTES};
@@ -0,0 +1,142 @@
+use core::fmt;
+use primitives::{bytes, Bytes};
+
+/// Stylus program magic number in array form.
+///
+/// Deployed Stylus programs consist of the magic, a dictionary byte and the
+/// brotli-compressed WASM module.
+pub static STYLUS_MAGIC_BYTES: Bytes = bytes!("eff000");
+
+/// Length of the Stylus prefix, magic followed by the dictionary byte.
+pub const STYLUS_PREFIX_LEN: usize = 4;
+
+/// Brotli dictionary used to compress the WASM module of a Stylus program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StylusDictionary {
+    /// Compressed without dictionary, `0x00`.
+    Empty,
+    /// Compressed with the Stylus program dictionary, `0x01`.
+    StylusProgram,
+}
+
+impl StylusDictionary {
+    /// Returns the dictionary encoded in the given prefix byte.
+    #[inline]
+    pub const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0x00 => Some(Self::Empty),
+            0x01 => Some(Self::StylusProgram),
+            _ => None,
+        }
+    }
+}
+
+/// Bytecode of an Arbitrum Stylus program.
+///
+/// Format of Stylus bytecode consist of:
+/// `0xEFF000` (MAGIC) + dictionary byte + brotli-compressed WASM.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StylusBytecode {
+    pub dictionary: StylusDictionary,
+    pub raw: Bytes,
+}
+
+impl StylusBytecode {
+    /// Creates a new Stylus bytecode or returns an error if the prefix is invalid.
+    ///
+    /// The compressed WASM is not checked here, it is decoded when the program is executed.
+    #[inline]
+    pub fn new_raw(raw: Bytes) -> Result<Self, StylusDecodeError> {
+        if !raw.starts_with(&STYLUS_MAGIC_BYTES) {
+            return Err(StylusDecodeError::InvalidMagic);
+        }
+        let Some(&dictionary) = raw.get(STYLUS_MAGIC_BYTES.len()) else {
+            return Err(StylusDecodeError::InvalidLength);
+        };
+        let dictionary =
+            StylusDictionary::from_byte(dictionary).ok_or(StylusDecodeError::UnknownDictionary)?;
+        Ok(Self { dictionary, raw })
+    }
+
+    /// Creates a new Stylus bytecode from the compressed WASM module.
+    pub fn new(dictionary: StylusDictionary, compressed_wasm: &[u8]) -> Self {
+        let mut raw = STYLUS_MAGIC_BYTES.to_vec();
+        raw.push(dictionary as u8);
+        raw.extend_from_slice(compressed_wasm);
+        Self {
+            dictionary,
+            raw: raw.into(),
+        }
+    }
+
+    /// Returns the raw bytecode with the magic and dictionary prefix.
+    #[inline]
+    pub fn raw(&self) -> &Bytes {
+        &self.raw
+    }
+
+    /// Returns the brotli-compressed WASM module.
+    #[inline]
+    pub fn compressed_wasm(&self) -> &[u8] {
+        &self.raw[STYLUS_PREFIX_LEN..]
+    }
+}
+
+/// Stylus bytecode errors
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StylusDecodeError {
+    /// Bytecode is missing the dictionary byte.
+    InvalidLength,
+    /// Invalid magic number
+    ///
+    /// All Stylus programs should start with the magic number 0xEFF000.
+    InvalidMagic,
+    /// Dictionary byte is not known.
+    UnknownDictionary,
+}
+
+impl fmt::Display for StylusDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::InvalidLength => "Stylus program is missing the dictionary byte",
+            Self::InvalidMagic => "Bytecode is not starting with 0xEFF000",
+            Self::UnknownDictionary => "Unknown Stylus dictionary",
+        };
+        f.write_str(s)
+    }
+}
+
+impl core::error::Error for StylusDecodeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanity_decode() {
+        assert_eq!(
+            StylusBytecode::new_raw(bytes!("eff0")),
+            Err(StylusDecodeError::InvalidMagic)
+        );
+        assert_eq!(
+            StylusBytecode::new_raw(bytes!("eff000")),
+            Err(StylusDecodeError::InvalidLength)
+        );
+        assert_eq!(
+            StylusBytecode::new_raw(bytes!("eff00002")),
+            Err(StylusDecodeError::UnknownDictionary)
+        );
+
+        let raw = bytes!("eff0000011223344");
+        let stylus = StylusBytecode::new_raw(raw.clone()).unwrap();
+        assert_eq!(stylus.dictionary, StylusDictionary::Empty);
+        assert_eq!(stylus.compressed_wasm(), &[0x11, 0x22, 0x33, 0x44]);
+        assert_eq!(
+            StylusBytecode::new(StylusDictionary::Empty, &[0x11, 0x22, 0x33, 0x44]),
+            stylus
+        );
+    }
+}
@@ -1,8 +1,12 @@
+pub mod gas_schedule;
+
+pub use gas_schedule::GasSchedule;
+
 use auto_impl::auto_impl;
 use core::fmt::Debug;
 use core::hash::Hash;
-use primitives::{TxKind, U256};
-use specification::hardfork::SpecId;
+use primitives::{Address, TxKind, U256};
+use specification::{constants::DEFAULT_INK_PRICE, hardfork::SpecId};
 
 #[auto_impl(&, &mut, Box, Arc)]
 pub trait Cfg {
@@ -20,6 +24,55 @@ pub trait Cfg {
 
     fn max_code_size(&self) -> usize;
 
+    /// Returns the gas costs of the instructions and transactions.
+    ///
+    /// Defaults to the schedule of the [`spec`][Cfg::spec].
+    fn gas_schedule(&self) -> &GasSchedule {
+        GasSchedule::for_spec(self.spec().into())
+    }
+
+    /// Returns `true` if the static gas of legacy bytecode is charged once per basic block.
+    ///
+    /// Defaults to `false`.
+    fn is_block_analysis_enabled(&self) -> bool {
+        false
+    }
+
+    /// Returns the number of executions after which legacy bytecode is compiled to native code.
+    ///
+    /// `None` disables native compilation and is the default.
+    fn jit_threshold(&self) -> Option<u64> {
+        None
+    }
+
+    /// Returns the price of one gas in ink, the unit of Stylus WASM execution.
+    ///
+    /// Defaults to [`DEFAULT_INK_PRICE`].
+    fn ink_price(&self) -> u32 {
+        DEFAULT_INK_PRICE
+    }
+
+    /// Returns the version Stylus programs have to be activated with.
+    ///
+    /// Version `0` disables Stylus and is the default.
+    fn stylus_version(&self) -> u16 {
+        0
+    }
+
+    /// Returns `true` if Stylus programs can be deployed, activated and executed.
+    ///
+    /// If disabled, EIP-3541 rejects Stylus programs like any other code starting with `0xEF`.
+    fn is_stylus_enabled(&self) -> bool {
+        self.stylus_version() != 0
+    }
+
+    /// Returns the precompiles moved to other addresses, as `(from, to)` pairs.
+    ///
+    /// Defaults to no moved precompiles.
+    fn precompile_moves(&self) -> &[(Address, Address)] {
+        &[]
+    }
+
     fn is_eip3607_disabled(&self) -> bool;
 
     fn is_balance_check_disabled(&self) -> bool;
@@ -66,3 +119,67 @@ pub trait CfgGetter {
 
     fn cfg(&self) -> &Self::Cfg;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Configuration implementing only the required methods.
+    struct MinimalCfg;
+
+    impl Cfg for MinimalCfg {
+        type Spec = SpecId;
+
+        fn chain_id(&self) -> u64 {
+            1
+        }
+
+        fn spec(&self) -> Self::Spec {
+            SpecId::PRAGUE
+        }
+
+        fn blob_max_count(&self, _spec_id: SpecId) -> u8 {
+            6
+        }
+
+        fn max_code_size(&self) -> usize {
+            0x6000
+        }
+
+        fn is_eip3607_disabled(&self) -> bool {
+            false
+        }
+
+        fn is_balance_check_disabled(&self) -> bool {
+            false
+        }
+
+        fn is_gas_refund_disabled(&self) -> bool {
+            false
+        }
+
+        fn is_block_gas_limit_disabled(&self) -> bool {
+            false
+        }
+
+        fn is_nonce_check_disabled(&self) -> bool {
+            false
+        }
+
+        fn is_base_fee_check_disabled(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        let cfg = MinimalCfg;
+        assert_eq!(cfg.gas_schedule(), GasSchedule::for_spec(SpecId::PRAGUE));
+        assert!(!cfg.is_block_analysis_enabled());
+        assert_eq!(cfg.jit_threshold(), None);
+        assert_eq!(cfg.ink_price(), DEFAULT_INK_PRICE);
+        assert_eq!(cfg.stylus_version(), 0);
+        assert!(!cfg.is_stylus_enabled());
+        assert!(cfg.precompile_moves().is_empty());
+    }
+}
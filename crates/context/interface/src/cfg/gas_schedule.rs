@@ -0,0 +1,380 @@
+//! Gas costs of the EVM instructions and transactions.
+use bytecode::opcode::*;
+use specification::{eip7702, hardfork::SpecId};
+
+/// Gas costs of the EVM instructions and transactions.
+///
+/// [`GasSchedule::new`] reproduces the costs of a hardfork, custom chains can start from it
+/// and override single entries, e.g. `GasSchedule::new(SpecId::CANCUN).with_static_cost(SLOAD, 500)`.
+///
+/// Rules that are not a price, as the EIP-150 call gas limit or the EIP-161 account creation
+/// conditions, still follow the [`SpecId`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasSchedule {
+    /// Static cost of every opcode, charged before the instruction is executed.
+    ///
+    /// Before EIP-2929 this includes the flat cost of the account and storage accesses.
+    #[cfg_attr(feature = "serde", serde(with = "static_costs_serde"))]
+    pub static_costs: [u64; 256],
+    /// EIP-2929: Cost of an account or a storage slot that was already accessed.
+    pub warm_storage_read_cost: u64,
+    /// EIP-2929: Cost of the first access of an account in the transaction.
+    pub cold_account_access_cost: u64,
+    /// EIP-2929: Cost of the first access of a storage slot in the transaction.
+    pub cold_sload_cost: u64,
+    /// Cost of every byte of the `EXP` exponent.
+    pub exp_byte_cost: u64,
+    /// Linear cost of a word of memory.
+    pub memory_word_cost: u64,
+    /// Divisor of the quadratic cost of the memory, zero disables the quadratic cost.
+    pub memory_quadratic_divisor: u64,
+    /// Cost of an `SSTORE` that sets a zero slot.
+    pub sstore_set_cost: u64,
+    /// Cost of an `SSTORE` that changes a non-zero slot, without the cold access.
+    pub sstore_reset_cost: u64,
+    /// Refund of an `SSTORE` that clears a slot.
+    pub sstore_clears_refund: u64,
+    /// EIP-2200: Charges and refunds `SSTORE` against the original value of the slot.
+    ///
+    /// The cost of a no-op store is the warm `SLOAD` cost.
+    pub sstore_net_metering: bool,
+    /// Base cost of a transaction.
+    pub tx_base_cost: u64,
+    /// EIP-2: Additional cost of a contract creation transaction.
+    pub tx_create_cost: u64,
+    /// Cost of a calldata token, a zero byte is one token.
+    pub tx_token_cost: u64,
+    /// EIP-2028: Number of tokens of a non-zero calldata byte.
+    pub tx_non_zero_byte_tokens: u64,
+    /// EIP-7623: Floor cost of a calldata token, `None` if transactions have no floor cost.
+    pub tx_floor_token_cost: Option<u64>,
+    /// EIP-2930: Cost of an address in the access list.
+    pub access_list_address_cost: u64,
+    /// EIP-2930: Cost of a storage key in the access list.
+    pub access_list_storage_key_cost: u64,
+    /// EIP-3860: Cost of a word of initcode.
+    pub initcode_word_cost: u64,
+    /// EIP-7702: Cost of an authorization.
+    pub authorization_cost: u64,
+}
+
+impl GasSchedule {
+    /// Hardforks that changed the gas schedule.
+    const FORKS: [SpecId; 9] = [
+        SpecId::FRONTIER,
+        SpecId::HOMESTEAD,
+        SpecId::TANGERINE,
+        SpecId::SPURIOUS_DRAGON,
+        SpecId::ISTANBUL,
+        SpecId::BERLIN,
+        SpecId::LONDON,
+        SpecId::SHANGHAI,
+        SpecId::PRAGUE,
+    ];
+
+    /// Creates the gas schedule of the hardfork.
+    ///
+    /// Opcodes that are not yet enabled in the hardfork still have their cost.
+    pub const fn new(spec_id: SpecId) -> Self {
+        const fn set(static_costs: &mut [u64; 256], opcodes: &[u8], cost: u64) {
+            let mut i = 0;
+            while i < opcodes.len() {
+                static_costs[opcodes[i] as usize] = cost;
+                i += 1;
+            }
+        }
+
+        let mut static_costs = [0; 256];
+        set(&mut static_costs, &[JUMPDEST], 1);
+        set(
+            &mut static_costs,
+            &[
+                ADDRESS,
+                ORIGIN,
+                CALLER,
+                CALLVALUE,
+                CALLDATASIZE,
+                CODESIZE,
+                GASPRICE,
+                RETURNDATASIZE,
+                COINBASE,
+                TIMESTAMP,
+                NUMBER,
+                DIFFICULTY,
+                GASLIMIT,
+                CHAINID,
+                BASEFEE,
+                BLOBBASEFEE,
+                POP,
+                PC,
+                MSIZE,
+                GAS,
+                PUSH0,
+                DATASIZE,
+                RJUMP,
+            ],
+            2,
+        );
+        set(
+            &mut static_costs,
+            &[
+                ADD,
+                SUB,
+                LT,
+                GT,
+                SLT,
+                SGT,
+                EQ,
+                ISZERO,
+                AND,
+                OR,
+                XOR,
+                NOT,
+                BYTE,
+                SHL,
+                SHR,
+                SAR,
+                CALLDATALOAD,
+                CALLDATACOPY,
+                CODECOPY,
+                RETURNDATACOPY,
+                BLOBHASH,
+                MLOAD,
+                MSTORE,
+                MSTORE8,
+                MCOPY,
+                DATALOADN,
+                DATACOPY,
+                RETF,
+                DUPN,
+                SWAPN,
+                EXCHANGE,
+                RETURNDATALOAD,
+            ],
+            3,
+        );
+        let mut op = PUSH1;
+        while op <= SWAP16 {
+            static_costs[op as usize] = 3;
+            op += 1;
+        }
+        set(&mut static_costs, &[DATALOAD, RJUMPI, RJUMPV], 4);
+        set(
+            &mut static_costs,
+            &[
+                MUL,
+                DIV,
+                SDIV,
+                MOD,
+                SMOD,
+                SIGNEXTEND,
+                SELFBALANCE,
+                CALLF,
+                JUMPF,
+            ],
+            5,
+        );
+        set(&mut static_costs, &[ADDMOD, MULMOD, JUMP], 8);
+        set(&mut static_costs, &[EXP, JUMPI], 10);
+        set(&mut static_costs, &[BLOCKHASH], 20);
+        set(&mut static_costs, &[KECCAK256], 30);
+        set(&mut static_costs, &[TLOAD, TSTORE], 100);
+        let mut topics = 0;
+        while topics <= 4 {
+            static_costs[(LOG0 + topics) as usize] = 375 * (topics as u64 + 1);
+            topics += 1;
+        }
+        set(&mut static_costs, &[CREATE, CREATE2, EOFCREATE], 32000);
+
+        // EIP-2929: Gas cost increases for state access opcodes
+        //
+        // The flat access costs are replaced by the warm and cold costs.
+        if !spec_id.is_enabled_in(SpecId::BERLIN) {
+            // EIP-150: Gas cost changes for IO-heavy operations
+            // EIP-1884: Repricing for trie-size-dependent opcodes
+            let (balance, extcode, extcodehash, sload, call) =
+                if spec_id.is_enabled_in(SpecId::ISTANBUL) {
+                    (700, 700, 700, 800, 700)
+                } else if spec_id.is_enabled_in(SpecId::TANGERINE) {
+                    (400, 700, 400, 200, 700)
+                } else {
+                    (20, 20, 400, 50, 40)
+                };
+            set(&mut static_costs, &[BALANCE], balance);
+            set(&mut static_costs, &[EXTCODESIZE, EXTCODECOPY], extcode);
+            set(&mut static_costs, &[EXTCODEHASH], extcodehash);
+            set(&mut static_costs, &[SLOAD], sload);
+            set(
+                &mut static_costs,
+                &[CALL, CALLCODE, DELEGATECALL, STATICCALL],
+                call,
+            );
+        }
+        if spec_id.is_enabled_in(SpecId::TANGERINE) {
+            set(&mut static_costs, &[SELFDESTRUCT], 5000);
+        }
+
+        let (warm_storage_read_cost, cold_account_access_cost, cold_sload_cost) =
+            if spec_id.is_enabled_in(SpecId::BERLIN) {
+                (100, 2600, 2100)
+            } else {
+                (0, 0, 0)
+            };
+
+        Self {
+            static_costs,
+            warm_storage_read_cost,
+            cold_account_access_cost,
+            cold_sload_cost,
+            // EIP-160: EXP cost increase
+            exp_byte_cost: if spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON) {
+                50
+            } else {
+                10
+            },
+            memory_word_cost: 3,
+            memory_quadratic_divisor: 512,
+            sstore_set_cost: 20000,
+            sstore_reset_cost: if spec_id.is_enabled_in(SpecId::BERLIN) {
+                5000 - cold_sload_cost
+            } else {
+                5000
+            },
+            // EIP-3529: Reduction in refunds
+            sstore_clears_refund: if spec_id.is_enabled_in(SpecId::LONDON) {
+                5000 - cold_sload_cost + 1900
+            } else {
+                15000
+            },
+            sstore_net_metering: spec_id.is_enabled_in(SpecId::ISTANBUL),
+            tx_base_cost: 21000,
+            tx_create_cost: if spec_id.is_enabled_in(SpecId::HOMESTEAD) {
+                32000
+            } else {
+                0
+            },
+            tx_token_cost: 4,
+            // EIP-2028: Transaction data gas cost reduction
+            tx_non_zero_byte_tokens: if spec_id.is_enabled_in(SpecId::ISTANBUL) {
+                4
+            } else {
+                17
+            },
+            tx_floor_token_cost: if spec_id.is_enabled_in(SpecId::PRAGUE) {
+                Some(10)
+            } else {
+                None
+            },
+            access_list_address_cost: 2400,
+            access_list_storage_key_cost: 1900,
+            initcode_word_cost: if spec_id.is_enabled_in(SpecId::SHANGHAI) {
+                2
+            } else {
+                0
+            },
+            authorization_cost: if spec_id.is_enabled_in(SpecId::PRAGUE) {
+                eip7702::PER_EMPTY_ACCOUNT_COST
+            } else {
+                0
+            },
+        }
+    }
+
+    /// Returns the shared gas schedule of the hardfork.
+    pub fn for_spec(spec_id: SpecId) -> &'static Self {
+        static SCHEDULES: [GasSchedule; GasSchedule::FORKS.len()] = {
+            let mut i = 0;
+            let mut schedules =
+                [const { GasSchedule::new(SpecId::FRONTIER) }; GasSchedule::FORKS.len()];
+            while i < GasSchedule::FORKS.len() {
+                schedules[i] = GasSchedule::new(GasSchedule::FORKS[i]);
+                i += 1;
+            }
+            schedules
+        };
+
+        let index = Self::FORKS
+            .iter()
+            .rposition(|fork| spec_id.is_enabled_in(*fork))
+            .unwrap_or_default();
+        &SCHEDULES[index]
+    }
+
+    /// Sets the static cost of the opcode.
+    pub const fn with_static_cost(mut self, opcode: u8, cost: u64) -> Self {
+        self.static_costs[opcode as usize] = cost;
+        self
+    }
+}
+
+impl Default for GasSchedule {
+    fn default() -> Self {
+        Self::for_spec(SpecId::default()).clone()
+    }
+}
+
+#[cfg(feature = "serde")]
+mod static_costs_serde {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+    use std::vec::Vec;
+
+    pub(super) fn serialize<S: Serializer>(
+        costs: &[u64; 256],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        costs.as_slice().serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[u64; 256], D::Error> {
+        Vec::<u64>::deserialize(deserializer)?
+            .try_into()
+            .map_err(|costs: Vec<u64>| Error::invalid_length(costs.len(), &"256 static costs"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_schedules_match_hardforks() {
+        for spec_id in (0..=u8::MAX).filter_map(SpecId::try_from_u8) {
+            assert_eq!(
+                GasSchedule::for_spec(spec_id),
+                &GasSchedule::new(spec_id),
+                "{spec_id:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn hardfork_costs() {
+        let frontier = GasSchedule::new(SpecId::FRONTIER);
+        assert_eq!(frontier.static_costs[SLOAD as usize], 50);
+        assert_eq!(frontier.static_costs[CALL as usize], 40);
+        assert_eq!(frontier.static_costs[SELFDESTRUCT as usize], 0);
+        assert_eq!(frontier.tx_create_cost, 0);
+
+        let istanbul = GasSchedule::new(SpecId::ISTANBUL);
+        assert_eq!(istanbul.static_costs[BALANCE as usize], 700);
+        assert_eq!(istanbul.static_costs[SLOAD as usize], 800);
+        assert_eq!(istanbul.tx_non_zero_byte_tokens, 4);
+        assert!(istanbul.sstore_net_metering);
+
+        let london = GasSchedule::new(SpecId::LONDON);
+        assert_eq!(london.static_costs[SLOAD as usize], 0);
+        assert_eq!(london.cold_sload_cost, 2100);
+        assert_eq!(london.sstore_reset_cost, 2900);
+        assert_eq!(london.sstore_clears_refund, 4800);
+        assert_eq!(london.tx_floor_token_cost, None);
+
+        let prague = GasSchedule::new(SpecId::PRAGUE);
+        assert_eq!(prague.tx_floor_token_cost, Some(10));
+        assert_eq!(prague.static_costs[LOG2 as usize], 1125);
+        assert_eq!(prague.static_costs[PUSH32 as usize], 3);
+        assert_eq!(prague.static_costs[STOP as usize], 0);
+    }
+}
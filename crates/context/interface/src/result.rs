@@ -476,6 +476,11 @@ pub enum HaltReason {
     SubRoutineStackOverflow,
     /// Check for target address validity is only done inside subcall.
     InvalidEXTCALLTarget,
+
+    /// Stylus program is not activated.
+    ProgramNotActivated,
+    /// Stylus program was activated with an older version and needs to be reactivated.
+    ProgramVersionMismatch,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
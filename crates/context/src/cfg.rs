@@ -1,7 +1,8 @@
-pub use context_interface::Cfg;
+pub use context_interface::{Cfg, GasSchedule};
 
 use interpreter::MAX_CODE_SIZE;
-use specification::hardfork::SpecId;
+use primitives::Address;
+use specification::{constants::DEFAULT_INK_PRICE, hardfork::SpecId};
 use std::{vec, vec::Vec};
 
 /// EVM configuration
@@ -29,6 +30,47 @@ pub struct CfgEnv<SPEC: Into<SpecId> = SpecId> {
     ///
     /// Note : Items must be sorted by `SpecId`.
     pub blob_target_and_max_count: Vec<(SpecId, u8, u8)>,
+    /// Gas costs of the instructions and transactions.
+    ///
+    /// By default it is `None` and the schedule of the `spec` hardfork is used.
+    pub gas_schedule: Option<GasSchedule>,
+    /// Charges the static gas once per basic block of the legacy bytecode and executes common
+    /// instruction sequences as superinstructions.
+    ///
+    /// Gas usage is not affected, but a block that runs out of gas halts with out of gas even
+    /// if one of its instructions would halt earlier. Analyses are cached by code hash, init
+    /// code and execution with an inspector are metered per instruction.
+    ///
+    /// By default it is `false`.
+    pub block_analysis: bool,
+    /// Number of executions of legacy bytecode after which it is compiled to native code.
+    ///
+    /// Compiled code is cached by code hash and produces the same results and gas usage as the
+    /// interpreter. Requires the `jit` feature, init code and execution with an inspector are
+    /// always interpreted.
+    ///
+    /// By default it is `None` and bytecode is not compiled.
+    pub jit_threshold: Option<u64>,
+    /// Price of one gas in ink, the unit of Stylus WASM execution.
+    ///
+    /// Ink consumed by Stylus programs is converted to gas at the call boundary.
+    ///
+    /// By default it is `10_000`.
+    pub ink_price: u32,
+    /// Version Stylus programs have to be activated with.
+    ///
+    /// Programs activated with another version halt until they are reactivated. Set it to
+    /// [`STYLUS_VERSION`][specification::constants::STYLUS_VERSION] to enable Stylus.
+    ///
+    /// By default it is `0` and Stylus is disabled.
+    pub stylus_version: u16,
+    /// Precompiles moved to other addresses, as `(from, to)` pairs.
+    ///
+    /// The precompile is served at `to`, while `from` becomes a regular account. Used to
+    /// simulate calls with [`StateOverride`][crate::StateOverride].
+    ///
+    /// By default it is empty.
+    pub precompile_moves: Vec<(Address, Address)>,
     /// A hard memory limit in bytes beyond which
     /// [OutOfGasError::Memory][context_interface::result::OutOfGasError::Memory] cannot be resized.
     ///
@@ -119,6 +161,33 @@ impl<SPEC: Into<SpecId> + Copy> Cfg for CfgEnv<SPEC> {
         self.limit_contract_code_size.unwrap_or(MAX_CODE_SIZE)
     }
 
+    fn gas_schedule(&self) -> &GasSchedule {
+        match &self.gas_schedule {
+            Some(schedule) => schedule,
+            None => GasSchedule::for_spec(self.spec.into()),
+        }
+    }
+
+    fn is_block_analysis_enabled(&self) -> bool {
+        self.block_analysis
+    }
+
+    fn jit_threshold(&self) -> Option<u64> {
+        self.jit_threshold
+    }
+
+    fn ink_price(&self) -> u32 {
+        self.ink_price
+    }
+
+    fn stylus_version(&self) -> u16 {
+        self.stylus_version
+    }
+
+    fn precompile_moves(&self) -> &[(Address, Address)] {
+        &self.precompile_moves
+    }
+
     fn is_eip3607_disabled(&self) -> bool {
         cfg_if::cfg_if! {
             if #[cfg(feature = "optional_eip3607")] {
@@ -182,6 +251,12 @@ impl Default for CfgEnv {
             spec: SpecId::PRAGUE,
             disable_nonce_check: false,
             blob_target_and_max_count: vec![(SpecId::CANCUN, 3, 6), (SpecId::PRAGUE, 6, 9)],
+            gas_schedule: None,
+            block_analysis: false,
+            jit_threshold: None,
+            ink_price: DEFAULT_INK_PRICE,
+            stylus_version: 0,
+            precompile_moves: Vec::new(),
             #[cfg(feature = "memory_limit")]
             memory_limit: (1 << 32) - 1,
             #[cfg(feature = "optional_balance_check")]
@@ -201,6 +276,16 @@ impl Default for CfgEnv {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{BlockEnv, TxEnv};
+    use bytecode::{opcode, Bytecode};
+    use core::cell::RefCell;
+    use interpreter::{
+        gas::calculate_initial_tx_gas,
+        interpreter::{EthInterpreter, ExtBytecode},
+        table::make_metered_instruction_table,
+        DummyHost, InputsImpl, Interpreter, SharedMemory,
+    };
+    use std::rc::Rc;
 
     #[test]
     fn blob_max_and_target_count() {
@@ -210,4 +295,45 @@ mod test {
         assert_eq!(cfg.blob_max_count(SpecId::PRAGUE), (9));
         assert_eq!(cfg.blob_max_count(SpecId::OSAKA), (9));
     }
+
+    /// Returns the gas spent by the code with the gas schedule of the configuration.
+    fn gas_spent(cfg: CfgEnv, code: &[u8]) -> u64 {
+        let mut host = DummyHost::<BlockEnv, TxEnv, CfgEnv> {
+            cfg,
+            ..Default::default()
+        };
+        let table = make_metered_instruction_table(host.cfg.gas_schedule());
+        let mut interpreter = Interpreter::<EthInterpreter>::new(
+            Rc::new(RefCell::new(SharedMemory::new())),
+            ExtBytecode::new(Bytecode::new_raw(code.to_vec().into())),
+            InputsImpl::default(),
+            false,
+            false,
+            host.cfg.spec,
+            100_000,
+        );
+        let _ = interpreter.run(&table, &mut host);
+        interpreter.control.gas.spent()
+    }
+
+    #[test]
+    fn custom_gas_schedule() {
+        let code = [opcode::PUSH0, opcode::SLOAD, opcode::STOP];
+        // PUSH0 + cold SLOAD.
+        assert_eq!(gas_spent(CfgEnv::default(), &code), 2 + 2_100);
+
+        let mut cfg = CfgEnv::default();
+        let schedule = GasSchedule::for_spec(cfg.spec)
+            .clone()
+            .with_static_cost(opcode::SLOAD, 1_000);
+        cfg.gas_schedule = Some(GasSchedule {
+            tx_base_cost: 1_000,
+            ..schedule
+        });
+        assert_eq!(gas_spent(cfg.clone(), &code), 2 + 1_000 + 2_100);
+        assert_eq!(
+            calculate_initial_tx_gas(cfg.gas_schedule(), &[], false, 0, 0, 0).initial_gas,
+            1_000
+        );
+    }
 }
@@ -32,4 +32,16 @@ impl<DB> JournaledState<DB> {
             precompiles: self.precompiles.clone(),
         }
     }
+
+    /// Replaces the journal with the journal of the init, keeps the database.
+    pub fn restore_init(&mut self, init: JournalInit) {
+        self.state = init.state;
+        self.transient_storage = init.transient_storage;
+        self.logs = init.logs;
+        self.depth = init.depth;
+        self.journal = init.journal;
+        self.spec = init.spec;
+        self.warm_preloaded_addresses = init.warm_preloaded_addresses;
+        self.precompiles = init.precompiles;
+    }
 }
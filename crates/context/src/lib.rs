@@ -10,6 +10,7 @@ pub mod cfg;
 pub mod context;
 mod journal_init;
 pub mod journaled_state;
+pub mod overrides;
 pub mod tx;
 
 pub use block::BlockEnv;
@@ -17,4 +18,5 @@ pub use cfg::{Cfg, CfgEnv};
 pub use context::*;
 pub use journal_init::JournalInit;
 pub use journaled_state::*;
+pub use overrides::{AccountOverride, BlockOverride, OverrideDB, StateOverride, StorageOverride};
 pub use tx::TxEnv;
@@ -0,0 +1,395 @@
+//! State and block overrides to simulate calls on a modified state.
+use crate::{BlockEnv, CfgEnv, Context, JournaledState};
+use bytecode::Bytecode;
+use context_interface::{Block, Journal, Transaction};
+use database_interface::{Database, DatabaseRef};
+use primitives::{Address, HashMap, B256, U256};
+use specification::hardfork::SpecId;
+use state::AccountInfo;
+use std::vec::Vec;
+
+/// Override of the account storage.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StorageOverride {
+    /// Replaces the whole storage, slots not in the map are zero.
+    Replace(HashMap<U256, U256>),
+    /// Replaces the given slots, other slots are read from the database.
+    Diff(HashMap<U256, U256>),
+}
+
+/// Override of a single account, fields that are `None` are left unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountOverride {
+    /// Balance of the account.
+    pub balance: Option<U256>,
+    /// Nonce of the account.
+    pub nonce: Option<u64>,
+    /// Code of the account.
+    pub code: Option<Bytecode>,
+    /// Storage of the account.
+    pub storage: Option<StorageOverride>,
+    /// Address the precompile at this address is moved to.
+    ///
+    /// The address itself becomes a regular account.
+    pub move_precompile_to: Option<Address>,
+}
+
+impl AccountOverride {
+    /// Sets the balance of the account.
+    pub fn with_balance(mut self, balance: U256) -> Self {
+        self.balance = Some(balance);
+        self
+    }
+
+    /// Sets the nonce of the account.
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// Sets the code of the account.
+    pub fn with_code(mut self, code: Bytecode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Replaces the whole storage of the account.
+    pub fn with_storage(mut self, storage: HashMap<U256, U256>) -> Self {
+        self.storage = Some(StorageOverride::Replace(storage));
+        self
+    }
+
+    /// Replaces the given storage slots of the account.
+    pub fn with_storage_diff(mut self, storage: HashMap<U256, U256>) -> Self {
+        self.storage = Some(StorageOverride::Diff(storage));
+        self
+    }
+
+    /// Moves the precompile at this address to the given address.
+    pub fn with_precompile_moved_to(mut self, address: Address) -> Self {
+        self.move_precompile_to = Some(address);
+        self
+    }
+
+    /// Returns `true` if the account state is overridden, not only the precompile moved.
+    fn overrides_account(&self) -> bool {
+        self.balance.is_some()
+            || self.nonce.is_some()
+            || self.code.is_some()
+            || self.storage.is_some()
+    }
+}
+
+/// Overrides of the account states, like the state override set of geth `eth_call`.
+///
+/// Applied to a database with the [OverrideDB], which leaves the database itself untouched.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateOverride {
+    /// Overrides by the account address.
+    pub accounts: HashMap<Address, AccountOverride>,
+}
+
+impl StateOverride {
+    /// Adds the override of the account, replacing the previous one.
+    pub fn with_account(mut self, address: Address, account: AccountOverride) -> Self {
+        self.accounts.insert(address, account);
+        self
+    }
+
+    /// Returns `true` if there are no overrides.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Returns the precompile moves as `(from, to)` pairs, see [`CfgEnv::precompile_moves`].
+    pub fn precompile_moves(&self) -> Vec<(Address, Address)> {
+        let mut moves: Vec<_> = self
+            .accounts
+            .iter()
+            .filter_map(|(from, account)| account.move_precompile_to.map(|to| (*from, to)))
+            .collect();
+        moves.sort_unstable();
+        moves
+    }
+}
+
+impl FromIterator<(Address, AccountOverride)> for StateOverride {
+    fn from_iter<T: IntoIterator<Item = (Address, AccountOverride)>>(iter: T) -> Self {
+        Self {
+            accounts: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Overrides of the block environment, fields that are `None` are left unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockOverride {
+    /// Number of the block.
+    pub number: Option<u64>,
+    /// Beneficiary of the block.
+    pub beneficiary: Option<Address>,
+    /// Timestamp of the block.
+    pub timestamp: Option<u64>,
+    /// Gas limit of the block.
+    pub gas_limit: Option<u64>,
+    /// Base fee of the block.
+    pub basefee: Option<u64>,
+    /// Difficulty of the block.
+    pub difficulty: Option<U256>,
+    /// Randomness of the block.
+    pub prevrandao: Option<B256>,
+}
+
+/// A [Database] applying the [StateOverride] on top of the wrapped database.
+///
+/// Overrides live only in the wrapper, so the wrapped database and later transactions
+/// executed on it are not affected.
+#[derive(Clone, Debug, Default)]
+pub struct OverrideDB<DB> {
+    /// The underlying database.
+    pub db: DB,
+    overrides: StateOverride,
+    /// Code of the overridden accounts by their hash.
+    codes: HashMap<B256, Bytecode>,
+}
+
+impl<DB> OverrideDB<DB> {
+    /// Creates a new database applying the overrides on top of the database.
+    pub fn new(db: DB, overrides: StateOverride) -> Self {
+        let codes = overrides
+            .accounts
+            .values()
+            .filter_map(|account| account.code.clone())
+            .map(|code| (code.hash_slow(), code))
+            .collect();
+        Self {
+            db,
+            overrides,
+            codes,
+        }
+    }
+
+    /// Returns the overrides.
+    pub fn overrides(&self) -> &StateOverride {
+        &self.overrides
+    }
+
+    fn override_account(&self, address: Address, info: Option<AccountInfo>) -> Option<AccountInfo> {
+        let Some(account) = self
+            .overrides
+            .accounts
+            .get(&address)
+            .filter(|account| account.overrides_account())
+        else {
+            return info;
+        };
+        // Overridden accounts exist even if they are missing in the database.
+        let mut info = info.unwrap_or_default();
+        if let Some(balance) = account.balance {
+            info.balance = balance;
+        }
+        if let Some(nonce) = account.nonce {
+            info.nonce = nonce;
+        }
+        if let Some(code) = &account.code {
+            info.code_hash = code.hash_slow();
+            info.code = Some(code.clone());
+        }
+        Some(info)
+    }
+
+    /// Returns the overridden storage value, or `None` if it has to be read from the database.
+    fn override_storage(&self, address: Address, index: U256) -> Option<U256> {
+        match self.overrides.accounts.get(&address)?.storage.as_ref()? {
+            StorageOverride::Replace(storage) => {
+                Some(storage.get(&index).copied().unwrap_or_default())
+            }
+            StorageOverride::Diff(storage) => storage.get(&index).copied(),
+        }
+    }
+}
+
+impl<DB: Database> Database for OverrideDB<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic(address)?;
+        Ok(self.override_account(address, info))
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.codes.get(&code_hash) {
+            Some(code) => Ok(code.clone()),
+            None => self.db.code_by_hash(code_hash),
+        }
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        match self.override_storage(address, index) {
+            Some(value) => Ok(value),
+            None => self.db.storage(address, index),
+        }
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash(number)
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for OverrideDB<DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic_ref(address)?;
+        Ok(self.override_account(address, info))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.codes.get(&code_hash) {
+            Some(code) => Ok(code.clone()),
+            None => self.db.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        match self.override_storage(address, index) {
+            Some(value) => Ok(value),
+            None => self.db.storage_ref(address, index),
+        }
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+impl<BLOCK, TX, SPEC, DB, JOURNAL, CHAIN> Context<BLOCK, TX, CfgEnv<SPEC>, DB, JOURNAL, CHAIN>
+where
+    BLOCK: Block,
+    TX: Transaction,
+    SPEC: Into<SpecId> + Copy,
+    DB: Database,
+    JOURNAL: Journal<Database = DB>,
+{
+    /// Creates a new context executing on the database with the state overrides applied.
+    ///
+    /// Precompile moves of the overrides replace [`CfgEnv::precompile_moves`].
+    #[allow(clippy::type_complexity)]
+    pub fn with_state_override<ODB: Database>(
+        mut self,
+        db: ODB,
+        overrides: StateOverride,
+    ) -> Context<BLOCK, TX, CfgEnv<SPEC>, OverrideDB<ODB>, JournaledState<OverrideDB<ODB>>, CHAIN>
+    {
+        self.cfg.precompile_moves = overrides.precompile_moves();
+        self.with_db(OverrideDB::new(db, overrides))
+    }
+}
+
+impl BlockEnv {
+    /// Applies the block overrides.
+    pub fn apply_override(&mut self, overrides: &BlockOverride) {
+        if let Some(number) = overrides.number {
+            self.number = number;
+        }
+        if let Some(beneficiary) = overrides.beneficiary {
+            self.beneficiary = beneficiary;
+        }
+        if let Some(timestamp) = overrides.timestamp {
+            self.timestamp = timestamp;
+        }
+        if let Some(gas_limit) = overrides.gas_limit {
+            self.gas_limit = gas_limit;
+        }
+        if let Some(basefee) = overrides.basefee {
+            self.basefee = basefee;
+        }
+        if let Some(difficulty) = overrides.difficulty {
+            self.difficulty = difficulty;
+        }
+        if let Some(prevrandao) = overrides.prevrandao {
+            self.prevrandao = Some(prevrandao);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::CacheDB;
+    use database_interface::EmptyDB;
+    use primitives::address;
+
+    #[test]
+    fn override_db() {
+        let contract = Address::ZERO;
+        let other = Address::with_last_byte(0xaa);
+        let code = Bytecode::new_raw([0x00].into());
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(contract, AccountInfo::default());
+        db.insert_account_storage(contract, U256::from(1), U256::from(1))
+            .unwrap();
+        db.insert_account_storage(contract, U256::from(2), U256::from(2))
+            .unwrap();
+
+        let overrides = StateOverride::default()
+            .with_account(
+                contract,
+                AccountOverride::default()
+                    .with_balance(U256::from(10))
+                    .with_storage_diff([(U256::from(1), U256::from(5))].into_iter().collect()),
+            )
+            .with_account(
+                other,
+                AccountOverride::default()
+                    .with_code(code.clone())
+                    .with_storage(HashMap::default()),
+            );
+        let mut override_db = OverrideDB::new(&mut db, overrides);
+
+        assert_eq!(
+            override_db.basic(contract).unwrap().unwrap().balance,
+            U256::from(10)
+        );
+        assert_eq!(
+            override_db.storage(contract, U256::from(1)),
+            Ok(U256::from(5))
+        );
+        assert_eq!(
+            override_db.storage(contract, U256::from(2)),
+            Ok(U256::from(2))
+        );
+
+        // Missing account is created with the code and an empty storage.
+        let info = override_db.basic(other).unwrap().unwrap();
+        assert_eq!(info.code_hash, code.hash_slow());
+        assert_eq!(override_db.code_by_hash(info.code_hash), Ok(code));
+        assert_eq!(override_db.storage(other, U256::from(1)), Ok(U256::ZERO));
+
+        // The wrapped database is untouched.
+        assert_eq!(db.basic(contract).unwrap().unwrap().balance, U256::ZERO);
+        assert_eq!(db.storage(contract, U256::from(1)), Ok(U256::from(1)));
+        assert_eq!(db.basic(other).unwrap(), None);
+    }
+
+    #[test]
+    fn precompile_moves() {
+        let ecrecover = address!("0000000000000000000000000000000000000001");
+        let moved = Address::with_last_byte(0xaa);
+        let overrides = StateOverride::default().with_account(
+            ecrecover,
+            AccountOverride::default().with_precompile_moved_to(moved),
+        );
+        let context = Context::default().with_state_override(EmptyDB::default(), overrides);
+        assert_eq!(context.cfg.precompile_moves, vec![(ecrecover, moved)]);
+        // Moving the precompile alone does not create the account.
+        assert_eq!(
+            context.journaled_state.database.basic_ref(ecrecover),
+            Ok(None)
+        );
+    }
+}
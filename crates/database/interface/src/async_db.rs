@@ -2,8 +2,10 @@ use core::future::Future;
 
 use crate::{DBErrorMarker, Database, DatabaseRef};
 use core::error::Error;
+use futures::future::try_join_all;
 use primitives::{Address, B256, U256};
 use state::{AccountInfo, Bytecode};
+use std::vec::Vec;
 use tokio::runtime::{Handle, Runtime};
 
 /// The async EVM database interface
@@ -166,6 +168,29 @@ impl<T: DatabaseAsyncRef> DatabaseRef for WrapDatabaseAsync<T> {
     fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
         self.rt.block_on(self.db.block_hash_async_ref(number))
     }
+
+    /// Fetches the accounts concurrently.
+    #[inline]
+    fn basic_many_ref(
+        &self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        self.rt.block_on(try_join_all(
+            addresses
+                .iter()
+                .map(|address| self.db.basic_async_ref(*address)),
+        ))
+    }
+
+    /// Fetches the storage values concurrently.
+    #[inline]
+    fn storage_many_ref(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        self.rt.block_on(try_join_all(
+            slots
+                .iter()
+                .map(|(address, index)| self.db.storage_async_ref(*address, *index)),
+        ))
+    }
 }
 
 // Hold a tokio runtime handle or full runtime
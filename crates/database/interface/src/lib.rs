@@ -11,7 +11,7 @@ use auto_impl::auto_impl;
 use core::error::Error;
 use primitives::{Address, HashMap, B256, U256};
 use state::{Account, AccountInfo, Bytecode};
-use std::string::String;
+use std::{string::String, vec::Vec};
 
 #[cfg(feature = "asyncdb")]
 pub mod async_db;
@@ -50,6 +50,31 @@ pub trait Database {
 
     /// Gets block hash by block number.
     fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error>;
+
+    /// Gets basic information of many accounts.
+    ///
+    /// Default implementation fetches accounts one by one, databases with expensive round trips
+    /// should fetch them in a batch.
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        addresses
+            .iter()
+            .map(|address| self.basic(*address))
+            .collect()
+    }
+
+    /// Gets many storage values, given as address and index pairs.
+    ///
+    /// Default implementation fetches values one by one, databases with expensive round trips
+    /// should fetch them in a batch.
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        slots
+            .iter()
+            .map(|(address, index)| self.storage(*address, *index))
+            .collect()
+    }
 }
 
 /// EVM database commit interface.
@@ -81,6 +106,29 @@ pub trait DatabaseRef {
 
     /// Gets block hash by block number.
     fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error>;
+
+    /// Gets basic information of many accounts.
+    ///
+    /// Default implementation fetches accounts one by one, see [`Database::basic_many`].
+    fn basic_many_ref(
+        &self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        addresses
+            .iter()
+            .map(|address| self.basic_ref(*address))
+            .collect()
+    }
+
+    /// Gets many storage values, given as address and index pairs.
+    ///
+    /// Default implementation fetches values one by one, see [`Database::storage_many`].
+    fn storage_many_ref(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        slots
+            .iter()
+            .map(|(address, index)| self.storage_ref(*address, *index))
+            .collect()
+    }
 }
 
 /// Wraps a [`DatabaseRef`] to provide a [`Database`] implementation.
@@ -116,6 +164,19 @@ impl<T: DatabaseRef> Database for WrapDatabaseRef<T> {
     fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
         self.0.block_hash_ref(number)
     }
+
+    #[inline]
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        self.0.basic_many_ref(addresses)
+    }
+
+    #[inline]
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        self.0.storage_many_ref(slots)
+    }
 }
 
 impl<T: DatabaseRef + DatabaseCommit> DatabaseCommit for WrapDatabaseRef<T> {
@@ -277,6 +277,62 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
             }
         }
     }
+
+    /// Loads the missing accounts in one batch, see [DatabaseRef::basic_many_ref].
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        let missing: Vec<Address> = addresses
+            .iter()
+            .copied()
+            .filter(|address| !self.accounts.contains_key(address))
+            .collect();
+        let infos = self.db.basic_many_ref(&missing)?;
+        for (address, info) in missing.into_iter().zip(infos) {
+            self.accounts.entry(address).or_insert_with(|| {
+                info.map(|info| DbAccount {
+                    info,
+                    ..Default::default()
+                })
+                .unwrap_or_else(DbAccount::new_not_existing)
+            });
+        }
+        addresses
+            .iter()
+            .map(|address| self.basic(*address))
+            .collect()
+    }
+
+    /// Loads the missing slots of loaded accounts in one batch, see
+    /// [DatabaseRef::storage_many_ref].
+    ///
+    /// Slots of the accounts that are not loaded are fetched one by one.
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        let missing: Vec<(Address, U256)> = slots
+            .iter()
+            .copied()
+            .filter(|(address, index)| {
+                self.accounts.get(address).is_some_and(|account| {
+                    !account.storage.contains_key(index)
+                        && !matches!(
+                            account.account_state,
+                            AccountState::StorageCleared | AccountState::NotExisting
+                        )
+                })
+            })
+            .collect();
+        let values = self.db.storage_many_ref(&missing)?;
+        for ((address, index), value) in missing.into_iter().zip(values) {
+            if let Some(account) = self.accounts.get_mut(&address) {
+                account.storage.entry(index).or_insert(value);
+            }
+        }
+        slots
+            .iter()
+            .map(|(address, index)| self.storage(*address, *index))
+            .collect()
+    }
 }
 
 impl<ExtDB: DatabaseRef> DatabaseRef for CacheDB<ExtDB> {
@@ -444,9 +500,10 @@ impl Database for BenchmarkDB {
 #[cfg(test)]
 mod tests {
     use super::{CacheDB, EmptyDB};
-    use database_interface::Database;
-    use primitives::{Address, HashMap, U256};
-    use state::AccountInfo;
+    use core::{cell::Cell, convert::Infallible};
+    use database_interface::{Database, DatabaseRef};
+    use primitives::{Address, HashMap, B256, U256};
+    use state::{AccountInfo, Bytecode};
 
     #[test]
     fn test_insert_account_storage() {
@@ -500,6 +557,67 @@ mod tests {
         assert_eq!(new_state.storage(account, key1), Ok(value1));
     }
 
+    #[test]
+    fn test_batch_load() {
+        /// Database that counts the reads that are not batched.
+        struct BatchDB(CacheDB<EmptyDB>, Cell<usize>);
+
+        impl DatabaseRef for BatchDB {
+            type Error = Infallible;
+
+            fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Infallible> {
+                self.1.set(self.1.get() + 1);
+                self.0.basic_ref(address)
+            }
+
+            fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Infallible> {
+                self.0.code_by_hash_ref(code_hash)
+            }
+
+            fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Infallible> {
+                self.1.set(self.1.get() + 1);
+                self.0.storage_ref(address, index)
+            }
+
+            fn block_hash_ref(&self, number: u64) -> Result<B256, Infallible> {
+                self.0.block_hash_ref(number)
+            }
+
+            fn basic_many_ref(
+                &self,
+                addresses: &[Address],
+            ) -> Result<Vec<Option<AccountInfo>>, Infallible> {
+                self.0.basic_many_ref(addresses)
+            }
+
+            fn storage_many_ref(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Infallible> {
+                self.0.storage_many_ref(slots)
+            }
+        }
+
+        let (account, missing) = (Address::with_last_byte(42), Address::with_last_byte(43));
+        let (key, value) = (U256::from(123), U256::from(456));
+        let mut init_state = CacheDB::new(EmptyDB::default());
+        init_state.insert_account_info(account, AccountInfo::from_balance(U256::from(1)));
+        init_state
+            .insert_account_storage(account, key, value)
+            .unwrap();
+
+        let mut state = CacheDB::new(BatchDB(init_state, Cell::new(0)));
+        let infos = state.basic_many(&[account, missing]).unwrap();
+        assert_eq!(infos[0].as_ref().unwrap().balance, U256::from(1));
+        assert_eq!(infos[1], None);
+        assert_eq!(
+            state.storage_many(&[(account, key)]),
+            Ok(Vec::from([value]))
+        );
+
+        // Everything is served from the cache.
+        assert_eq!(state.storage(account, key), Ok(value));
+        assert_eq!(state.basic(missing), Ok(None));
+        assert_eq!(state.db.1.get(), 0);
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_serialize_deserialize_cachedb() {
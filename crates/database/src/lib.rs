@@ -4,14 +4,20 @@
 mod alloydb;
 
 pub mod in_memory_db;
+#[cfg(feature = "persistent-cache")]
+mod persistent_db;
 pub mod states;
+pub mod witness;
 
 #[cfg(feature = "alloydb")]
 pub use alloydb::{AlloyDB, BlockId};
 
 pub use in_memory_db::*;
+#[cfg(feature = "persistent-cache")]
+pub use persistent_db::{PersistentCacheDB, PERSISTENT_CACHE_VERSION};
 pub use states::{
-    AccountRevert, AccountStatus, BundleAccount, BundleState, CacheState, DBBox,
+    AccountRevert, AccountStatus, BundleAccount, BundleState, CacheState, DBBox, MergeError,
     OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder, StateDBBox,
     StorageWithOriginalValues, TransitionAccount, TransitionState,
 };
+pub use witness::{ExecutionWitness, WitnessDB, WitnessDBError, WitnessRecorder};
@@ -0,0 +1,437 @@
+use crate::ExecutionWitness;
+use database_interface::{Database, DatabaseRef};
+use primitives::{Address, B256, U256};
+use state::{AccountInfo, Bytecode};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        PoisonError, RwLock,
+    },
+    vec::Vec,
+};
+
+/// Version of the file format of the [PersistentCacheDB].
+///
+/// Files of other versions are ignored and overwritten.
+pub const PERSISTENT_CACHE_VERSION: u32 = 1;
+
+/// Fields of the [PersistedCache] that every version of the file starts with.
+#[derive(Debug, serde::Deserialize)]
+struct PersistedCacheHeader {
+    version: u32,
+    chain_id: u64,
+    block_number: u64,
+}
+
+/// File of the [PersistentCacheDB].
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct PersistedCache {
+    version: u32,
+    chain_id: u64,
+    block_number: u64,
+    state: ExecutionWitness,
+}
+
+/// A [DatabaseRef] that caches all data fetched from the underlying database in a local file.
+///
+/// The file is keyed by chain id and block number, so the state of a forked block is fetched
+/// once and reloaded on the next runs. It is written on [PersistentCacheDB::flush] and when the
+/// database is dropped.
+///
+/// It is meant to wrap a remote database, e.g. `AlloyDB`, and to be wrapped by
+/// a [CacheDB][crate::CacheDB] or a [State][crate::State] that hold the changes.
+#[derive(Debug)]
+pub struct PersistentCacheDB<ExtDB> {
+    /// The underlying database that is used to fetch data missing in the cache.
+    pub db: ExtDB,
+    path: PathBuf,
+    chain_id: u64,
+    block_number: u64,
+    cache: RwLock<ExecutionWitness>,
+    /// Whether the cache has data not written to the file yet.
+    dirty: AtomicBool,
+}
+
+impl<ExtDB> PersistentCacheDB<ExtDB> {
+    /// Creates a new database caching the state of the block in the given directory.
+    ///
+    /// Loads the cache file of the block if it exists. Files of other versions, of other blocks
+    /// or that can't be decoded are ignored and overwritten.
+    pub fn new(
+        db: ExtDB,
+        dir: impl AsRef<Path>,
+        chain_id: u64,
+        block_number: u64,
+    ) -> io::Result<Self> {
+        let path = Self::file_path(dir, chain_id, block_number);
+        let cache = match fs::read(&path) {
+            // Header is checked first, as the layout of other versions can differ.
+            Ok(bytes) => serde_json::from_slice::<PersistedCacheHeader>(&bytes)
+                .ok()
+                .filter(|header| {
+                    header.version == PERSISTENT_CACHE_VERSION
+                        && header.chain_id == chain_id
+                        && header.block_number == block_number
+                })
+                .and_then(|_| serde_json::from_slice::<PersistedCache>(&bytes).ok())
+                .map(|file| file.state)
+                .unwrap_or_default(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => ExecutionWitness::default(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            db,
+            path,
+            chain_id,
+            block_number,
+            cache: RwLock::new(cache),
+            dirty: AtomicBool::new(false),
+        })
+    }
+
+    /// Returns the path of the cache file of the block.
+    pub fn file_path(dir: impl AsRef<Path>, chain_id: u64, block_number: u64) -> PathBuf {
+        dir.as_ref().join(format!("{chain_id}-{block_number}.json"))
+    }
+
+    /// Returns the path of the cache file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Writes the cache to the file if it has changed.
+    ///
+    /// The file is replaced atomically, so concurrent runs never read a partial file.
+    pub fn flush(&self) -> io::Result<()> {
+        if !self.dirty.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+        let result = self.write();
+        if result.is_err() {
+            self.dirty.store(true, Ordering::Release);
+        }
+        result
+    }
+
+    fn write(&self) -> io::Result<()> {
+        let file = PersistedCache {
+            version: PERSISTENT_CACHE_VERSION,
+            chain_id: self.chain_id,
+            block_number: self.block_number,
+            state: self
+                .cache
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = self
+            .path
+            .with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&tmp, serde_json::to_vec(&file)?)?;
+        fs::rename(tmp, &self.path)
+    }
+
+    fn insert_account(&self, address: Address, mut info: Option<AccountInfo>) {
+        let mut cache = self.cache.write().unwrap_or_else(PoisonError::into_inner);
+        // Bytecode is stored separately and served by `code_by_hash`.
+        if let Some(info) = info.as_mut() {
+            if let Some(code) = info.code.take() {
+                cache.bytecodes.insert(info.code_hash, code);
+            }
+        }
+        cache.accounts.insert(address, info);
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    fn insert_storage(&self, address: Address, index: U256, value: U256) {
+        self.cache
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .storage
+            .entry(address)
+            .or_default()
+            .insert(index, value);
+        self.dirty.store(true, Ordering::Release);
+    }
+}
+
+impl<ExtDB> Drop for PersistentCacheDB<ExtDB> {
+    fn drop(&mut self) {
+        // Errors can't be reported here, call `flush` to handle them.
+        let _ = self.flush();
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for PersistentCacheDB<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(info) = self
+            .cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .accounts
+            .get(&address)
+        {
+            return Ok(info.clone());
+        }
+        let info = self.db.basic_ref(address)?;
+        self.insert_account(address, info.clone());
+        Ok(info)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if let Some(code) = self
+            .cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .bytecodes
+            .get(&code_hash)
+        {
+            return Ok(code.clone());
+        }
+        let code = self.db.code_by_hash_ref(code_hash)?;
+        self.cache
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .bytecodes
+            .insert(code_hash, code.clone());
+        self.dirty.store(true, Ordering::Release);
+        Ok(code)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let cached = self
+            .cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .storage
+            .get(&address)
+            .and_then(|storage| storage.get(&index).copied());
+        if let Some(value) = cached {
+            return Ok(value);
+        }
+        let value = self.db.storage_ref(address, index)?;
+        self.insert_storage(address, index, value);
+        Ok(value)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        if let Some(hash) = self
+            .cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .block_hashes
+            .get(&number)
+        {
+            return Ok(*hash);
+        }
+        let hash = self.db.block_hash_ref(number)?;
+        self.cache
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .block_hashes
+            .insert(number, hash);
+        self.dirty.store(true, Ordering::Release);
+        Ok(hash)
+    }
+
+    /// Fetches the accounts missing in the cache in one batch.
+    fn basic_many_ref(
+        &self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        let missing: Vec<Address> = {
+            let cache = self.cache.read().unwrap_or_else(PoisonError::into_inner);
+            addresses
+                .iter()
+                .copied()
+                .filter(|address| !cache.accounts.contains_key(address))
+                .collect()
+        };
+        for (address, info) in missing.iter().zip(self.db.basic_many_ref(&missing)?) {
+            self.insert_account(*address, info);
+        }
+        addresses
+            .iter()
+            .map(|address| self.basic_ref(*address))
+            .collect()
+    }
+
+    /// Fetches the storage values missing in the cache in one batch.
+    fn storage_many_ref(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        let missing: Vec<(Address, U256)> = {
+            let cache = self.cache.read().unwrap_or_else(PoisonError::into_inner);
+            slots
+                .iter()
+                .copied()
+                .filter(|(address, index)| {
+                    !cache
+                        .storage
+                        .get(address)
+                        .is_some_and(|storage| storage.contains_key(index))
+                })
+                .collect()
+        };
+        for ((address, index), value) in missing.iter().zip(self.db.storage_many_ref(&missing)?) {
+            self.insert_storage(*address, *index, value);
+        }
+        slots
+            .iter()
+            .map(|(address, index)| self.storage_ref(*address, *index))
+            .collect()
+    }
+}
+
+impl<ExtDB: DatabaseRef> Database for PersistentCacheDB<ExtDB> {
+    type Error = ExtDB::Error;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+
+    #[inline]
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        self.basic_many_ref(addresses)
+    }
+
+    #[inline]
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        self.storage_many_ref(slots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CacheDB;
+    use core::{cell::Cell, convert::Infallible};
+    use database_interface::EmptyDB;
+
+    /// Local stand-in of the remote provider that counts the fetches.
+    struct Provider(CacheDB<EmptyDB>, Cell<usize>);
+
+    impl DatabaseRef for Provider {
+        type Error = Infallible;
+
+        fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Infallible> {
+            self.1.set(self.1.get() + 1);
+            self.0.basic_ref(address)
+        }
+
+        fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Infallible> {
+            self.1.set(self.1.get() + 1);
+            self.0.code_by_hash_ref(code_hash)
+        }
+
+        fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Infallible> {
+            self.1.set(self.1.get() + 1);
+            self.0.storage_ref(address, index)
+        }
+
+        fn block_hash_ref(&self, number: u64) -> Result<B256, Infallible> {
+            self.1.set(self.1.get() + 1);
+            self.0.block_hash_ref(number)
+        }
+    }
+
+    fn provider() -> Provider {
+        let mut db = CacheDB::new(EmptyDB::default());
+        let code = Bytecode::new_raw([0x00].into());
+        db.insert_account_info(
+            Address::with_last_byte(1),
+            AccountInfo::new(U256::from(1), 1, code.hash_slow(), code),
+        );
+        db.insert_account_storage(Address::with_last_byte(1), U256::from(1), U256::from(2))
+            .unwrap();
+        Provider(db, Cell::new(0))
+    }
+
+    fn read_state<DB: Database>(db: &mut DB) -> (Option<AccountInfo>, Bytecode, U256, B256) {
+        let info = db.basic(Address::with_last_byte(1)).unwrap().unwrap();
+        let code = db.code_by_hash(info.code_hash).unwrap();
+        let value = db
+            .storage(Address::with_last_byte(1), U256::from(1))
+            .unwrap();
+        let hash = db.block_hash(10).unwrap();
+        let missing = db.basic(Address::with_last_byte(2)).unwrap();
+        assert_eq!(missing, None);
+        (Some(info.without_code()), code, value, hash)
+    }
+
+    #[test]
+    fn persistent_cache() {
+        let dir =
+            std::env::temp_dir().join(format!("revm-persistent-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut db = PersistentCacheDB::new(provider(), &dir, 1, 100).unwrap();
+        let fetched = read_state(&mut db);
+        // Bytecode is fetched with the account.
+        assert_eq!(db.db.1.get(), 4);
+        db.flush().unwrap();
+        drop(db);
+
+        // Next run is served from the file.
+        let mut db = PersistentCacheDB::new(provider(), &dir, 1, 100).unwrap();
+        assert_eq!(read_state(&mut db), fetched);
+        assert_eq!(db.db.1.get(), 0);
+        drop(db);
+
+        // Other blocks and versions are not reused.
+        let mut db = PersistentCacheDB::new(provider(), &dir, 1, 101).unwrap();
+        read_state(&mut db);
+        assert_eq!(db.db.1.get(), 4);
+
+        let path = PersistentCacheDB::<EmptyDB>::file_path(&dir, 1, 100);
+        let mut file: serde_json::Value =
+            serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        file["version"] = (PERSISTENT_CACHE_VERSION + 1).into();
+        fs::write(&path, serde_json::to_vec(&file).unwrap()).unwrap();
+        let mut db = PersistentCacheDB::new(provider(), &dir, 1, 100).unwrap();
+        read_state(&mut db);
+        assert_eq!(db.db.1.get(), 4);
+        drop(db);
+
+        // Other versions can have another layout.
+        file["state"] = "other layout".into();
+        fs::write(&path, serde_json::to_vec(&file).unwrap()).unwrap();
+        let mut db = PersistentCacheDB::new(provider(), &dir, 1, 100).unwrap();
+        read_state(&mut db);
+        assert_eq!(db.db.1.get(), 4);
+        drop(db);
+
+        // Files that can't be decoded are ignored.
+        fs::write(&path, b"{").unwrap();
+        let mut db = PersistentCacheDB::new(provider(), &dir, 1, 100).unwrap();
+        assert_eq!(read_state(&mut db), fetched);
+        assert_eq!(db.db.1.get(), 4);
+        drop(db);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
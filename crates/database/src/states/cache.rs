@@ -4,7 +4,7 @@ use super::{
 use bytecode::Bytecode;
 use primitives::{Address, HashMap, B256};
 use state::{Account, AccountInfo, EvmState};
-use std::vec::Vec;
+use std::{sync::Arc, vec::Vec};
 
 /// Cache state contains both modified and original values
 ///
@@ -17,12 +17,19 @@ use std::vec::Vec;
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CacheState {
     /// Block state account with account state
-    pub accounts: HashMap<Address, CacheAccount>,
+    ///
+    /// Private so that accounts of the base layers are always looked up, see
+    /// [CacheState::account] and [CacheState::account_mut].
+    accounts: HashMap<Address, CacheAccount>,
     /// Created contracts
     // TODO : Add bytecode counter for number of bytecodes added/removed.
-    pub contracts: HashMap<B256, Bytecode>,
+    contracts: HashMap<B256, Bytecode>,
     /// Has EIP-161 state clear enabled (Spurious Dragon hardfork)
     pub has_state_clear: bool,
+    /// Frozen layer shared with the forks of this cache, see [CacheState::fork]
+    ///
+    /// Accounts and contracts not found in this cache are looked up in the base.
+    base: Option<Arc<CacheState>>,
 }
 
 impl Default for CacheState {
@@ -38,6 +45,127 @@ impl CacheState {
             accounts: HashMap::default(),
             contracts: HashMap::default(),
             has_state_clear,
+            base: None,
+        }
+    }
+
+    /// Forks the cache in O(1).
+    ///
+    /// Accounts and contracts of this cache are frozen into a base layer that is shared by
+    /// this cache and the returned fork. Accounts are copied from the base on first
+    /// modification, so both caches can be changed independently.
+    pub fn fork(&mut self) -> Self {
+        if !self.accounts.is_empty() || !self.contracts.is_empty() {
+            let layer = Self {
+                accounts: core::mem::take(&mut self.accounts),
+                contracts: core::mem::take(&mut self.contracts),
+                has_state_clear: self.has_state_clear,
+                base: self.base.take(),
+            };
+            self.base = Some(Arc::new(layer));
+        }
+        Self {
+            base: self.base.clone(),
+            ..Self::new(self.has_state_clear)
+        }
+    }
+
+    /// Merges the fork created by [CacheState::fork] back into this cache.
+    ///
+    /// Accounts of the fork replace the accounts of this cache.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fork` is not a fork of this cache.
+    pub fn merge(&mut self, fork: Self) {
+        // Collect the layers frozen by the fork after it was created.
+        let mut layers = Vec::new();
+        let mut base = fork.base.as_ref();
+        while !is_same_layer(base, self.base.as_ref()) {
+            let layer = base.expect("Merged cache should be a fork of this cache");
+            layers.push(layer);
+            base = layer.base.as_ref();
+        }
+        for layer in layers.into_iter().rev() {
+            self.accounts.extend(
+                layer
+                    .accounts
+                    .iter()
+                    .map(|(address, account)| (*address, account.clone())),
+            );
+            self.contracts.extend(
+                layer
+                    .contracts
+                    .iter()
+                    .map(|(hash, code)| (*hash, code.clone())),
+            );
+        }
+        self.accounts.extend(fork.accounts);
+        self.contracts.extend(fork.contracts);
+        self.has_state_clear = fork.has_state_clear;
+    }
+
+    /// Returns all accounts, including the ones of the base layers.
+    pub fn accounts(&self) -> impl Iterator<Item = (&Address, &CacheAccount)> {
+        // Accounts of the upper layers shadow the ones of the base.
+        let mut accounts: HashMap<&Address, &CacheAccount> = HashMap::default();
+        let mut layer = Some(self);
+        while let Some(cache) = layer {
+            for (address, account) in &cache.accounts {
+                accounts.entry(address).or_insert(account);
+            }
+            layer = cache.base.as_deref();
+        }
+        accounts.into_iter()
+    }
+
+    /// Returns the account, looking it up in the base layers if it is not in this cache.
+    pub fn account(&self, address: &Address) -> Option<&CacheAccount> {
+        self.accounts
+            .get(address)
+            .or_else(|| self.base.as_ref()?.account(address))
+    }
+
+    /// Returns the mutable account, copying it from the base layers into this cache.
+    pub fn account_mut(&mut self, address: Address) -> Option<&mut CacheAccount> {
+        self.unshare_account(address);
+        self.accounts.get_mut(&address)
+    }
+
+    /// Returns the contract, looking it up in the base layers if it is not in this cache.
+    pub fn contract(&self, code_hash: &B256) -> Option<&Bytecode> {
+        self.contracts
+            .get(code_hash)
+            .or_else(|| self.base.as_ref()?.contract(code_hash))
+    }
+
+    /// Returns the mutable account, inserting it with `default` if it is not in any layer.
+    pub fn account_or_insert_with(
+        &mut self,
+        address: Address,
+        default: impl FnOnce() -> CacheAccount,
+    ) -> &mut CacheAccount {
+        self.unshare_account(address);
+        self.accounts.entry(address).or_insert_with(default)
+    }
+
+    /// Inserts the contract.
+    pub fn insert_contract(&mut self, code_hash: B256, code: Bytecode) {
+        self.contracts.insert(code_hash, code);
+    }
+
+    /// Copies the account from the base layers into this cache so it can be modified.
+    pub(crate) fn unshare_account(&mut self, address: Address) {
+        if self.accounts.contains_key(&address) {
+            return;
+        }
+        if let Some(account) = self
+            .base
+            .as_ref()
+            .and_then(|base| base.account(&address))
+            .cloned()
+        {
+            self.accounts.insert(address, account);
         }
     }
 
@@ -50,7 +178,7 @@ impl CacheState {
     ///
     /// Used inside tests to generate merkle tree.
     pub fn trie_account(&self) -> impl IntoIterator<Item = (Address, &PlainAccount)> {
-        self.accounts.iter().filter_map(|(address, account)| {
+        self.accounts().filter_map(|(address, account)| {
             account
                 .account
                 .as_ref()
@@ -113,9 +241,9 @@ impl CacheState {
             return None;
         }
 
+        let has_state_clear = self.has_state_clear;
         let this_account = self
-            .accounts
-            .get_mut(&address)
+            .account_mut(address)
             .expect("All accounts should be present inside cache");
 
         // If it is marked as selfdestructed inside revm
@@ -152,7 +280,7 @@ impl CacheState {
         // And when empty account is touched it needs to be removed from database.
         // EIP-161 state clear
         if is_empty {
-            if self.has_state_clear {
+            if has_state_clear {
                 // Touch empty account.
                 this_account.touch_empty_eip161()
             } else {
@@ -165,3 +293,12 @@ impl CacheState {
         }
     }
 }
+
+/// Returns true if both are the same frozen layer.
+fn is_same_layer(a: Option<&Arc<CacheState>>, b: Option<&Arc<CacheState>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
@@ -1,14 +1,19 @@
 use super::{
-    bundle_state::BundleRetention, cache::CacheState, plain_account::PlainStorage, BundleState,
-    CacheAccount, StateBuilder, TransitionAccount, TransitionState,
+    bundle_state::BundleRetention, cache::CacheState, plain_account::PlainStorage, BundleAccount,
+    BundleState, CacheAccount, StateBuilder, TransitionAccount, TransitionState,
 };
 use bytecode::Bytecode;
+use core::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
 use database_interface::{Database, DatabaseCommit, EmptyDB};
 use primitives::{hash_map, Address, HashMap, B256, BLOCK_HASH_HISTORY, U256};
 use state::{Account, AccountInfo};
 use std::{
     boxed::Box,
     collections::{btree_map, BTreeMap},
+    sync::Arc,
     vec::Vec,
 };
 
@@ -20,6 +25,23 @@ pub type DBBox<'a, E> = Box<dyn Database<Error = E> + Send + 'a>;
 /// This is used to make it easier to use State.
 pub type StateDBBox<'a, E> = State<DBBox<'a, E>>;
 
+/// Error of [State::merge].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeError {
+    /// State was changed after the fork was created, so the fork does not build on it.
+    ParentChanged,
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ParentChanged => f.write_str("state was changed after the fork"),
+        }
+    }
+}
+
+impl core::error::Error for MergeError {}
+
 /// State of blockchain
 ///
 /// State clear flag is set inside CacheState and by default it is enabled.
@@ -50,6 +72,10 @@ pub struct State<DB> {
     ///
     /// Bundle state can be set on initialization if we want to use preloaded bundle.
     pub bundle_state: BundleState,
+    /// Bundles frozen by [State::fork], oldest first
+    ///
+    /// They are shared with the forks of this state and precede the [State::bundle_state].
+    pub(crate) bundle_base: Vec<Arc<BundleState>>,
     /// Addition layer that is going to be used to fetched values before fetching values
     /// from database
     ///
@@ -63,6 +89,13 @@ pub struct State<DB> {
     ///
     /// The fork block is different or some blocks are not saved inside database.
     pub block_hashes: BTreeMap<u64, B256>,
+    /// Revision of the last change made through the methods of this state
+    ///
+    /// Revisions are unique in the process, forks start from the revision of their parent, see
+    /// [State::merge].
+    pub(crate) revision: u64,
+    /// Revision of the parent when this state was forked
+    pub(crate) fork_revision: u64,
 }
 
 // Have ability to call State::builder without having to specify the type.
@@ -76,9 +109,78 @@ impl State<EmptyDB> {
 impl<DB: Database> State<DB> {
     /// Returns the size hint for the inner bundle state.
     ///
-    /// See [BundleState::size_hint] for more info.
+    /// See [BundleState::size_hint] for more info. If the `State` has been forked, the hints of
+    /// all the bundle layers are summed up.
     pub fn bundle_size_hint(&self) -> usize {
-        self.bundle_state.size_hint()
+        self.bundle_base
+            .iter()
+            .map(|bundle| bundle.size_hint())
+            .sum::<usize>()
+            + self.bundle_state.size_hint()
+    }
+
+    /// Forks the state in O(1).
+    ///
+    /// Cache and bundle are frozen into layers shared by this state and the returned fork,
+    /// see [CacheState::fork]. Both states can be changed independently and the fork can be
+    /// either discarded or merged back with [State::merge].
+    ///
+    /// Database is cloned, so it should be cheap to clone. Pending transitions are cloned as
+    /// well, it is recommended to call [State::merge_transitions] before forking.
+    pub fn fork(&mut self) -> Self
+    where
+        DB: Clone,
+    {
+        if !self.bundle_state.is_empty() {
+            let bundle = core::mem::take(&mut self.bundle_state);
+            self.bundle_base.push(Arc::new(bundle));
+        }
+        Self {
+            cache: self.cache.fork(),
+            database: self.database.clone(),
+            transition_state: self.transition_state.clone(),
+            bundle_state: BundleState::default(),
+            bundle_base: self.bundle_base.clone(),
+            use_preloaded_bundle: self.use_preloaded_bundle,
+            block_hashes: self.block_hashes.clone(),
+            revision: self.revision,
+            fork_revision: self.revision,
+        }
+    }
+
+    /// Merges the fork created by [State::fork] back into this state.
+    ///
+    /// Changes of the fork replace the ones of this state. Accounts loaded by this state in the
+    /// meantime are kept.
+    ///
+    /// Returns [MergeError::ParentChanged] and leaves this state untouched if it was changed
+    /// after the fork, as the changes of the fork do not include them. Changes made through the
+    /// methods of [State] and [DatabaseCommit] are tracked, not the ones made directly to its
+    /// public fields.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fork` is not a fork of this state.
+    pub fn merge(&mut self, fork: Self) -> Result<(), MergeError> {
+        assert!(
+            self.bundle_base.len() <= fork.bundle_base.len()
+                && self
+                    .bundle_base
+                    .iter()
+                    .zip(&fork.bundle_base)
+                    .all(|(a, b)| Arc::ptr_eq(a, b)),
+            "Merged state should be a fork of this state"
+        );
+        if self.revision != fork.fork_revision {
+            return Err(MergeError::ParentChanged);
+        }
+        self.cache.merge(fork.cache);
+        self.transition_state = fork.transition_state;
+        self.bundle_state = fork.bundle_state;
+        self.bundle_base = fork.bundle_base;
+        self.block_hashes.extend(fork.block_hashes);
+        self.revision = fork.revision;
+        Ok(())
     }
 
     /// Iterates over received balances and increment all account balances.
@@ -109,10 +211,7 @@ impl<DB: Database> State<DB> {
                     .expect("Balance is not zero"),
             ))
         }
-        // Append transition
-        if let Some(s) = self.transition_state.as_mut() {
-            s.add_transitions(transitions)
-        }
+        self.apply_transition(transitions);
         Ok(())
     }
 
@@ -132,10 +231,7 @@ impl<DB: Database> State<DB> {
             balances.push(balance);
             transitions.push((address, transition))
         }
-        // Append transition
-        if let Some(s) = self.transition_state.as_mut() {
-            s.add_transitions(transitions)
-        }
+        self.apply_transition(transitions);
         Ok(balances)
     }
 
@@ -145,10 +241,12 @@ impl<DB: Database> State<DB> {
     }
 
     pub fn insert_not_existing(&mut self, address: Address) {
+        self.revision = next_revision();
         self.cache.insert_not_existing(address)
     }
 
     pub fn insert_account(&mut self, address: Address, info: AccountInfo) {
+        self.revision = next_revision();
         self.cache.insert_account(address, info)
     }
 
@@ -158,12 +256,14 @@ impl<DB: Database> State<DB> {
         info: AccountInfo,
         storage: PlainStorage,
     ) {
+        self.revision = next_revision();
         self.cache
             .insert_account_with_storage(address, info, storage)
     }
 
     /// Applies evm transitions to transition state.
     pub fn apply_transition(&mut self, transitions: Vec<(Address, TransitionAccount)>) {
+        self.revision = next_revision();
         // Add transition to transition state.
         if let Some(s) = self.transition_state.as_mut() {
             s.add_transitions(transitions)
@@ -177,6 +277,7 @@ impl<DB: Database> State<DB> {
     /// is applied.
     pub fn merge_transitions(&mut self, retention: BundleRetention) {
         if let Some(transition_state) = self.transition_state.as_mut().map(TransitionState::take) {
+            self.revision = next_revision();
             self.bundle_state
                 .apply_transitions_and_create_reverts(transition_state, retention);
         }
@@ -187,29 +288,26 @@ impl<DB: Database> State<DB> {
     /// If the account is not found in the cache, it will be loaded from the
     /// database and inserted into the cache.
     pub fn load_cache_account(&mut self, address: Address) -> Result<&mut CacheAccount, DB::Error> {
-        match self.cache.accounts.entry(address) {
-            hash_map::Entry::Vacant(entry) => {
-                if self.use_preloaded_bundle {
-                    // Load account from bundle state
-                    if let Some(account) =
-                        self.bundle_state.account(&address).cloned().map(Into::into)
-                    {
-                        return Ok(entry.insert(account));
-                    }
-                }
-                // If not found in bundle, load it from database
-                let info = self.database.basic(address)?;
-                let account = match info {
-                    None => CacheAccount::new_loaded_not_existing(),
-                    Some(acc) if acc.is_empty() => {
-                        CacheAccount::new_loaded_empty_eip161(HashMap::default())
-                    }
-                    Some(acc) => CacheAccount::new_loaded(acc, HashMap::default()),
-                };
-                Ok(entry.insert(account))
-            }
-            hash_map::Entry::Occupied(entry) => Ok(entry.into_mut()),
+        // Account can be found in the layers frozen by the fork.
+        if self.cache.account(&address).is_some() {
+            return Ok(self
+                .cache
+                .account_mut(address)
+                .expect("Account is in the cache"));
         }
+        // Load account from bundle state
+        let preloaded = self
+            .use_preloaded_bundle
+            .then(|| bundle_account(&self.bundle_state, &self.bundle_base, &address))
+            .flatten()
+            .cloned()
+            .map(Into::into);
+        let account = match preloaded {
+            Some(account) => account,
+            // If not found in bundle, load it from database
+            None => loaded_cache_account(self.database.basic(address)?),
+        };
+        Ok(self.cache.account_or_insert_with(address, || account))
     }
 
     // TODO : Make cache aware of transitions dropping by having global transition counter.
@@ -223,8 +321,20 @@ impl<DB: Database> State<DB> {
     /// If the `State` has been built with the
     /// [`StateBuilder::with_bundle_prestate`] option, the pre-state will be
     /// taken along with any changes made by [`State::merge_transitions`].
+    ///
+    /// If the `State` has been forked, bundles of all the layers are combined.
     pub fn take_bundle(&mut self) -> BundleState {
-        core::mem::take(&mut self.bundle_state)
+        let bundle = core::mem::take(&mut self.bundle_state);
+        let mut layers = core::mem::take(&mut self.bundle_base).into_iter();
+        let Some(first) = layers.next() else {
+            return bundle;
+        };
+        let mut combined = Arc::unwrap_or_clone(first);
+        for layer in layers {
+            combined.extend(Arc::unwrap_or_clone(layer));
+        }
+        combined.extend(bundle);
+        combined
     }
 }
 
@@ -236,28 +346,29 @@ impl<DB: Database> Database for State<DB> {
     }
 
     fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
-        let res = match self.cache.contracts.entry(code_hash) {
-            hash_map::Entry::Occupied(entry) => Ok(entry.get().clone()),
-            hash_map::Entry::Vacant(entry) => {
-                if self.use_preloaded_bundle {
-                    if let Some(code) = self.bundle_state.contracts.get(&code_hash) {
-                        entry.insert(code.clone());
-                        return Ok(code.clone());
-                    }
-                }
-                // If not found in bundle ask database
-                let code = self.database.code_by_hash(code_hash)?;
-                entry.insert(code.clone());
-                Ok(code)
+        if let Some(code) = self.cache.contract(&code_hash) {
+            return Ok(code.clone());
+        }
+        if self.use_preloaded_bundle {
+            let code = core::iter::once(&self.bundle_state)
+                .chain(self.bundle_base.iter().rev().map(|bundle| &**bundle))
+                .find_map(|bundle| bundle.contracts.get(&code_hash))
+                .cloned();
+            if let Some(code) = code {
+                self.cache.insert_contract(code_hash, code.clone());
+                return Ok(code);
             }
-        };
-        res
+        }
+        // If not found in bundle ask database
+        let code = self.database.code_by_hash(code_hash)?;
+        self.cache.insert_contract(code_hash, code.clone());
+        Ok(code)
     }
 
     fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
         // Account is guaranteed to be loaded.
         // Note that storage from bundle is already loaded with account.
-        if let Some(account) = self.cache.accounts.get_mut(&address) {
+        if let Some(account) = self.cache.account_mut(address) {
             // Account will always be some, but if it is not, U256::ZERO will be returned.
             let is_storage_known = account.status.is_storage_known();
             Ok(account
@@ -304,6 +415,91 @@ impl<DB: Database> Database for State<DB> {
             }
         }
     }
+
+    /// Loads the accounts missing in the cache and the bundle in one batch, see
+    /// [Database::basic_many].
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        let missing: Vec<Address> = addresses
+            .iter()
+            .copied()
+            .filter(|address| {
+                self.cache.account(address).is_none()
+                    && !(self.use_preloaded_bundle
+                        && bundle_account(&self.bundle_state, &self.bundle_base, address).is_some())
+            })
+            .collect();
+        let infos = self.database.basic_many(&missing)?;
+        for (address, info) in missing.into_iter().zip(infos) {
+            self.cache
+                .account_or_insert_with(address, || loaded_cache_account(info));
+        }
+        addresses
+            .iter()
+            .map(|address| self.basic(*address))
+            .collect()
+    }
+
+    /// Loads the slots missing in the cache in one batch, see [Database::storage_many].
+    ///
+    /// Slots of the accounts that are not loaded are fetched one by one.
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        let missing: Vec<(Address, U256)> = slots
+            .iter()
+            .copied()
+            .filter(|(address, index)| {
+                self.cache.account(address).is_some_and(|account| {
+                    !account.status.is_storage_known()
+                        && account
+                            .account
+                            .as_ref()
+                            .is_some_and(|account| !account.storage.contains_key(index))
+                })
+            })
+            .collect();
+        let values = self.database.storage_many(&missing)?;
+        for ((address, index), value) in missing.into_iter().zip(values) {
+            if let Some(account) = self
+                .cache
+                .account_mut(address)
+                .and_then(|account| account.account.as_mut())
+            {
+                account.storage.entry(index).or_insert(value);
+            }
+        }
+        slots
+            .iter()
+            .map(|(address, index)| self.storage(*address, *index))
+            .collect()
+    }
+}
+
+/// Returns a revision that was not returned before in the process.
+fn next_revision() -> u64 {
+    static REVISION: AtomicU64 = AtomicU64::new(1);
+    REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Creates the cache account loaded from the database.
+fn loaded_cache_account(info: Option<AccountInfo>) -> CacheAccount {
+    match info {
+        None => CacheAccount::new_loaded_not_existing(),
+        Some(acc) if acc.is_empty() => CacheAccount::new_loaded_empty_eip161(HashMap::default()),
+        Some(acc) => CacheAccount::new_loaded(acc, HashMap::default()),
+    }
+}
+
+/// Returns the account from the bundle or the frozen layers, newest first.
+fn bundle_account<'a>(
+    bundle: &'a BundleState,
+    base: &'a [Arc<BundleState>],
+    address: &Address,
+) -> Option<&'a BundleAccount> {
+    bundle
+        .account(address)
+        .or_else(|| base.iter().rev().find_map(|bundle| bundle.account(address)))
 }
 
 impl<DB: Database> DatabaseCommit for State<DB> {
@@ -805,4 +1001,113 @@ mod tests {
             )])])
         )
     }
+
+    #[test]
+    fn fork_is_independent() {
+        let address = Address::with_last_byte(1);
+        let slot = U256::from(1);
+        let mut state = State::builder().with_bundle_update().build();
+        state.insert_account_with_storage(
+            address,
+            AccountInfo::from_balance(U256::from(100)),
+            HashMap::from_iter([(slot, U256::from(10))]),
+        );
+
+        let mut fork = state.fork();
+        assert_eq!(state.cache.accounts().count(), 1);
+        assert_eq!(fork.cache.accounts().count(), 1);
+
+        fork.increment_balances([(address, 5)]).unwrap();
+        assert_eq!(fork.cache.accounts().count(), 1);
+        assert_eq!(
+            fork.basic(address).unwrap().unwrap().balance,
+            U256::from(105)
+        );
+        assert_eq!(fork.storage(address, slot).unwrap(), U256::from(10));
+        assert_eq!(
+            state.basic(address).unwrap().unwrap().balance,
+            U256::from(100)
+        );
+        assert_eq!(fork.cache.trie_account().into_iter().count(), 1);
+
+        fork.merge_transitions(BundleRetention::Reverts);
+        assert_eq!(fork.take_bundle().state.len(), 1);
+        assert!(state.take_bundle().is_empty());
+    }
+
+    #[test]
+    fn fork_merge_matches_sequential() {
+        let (a, b, c) = (
+            Address::with_last_byte(1),
+            Address::with_last_byte(2),
+            Address::with_last_byte(3),
+        );
+        let new_state = || {
+            let mut state = State::builder().with_bundle_update().build();
+            state.insert_account(a, AccountInfo::from_balance(U256::from(100)));
+            state.increment_balances([(a, 1)]).unwrap();
+            state.merge_transitions(BundleRetention::Reverts);
+            state
+        };
+
+        let mut sequential = new_state();
+        sequential.increment_balances([(a, 2), (b, 3)]).unwrap();
+        sequential.merge_transitions(BundleRetention::Reverts);
+        sequential.increment_balances([(b, 4)]).unwrap();
+        sequential.merge_transitions(BundleRetention::Reverts);
+
+        let mut state = new_state();
+        // Discarded branch does not affect the state.
+        let mut discarded = state.fork();
+        discarded.increment_balances([(a, 10), (c, 10)]).unwrap();
+        discarded.merge_transitions(BundleRetention::Reverts);
+
+        let mut fork = state.fork();
+        fork.increment_balances([(a, 2), (b, 3)]).unwrap();
+        fork.merge_transitions(BundleRetention::Reverts);
+        let mut nested = fork.fork();
+        nested.increment_balances([(b, 4)]).unwrap();
+        nested.merge_transitions(BundleRetention::Reverts);
+        fork.merge(nested).unwrap();
+        state.merge(fork).unwrap();
+
+        for address in [a, b, c] {
+            assert_eq!(
+                state.basic(address).unwrap(),
+                sequential.basic(address).unwrap()
+            );
+        }
+        assert_eq!(state.take_bundle(), sequential.take_bundle());
+    }
+
+    #[test]
+    fn merge_after_parent_changed() {
+        let (a, b) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let mut state = State::builder().with_bundle_update().build();
+        state.insert_account(a, AccountInfo::from_balance(U256::from(100)));
+
+        // Loading accounts does not change the state.
+        let mut fork = state.fork();
+        fork.increment_balances([(a, 1)]).unwrap();
+        state.basic(b).unwrap();
+        state.merge(fork).unwrap();
+        assert_eq!(state.basic(a).unwrap().unwrap().balance, U256::from(101));
+
+        let mut fork = state.fork();
+        fork.increment_balances([(a, 1)]).unwrap();
+        state.increment_balances([(b, 1)]).unwrap();
+        assert_eq!(state.merge(fork), Err(MergeError::ParentChanged));
+        assert_eq!(state.basic(a).unwrap().unwrap().balance, U256::from(101));
+        assert_eq!(state.basic(b).unwrap().unwrap().balance, U256::from(1));
+
+        // Sibling merged first changes the state.
+        let mut first = state.fork();
+        let mut second = state.fork();
+        first.increment_balances([(a, 1)]).unwrap();
+        second.increment_balances([(b, 1)]).unwrap();
+        let nested = second.fork();
+        state.merge(first).unwrap();
+        assert_eq!(state.merge(nested), Err(MergeError::ParentChanged));
+        assert_eq!(state.merge(second), Err(MergeError::ParentChanged));
+    }
 }
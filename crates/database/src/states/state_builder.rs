@@ -171,8 +171,11 @@ impl<DB: Database> StateBuilder<DB> {
             database: self.database,
             transition_state: self.with_bundle_update.then(TransitionState::default),
             bundle_state: self.with_bundle_prestate.unwrap_or_default(),
+            bundle_base: Vec::new(),
             use_preloaded_bundle,
             block_hashes: self.with_block_hashes,
+            revision: 0,
+            fork_revision: 0,
         }
     }
 }
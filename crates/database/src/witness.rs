@@ -0,0 +1,344 @@
+//! Execution witness and the [Database] to execute statelessly from it.
+use core::{error::Error, fmt};
+use database_interface::{DBErrorMarker, Database, DatabaseRef};
+use primitives::{Address, Bytes, B256, U256};
+use state::{AccountInfo, Bytecode};
+use std::{collections::BTreeMap, vec::Vec};
+
+/// Reads of the execution from the underlying [Database].
+///
+/// It contains everything needed to re-execute the block with the [WitnessDB].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionWitness {
+    /// Accounts where None means it is not existing.
+    ///
+    /// `code` is always `None`, and bytecode can be found in `bytecodes`.
+    pub accounts: BTreeMap<Address, Option<AccountInfo>>,
+    /// Storage slots of the accounts.
+    pub storage: BTreeMap<Address, BTreeMap<U256, U256>>,
+    /// Bytecodes by their code hash.
+    pub bytecodes: BTreeMap<B256, Bytecode>,
+    /// Block hashes by their number.
+    pub block_hashes: BTreeMap<u64, B256>,
+    /// Committed storage roots of the accounts the storage slots are proven against.
+    ///
+    /// The recorder does not fill them, as the [Database] does not know the roots.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub storage_roots: BTreeMap<Address, B256>,
+    /// Encoded storage trie nodes of the accounts that prove the slots in `storage`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub storage_nodes: BTreeMap<Address, Vec<Bytes>>,
+}
+
+/// A [Database] that records all reads from the wrapped database into the [ExecutionWitness].
+///
+/// It is meant to be used as the database of the [State][crate::State], which caches the
+/// reads so every value is recorded once for the whole block.
+#[derive(Clone, Debug, Default)]
+pub struct WitnessRecorder<DB> {
+    /// The underlying database.
+    pub db: DB,
+    witness: ExecutionWitness,
+}
+
+impl<DB> WitnessRecorder<DB> {
+    /// Creates a new recorder wrapping the database.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            witness: ExecutionWitness::default(),
+        }
+    }
+
+    /// Returns the witness recorded so far.
+    pub fn witness(&self) -> &ExecutionWitness {
+        &self.witness
+    }
+
+    /// Takes the witness recorded so far, replacing it with an empty one.
+    pub fn take_witness(&mut self) -> ExecutionWitness {
+        core::mem::take(&mut self.witness)
+    }
+
+    /// Consumes the recorder and returns the witness.
+    pub fn into_witness(self) -> ExecutionWitness {
+        self.witness
+    }
+
+    fn record_account(&mut self, address: Address, mut info: Option<AccountInfo>) {
+        // Bytecode is recorded separately as it can be shared between accounts.
+        if let Some(info) = info.as_mut() {
+            if let Some(code) = info.code.take() {
+                self.witness.bytecodes.insert(info.code_hash, code);
+            }
+        }
+        self.witness.accounts.insert(address, info);
+    }
+
+    fn record_storage(&mut self, address: Address, index: U256, value: U256) {
+        self.witness
+            .storage
+            .entry(address)
+            .or_default()
+            .insert(index, value);
+    }
+}
+
+impl<DB: Database> Database for WitnessRecorder<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic(address)?;
+        self.record_account(address, info.clone());
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.db.code_by_hash(code_hash)?;
+        self.witness.bytecodes.insert(code_hash, code.clone());
+        Ok(code)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let value = self.db.storage(address, index)?;
+        self.record_storage(address, index, value);
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash(number)?;
+        self.witness.block_hashes.insert(number, hash);
+        Ok(hash)
+    }
+
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        let infos = self.db.basic_many(addresses)?;
+        for (address, info) in addresses.iter().zip(&infos) {
+            self.record_account(*address, info.clone());
+        }
+        Ok(infos)
+    }
+
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        let values = self.db.storage_many(slots)?;
+        for ((address, index), value) in slots.iter().zip(&values) {
+            self.record_storage(*address, *index, *value);
+        }
+        Ok(values)
+    }
+}
+
+/// A [Database] that serves the reads from the [ExecutionWitness] only.
+///
+/// Any read missing in the witness fails with the [WitnessDBError].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WitnessDB {
+    /// Witness the reads are served from.
+    pub witness: ExecutionWitness,
+}
+
+impl WitnessDB {
+    /// Creates a new database from the witness.
+    pub fn new(witness: ExecutionWitness) -> Self {
+        Self { witness }
+    }
+}
+
+impl DatabaseRef for WitnessDB {
+    type Error = WitnessDBError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.witness
+            .accounts
+            .get(&address)
+            .cloned()
+            .ok_or(WitnessDBError::MissingAccount(address))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.witness
+            .bytecodes
+            .get(&code_hash)
+            .cloned()
+            .ok_or(WitnessDBError::MissingBytecode(code_hash))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.witness
+            .storage
+            .get(&address)
+            .and_then(|storage| storage.get(&index))
+            .copied()
+            .ok_or(WitnessDBError::MissingStorage(address, index))
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.witness
+            .block_hashes
+            .get(&number)
+            .copied()
+            .ok_or(WitnessDBError::MissingBlockHash(number))
+    }
+}
+
+impl Database for WitnessDB {
+    type Error = WitnessDBError;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+/// Error of the [WitnessDB], the read is missing in the witness.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WitnessDBError {
+    /// Account is missing.
+    MissingAccount(Address),
+    /// Storage slot of the account is missing.
+    MissingStorage(Address, U256),
+    /// Bytecode is missing.
+    MissingBytecode(B256),
+    /// Block hash is missing.
+    MissingBlockHash(u64),
+}
+
+impl DBErrorMarker for WitnessDBError {}
+
+impl fmt::Display for WitnessDBError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingAccount(address) => write!(f, "account {address} missing in witness"),
+            Self::MissingStorage(address, index) => {
+                write!(f, "storage slot {index} of {address} missing in witness")
+            }
+            Self::MissingBytecode(code_hash) => {
+                write!(f, "bytecode {code_hash} missing in witness")
+            }
+            Self::MissingBlockHash(number) => {
+                write!(f, "block hash {number} missing in witness")
+            }
+        }
+    }
+}
+
+impl Error for WitnessDBError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BenchmarkDB, State};
+    use primitives::KECCAK_EMPTY;
+
+    #[test]
+    fn witness_reexecution() {
+        let code = Bytecode::new_raw([0x00].into());
+        let code_hash = code.hash_slow();
+        let mut state = State::builder()
+            .with_database(WitnessRecorder::new(BenchmarkDB::new_bytecode(
+                code.clone(),
+            )))
+            .build();
+        let contract = state.basic(Address::ZERO).unwrap();
+        let caller = state.basic(Address::with_last_byte(1)).unwrap();
+        let slot = state.storage(Address::ZERO, U256::from(1)).unwrap();
+        let hash = state.block_hash(1).unwrap();
+        // Cached reads are recorded once.
+        state.storage(Address::ZERO, U256::from(1)).unwrap();
+
+        let witness = state.database.into_witness();
+        assert_eq!(witness.accounts.len(), 2);
+        assert!(witness
+            .accounts
+            .values()
+            .flatten()
+            .all(|a| a.code.is_none()));
+        assert_eq!(
+            witness.bytecodes.get(&code_hash),
+            contract.as_ref().unwrap().code.as_ref()
+        );
+        assert_ne!(caller.as_ref().unwrap().code_hash, code_hash);
+
+        let mut state = State::builder()
+            .with_database(WitnessDB::new(witness))
+            .build();
+        let replayed = state.basic(Address::ZERO).unwrap().unwrap();
+        assert_eq!(replayed.code_hash, code_hash);
+        assert_eq!(state.code_by_hash(code_hash).unwrap(), code);
+        assert_eq!(state.basic(Address::with_last_byte(1)).unwrap(), caller);
+        assert_eq!(state.storage(Address::ZERO, U256::from(1)).unwrap(), slot);
+        assert_eq!(state.block_hash(1).unwrap(), hash);
+
+        assert_eq!(
+            state.basic(Address::with_last_byte(2)),
+            Err(WitnessDBError::MissingAccount(Address::with_last_byte(2)))
+        );
+        assert_eq!(
+            state.storage(Address::ZERO, U256::from(2)),
+            Err(WitnessDBError::MissingStorage(Address::ZERO, U256::from(2)))
+        );
+        assert_eq!(
+            state.code_by_hash(KECCAK_EMPTY),
+            Err(WitnessDBError::MissingBytecode(KECCAK_EMPTY))
+        );
+        assert_eq!(
+            state.block_hash(2),
+            Err(WitnessDBError::MissingBlockHash(2))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn witness_serde() {
+        let mut recorder =
+            WitnessRecorder::new(BenchmarkDB::new_bytecode(Bytecode::new_raw([0x00].into())));
+        recorder.basic(Address::ZERO).unwrap();
+        recorder.storage(Address::ZERO, U256::from(1)).unwrap();
+        recorder.block_hash(1).unwrap();
+        let mut witness = recorder.into_witness();
+        // Recorder does not know the storage roots.
+        assert!(witness.storage_roots.is_empty() && witness.storage_nodes.is_empty());
+
+        // Witnesses without the storage proofs still decode.
+        let json = serde_json::to_string(&witness).unwrap();
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let object = value.as_object_mut().unwrap();
+        object.remove("storage_roots");
+        object.remove("storage_nodes");
+        assert_eq!(
+            serde_json::from_value::<ExecutionWitness>(value).unwrap(),
+            witness
+        );
+
+        witness
+            .storage_roots
+            .insert(Address::ZERO, B256::with_last_byte(1));
+        witness
+            .storage_nodes
+            .insert(Address::ZERO, vec![Bytes::from_static(&[0xc0])]);
+        let json = serde_json::to_string(&witness).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ExecutionWitness>(&json).unwrap(),
+            witness
+        );
+    }
+}
@@ -0,0 +1,17 @@
+//! Execution of a single instruction for fault proofs.
+//!
+//! [`StepRecorder`] records the serializable [`StepState`] before the instructions of a
+//! transaction and [`execute_step`] executes one instruction of a state. It returns the state
+//! after the instruction and the [`StepProof`] of the stack items, memory words and storage
+//! slots the instruction accesses, against the [`merkle`] roots of the states.
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+
+pub mod merkle;
+pub mod recorder;
+pub mod state;
+pub mod step;
+
+pub use merkle::{CellProof, MerkleTree};
+pub use recorder::StepRecorder;
+pub use state::{StateRoots, StepState};
+pub use step::{execute_step, StepError, StepOutput, StepProof, StorageProof};
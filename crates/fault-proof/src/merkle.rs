@@ -0,0 +1,176 @@
+//! Binary Merkle trees of the stack and the memory.
+//!
+//! Trees have a fixed depth and are padded with zero words, the leaf of a word is its
+//! `keccak256` hash and the parent of two nodes is the hash of their concatenation.
+use revm::primitives::{keccak256, B256, U256};
+use serde::{Deserialize, Serialize};
+use std::vec::Vec;
+
+/// Depth of the stack tree, it has a leaf for each of the 1024 stack items.
+pub const STACK_DEPTH: usize = 10;
+
+/// Depth of the memory tree, it has a leaf for each 32-byte word of the memory.
+pub const MEMORY_DEPTH: usize = 32;
+
+/// Proof of a word of the stack or the memory.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellProof {
+    /// Index of the leaf, the stack position from the bottom or the memory word.
+    pub index: u64,
+    /// Word at the index.
+    pub value: B256,
+    /// Siblings on the path from the leaf to the root.
+    pub siblings: Vec<B256>,
+}
+
+impl CellProof {
+    /// Returns the root of the tree with the value at the index.
+    pub fn root(&self) -> B256 {
+        let mut index = self.index;
+        let mut node = keccak256(self.value);
+        for sibling in &self.siblings {
+            node = if index & 1 == 0 {
+                hash_pair(node, *sibling)
+            } else {
+                hash_pair(*sibling, node)
+            };
+            index >>= 1;
+        }
+        node
+    }
+
+    /// Returns `true` if the proof is valid for the root.
+    pub fn verify(&self, root: B256) -> bool {
+        self.root() == root
+    }
+}
+
+/// Merkle tree of fixed depth over the words, padded with zero words.
+#[derive(Clone, Debug)]
+pub struct MerkleTree {
+    /// Words of the leaves.
+    words: Vec<B256>,
+    /// Leaves and the levels above them, without the padding.
+    levels: Vec<Vec<B256>>,
+    /// Root of the tree of zero words for each depth.
+    zeros: Vec<B256>,
+}
+
+impl MerkleTree {
+    /// Builds the tree of the given depth over the words.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more words than leaves.
+    pub fn new(words: impl IntoIterator<Item = B256>, depth: usize) -> Self {
+        let mut zeros = Vec::with_capacity(depth + 1);
+        zeros.push(keccak256(B256::ZERO));
+        for level in 0..depth {
+            zeros.push(hash_pair(zeros[level], zeros[level]));
+        }
+
+        let words: Vec<_> = words.into_iter().collect();
+        let leaves: Vec<_> = words.iter().map(keccak256).collect();
+        assert!(
+            depth >= u64::BITS as usize || leaves.len() as u64 <= 1 << depth,
+            "too many words for the tree depth"
+        );
+        let mut levels = vec![leaves];
+        for level in 0..depth {
+            let nodes = levels[level]
+                .chunks(2)
+                .map(|pair| hash_pair(pair[0], pair.get(1).copied().unwrap_or(zeros[level])))
+                .collect();
+            levels.push(nodes);
+        }
+        Self {
+            words,
+            levels,
+            zeros,
+        }
+    }
+
+    /// Builds the tree of the stack, from the bottom to the top of the stack.
+    pub fn stack<'a>(stack: impl IntoIterator<Item = &'a U256>) -> Self {
+        Self::new(
+            stack.into_iter().map(|value| value.to_be_bytes().into()),
+            STACK_DEPTH,
+        )
+    }
+
+    /// Builds the tree of the memory, its length has to be a multiple of 32.
+    pub fn memory(memory: &[u8]) -> Self {
+        Self::new(memory.chunks(32).map(B256::from_slice), MEMORY_DEPTH)
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> B256 {
+        let depth = self.zeros.len() - 1;
+        self.node(depth, 0)
+    }
+
+    /// Returns the proof of the word at the index, the proof of a padding word proves zero.
+    pub fn proof(&self, index: u64) -> CellProof {
+        let depth = self.zeros.len() - 1;
+        let value = usize::try_from(index)
+            .ok()
+            .and_then(|index| self.words.get(index))
+            .copied()
+            .unwrap_or_default();
+        CellProof {
+            index,
+            value,
+            siblings: (0..depth)
+                .map(|level| self.node(level, (index >> level) ^ 1))
+                .collect(),
+        }
+    }
+
+    /// Returns the node at the index of the level, counted from the leaves.
+    fn node(&self, level: usize, index: u64) -> B256 {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.levels[level].get(index))
+            .copied()
+            .unwrap_or(self.zeros[level])
+    }
+}
+
+fn hash_pair(left: B256, right: B256) -> B256 {
+    let mut pair = [0; 64];
+    pair[..32].copy_from_slice(left.as_slice());
+    pair[32..].copy_from_slice(right.as_slice());
+    keccak256(pair)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proofs() {
+        let empty = MerkleTree::new([], 3);
+        let zero = MerkleTree::new([B256::ZERO; 8], 3);
+        assert_eq!(empty.root(), zero.root());
+
+        let words: Vec<_> = (1..6u8).map(B256::with_last_byte).collect();
+        let tree = MerkleTree::new(words.clone(), 3);
+        for index in 0..8 {
+            let proof = tree.proof(index);
+            assert_eq!(
+                proof.value,
+                words.get(index as usize).copied().unwrap_or_default()
+            );
+            assert!(proof.verify(tree.root()), "index {index}");
+        }
+        let mut proof = tree.proof(2);
+        proof.value = B256::ZERO;
+        assert!(!proof.verify(tree.root()));
+
+        let memory = MerkleTree::memory(&[0xff; 96]);
+        let proof = memory.proof(1);
+        assert_eq!(proof.siblings.len(), MEMORY_DEPTH);
+        assert!(proof.verify(memory.root()));
+        assert!(memory.proof(1 << 20).verify(memory.root()));
+    }
+}
@@ -0,0 +1,99 @@
+//! [Inspector] recording the machine state before instructions.
+use crate::{
+    state::{copy_interpreter, StepState},
+    step::step_witness,
+};
+use database::{ExecutionWitness, WitnessDB};
+use inspector::Inspector;
+use revm::{
+    context::{block::BlockEnv, tx::TxEnv, CfgEnv, Context, JournaledState},
+    interpreter::{interpreter::EthInterpreter, Interpreter},
+    Database,
+};
+use std::{collections::BTreeSet, vec::Vec};
+
+/// Records the [`StepState`] before the instructions of a transaction.
+///
+/// Steps are counted over all frames of the transaction. The recorded states have an empty
+/// witness, it is set by [`StepRecorder::into_states`] once the transaction is executed.
+#[derive(Debug, Default)]
+pub struct StepRecorder {
+    /// Steps to record, all steps if `None`.
+    steps: Option<BTreeSet<u64>>,
+    /// Number of executed steps.
+    step: u64,
+    states: Vec<(u64, StepState)>,
+}
+
+impl StepRecorder {
+    /// Creates the recorder of the steps.
+    pub fn new(steps: impl IntoIterator<Item = u64>) -> Self {
+        Self {
+            steps: Some(steps.into_iter().collect()),
+            ..Default::default()
+        }
+    }
+
+    /// Creates the recorder of all steps.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of executed steps.
+    pub fn step_count(&self) -> u64 {
+        self.step
+    }
+
+    /// Returns the recorded states with their step, given the witness of the transaction,
+    /// for example recorded by the [`WitnessRecorder`].
+    ///
+    /// Every state gets the part of the witness its instruction needs. If that can't be
+    /// determined, because the witness misses a read of the instruction, the state gets the
+    /// whole witness and [`execute_step`] reports the error.
+    ///
+    /// [`WitnessRecorder`]: database::WitnessRecorder
+    /// [`execute_step`]: crate::execute_step
+    pub fn into_states(self, witness: &ExecutionWitness) -> Vec<(u64, StepState)> {
+        let witness = WitnessDB::new(witness.clone());
+        self.states
+            .into_iter()
+            .map(|(step, mut state)| {
+                state.witness =
+                    step_witness(&state, &witness).unwrap_or_else(|_| witness.witness.clone());
+                (step, state)
+            })
+            .collect()
+    }
+}
+
+impl<DB: Database, CHAIN>
+    Inspector<Context<BlockEnv, TxEnv, CfgEnv, DB, JournaledState<DB>, CHAIN>, EthInterpreter>
+    for StepRecorder
+{
+    fn step(
+        &mut self,
+        interp: &mut Interpreter<EthInterpreter>,
+        context: &mut Context<BlockEnv, TxEnv, CfgEnv, DB, JournaledState<DB>, CHAIN>,
+    ) {
+        let step = self.step;
+        self.step += 1;
+        if self
+            .steps
+            .as_ref()
+            .is_some_and(|steps| !steps.contains(&step))
+        {
+            return;
+        }
+        self.states.push((
+            step,
+            StepState {
+                block: context.block.clone(),
+                tx: context.tx.clone(),
+                cfg: context.cfg.clone(),
+                journal: context.journaled_state.to_init(),
+                witness: ExecutionWitness::default(),
+                interpreter: copy_interpreter(interp),
+            },
+        ));
+    }
+}
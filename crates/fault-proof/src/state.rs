@@ -0,0 +1,170 @@
+//! Serializable machine state of a single step.
+use crate::merkle::MerkleTree;
+use core::cell::RefCell;
+use database::ExecutionWitness;
+use revm::{
+    context::{block::BlockEnv, tx::TxEnv, CfgEnv, JournalInit},
+    interpreter::{interpreter::EthInterpreter, Interpreter},
+    primitives::{keccak256, Address, B256, U256},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, rc::Rc};
+use trie::{ProofError, Trie, EMPTY_ROOT_HASH};
+
+/// Machine state before the execution of an instruction.
+///
+/// The interpreter holds the memory shared by all frames of the transaction, the
+/// [`ExecutionWitness`] has the database reads of the transaction.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StepState {
+    pub block: BlockEnv,
+    pub tx: TxEnv,
+    pub cfg: CfgEnv,
+    /// Journal of the transaction without the database.
+    pub journal: JournalInit,
+    /// Database reads the instruction may need.
+    pub witness: ExecutionWitness,
+    /// Interpreter of the executing frame.
+    pub interpreter: Interpreter<EthInterpreter>,
+}
+
+impl Clone for StepState {
+    /// Clones the state with a copy of the memory, see [`copy_interpreter`].
+    fn clone(&self) -> Self {
+        Self {
+            block: self.block.clone(),
+            tx: self.tx.clone(),
+            cfg: self.cfg.clone(),
+            journal: self.journal.clone(),
+            witness: self.witness.clone(),
+            interpreter: copy_interpreter(&self.interpreter),
+        }
+    }
+}
+
+/// Returns a copy of the interpreter with its own memory, cloning the interpreter would share
+/// the memory with it.
+pub(crate) fn copy_interpreter(
+    interpreter: &Interpreter<EthInterpreter>,
+) -> Interpreter<EthInterpreter> {
+    let mut copy = interpreter.clone();
+    copy.memory = Rc::new(RefCell::new(interpreter.memory.borrow().clone()));
+    copy
+}
+
+/// Commitments to the parts of a [`StepState`] an instruction accesses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateRoots {
+    /// Root of the [`MerkleTree::stack`].
+    pub stack: B256,
+    /// Number of items on the stack.
+    pub stack_len: usize,
+    /// Root of the [`MerkleTree::memory`] of the executing frame.
+    pub memory: B256,
+    /// Size of the memory of the executing frame.
+    pub memory_size: usize,
+    /// Storage root of the executing account, see [`StepState::storage_trie`].
+    pub storage: B256,
+}
+
+impl StepState {
+    /// Returns the address whose storage the executing frame accesses.
+    pub fn address(&self) -> Address {
+        self.interpreter.input.target_address
+    }
+
+    /// Returns the storage slots of the executing account known to the state.
+    ///
+    /// Slots loaded by the journal have their present value, the other slots of the witness
+    /// have their value in the database.
+    pub fn storage(&self) -> BTreeMap<U256, U256> {
+        let address = self.address();
+        let account = self.journal.state.get(&address);
+        let mut storage = match self.witness.storage.get(&address) {
+            // Storage of the database is cleared if the account is created.
+            Some(storage) if !account.is_some_and(|account| account.is_created()) => {
+                storage.clone()
+            }
+            _ => BTreeMap::new(),
+        };
+        if let Some(account) = account {
+            storage.extend(
+                account
+                    .storage
+                    .iter()
+                    .map(|(slot, value)| (*slot, value.present_value)),
+            );
+        }
+        storage
+    }
+
+    /// Returns the Merkle tree of the stack.
+    pub fn stack_tree(&self) -> MerkleTree {
+        MerkleTree::stack(self.interpreter.stack.data())
+    }
+
+    /// Returns the Merkle tree of the memory of the executing frame.
+    pub fn memory_tree(&self) -> MerkleTree {
+        MerkleTree::memory(self.interpreter.memory.borrow().context_memory())
+    }
+
+    /// Returns the storage trie of the executing account with the slots loaded by the journal.
+    ///
+    /// The trie is created from the committed storage root and the storage trie nodes of the
+    /// witness, so its root commits to all slots of the account and not only to the slots of
+    /// the witness. Fails if the nodes do not prove the slots of the journal.
+    pub fn storage_trie(&self) -> Result<Trie, ProofError> {
+        let address = self.address();
+        let account = self.journal.state.get(&address);
+        let mut trie = if account.is_some_and(|account| account.is_created()) {
+            // Storage of the database is cleared if the account is created.
+            Trie::new()
+        } else {
+            let root = match self.witness.accounts.get(&address) {
+                Some(None) => EMPTY_ROOT_HASH,
+                _ => *self
+                    .witness
+                    .storage_roots
+                    .get(&address)
+                    .ok_or(ProofError::MissingNode)?,
+            };
+            let nodes = self
+                .witness
+                .storage_nodes
+                .get(&address)
+                .into_iter()
+                .flatten();
+            Trie::from_proof(root, nodes)?
+        };
+        if let Some(account) = account {
+            for (slot, value) in &account.storage {
+                trie.try_insert(storage_key(*slot), storage_value(value.present_value))?;
+            }
+        }
+        Ok(trie)
+    }
+
+    /// Returns the roots of the stack, the memory and the storage.
+    pub fn roots(&self) -> Result<StateRoots, ProofError> {
+        Ok(StateRoots {
+            stack: self.stack_tree().root(),
+            stack_len: self.interpreter.stack.len(),
+            memory: self.memory_tree().root(),
+            memory_size: self.interpreter.memory.borrow().len(),
+            storage: self.storage_trie()?.root(),
+        })
+    }
+}
+
+/// Returns the key of the slot in the storage trie.
+pub(crate) fn storage_key(slot: U256) -> B256 {
+    keccak256(slot.to_be_bytes::<32>())
+}
+
+/// Returns the value of the slot in the storage trie, empty for zero.
+pub(crate) fn storage_value(value: U256) -> Vec<u8> {
+    if value.is_zero() {
+        return Vec::new();
+    }
+    alloy_rlp::encode(value)
+}
@@ -0,0 +1,697 @@
+//! Execution of a single instruction with the proofs of the cells it accesses.
+use crate::{
+    merkle::{CellProof, MerkleTree},
+    state::{copy_interpreter, storage_key, storage_value, StateRoots, StepState},
+};
+use core::{fmt, mem};
+use database::{ExecutionWitness, WitnessDB, WitnessDBError, WitnessRecorder};
+use revm::{
+    bytecode::opcode::{self, OpCode},
+    context::{block::BlockEnv, tx::TxEnv, CfgEnv, Context, JournaledState},
+    context_interface::Cfg,
+    database_interface::WrapDatabaseRef,
+    interpreter::{
+        interpreter::EthInterpreter,
+        interpreter_types::{Immediates, Jumps, LoopControl},
+        table::make_metered_instruction_table,
+        InstructionResult, Interpreter,
+    },
+    primitives::{Bytes, B256, U256},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, vec::Vec};
+use trie::{verify_proof, ProofError, Trie};
+
+/// Context the instruction is executed with, it reads the database from the witness.
+type StepContext = Context<BlockEnv, TxEnv, CfgEnv, WitnessDB, JournaledState<WitnessDB>>;
+
+/// Proof of a storage slot of the executing account.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageProof {
+    pub slot: U256,
+    pub value: U256,
+    /// Encoded trie nodes, see [`Trie::proof`].
+    pub proof: Vec<Bytes>,
+}
+
+impl StorageProof {
+    fn new(trie: &mut Trie, state: &StepState, slot: U256) -> Self {
+        Self {
+            slot,
+            value: state.storage().get(&slot).copied().unwrap_or_default(),
+            proof: trie.proof(storage_key(slot)),
+        }
+    }
+
+    /// Returns `true` if the proof is valid for the storage root of the account, see
+    /// [`StateRoots::storage`].
+    pub fn verify(&self, root: B256) -> bool {
+        let value = storage_value(self.value);
+        verify_proof(root, storage_key(self.slot), &self.proof)
+            == Ok((!value.is_empty()).then_some(value))
+    }
+}
+
+/// Proof of the cells accessed by the instruction.
+///
+/// Memory proofs are of the words the instruction reads or writes that are within the memory
+/// before and after the step. Memory the instruction expands is zero.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepProof {
+    pub opcode: u8,
+    /// Program counter of the instruction.
+    pub pc: usize,
+    /// Roots before the step.
+    pub pre: StateRoots,
+    /// Roots after the step.
+    pub post: StateRoots,
+    /// Stack items the instruction pops, before the step.
+    pub pre_stack: Vec<CellProof>,
+    /// Stack items the instruction pushes, after the step.
+    pub post_stack: Vec<CellProof>,
+    /// Memory words the instruction accesses, before the step.
+    pub pre_memory: Vec<CellProof>,
+    /// Memory words the instruction accesses, after the step.
+    pub post_memory: Vec<CellProof>,
+    /// Storage slot `SLOAD` and `SSTORE` access, before the step.
+    pub pre_storage: Vec<StorageProof>,
+    /// Storage slot `SLOAD` and `SSTORE` access, after the step.
+    pub post_storage: Vec<StorageProof>,
+}
+
+impl StepProof {
+    /// Returns `true` if all proofs are valid for the roots.
+    pub fn verify(&self) -> bool {
+        self.pre_stack
+            .iter()
+            .all(|cell| cell.verify(self.pre.stack))
+            && self
+                .post_stack
+                .iter()
+                .all(|cell| cell.verify(self.post.stack))
+            && self
+                .pre_memory
+                .iter()
+                .all(|cell| cell.verify(self.pre.memory))
+            && self
+                .post_memory
+                .iter()
+                .all(|cell| cell.verify(self.post.memory))
+            && self
+                .pre_storage
+                .iter()
+                .all(|slot| slot.verify(self.pre.storage))
+            && self
+                .post_storage
+                .iter()
+                .all(|slot| slot.verify(self.post.storage))
+    }
+}
+
+/// State after the step and the proof of the step.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StepOutput {
+    pub state: StepState,
+    pub proof: StepProof,
+}
+
+/// Error of [`execute_step`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StepError {
+    /// Interpreter stopped before the step, its action has to be handled by the frame.
+    Stopped(InstructionResult),
+    /// Instruction reads the database and the read is missing in the witness.
+    MissingWitness(WitnessDBError),
+    /// Storage trie nodes of the witness do not prove the slots against the storage root.
+    InvalidStorageWitness(ProofError),
+}
+
+impl fmt::Display for StepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stopped(result) => write!(f, "interpreter stopped with {result:?}"),
+            Self::MissingWitness(error) => error.fmt(f),
+            Self::InvalidStorageWitness(error) => write!(f, "invalid storage witness: {error}"),
+        }
+    }
+}
+
+impl core::error::Error for StepError {}
+
+/// Executes the instruction at the program counter of the state.
+///
+/// Gas is charged as by the inspector, with the static cost of the gas schedule for every
+/// instruction. If the instruction stops the interpreter, the result and the pending action
+/// stay in the interpreter of the returned state, [`Interpreter::take_action`] returns them.
+pub fn execute_step(state: StepState) -> Result<StepOutput, StepError> {
+    let result = state.interpreter.control.instruction_result();
+    if !result.is_continue() {
+        return Err(StepError::Stopped(result));
+    }
+    let access = Access::new(&state.interpreter);
+    let opcode = state.interpreter.bytecode.opcode();
+    let pc = state.interpreter.bytecode.pc();
+
+    let mut proofs = CellProofs::new(&state)?;
+    let pre = proofs.roots;
+    let pre_stack = proofs.stack(access.inputs);
+    let pre_memory = proofs.memory(&access.memory);
+    let pre_storage = proofs.storage(&state, access.slot);
+
+    let StepState {
+        block,
+        tx,
+        cfg,
+        journal,
+        witness,
+        mut interpreter,
+    } = state;
+    let mut context = StepContext {
+        block,
+        tx,
+        cfg,
+        journaled_state: JournaledState::from_init(&journal, WitnessDB::new(witness)),
+        chain: (),
+        error: Ok(()),
+    };
+    let table =
+        make_metered_instruction_table::<EthInterpreter, StepContext>(context.cfg.gas_schedule());
+    interpreter.step(&table, &mut context);
+    if let Err(error) = context.error {
+        return Err(StepError::MissingWitness(error));
+    }
+    let witness = mem::take(&mut context.journaled_state.database.witness);
+    let state = StepState {
+        block: context.block,
+        tx: context.tx,
+        cfg: context.cfg,
+        journal: context.journaled_state.into_init(),
+        witness,
+        interpreter,
+    };
+
+    let mut proofs = CellProofs::new(&state)?;
+    let proof = StepProof {
+        opcode,
+        pc,
+        pre,
+        post: proofs.roots,
+        pre_stack,
+        post_stack: proofs.stack(access.outputs),
+        pre_memory,
+        post_memory: proofs.memory(&access.memory),
+        pre_storage,
+        post_storage: proofs.storage(&state, access.slot),
+    };
+    Ok(StepOutput { state, proof })
+}
+
+/// Returns the part of the transaction witness the instruction of the state needs.
+///
+/// The instruction is executed against the witness of the transaction to record its reads.
+/// The storage root of the executing account is added with the storage trie nodes proving
+/// the slots of the journal and the slot the instruction accesses.
+pub(crate) fn step_witness(
+    state: &StepState,
+    witness: &WitnessDB,
+) -> Result<ExecutionWitness, StepError> {
+    type RecordingDB<'a> = WitnessRecorder<WrapDatabaseRef<&'a WitnessDB>>;
+
+    let mut context = Context::<BlockEnv, TxEnv, CfgEnv, RecordingDB<'_>, _> {
+        block: state.block.clone(),
+        tx: state.tx.clone(),
+        cfg: state.cfg.clone(),
+        journaled_state: JournaledState::from_init(
+            &state.journal,
+            WitnessRecorder::new(WrapDatabaseRef(witness)),
+        ),
+        chain: (),
+        error: Ok(()),
+    };
+    let mut interpreter = copy_interpreter(&state.interpreter);
+    let table = make_metered_instruction_table(context.cfg.gas_schedule());
+    interpreter.step(&table, &mut context);
+    context.error.map_err(StepError::MissingWitness)?;
+    let mut step_witness = context.journaled_state.database.into_witness();
+
+    let address = state.address();
+    let witness = &witness.witness;
+    if let Some(account) = witness.accounts.get(&address) {
+        step_witness.accounts.insert(address, account.clone());
+    }
+    if let Some(root) = witness.storage_roots.get(&address) {
+        let nodes = witness.storage_nodes.get(&address).into_iter().flatten();
+        let mut trie = Trie::from_proof(*root, nodes).map_err(StepError::InvalidStorageWitness)?;
+        let journal_slots = state
+            .journal
+            .state
+            .get(&address)
+            .into_iter()
+            .flat_map(|account| account.storage.keys().copied());
+        let slots = journal_slots.chain(Access::new(&state.interpreter).slot);
+        step_witness.storage_roots.insert(address, *root);
+        step_witness
+            .storage_nodes
+            .insert(address, trie.proof_nodes(slots.map(storage_key)));
+    }
+    Ok(step_witness)
+}
+
+/// Trees of a state and their roots.
+struct CellProofs {
+    stack: MerkleTree,
+    memory: MerkleTree,
+    storage: Trie,
+    roots: StateRoots,
+}
+
+impl CellProofs {
+    fn new(state: &StepState) -> Result<Self, StepError> {
+        let stack = state.stack_tree();
+        let memory = state.memory_tree();
+        let mut storage = state
+            .storage_trie()
+            .map_err(StepError::InvalidStorageWitness)?;
+        let roots = StateRoots {
+            stack: stack.root(),
+            stack_len: state.interpreter.stack.len(),
+            memory: memory.root(),
+            memory_size: state.interpreter.memory.borrow().len(),
+            storage: storage.root(),
+        };
+        Ok(Self {
+            stack,
+            memory,
+            storage,
+            roots,
+        })
+    }
+
+    /// Returns the proofs of the top items of the stack.
+    fn stack(&self, count: usize) -> Vec<CellProof> {
+        let len = self.roots.stack_len;
+        (len.saturating_sub(count)..len)
+            .map(|index| self.stack.proof(index as u64))
+            .collect()
+    }
+
+    /// Returns the proofs of the words of the ranges within the memory.
+    fn memory(&self, ranges: &[(usize, usize)]) -> Vec<CellProof> {
+        let words = self.roots.memory_size / 32;
+        let indices: BTreeSet<usize> = ranges
+            .iter()
+            .flat_map(|&(offset, len)| {
+                let end = offset.saturating_add(len).div_ceil(32).min(words);
+                offset / 32..end
+            })
+            .collect();
+        indices
+            .into_iter()
+            .map(|index| self.memory.proof(index as u64))
+            .collect()
+    }
+
+    fn storage(&mut self, state: &StepState, slot: Option<U256>) -> Vec<StorageProof> {
+        slot.map(|slot| StorageProof::new(&mut self.storage, state, slot))
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Cells the instruction accesses, computed from the state before the step.
+struct Access {
+    /// Number of items popped from the stack.
+    inputs: usize,
+    /// Number of items pushed to the stack.
+    outputs: usize,
+    /// Offset and length of the memory ranges.
+    memory: Vec<(usize, usize)>,
+    /// Storage slot of the executing account.
+    slot: Option<U256>,
+}
+
+impl Access {
+    fn new(interpreter: &Interpreter<EthInterpreter>) -> Self {
+        let opcode = interpreter.bytecode.opcode();
+        let is_eof = interpreter.runtime_flag.is_eof;
+        let stack = interpreter.stack.data();
+        // Stack item by its position from the top.
+        let input = |index: usize| {
+            stack
+                .len()
+                .checked_sub(index + 1)
+                .map(|position| stack[position])
+        };
+        // Memory range of the offset and the length on the stack.
+        let range = |offset: usize, len: usize| -> Option<(usize, usize)> {
+            let offset = usize::try_from(input(offset)?).ok()?;
+            let len = usize::try_from(input(len)?).ok()?;
+            (len != 0).then_some((offset, len))
+        };
+        // Memory range of the offset on the stack and a fixed length.
+        let word = |offset: usize, len: usize| {
+            let offset = usize::try_from(input(offset)?).ok()?;
+            Some((offset, len))
+        };
+
+        let (inputs, outputs) = match opcode {
+            // Immediate of the EOF stack instructions is after the opcode.
+            opcode::DUPN if is_eof => {
+                let n = interpreter.bytecode.read_slice(2)[1] as usize;
+                (n + 1, n + 2)
+            }
+            opcode::SWAPN if is_eof => {
+                let n = interpreter.bytecode.read_slice(2)[1] as usize;
+                (n + 2, n + 2)
+            }
+            opcode::EXCHANGE if is_eof => {
+                let imm = interpreter.bytecode.read_slice(2)[1] as usize;
+                let depth = (imm >> 4) + (imm & 0x0f) + 3;
+                (depth, depth)
+            }
+            _ => OpCode::new(opcode).map_or((0, 0), |opcode| {
+                (opcode.inputs() as usize, opcode.outputs() as usize)
+            }),
+        };
+
+        let memory = match opcode {
+            opcode::MLOAD | opcode::MSTORE => vec![word(0, 32)],
+            opcode::MSTORE8 => vec![word(0, 1)],
+            opcode::MCOPY => vec![range(0, 2), range(1, 2)],
+            opcode::KECCAK256
+            | opcode::LOG0
+            | opcode::LOG1
+            | opcode::LOG2
+            | opcode::LOG3
+            | opcode::LOG4
+            | opcode::RETURN
+            | opcode::REVERT => vec![range(0, 1)],
+            opcode::CALLDATACOPY | opcode::CODECOPY | opcode::RETURNDATACOPY => {
+                vec![range(0, 2)]
+            }
+            opcode::EXTCODECOPY => vec![range(1, 3)],
+            opcode::CREATE | opcode::CREATE2 => vec![range(1, 2)],
+            opcode::CALL | opcode::CALLCODE => vec![range(3, 4)],
+            opcode::DELEGATECALL | opcode::STATICCALL => vec![range(2, 3)],
+            opcode::DATACOPY if is_eof => vec![range(0, 2)],
+            opcode::RETURNCONTRACT if is_eof => vec![range(0, 1)],
+            opcode::EOFCREATE if is_eof => vec![range(2, 3)],
+            opcode::EXTCALL | opcode::EXTDELEGATECALL | opcode::EXTSTATICCALL if is_eof => {
+                vec![range(1, 2)]
+            }
+            _ => Vec::new(),
+        };
+
+        let slot = match opcode {
+            opcode::SLOAD | opcode::SSTORE => input(0),
+            _ => None,
+        };
+
+        Self {
+            inputs,
+            outputs,
+            memory: memory.into_iter().flatten().collect(),
+            slot,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StepRecorder;
+    use database::{CacheDB, WitnessRecorder};
+    use inspector::{inspector_context::InspectorContext, inspector_handler, InspectorMainEvm};
+    use revm::{
+        bytecode::{opcode::*, Bytecode},
+        database_interface::EmptyDB,
+        interpreter::InterpreterAction,
+        primitives::{keccak256, Address, TxKind},
+        state::AccountInfo,
+        EvmExec,
+    };
+    use std::collections::BTreeMap;
+    use trie::{storage_root, StateTrie};
+
+    const CONTRACT: Address = Address::with_last_byte(0xa0);
+    const CALLEE: Address = Address::with_last_byte(0xb0);
+
+    /// Stores, loads, hashes and copies values, logs and calls the callee.
+    const CODE: &[u8] = &[
+        PUSH1, 0x2a, PUSH0, SSTORE, // Stores 42 at slot 0.
+        PUSH1, 0x01, SLOAD, PUSH1, 0x40, MSTORE, // Stores slot 1 in memory.
+        PUSH1, 0x20, PUSH1, 0x40, KECCAK256, PUSH1, 0x01,
+        SSTORE, // Stores the hash at slot 1.
+        PUSH1, 0x20, PUSH1, 0x40, PUSH0, MCOPY, PUSH0, MLOAD, PUSH1, 0x02, MSTORE8, PUSH1, 0x20,
+        PUSH0, LOG0, // CALL(GAS, CALLEE, 0, 0, 0, 0, 0x20)
+        PUSH1, 0x20, PUSH0, PUSH0, PUSH0, PUSH0, PUSH1, 0xb0, GAS, CALL, POP, PUSH0, MLOAD, POP,
+        STOP,
+    ];
+
+    /// Executes the transaction and returns the states of all steps.
+    fn record() -> Vec<(u64, StepState)> {
+        let code = Bytecode::new_raw(CODE.into());
+        // Returns the caller.
+        let callee = Bytecode::new_raw([CALLER, PUSH0, MSTORE, PUSH1, 0x20, PUSH0, RETURN].into());
+        let mut db = CacheDB::new(EmptyDB::default());
+        let info = AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code);
+        db.insert_account_info(CONTRACT, info);
+        db.insert_account_storage(CONTRACT, U256::from(1), U256::from(5))
+            .unwrap();
+        // Slot the transaction does not access, the storage roots commit to it.
+        db.insert_account_storage(CONTRACT, U256::from(7), U256::from(9))
+            .unwrap();
+        let info = AccountInfo::new(U256::ZERO, 1, callee.hash_slow(), callee);
+        db.insert_account_info(CALLEE, info);
+
+        let ctx = Context::builder()
+            .with_db(WitnessRecorder::new(db))
+            .modify_tx_chained(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.kind = TxKind::Call(CONTRACT);
+                tx.gas_limit = 100_000;
+            });
+        let mut evm = InspectorMainEvm::new(
+            InspectorContext::new(ctx, StepRecorder::all()),
+            inspector_handler(),
+        );
+        assert!(evm.exec().unwrap().result.is_success());
+        let database = &evm.context.inner.journaled_state.database;
+        let mut witness = database.witness().clone();
+        let mut state_trie = StateTrie::new();
+        for (address, account) in &database.db.accounts {
+            state_trie.update_account(*address, Some(&account.info), false, &account.storage);
+        }
+        state_trie.prove_storage(&mut witness);
+        let recorder = core::mem::take(&mut evm.context.inspector);
+        recorder.into_states(&witness)
+    }
+
+    /// Returns the first recorded state of the executing account before the opcode.
+    fn state_before(states: &[(u64, StepState)], opcode: u8) -> StepState {
+        states
+            .iter()
+            .map(|(_, state)| state)
+            .find(|state| {
+                state.address() == CONTRACT && state.interpreter.bytecode.opcode() == opcode
+            })
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn steps_match_execution() {
+        let states = record();
+        // Contract and callee steps.
+        assert_eq!(states.len(), 36 + 6);
+
+        for pair in states.windows(2) {
+            let [(step, state), (_, next)] = pair else {
+                unreachable!()
+            };
+            let state: StepState =
+                serde_json::from_str(&serde_json::to_string(state).unwrap()).unwrap();
+            let roots = state.roots().unwrap();
+            let StepOutput { mut state, proof } = execute_step(state).unwrap();
+            assert_eq!(proof.pre, roots, "step {step}");
+            assert!(proof.verify(), "step {step}");
+
+            if !state.interpreter.control.instruction_result().is_continue() {
+                // Frame handles the action, the next step is in the callee or the caller.
+                match state.interpreter.take_action() {
+                    InterpreterAction::NewFrame(_) => {
+                        assert_eq!(proof.opcode, CALL);
+                        assert_eq!(next.address(), CALLEE);
+                    }
+                    InterpreterAction::Return { result } => {
+                        assert_eq!(proof.opcode, RETURN);
+                        assert_eq!(result.output.len(), 0x20);
+                        assert_eq!(next.address(), CONTRACT);
+                    }
+                    InterpreterAction::None => panic!("step {step} has no action"),
+                }
+                continue;
+            }
+            assert_eq!(proof.post, next.roots().unwrap(), "step {step}");
+            assert_eq!(state.journal, next.journal, "step {step}");
+            let (interpreter, next) = (&state.interpreter, &next.interpreter);
+            assert_eq!(interpreter.bytecode.pc(), next.bytecode.pc(), "step {step}");
+            assert_eq!(interpreter.control.gas, next.control.gas, "step {step}");
+            assert_eq!(interpreter.stack, next.stack, "step {step}");
+            assert_eq!(
+                *interpreter.memory.borrow(),
+                *next.memory.borrow(),
+                "step {step}"
+            );
+        }
+    }
+
+    #[test]
+    fn proves_accessed_cells() {
+        let states = record();
+
+        // `SSTORE` of the hash reads the stack and the slot and writes the slot.
+        let state = states
+            .iter()
+            .map(|(_, state)| state)
+            .filter(|state| state.interpreter.bytecode.opcode() == SSTORE)
+            .nth(1)
+            .unwrap();
+        let hash = keccak256(U256::from(5).to_be_bytes::<32>());
+        let StepOutput { proof, .. } = execute_step(state.clone()).unwrap();
+        assert_eq!(proof.pre_stack.len(), 2);
+        assert_eq!(proof.pre_stack[0].value, hash);
+        assert_eq!(proof.pre_stack[1].value, B256::with_last_byte(1));
+        assert!(proof.post_stack.is_empty());
+        assert_eq!(proof.pre_storage[0].value, U256::from(5));
+        assert_eq!(proof.post_storage[0].value, U256::from_be_bytes(hash.0));
+        assert!(proof.verify());
+        // Proofs do not verify against the roots of the other state.
+        assert!(!proof.pre_storage[0].verify(proof.post.storage));
+        assert!(!proof.pre_stack[0].verify(proof.post.stack));
+
+        // `MCOPY` reads and writes the words of both ranges.
+        let state = state_before(&states, MCOPY);
+        let StepOutput { proof, .. } = execute_step(state).unwrap();
+        let words = |proofs: &[CellProof]| proofs.iter().map(|cell| cell.index).collect::<Vec<_>>();
+        assert_eq!(words(&proof.pre_memory), [0, 2]);
+        assert_eq!(words(&proof.post_memory), [0, 2]);
+        assert_eq!(proof.pre_memory[0].value, B256::ZERO);
+        assert_eq!(proof.post_memory[0].value, proof.pre_memory[1].value);
+        assert!(proof.verify());
+    }
+
+    #[test]
+    fn anchors_storage_to_committed_root() {
+        let states = record();
+        let slots = |storage: &[(u64, U256)]| {
+            storage_root(
+                &storage
+                    .iter()
+                    .map(|(slot, value)| (U256::from(*slot), *value))
+                    .collect::<BTreeMap<_, _>>(),
+            )
+        };
+
+        // Storage root before the transaction writes has the slot it does not access.
+        let (_, first) = &states[0];
+        let committed = slots(&[(1, U256::from(5)), (7, U256::from(9))]);
+        assert_eq!(first.roots().unwrap().storage, committed);
+        assert_eq!(first.witness.storage_roots[&CONTRACT], committed);
+
+        // `SSTORE` of the hash proves the written slot against the root with the writes.
+        let state = states
+            .iter()
+            .map(|(_, state)| state)
+            .filter(|state| state.interpreter.bytecode.opcode() == SSTORE)
+            .nth(1)
+            .unwrap();
+        let hash = keccak256(U256::from(5).to_be_bytes::<32>());
+        let StepOutput { proof, .. } = execute_step(state.clone()).unwrap();
+        assert_eq!(
+            proof.pre.storage,
+            slots(&[(0, U256::from(42)), (1, U256::from(5)), (7, U256::from(9))])
+        );
+        assert_eq!(
+            proof.post.storage,
+            slots(&[
+                (0, U256::from(42)),
+                (1, U256::from_be_bytes(hash.0)),
+                (7, U256::from(9))
+            ])
+        );
+        assert!(proof.verify());
+
+        // Proof from the storage of the witness slots only does not verify against the root.
+        let mut partial = Trie::new();
+        partial.insert(storage_key(U256::from(0)), storage_value(U256::from(42)));
+        partial.insert(storage_key(U256::from(1)), storage_value(U256::from(5)));
+        let forged = StorageProof {
+            slot: U256::from(1),
+            value: U256::from(5),
+            proof: partial.proof(storage_key(U256::from(1))),
+        };
+        assert!(forged.verify(partial.root()));
+        assert!(!forged.verify(proof.pre.storage));
+    }
+
+    #[test]
+    fn witnesses_are_step_slices() {
+        let states = record();
+
+        // `PUSH1` reads nothing, only the storage root and the journal slots are proven.
+        let (_, state) = &states[0];
+        assert_eq!(state.interpreter.bytecode.opcode(), PUSH1);
+        let witness = &state.witness;
+        assert!(witness.storage.is_empty());
+        assert!(witness.bytecodes.is_empty());
+        assert_eq!(witness.accounts.keys().collect::<Vec<_>>(), [&CONTRACT]);
+        assert!(witness.storage_roots.contains_key(&CONTRACT));
+
+        // `SLOAD` reads the slot that is not in the journal yet.
+        let state = state_before(&states, SLOAD);
+        assert_eq!(
+            state.witness.storage[&CONTRACT],
+            BTreeMap::from([(U256::from(1), U256::from(5))])
+        );
+        assert!(execute_step(state).unwrap().proof.verify());
+
+        // `CALL` loads the callee and its code.
+        let state = state_before(&states, CALL);
+        assert!(state.witness.accounts.contains_key(&CALLEE));
+        assert_eq!(state.witness.bytecodes.len(), 1);
+        assert!(execute_step(state).unwrap().proof.verify());
+    }
+
+    #[test]
+    fn errors() {
+        let states = record();
+
+        let mut state = state_before(&states, SLOAD);
+        state.witness.storage.clear();
+        assert_eq!(
+            execute_step(state).unwrap_err(),
+            StepError::MissingWitness(WitnessDBError::MissingStorage(CONTRACT, U256::from(1)))
+        );
+
+        // Storage trie nodes must prove the slots of the journal against the committed root.
+        let mut state = state_before(&states, SLOAD);
+        state.witness.storage_nodes.clear();
+        assert_eq!(
+            execute_step(state).unwrap_err(),
+            StepError::InvalidStorageWitness(ProofError::MissingNode)
+        );
+        let mut state = state_before(&states, SLOAD);
+        state.witness.storage_roots.clear();
+        assert_eq!(
+            execute_step(state).unwrap_err(),
+            StepError::InvalidStorageWitness(ProofError::MissingNode)
+        );
+
+        let StepOutput { state, .. } = execute_step(state_before(&states, STOP)).unwrap();
+        assert_eq!(
+            execute_step(state).unwrap_err(),
+            StepError::Stopped(InstructionResult::Stop)
+        );
+    }
+}
@@ -2,6 +2,26 @@ use crate::util::FrameOrFrameResult;
 pub use crate::{Frame, FrameOrResultGen};
 pub use std::{vec, vec::Vec};
 
+/// Frames of an execution and the frame input or result that is handled next.
+///
+/// Execution can be suspended between two [`ExecutionHandler::step`]s.
+pub struct ExecutionStack<FRAME: Frame> {
+    /// Frames from the first frame to the executing frame.
+    pub frames: Vec<FRAME>,
+    /// Input of the frame that is created next or result that is returned to the executing frame.
+    pub pending: Option<FrameOrResultGen<FRAME::FrameInit, FRAME::FrameResult>>,
+}
+
+impl<FRAME: Frame> ExecutionStack<FRAME> {
+    /// Creates a stack with the first frame.
+    pub fn new(frame: FRAME) -> Self {
+        Self {
+            frames: vec![frame],
+            pending: None,
+        }
+    }
+}
+
 pub trait ExecutionHandler {
     type Context;
     type Error;
@@ -27,32 +47,48 @@ pub trait ExecutionHandler {
         context: &mut Self::Context,
         frame: Self::Frame,
     ) -> Result<Self::ExecResult, Self::Error> {
-        let mut frame_stack: Vec<<Self as ExecutionHandler>::Frame> = vec![frame];
+        let mut stack = ExecutionStack::new(frame);
         loop {
-            let frame = frame_stack.last_mut().unwrap();
-            let call_or_result = frame.run(context)?;
+            if let Some(result) = self.step(context, &mut stack)? {
+                return self.last_frame_result(context, result);
+            }
+        }
+    }
 
-            let mut result = match call_or_result {
-                FrameOrResultGen::Frame(init) => match frame.init(context, init)? {
-                    FrameOrResultGen::Frame(new_frame) => {
-                        frame_stack.push(new_frame);
-                        continue;
+    /// Runs the executing frame, creates the pending frame or returns the pending result to the
+    /// executing frame.
+    ///
+    /// Returns the result of the first frame when it returned.
+    fn step(
+        &self,
+        context: &mut Self::Context,
+        stack: &mut ExecutionStack<Self::Frame>,
+    ) -> Result<Option<<Self::Frame as Frame>::FrameResult>, Self::Error> {
+        let frame = stack.frames.last_mut().unwrap();
+        match stack.pending.take() {
+            None => match frame.run(context)? {
+                FrameOrResultGen::Frame(init) => {
+                    stack.pending = Some(FrameOrResultGen::Frame(init))
+                }
+                FrameOrResultGen::Result(mut result) => {
+                    // Pop frame that returned result
+                    stack.frames.pop();
+                    if stack.frames.is_empty() {
+                        Self::Frame::final_return(context, &mut result)?;
+                        return Ok(Some(result));
                     }
-                    // Dont pop the frame as new frame was not created.
-                    FrameOrResultGen::Result(result) => result,
-                },
+                    stack.pending = Some(FrameOrResultGen::Result(result));
+                }
+            },
+            Some(FrameOrResultGen::Frame(init)) => match frame.init(context, init)? {
+                FrameOrResultGen::Frame(new_frame) => stack.frames.push(new_frame),
+                // Dont pop the frame as new frame was not created.
                 FrameOrResultGen::Result(result) => {
-                    // Pop frame that returned result
-                    frame_stack.pop();
-                    result
+                    stack.pending = Some(FrameOrResultGen::Result(result))
                 }
-            };
-
-            let Some(frame) = frame_stack.last_mut() else {
-                Self::Frame::final_return(context, &mut result)?;
-                return self.last_frame_result(context, result);
-            };
-            frame.return_result(context, result)?;
+            },
+            Some(FrameOrResultGen::Result(result)) => frame.return_result(context, result)?,
         }
+        Ok(None)
     }
 }
@@ -14,7 +14,7 @@ pub mod precompile_provider;
 pub mod util;
 pub mod validation;
 
-pub use execution::ExecutionHandler;
+pub use execution::{ExecutionHandler, ExecutionStack};
 pub use frame::Frame;
 pub use handler::Handler;
 pub use post_execution::PostExecutionHandler;
@@ -1,5 +1,7 @@
 use crate::Frame;
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FrameOrResultGen<Frame, Result> {
     Frame(Frame),
     Result(Result),
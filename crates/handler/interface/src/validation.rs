@@ -17,6 +17,7 @@ pub trait ValidationHandler {
 
 /// Init and floor gas from transaction
 #[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InitialAndFloorGas {
     /// Initial gas for transaction.
     pub initial_gas: u64,
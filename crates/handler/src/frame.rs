@@ -1,7 +1,9 @@
 use super::frame_data::*;
-use bytecode::{Eof, EOF_MAGIC_BYTES};
+use crate::snapshot::{FrameSnapshot, SnapshotError};
+use bytecode::{legacy::BlockAnalysis, Eof, LegacyAnalyzedBytecode, EOF_MAGIC_BYTES};
 use context_interface::{
     journaled_state::{Journal, JournalCheckpoint},
+    result::FromStringError,
     BlockGetter, Cfg, CfgGetter, ErrorGetter, JournalDBError, JournalGetter, Transaction,
     TransactionGetter,
 };
@@ -11,10 +13,11 @@ use interpreter::{
     gas,
     interpreter::{EthInterpreter, ExtBytecode, InstructionProvider},
     interpreter_types::{LoopControl, ReturnData, RuntimeFlag},
-    return_ok, return_revert, CallInputs, CallOutcome, CallValue, CreateInputs, CreateOutcome,
-    CreateScheme, EOFCreateInputs, EOFCreateKind, FrameInput, Gas, Host, InputsImpl,
-    InstructionResult, Interpreter, InterpreterAction, InterpreterResult, InterpreterTypes,
-    SharedMemory,
+    return_ok, return_revert,
+    table::MeteredInstruction,
+    CallInputs, CallOutcome, CallValue, CreateInputs, CreateOutcome, CreateScheme, EOFCreateInputs,
+    EOFCreateKind, FrameInput, Gas, Host, InputsImpl, InstructionResult, Interpreter,
+    InterpreterAction, InterpreterResult, InterpreterTypes, SharedMemory, EMPTY_SHARED_MEMORY,
 };
 use precompile::PrecompileErrors;
 use primitives::{keccak256, Address, Bytes, B256, U256};
@@ -41,6 +44,14 @@ pub struct EthFrame<CTX, ERROR, IW: InterpreterTypes, PRECOMPILE, INSTRUCTIONS>
     pub instructions: INSTRUCTIONS,
     // This is worth making as a generic type FrameSharedContext.
     pub memory: Rc<RefCell<SharedMemory>>,
+    /// Block analysis if the frame executes legacy bytecode with block metering.
+    pub block_analysis: Option<Arc<BlockAnalysis>>,
+    /// Native code if the frame executes hot legacy bytecode.
+    #[cfg(feature = "jit")]
+    pub jit: Option<Arc<jit::CompiledCode>>,
+    /// Stylus runtime if frame executes a Stylus program.
+    #[cfg(feature = "stylus")]
+    pub stylus: Option<Box<stylus::StylusRuntime>>,
 }
 
 impl<CTX, IW, ERROR, PRECOMP, INST> EthFrame<CTX, ERROR, IW, PRECOMP, INST>
@@ -66,6 +77,11 @@ where
             precompiles,
             instructions,
             memory,
+            block_analysis: None,
+            #[cfg(feature = "jit")]
+            jit: None,
+            #[cfg(feature = "stylus")]
+            stylus: None,
         }
     }
 }
@@ -76,6 +92,7 @@ where
     CTX: EthFrameContext,
     ERROR: EthFrameError<CTX>,
     PRECOMPILE: PrecompileProvider<Context = CTX, Error = ERROR, Output = InterpreterResult>,
+    INSTRUCTION: InstructionProvider<WIRE = EthInterpreter<()>, Host = CTX>,
 {
     /// Make call frame
     #[inline]
@@ -85,7 +102,7 @@ where
         memory: Rc<RefCell<SharedMemory>>,
         inputs: &CallInputs,
         mut precompile: PRECOMPILE,
-        instructions: INSTRUCTION,
+        mut instructions: INSTRUCTION,
     ) -> Result<FrameOrResultGen<Self, FrameResult>, ERROR> {
         let gas = Gas::new(inputs.gas_limit);
 
@@ -146,6 +163,22 @@ where
             }
         }
 
+        // Programs are activated by the `ArbWasm` account.
+        #[cfg(feature = "stylus")]
+        if context.cfg().is_stylus_enabled() && inputs.bytecode_address == stylus::ARB_WASM_ADDRESS
+        {
+            let result = crate::stylus::activate_program(context, inputs)?;
+            if result.result.is_ok() {
+                context.journal().checkpoint_commit();
+            } else {
+                context.journal().checkpoint_revert(checkpoint);
+            }
+            return Ok(FrameOrResultGen::Result(FrameResult::Call(CallOutcome {
+                result,
+                memory_offset: inputs.return_memory_offset.clone(),
+            })));
+        }
+
         let account = context
             .journal()
             .load_account_code(inputs.bytecode_address)?;
@@ -172,6 +205,74 @@ where
             code_hash = account.code_hash();
         }
 
+        // Stylus programs are legacy bytecode if Stylus is disabled.
+        if let Bytecode::Stylus(program) = &bytecode {
+            if !cfg!(feature = "stylus") || !context.cfg().is_stylus_enabled() {
+                bytecode = Bytecode::new_legacy(program.raw().clone());
+            }
+        }
+
+        // Stylus programs are executed by the WASM runtime instead of the interpreter.
+        #[cfg(feature = "stylus")]
+        let stylus = match &bytecode {
+            Bytecode::Stylus(program) => {
+                let mut gas = gas;
+                let program =
+                    match crate::stylus::load_program(context, code_hash, program, &mut gas)? {
+                        Ok(program) => program,
+                        Err(result) => {
+                            context.journal().checkpoint_revert(checkpoint);
+                            return Ok(FrameOrResultGen::Result(FrameResult::Call(CallOutcome {
+                                result: InterpreterResult {
+                                    result,
+                                    gas,
+                                    output: Bytes::new(),
+                                },
+                                memory_offset: inputs.return_memory_offset.clone(),
+                            })));
+                        }
+                    };
+                // Program runs with the gas left after reading its activation.
+                let inputs = CallInputs {
+                    gas_limit: gas.remaining(),
+                    ..inputs.clone()
+                };
+                let Ok(runtime) = stylus::StylusRuntime::new_call(&program, &inputs, context)
+                else {
+                    context.journal().checkpoint_revert(checkpoint);
+                    return return_result(InstructionResult::OpcodeNotFound);
+                };
+                Some(Box::new(runtime))
+            }
+            _ => None,
+        };
+
+        let block_analysis = match &bytecode {
+            Bytecode::LegacyAnalyzed(legacy) if context.cfg().is_block_analysis_enabled() => {
+                instructions
+                    .metered_table()
+                    .map(|table| block_analysis(code_hash, legacy, table))
+            }
+            _ => None,
+        };
+
+        // Hot bytecode is compiled once it was executed often enough, inspectors do not provide
+        // a metered table and always interpret.
+        #[cfg(feature = "jit")]
+        let jit = match (&bytecode, context.cfg().jit_threshold()) {
+            (Bytecode::LegacyAnalyzed(legacy), Some(threshold)) => {
+                instructions.metered_table().and_then(|table| {
+                    jit::JitCache::global().get_or_compile(
+                        code_hash,
+                        legacy,
+                        &static_costs(table),
+                        threshold,
+                    )
+                })
+            }
+            _ => None,
+        };
+
         // Create interpreter and executes call and push new CallStackFrame.
         let interpreter_input = InputsImpl {
             target_address: inputs.target_address,
@@ -180,7 +281,8 @@ where
             call_value: inputs.value.get(),
         };
 
-        Ok(FrameOrResultGen::Frame(Self::new(
+        #[allow(unused_mut)]
+        let mut frame = Self::new(
             FrameData::Call(CallFrame {
                 return_memory_range: inputs.return_memory_offset.clone(),
             }),
@@ -198,7 +300,18 @@ where
             precompile,
             instructions,
             memory,
-        )))
+        );
+        frame.block_analysis = block_analysis;
+        #[cfg(feature = "jit")]
+        {
+            frame.jit = jit;
+        }
+        #[cfg(feature = "stylus")]
+        {
+            frame.stylus = stylus;
+        }
+
+        Ok(FrameOrResultGen::Frame(frame))
     }
 
     /// Make create frame.
@@ -432,7 +545,7 @@ where
         instructions: INSTRUCTION,
         context: &mut CTX,
     ) -> Result<FrameOrResultGen<Self, FrameResult>, ERROR> {
-        match frame_init {
+        let mut frame = match frame_init {
             FrameInput::Call(inputs) => {
                 Self::make_call_frame(context, depth, memory, &inputs, precompile, instructions)
             }
@@ -447,7 +560,95 @@ where
                 precompile,
                 instructions,
             ),
+        }?;
+        if let FrameOrResultGen::Frame(frame) = &mut frame {
+            let schedule = context.cfg().gas_schedule();
+            frame
+                .interpreter
+                .control
+                .gas()
+                .set_memory_cost(schedule.memory_word_cost, schedule.memory_quadratic_divisor);
+        }
+        Ok(frame)
+    }
+
+    /// Runs the compiled code of the frame, or the interpreter with block metering if the frame
+    /// has a block analysis.
+    #[inline]
+    fn run_interpreter(&mut self, context: &mut CTX) -> InterpreterAction {
+        #[cfg(feature = "jit")]
+        if let Some(code) = &self.jit {
+            if let Some(table) = self.instructions.metered_table() {
+                return code.run(&mut self.interpreter, table, context);
+            }
+        }
+        if let Some(analysis) = &self.block_analysis {
+            if let Some(table) = self.instructions.metered_table() {
+                return self.interpreter.run_blocks(table, analysis, context);
+            }
+        }
+        self.interpreter.run(self.instructions.table(), context)
+    }
+
+    /// Returns the serializable state of the frame.
+    pub fn snapshot(&self) -> Result<FrameSnapshot, SnapshotError> {
+        #[cfg(feature = "stylus")]
+        if self.stylus.is_some() {
+            return Err(SnapshotError::StylusFrame);
         }
+        let mut interpreter = self.interpreter.clone();
+        // Memory is shared by all frames and is snapshotted once.
+        interpreter.memory = Rc::new(RefCell::new(EMPTY_SHARED_MEMORY));
+        Ok(FrameSnapshot {
+            data: self.data.clone(),
+            depth: self.depth,
+            checkpoint: self.checkpoint,
+            interpreter,
+        })
+    }
+
+    /// Creates the frame from its snapshot and the memory shared by the frames.
+    ///
+    /// Block analysis is redone if enabled, hot bytecode is interpreted until a new frame
+    /// executes it.
+    pub fn from_snapshot(
+        context: &mut CTX,
+        snapshot: FrameSnapshot,
+        memory: Rc<RefCell<SharedMemory>>,
+        precompiles: PRECOMPILE,
+        mut instructions: INSTRUCTION,
+    ) -> Self {
+        let FrameSnapshot {
+            data,
+            depth,
+            checkpoint,
+            mut interpreter,
+        } = snapshot;
+        interpreter.memory = memory.clone();
+
+        let code_hash = interpreter.bytecode.hash();
+        let block_analysis = match (&data, &*interpreter.bytecode, code_hash) {
+            (FrameData::Call(_), Bytecode::LegacyAnalyzed(legacy), Some(code_hash))
+                if context.cfg().is_block_analysis_enabled() =>
+            {
+                instructions
+                    .metered_table()
+                    .map(|table| block_analysis(code_hash, legacy, table))
+            }
+            _ => None,
+        };
+
+        let mut frame = Self::new(
+            data,
+            depth,
+            interpreter,
+            checkpoint,
+            precompiles,
+            instructions,
+            memory,
+        );
+        frame.block_analysis = block_analysis;
+        frame
     }
 }
 
@@ -511,7 +712,15 @@ where
         let spec = context.cfg().spec().into();
 
         // Run interpreter
-        let next_action = self.interpreter.run(self.instructions.table(), context);
+        #[cfg(feature = "stylus")]
+        let next_action = match &mut self.stylus {
+            Some(runtime) => runtime
+                .run(context)
+                .map_err(|e| ERROR::from_string(e.to_string()))?,
+            None => self.run_interpreter(context),
+        };
+        #[cfg(not(feature = "stylus"))]
+        let next_action = self.run_interpreter(context);
 
         let mut interpreter_result = match next_action {
             InterpreterAction::NewFrame(new_frame) => {
@@ -538,6 +747,7 @@ where
             }
             FrameData::Create(frame) => {
                 let max_code_size = context.cfg().max_code_size();
+                let is_stylus_enabled = context.cfg().is_stylus_enabled();
                 return_create(
                     context.journal(),
                     self.checkpoint,
@@ -545,6 +755,7 @@ where
                     frame.created_address,
                     max_code_size,
                     spec,
+                    is_stylus_enabled,
                 );
 
                 FrameOrResultGen::Result(FrameResult::Create(CreateOutcome::new(
@@ -580,6 +791,18 @@ where
         self.memory.borrow_mut().free_context();
         context.take_error()?;
 
+        // Stylus program is resumed with the outcome on the next run.
+        #[cfg(feature = "stylus")]
+        if let Some(runtime) = &mut self.stylus {
+            return match result {
+                FrameResult::Call(outcome) => runtime.insert_call_outcome(outcome),
+                FrameResult::Create(outcome) | FrameResult::EOFCreate(outcome) => {
+                    runtime.insert_create_outcome(outcome)
+                }
+            }
+            .map_err(|e| ERROR::from_string(e.to_string()));
+        }
+
         // Insert result to the top frame.
         match result {
             FrameResult::Call(outcome) => {
@@ -705,6 +928,7 @@ pub fn return_create<JOURNAL: Journal>(
     address: Address,
     max_code_size: usize,
     spec_id: SpecId,
+    is_stylus_enabled: bool,
 ) {
     // If return is not ok revert and return.
     if !interpreter_result.result.is_ok() {
@@ -714,8 +938,14 @@ pub fn return_create<JOURNAL: Journal>(
     // Host error if present on execution
     // If ok, check contract creation limit and calculate gas deduction on output len.
     //
+    // Stylus programs start with `0xEFF000` and are allowed if enabled.
+    let is_stylus = is_stylus_enabled && is_stylus_program(&interpreter_result.output);
+
     // EIP-3541: Reject new contract code starting with the 0xEF byte
-    if spec_id.is_enabled_in(LONDON) && interpreter_result.output.first() == Some(&0xEF) {
+    if spec_id.is_enabled_in(LONDON)
+        && interpreter_result.output.first() == Some(&0xEF)
+        && !is_stylus
+    {
         journal.checkpoint_revert(checkpoint);
         interpreter_result.result = InstructionResult::CreateContractStartingWithEF;
         return;
@@ -746,7 +976,11 @@ pub fn return_create<JOURNAL: Journal>(
     journal.checkpoint_commit();
 
     // Do analysis of bytecode straight away.
-    let bytecode = Bytecode::new_legacy(interpreter_result.output.clone());
+    let bytecode = if is_stylus {
+        Bytecode::new_raw(interpreter_result.output.clone())
+    } else {
+        Bytecode::new_legacy(interpreter_result.output.clone())
+    };
 
     // Set code
     journal.set_code(address, bytecode);
@@ -754,6 +988,52 @@ pub fn return_create<JOURNAL: Journal>(
     interpreter_result.result = InstructionResult::Return;
 }
 
+/// Returns `true` if the code is a valid Stylus program and the `stylus` feature is enabled.
+#[inline]
+fn is_stylus_program(code: &Bytes) -> bool {
+    #[cfg(feature = "stylus")]
+    {
+        bytecode::StylusBytecode::new_raw(code.clone()).is_ok()
+    }
+    #[cfg(not(feature = "stylus"))]
+    {
+        let _ = code;
+        false
+    }
+}
+
+/// Returns the block analysis of the bytecode for the static gas costs of the table.
+///
+/// Analyses are shared through the [`BlockAnalysisCache`][bytecode::legacy::BlockAnalysisCache]
+/// if `std` is enabled.
+#[inline]
+fn block_analysis<W: InterpreterTypes, H>(
+    code_hash: B256,
+    bytecode: &LegacyAnalyzedBytecode,
+    table: &[MeteredInstruction<W, H>; 256],
+) -> Arc<BlockAnalysis> {
+    let static_costs = static_costs(table);
+    #[cfg(feature = "std")]
+    {
+        bytecode::legacy::BlockAnalysisCache::global().get_or_analyze(
+            code_hash,
+            bytecode,
+            &static_costs,
+        )
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        let _ = code_hash;
+        Arc::new(BlockAnalysis::new(bytecode, &static_costs))
+    }
+}
+
+/// Returns the static gas costs of the instructions of the table.
+#[inline]
+fn static_costs<W: InterpreterTypes, H>(table: &[MeteredInstruction<W, H>; 256]) -> [u64; 256] {
+    table.each_ref().map(|instruction| instruction.static_gas)
+}
+
 pub fn return_eofcreate<JOURNAL: Journal>(
     journal: &mut JOURNAL,
     checkpoint: JournalCheckpoint,
@@ -817,12 +1097,15 @@ impl<
 {
 }
 
+/// Errors of the Stylus runtime are reported as [`FromStringError`].
 pub trait EthFrameError<CTX: JournalGetter>:
-    From<JournalDBError<CTX>> + From<PrecompileErrors>
+    From<JournalDBError<CTX>> + From<PrecompileErrors> + FromStringError
 {
 }
 
-impl<CTX: JournalGetter, T: From<JournalDBError<CTX>> + From<PrecompileErrors>> EthFrameError<CTX>
-    for T
+impl<
+        CTX: JournalGetter,
+        T: From<JournalDBError<CTX>> + From<PrecompileErrors> + FromStringError,
+    > EthFrameError<CTX> for T
 {
 }
@@ -13,6 +13,9 @@ mod frame_data;
 mod post_execution;
 mod pre_execution;
 mod precompile_provider;
+mod snapshot;
+#[cfg(feature = "stylus")]
+mod stylus;
 mod validation;
 
 // Public exports
@@ -22,11 +25,13 @@ pub use frame::{return_create, return_eofcreate, EthFrame, EthFrameContext, EthF
 pub use frame_data::{FrameData, FrameResult};
 pub use post_execution::{EthPostExecution, EthPostExecutionContext, EthPostExecutionError};
 pub use pre_execution::{
-    apply_eip7702_auth_list, EthPreExecution, EthPreExecutionContext, EthPreExecutionError,
+    apply_eip7702_auth_list, prefetch_accounts, EthPreExecution, EthPreExecutionContext,
+    EthPreExecutionError, PrefetchPreExecution,
 };
 use precompile::PrecompileErrors;
 pub use precompile_provider::EthPrecompileProvider;
 use primitives::Log;
+pub use snapshot::{ExecutionSnapshot, FrameSnapshot, SnapshotError};
 use state::EvmState;
 use std::vec::Vec;
 pub use validation::{
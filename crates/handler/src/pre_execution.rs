@@ -11,7 +11,7 @@ use context_interface::{
     PerformantContextAccess, TransactionGetter,
 };
 use handler_interface::PreExecutionHandler;
-use primitives::{Address, BLOCKHASH_STORAGE_ADDRESS, KECCAK_EMPTY, U256};
+use primitives::{Address, TxKind, BLOCKHASH_STORAGE_ADDRESS, KECCAK_EMPTY, U256};
 use specification::{eip7702, hardfork::SpecId};
 use std::{boxed::Box, vec::Vec};
 
@@ -111,6 +111,95 @@ where
     }
 }
 
+/// Pre execution handler that prefetches the state of the transaction before loading accounts.
+///
+/// It pays off with a caching database over a slow backend, e.g. `CacheDB` over an RPC
+/// provider, see [`prefetch_accounts`].
+pub struct PrefetchPreExecution<CTX, ERROR> {
+    pub eth: EthPreExecution<CTX, ERROR>,
+}
+
+impl<CTX, ERROR> PrefetchPreExecution<CTX, ERROR> {
+    pub fn new() -> Self {
+        Self {
+            eth: EthPreExecution::new(),
+        }
+    }
+
+    pub fn new_boxed() -> Box<Self> {
+        Box::new(Self::new())
+    }
+}
+
+impl<CTX, ERROR> Default for PrefetchPreExecution<CTX, ERROR> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<CTX, ERROR> PreExecutionHandler for PrefetchPreExecution<CTX, ERROR>
+where
+    CTX: EthPreExecutionContext,
+    ERROR: EthPreExecutionError<CTX>,
+{
+    type Context = CTX;
+    type Error = ERROR;
+
+    fn load_accounts(&self, context: &mut Self::Context) -> Result<(), Self::Error> {
+        prefetch_accounts::<CTX, ERROR>(context)?;
+        self.eth.load_accounts(context)
+    }
+
+    fn apply_eip7702_auth_list(&self, context: &mut Self::Context) -> Result<u64, Self::Error> {
+        self.eth.apply_eip7702_auth_list(context)
+    }
+
+    #[inline]
+    fn deduct_caller(&self, context: &mut Self::Context) -> Result<(), Self::Error> {
+        self.eth.deduct_caller(context)
+    }
+}
+
+/// Fetches the accounts and the storage the transaction is known to access in a batch.
+///
+/// Those are the caller, the target, the beneficiary, the access list and the EIP-7702
+/// authorities, fetched with [`Database::basic_many`] and [`Database::storage_many`]. The
+/// values are not returned, the database is expected to cache them.
+pub fn prefetch_accounts<
+    CTX: TransactionGetter + BlockGetter + JournalGetter,
+    ERROR: From<JournalDBError<CTX>>,
+>(
+    context: &mut CTX,
+) -> Result<(), ERROR> {
+    let tx = context.tx();
+    let mut addresses = Vec::from([tx.caller(), context.block().beneficiary()]);
+    if let TxKind::Call(target) = tx.kind() {
+        addresses.push(target);
+    }
+
+    let mut slots = Vec::new();
+    if let Some(access_list) = tx.access_list() {
+        for (address, keys) in access_list {
+            addresses.push(*address);
+            slots.extend(
+                keys.iter()
+                    .map(|key| (*address, U256::from_be_bytes(key.0))),
+            );
+        }
+    }
+
+    if tx.tx_type() == TransactionType::Eip7702 {
+        addresses.extend(tx.authorization_list().filter_map(|a| a.0));
+    }
+    addresses.sort_unstable();
+    addresses.dedup();
+
+    let db = context.journal().db();
+    db.basic_many(&addresses)?;
+    db.storage_many(&slots)?;
+    Ok(())
+}
+
 /// Apply EIP-7702 auth list and return number gas refund on already created accounts.
 #[inline]
 pub fn apply_eip7702_auth_list<
@@ -4,16 +4,35 @@ use interpreter::{Gas, InstructionResult, InterpreterResult};
 use precompile::PrecompileErrors;
 use precompile::{PrecompileSpecId, Precompiles};
 use primitives::{Address, Bytes};
+use std::vec::Vec;
 
 pub struct EthPrecompileProvider<CTX, ERROR> {
     pub precompiles: &'static Precompiles,
+    /// Precompiles moved to other addresses, see [`Cfg::precompile_moves`].
+    pub moves: Vec<(Address, Address)>,
     pub _phantom: core::marker::PhantomData<(CTX, ERROR)>,
 }
 
+impl<CTX, ERROR> EthPrecompileProvider<CTX, ERROR> {
+    /// Returns the original address of the precompile served at the address.
+    ///
+    /// Returns `None` if the precompile was moved away from the address.
+    fn source<'a>(&'a self, address: &'a Address) -> Option<&'a Address> {
+        if let Some((from, _)) = self.moves.iter().find(|(_, to)| to == address) {
+            return Some(from);
+        }
+        if self.moves.iter().any(|(from, _)| from == address) {
+            return None;
+        }
+        Some(address)
+    }
+}
+
 impl<CTX, ERROR> Clone for EthPrecompileProvider<CTX, ERROR> {
     fn clone(&self) -> Self {
         Self {
             precompiles: self.precompiles,
+            moves: self.moves.clone(),
             _phantom: core::marker::PhantomData,
         }
     }
@@ -32,6 +51,7 @@ where
         let spec = context.cfg().spec().into();
         Self {
             precompiles: Precompiles::new(PrecompileSpecId::from_spec_id(spec)),
+            moves: context.cfg().precompile_moves().to_vec(),
             _phantom: core::marker::PhantomData,
         }
     }
@@ -43,7 +63,10 @@ where
         bytes: &Bytes,
         gas_limit: u64,
     ) -> Result<Option<InterpreterResult>, Self::Error> {
-        let Some(precompile) = self.precompiles.get(address) else {
+        let Some(precompile) = self
+            .source(address)
+            .and_then(|address| self.precompiles.get(address))
+        else {
             return Ok(None);
         };
 
@@ -73,10 +96,20 @@ where
     }
 
     fn warm_addresses(&self) -> impl Iterator<Item = Address> {
-        self.precompiles.addresses().cloned()
+        let moved = self
+            .moves
+            .iter()
+            .filter(|(from, _)| self.precompiles.contains(from))
+            .map(|(_, to)| to);
+        self.precompiles
+            .addresses()
+            .filter(|address| self.moves.iter().all(|(from, _)| from != *address))
+            .chain(moved)
+            .cloned()
     }
 
     fn contains(&self, address: &Address) -> bool {
-        self.precompiles.contains(address)
+        self.source(address)
+            .is_some_and(|address| self.precompiles.contains(address))
     }
 }
@@ -0,0 +1,116 @@
+//! Snapshots of suspended executions.
+//!
+//! An [`ExecutionSnapshot`] holds the frames of an [`ExecutionStack`] without their providers
+//! and compiled code, and a single copy of the memory shared by the frames.
+use crate::{EthFrame, EthFrameContext, EthFrameError, FrameData, FrameResult};
+use context_interface::journaled_state::JournalCheckpoint;
+use core::{cell::RefCell, fmt};
+use handler_interface::{ExecutionStack, FrameOrResultGen, PrecompileProvider};
+use interpreter::{
+    interpreter::{EthInterpreter, InstructionProvider},
+    FrameInput, Interpreter, InterpreterResult, SharedMemory,
+};
+use std::{rc::Rc, vec::Vec};
+
+/// Serializable state of an [`EthFrame`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameSnapshot {
+    pub data: FrameData,
+    pub depth: usize,
+    /// Journal checkpoint.
+    pub checkpoint: JournalCheckpoint,
+    /// Interpreter with an empty memory, the memory is part of the [`ExecutionSnapshot`].
+    pub interpreter: Interpreter<EthInterpreter>,
+}
+
+/// Serializable state of a suspended [`ExecutionStack`] of [`EthFrame`]s.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionSnapshot {
+    /// Frames from the first frame to the executing frame.
+    pub frames: Vec<FrameSnapshot>,
+    /// Memory shared by the frames, with a checkpoint for each frame.
+    pub memory: SharedMemory,
+    /// Input of the frame that is created next or result that is returned to the executing frame.
+    pub pending: Option<FrameOrResultGen<FrameInput, FrameResult>>,
+}
+
+/// Error returned if an execution can not be snapshotted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// A frame executes a Stylus program, its WASM instance can not be serialized.
+    StylusFrame,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StylusFrame => write!(f, "frame executes a Stylus program"),
+        }
+    }
+}
+
+impl core::error::Error for SnapshotError {}
+
+impl ExecutionSnapshot {
+    /// Takes a snapshot of the suspended execution.
+    pub fn new<CTX, ERROR, PRECOMPILE, INSTRUCTION>(
+        stack: &ExecutionStack<EthFrame<CTX, ERROR, EthInterpreter, PRECOMPILE, INSTRUCTION>>,
+    ) -> Result<Self, SnapshotError>
+    where
+        CTX: EthFrameContext,
+        ERROR: EthFrameError<CTX>,
+        PRECOMPILE: PrecompileProvider<Context = CTX, Error = ERROR, Output = InterpreterResult>,
+        INSTRUCTION: InstructionProvider<WIRE = EthInterpreter, Host = CTX>,
+    {
+        let memory = match stack.frames.first() {
+            Some(frame) => frame.memory.borrow().clone(),
+            None => SharedMemory::new(),
+        };
+        Ok(Self {
+            frames: stack
+                .frames
+                .iter()
+                .map(EthFrame::snapshot)
+                .collect::<Result<_, _>>()?,
+            memory,
+            pending: stack.pending.clone(),
+        })
+    }
+
+    /// Restores the frames of the snapshot.
+    ///
+    /// Providers are created for the context, which has to be in the state of the suspended
+    /// execution.
+    pub fn restore<CTX, ERROR, PRECOMPILE, INSTRUCTION>(
+        self,
+        context: &mut CTX,
+    ) -> ExecutionStack<EthFrame<CTX, ERROR, EthInterpreter, PRECOMPILE, INSTRUCTION>>
+    where
+        CTX: EthFrameContext,
+        ERROR: EthFrameError<CTX>,
+        PRECOMPILE: PrecompileProvider<Context = CTX, Error = ERROR, Output = InterpreterResult>,
+        INSTRUCTION: InstructionProvider<WIRE = EthInterpreter, Host = CTX>,
+    {
+        let memory = Rc::new(RefCell::new(self.memory));
+        let precompiles = PRECOMPILE::new(context);
+        let instructions = INSTRUCTION::new(context);
+        ExecutionStack {
+            frames: self
+                .frames
+                .into_iter()
+                .map(|frame| {
+                    EthFrame::from_snapshot(
+                        context,
+                        frame,
+                        memory.clone(),
+                        precompiles.clone(),
+                        instructions.clone(),
+                    )
+                })
+                .collect(),
+            pending: self.pending,
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Activation and loading of Stylus programs.
+use crate::EthFrameContext;
+use bytecode::{Bytecode, StylusBytecode};
+use context_interface::{journaled_state::Journal, Cfg, CfgGetter, JournalDBError};
+use interpreter::{gas, CallInputs, CallScheme, Gas, InstructionResult, InterpreterResult};
+use primitives::{Address, Bytes, B256, U256};
+use std::sync::Arc;
+use stylus::{
+    activation::{activation_data_fee, activation_gas, activation_slot, ACTIVATE_PROGRAM_SELECTOR},
+    decompress, ProgramCache, StylusProgram, ARB_WASM_ADDRESS,
+};
+
+/// Returns the compiled program if it is activated with the current version.
+///
+/// Reading the activation is charged to the gas of the call. Returns
+/// [`InstructionResult::ProgramNotActivated`] or [`InstructionResult::ProgramVersionMismatch`]
+/// if the program can't be executed.
+pub(crate) fn load_program<CTX: EthFrameContext>(
+    context: &mut CTX,
+    code_hash: B256,
+    bytecode: &StylusBytecode,
+    gas: &mut Gas,
+) -> Result<Result<Arc<StylusProgram>, InstructionResult>, JournalDBError<CTX>> {
+    let version = context.cfg().stylus_version();
+    let activated = match activated_version(context, code_hash, gas)? {
+        Ok(activated) => activated,
+        Err(result) => return Ok(Err(result)),
+    };
+    let Some(activated) = activated else {
+        return Ok(Err(InstructionResult::ProgramNotActivated));
+    };
+    if activated != version {
+        return Ok(Err(InstructionResult::ProgramVersionMismatch));
+    }
+    Ok(ProgramCache::global()
+        .get_or_compile(code_hash, version, bytecode)
+        .map_err(|_| InstructionResult::ProgramNotActivated))
+}
+
+/// Executes `activateProgram(address)` of the [`ARB_WASM_ADDRESS`] account.
+///
+/// The program is compiled into the [`ProgramCache`] and its version is stored in the
+/// storage of the account. Call value has to cover the data fee, the excess is returned to
+/// the caller.
+pub(crate) fn activate_program<CTX: EthFrameContext>(
+    context: &mut CTX,
+    inputs: &CallInputs,
+) -> Result<InterpreterResult, JournalDBError<CTX>> {
+    let mut gas = Gas::new(inputs.gas_limit);
+    let result =
+        |result: InstructionResult, gas: Gas| -> Result<InterpreterResult, JournalDBError<CTX>> {
+            Ok(InterpreterResult {
+                result,
+                output: Bytes::new(),
+                gas,
+            })
+        };
+
+    if inputs.is_static {
+        return result(InstructionResult::StateChangeDuringStaticCall, gas);
+    }
+    let input = &inputs.input;
+    if inputs.scheme != CallScheme::Call
+        || input.len() != 36
+        || input[..4] != ACTIVATE_PROGRAM_SELECTOR
+    {
+        return result(InstructionResult::Revert, gas);
+    }
+    let program = Address::from_word(B256::from_slice(&input[4..]));
+
+    let account = context.journal().load_account_code(program)?;
+    let is_cold = account.is_cold;
+    let code_hash = account.info.code_hash();
+    let code = account.info.code.clone();
+    if !gas.record_cost(gas::warm_cold_cost(context.cfg().gas_schedule(), is_cold)) {
+        return result(InstructionResult::OutOfGas, gas);
+    }
+    let Some(Bytecode::Stylus(bytecode)) = code else {
+        return result(InstructionResult::Revert, gas);
+    };
+    let version = context.cfg().stylus_version();
+    match activated_version(context, code_hash, &mut gas)? {
+        Ok(activated) if activated == Some(version) => {
+            return result(InstructionResult::Revert, gas)
+        }
+        Ok(_) => {}
+        Err(error) => return result(error, gas),
+    }
+
+    let Ok(wasm) = decompress(&bytecode) else {
+        return result(InstructionResult::Revert, gas);
+    };
+    if !gas.record_cost(activation_gas(wasm.len())) {
+        return result(InstructionResult::OutOfGas, gas);
+    }
+    let data_fee = activation_data_fee(wasm.len());
+    let value = inputs.call_value();
+    if value < data_fee {
+        return result(InstructionResult::Revert, gas);
+    }
+    let Ok(compiled) = StylusProgram::from_wasm(&wasm) else {
+        return result(InstructionResult::Revert, gas);
+    };
+
+    ProgramCache::global().insert(code_hash, version, compiled);
+    context.journal().sstore(
+        ARB_WASM_ADDRESS,
+        activation_slot(code_hash),
+        U256::from(version),
+    )?;
+    if let Some(error) =
+        context
+            .journal()
+            .transfer(&ARB_WASM_ADDRESS, &inputs.caller, value - data_fee)?
+    {
+        return result(error.into(), gas);
+    }
+
+    let mut output = [0; 64];
+    output[..32].copy_from_slice(&U256::from(version).to_be_bytes::<32>());
+    output[32..].copy_from_slice(&data_fee.to_be_bytes::<32>());
+    Ok(InterpreterResult {
+        result: InstructionResult::Return,
+        output: Bytes::copy_from_slice(&output),
+        gas,
+    })
+}
+
+/// Returns the version the program is activated with.
+///
+/// Access of the [`ARB_WASM_ADDRESS`] account and of the activation slot is charged as
+/// account access and `SLOAD`, returns [`InstructionResult::OutOfGas`] if the gas does not
+/// cover it.
+fn activated_version<CTX: EthFrameContext>(
+    context: &mut CTX,
+    code_hash: B256,
+    gas: &mut Gas,
+) -> Result<Result<Option<u16>, InstructionResult>, JournalDBError<CTX>> {
+    let account_is_cold = context.journal().load_account(ARB_WASM_ADDRESS)?.is_cold;
+    let version = context
+        .journal()
+        .sload(ARB_WASM_ADDRESS, activation_slot(code_hash))?;
+    let schedule = context.cfg().gas_schedule();
+    let cost = gas::warm_cold_cost(schedule, account_is_cold)
+        .saturating_add(gas::sload_cost(schedule, version.is_cold));
+    if !gas.record_cost(cost) {
+        return Ok(Err(InstructionResult::OutOfGas));
+    }
+    let version = version.data;
+    Ok(Ok((!version.is_zero()).then(|| version.saturating_to())))
+}
@@ -341,12 +341,11 @@ where
     CTX: TransactionGetter + CfgGetter,
     Error: From<InvalidTransaction>,
 {
-    let spec = context.cfg().spec().into();
     let tx = context.tx();
     let (accounts, storages) = tx.access_list_nums().unwrap_or_default();
 
     let gas = gas::calculate_initial_tx_gas(
-        spec_id,
+        context.cfg().gas_schedule(),
         tx.input(),
         tx.kind().is_create(),
         accounts as u64,
@@ -361,7 +360,7 @@ where
 
     // EIP-7623: Increase calldata cost
     // floor gas should be less than gas limit.
-    if spec.is_enabled_in(SpecId::PRAGUE) && gas.floor_gas > tx.gas_limit() {
+    if spec_id.is_enabled_in(SpecId::PRAGUE) && gas.floor_gas > tx.gas_limit() {
         return Err(InvalidTransaction::GasFloorMoreThanGasLimit.into());
     };
 
@@ -0,0 +1,214 @@
+//! Access list [Inspector] compatible with the geth access list tracer.
+use crate::Inspector;
+use revm::{
+    bytecode::opcode,
+    context_interface::result::HaltReason,
+    database_interface::Database,
+    handler::EthHandler,
+    interpreter::{
+        interpreter_types::{InputsTrait, Jumps},
+        Interpreter, InterpreterTypes, Stack,
+    },
+    primitives::{Address, B256},
+    AccessList, AccessListResult, CreateAccessListError, EthContext, MainEvm,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// [Inspector] that records every address and storage slot accessed by the transaction.
+///
+/// Like geth, storage slots are recorded for every contract, while addresses are skipped if
+/// they are excluded. Those are usually the caller, the target and the precompiles that are
+/// warm anyway, see [`PrecompileProvider::warm_addresses`].
+///
+/// [`Evm::create_access_list`](revm::Evm::create_access_list) generates the access list of a
+/// transaction without an inspector.
+///
+/// [`PrecompileProvider::warm_addresses`]: revm::handler_interface::PrecompileProvider::warm_addresses
+#[derive(Clone, Debug, Default)]
+pub struct AccessListInspector {
+    excluded: BTreeSet<Address>,
+    access_list: BTreeMap<Address, BTreeSet<B256>>,
+}
+
+impl AccessListInspector {
+    /// Creates the inspector starting with the given access list.
+    pub fn new(
+        access_list: &[(Address, Vec<B256>)],
+        excluded: impl IntoIterator<Item = Address>,
+    ) -> Self {
+        let mut inspector = Self {
+            excluded: excluded.into_iter().collect(),
+            access_list: BTreeMap::new(),
+        };
+        for (address, storage_keys) in access_list {
+            inspector.add_address(*address);
+            for key in storage_keys {
+                inspector.add_slot(*address, *key);
+            }
+        }
+        inspector
+    }
+
+    /// Returns the recorded access list, sorted by address and storage key.
+    pub fn access_list(&self) -> AccessList {
+        self.access_list
+            .iter()
+            .map(|(address, keys)| (*address, keys.iter().copied().collect()))
+            .collect()
+    }
+
+    fn add_address(&mut self, address: Address) {
+        if !self.excluded.contains(&address) {
+            self.access_list.entry(address).or_default();
+        }
+    }
+
+    fn add_slot(&mut self, address: Address, key: B256) {
+        self.access_list.entry(address).or_default().insert(key);
+    }
+}
+
+impl<CTX, INTR> Inspector<CTX, INTR> for AccessListInspector
+where
+    INTR: InterpreterTypes<Stack = Stack>,
+{
+    fn step(&mut self, interp: &mut Interpreter<INTR>, _context: &mut CTX) {
+        let stack = &interp.stack;
+        match interp.bytecode.opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                if let Ok(key) = stack.peek(0) {
+                    self.add_slot(interp.input.target_address(), key.into());
+                }
+            }
+            opcode::EXTCODECOPY
+            | opcode::EXTCODEHASH
+            | opcode::EXTCODESIZE
+            | opcode::BALANCE
+            | opcode::SELFDESTRUCT
+            | opcode::EXTCALL
+            | opcode::EXTDELEGATECALL
+            | opcode::EXTSTATICCALL => {
+                if let Ok(address) = stack.peek(0) {
+                    self.add_address(Address::from_word(address.into()));
+                }
+            }
+            opcode::CALL | opcode::CALLCODE | opcode::DELEGATECALL | opcode::STATICCALL
+                if stack.len() >= 5 =>
+            {
+                if let Ok(address) = stack.peek(1) {
+                    self.add_address(Address::from_word(address.into()));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Generates the access list of the transaction set in the context, see
+/// [`Evm::create_access_list`](revm::Evm::create_access_list).
+pub fn create_access_list<DB: Database>(
+    context: EthContext<DB>,
+) -> Result<AccessListResult<HaltReason>, CreateAccessListError<revm::Error<DB>>> {
+    MainEvm::new(context, EthHandler::default()).create_access_list()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{inspector_context::InspectorContext, inspector_handler, InspectorMainEvm};
+    use database::BenchmarkDB;
+    use revm::{
+        bytecode::{
+            eof::{EofBody, TypesSection},
+            Bytecode, Eof,
+        },
+        handler::EthHandler,
+        primitives::{Bytes, TxKind, U256},
+        specification::hardfork::SpecId,
+        Context, EthContext, EvmExec, MainEvm,
+    };
+
+    const CALLER: Address = Address::with_last_byte(1);
+
+    fn run(bytecode: Bytecode) -> (AccessListInspector, EthContext<BenchmarkDB>) {
+        let ctx = Context::builder()
+            .with_db(BenchmarkDB::new_bytecode(bytecode))
+            .modify_cfg_chained(|cfg| cfg.spec = SpecId::OSAKA)
+            .modify_tx_chained(|tx| {
+                tx.caller = CALLER;
+                tx.kind = TxKind::Call(Address::ZERO);
+                tx.gas_limit = 100_000;
+            });
+        let inspector =
+            AccessListInspector::new(&[], [CALLER, Address::ZERO, Address::with_last_byte(1)]);
+        let mut evm = InspectorMainEvm::new(
+            InspectorContext::new(ctx.clone(), inspector),
+            inspector_handler(),
+        );
+        assert!(evm.exec().unwrap().result.is_success());
+        (evm.context.inspector, ctx)
+    }
+
+    #[test]
+    fn access_list() {
+        let other = Address::with_last_byte(0xaa);
+        let mut code = vec![
+            // SLOAD(2)
+            opcode::PUSH1,
+            0x02,
+            opcode::SLOAD,
+            opcode::POP,
+            // BALANCE(other)
+            opcode::PUSH20,
+        ];
+        code.extend_from_slice(other.as_slice());
+        code.extend_from_slice(&[
+            opcode::BALANCE,
+            opcode::POP,
+            // STATICCALL(gas, 0x01, 0, 0, 0, 0) to the ecrecover precompile.
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH1,
+            0x01,
+            opcode::GAS,
+            opcode::STATICCALL,
+            opcode::STOP,
+        ]);
+        let (inspector, ctx) = run(Bytecode::new_raw(code.into()));
+
+        // Target and precompile are excluded, but the slots of the target are not.
+        let expected = vec![
+            (Address::ZERO, vec![B256::from(U256::from(2))]),
+            (other, vec![]),
+        ];
+        assert_eq!(inspector.access_list(), expected);
+
+        // Same as the access list generated from the loaded state.
+        let mut evm = MainEvm::new(ctx, EthHandler::default());
+        assert_eq!(evm.create_access_list().unwrap().access_list, expected);
+    }
+
+    #[test]
+    fn eof_calls() {
+        let other = Address::with_last_byte(0xaa);
+        // EXTSTATICCALL(other, 0, 0)
+        let mut code = vec![opcode::PUSH0, opcode::PUSH0, opcode::PUSH20];
+        code.extend_from_slice(other.as_slice());
+        code.extend_from_slice(&[opcode::EXTSTATICCALL, opcode::POP, opcode::STOP]);
+        let eof = Eof::new(EofBody {
+            types_section: vec![TypesSection {
+                inputs: 0,
+                outputs: 0x80,
+                max_stack_size: 3,
+            }],
+            code_section: vec![code.len()],
+            code: code.into(),
+            data_section: Bytes::new(),
+            ..Default::default()
+        });
+        let (inspector, _) = run(Bytecode::Eof(eof.into()));
+        assert_eq!(inspector.access_list(), vec![(other, vec![])]);
+    }
+}
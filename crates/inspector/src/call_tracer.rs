@@ -0,0 +1,748 @@
+//! Call-tree [Inspector] producing output compatible with geth `callTracer`.
+use crate::Inspector;
+use core::fmt;
+use revm::{
+    context_interface::{Cfg, CfgGetter, Transaction, TransactionGetter},
+    interpreter::{
+        CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, CreateScheme,
+        EOFCreateInputs, EOFCreateKind, Gas, InstructionResult, Interpreter, InterpreterTypes,
+    },
+    primitives::{Address, Bytes, Log, B256, U256},
+    specification::hardfork::SpecId,
+};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Options of the [CallTracer].
+///
+/// Mirrors the `tracerConfig` object accepted by geth `callTracer`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CallTracerConfig {
+    /// Record only the top-level call, skipping all nested frames.
+    pub only_top_call: bool,
+    /// Record logs emitted by each frame.
+    pub with_log: bool,
+}
+
+impl CallTracerConfig {
+    /// Sets the `onlyTopCall` option.
+    pub fn only_top_call(mut self, only_top_call: bool) -> Self {
+        self.only_top_call = only_top_call;
+        self
+    }
+
+    /// Sets the `withLog` option.
+    pub fn with_log(mut self, with_log: bool) -> Self {
+        self.with_log = with_log;
+        self
+    }
+}
+
+/// Type of the call frame, serialized as the opcode name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CallKind {
+    Call,
+    StaticCall,
+    CallCode,
+    DelegateCall,
+    ExtCall,
+    ExtStaticCall,
+    ExtDelegateCall,
+    Create,
+    Create2,
+    EofCreate,
+    SelfDestruct,
+}
+
+impl CallKind {
+    /// Returns the opcode name used by geth for this frame type.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Call => "CALL",
+            Self::StaticCall => "STATICCALL",
+            Self::CallCode => "CALLCODE",
+            Self::DelegateCall => "DELEGATECALL",
+            Self::ExtCall => "EXTCALL",
+            Self::ExtStaticCall => "EXTSTATICCALL",
+            Self::ExtDelegateCall => "EXTDELEGATECALL",
+            Self::Create => "CREATE",
+            Self::Create2 => "CREATE2",
+            Self::EofCreate => "EOFCREATE",
+            Self::SelfDestruct => "SELFDESTRUCT",
+        }
+    }
+
+    /// Parses the opcode name as printed by [CallKind::as_str].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "CALL" => Self::Call,
+            "STATICCALL" => Self::StaticCall,
+            "CALLCODE" => Self::CallCode,
+            "DELEGATECALL" => Self::DelegateCall,
+            "EXTCALL" => Self::ExtCall,
+            "EXTSTATICCALL" => Self::ExtStaticCall,
+            "EXTDELEGATECALL" => Self::ExtDelegateCall,
+            "CREATE" => Self::Create,
+            "CREATE2" => Self::Create2,
+            "EOFCREATE" => Self::EofCreate,
+            "SELFDESTRUCT" => Self::SelfDestruct,
+            _ => return None,
+        })
+    }
+
+    /// Returns true if the frame creates a contract.
+    pub const fn is_create(&self) -> bool {
+        matches!(self, Self::Create | Self::Create2 | Self::EofCreate)
+    }
+}
+
+impl fmt::Display for CallKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<CallScheme> for CallKind {
+    fn from(scheme: CallScheme) -> Self {
+        match scheme {
+            CallScheme::Call => Self::Call,
+            CallScheme::CallCode => Self::CallCode,
+            CallScheme::DelegateCall => Self::DelegateCall,
+            CallScheme::StaticCall => Self::StaticCall,
+            CallScheme::ExtCall => Self::ExtCall,
+            CallScheme::ExtStaticCall => Self::ExtStaticCall,
+            CallScheme::ExtDelegateCall => Self::ExtDelegateCall,
+        }
+    }
+}
+
+impl From<CreateScheme> for CallKind {
+    fn from(scheme: CreateScheme) -> Self {
+        match scheme {
+            CreateScheme::Create => Self::Create,
+            CreateScheme::Create2 { .. } => Self::Create2,
+        }
+    }
+}
+
+impl Serialize for CallKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for CallKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = <Cow<'de, str>>::deserialize(deserializer)?;
+        Self::from_name(&name).ok_or_else(|| de::Error::custom(format!("unknown call type {name}")))
+    }
+}
+
+/// Log emitted inside of a [CallFrame].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallLog {
+    /// Address of the contract that emitted the log.
+    pub address: Address,
+    /// Log topics.
+    pub topics: Vec<B256>,
+    /// Log data.
+    pub data: Bytes,
+    /// Number of sub-calls of the frame made before this log was emitted.
+    #[serde(with = "quantity")]
+    pub position: u64,
+}
+
+/// A single frame of the call tree.
+///
+/// Field order and naming follows geth `callTracer` so serialized frames can be
+/// compared directly with node output.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+    /// Caller of the frame.
+    pub from: Address,
+    /// Gas available to the frame.
+    #[serde(with = "quantity")]
+    pub gas: u64,
+    /// Gas consumed by the frame, including the gas of its sub-calls.
+    #[serde(with = "quantity")]
+    pub gas_used: u64,
+    /// Callee or created contract. `None` if contract creation failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    /// Call data or init code.
+    pub input: Bytes,
+    /// Returned data or deployed code.
+    #[serde(default, skip_serializing_if = "<[u8]>::is_empty")]
+    pub output: Bytes,
+    /// Error message if the frame did not succeed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Decoded Solidity revert reason.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+    /// Sub-calls made by this frame.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<CallFrame>,
+    /// Logs emitted by this frame, only recorded with [CallTracerConfig::with_log].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<CallLog>,
+    /// Value of the call. `None` for static calls.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    /// Type of the frame.
+    #[serde(rename = "type")]
+    pub kind: CallKind,
+}
+
+impl CallFrame {
+    /// Creates a new frame without result.
+    pub fn new(
+        kind: CallKind,
+        from: Address,
+        to: Option<Address>,
+        input: Bytes,
+        gas: u64,
+        value: Option<U256>,
+    ) -> Self {
+        Self {
+            from,
+            gas,
+            gas_used: 0,
+            to,
+            input,
+            output: Bytes::new(),
+            error: None,
+            revert_reason: None,
+            calls: Vec::new(),
+            logs: Vec::new(),
+            value,
+            kind,
+        }
+    }
+
+    /// Returns true if the frame failed.
+    pub fn is_failed(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Sets output, error and gas used of the frame from the interpreter result.
+    fn fill_result(&mut self, result: InstructionResult, output: &Bytes, gas_used: u64) {
+        self.gas_used = gas_used;
+        if result.is_ok() {
+            self.output = output.clone();
+            return;
+        }
+
+        self.error = Some(error_message(result));
+        if self.kind.is_create() {
+            self.to = None;
+        }
+        if result == InstructionResult::Revert && !output.is_empty() {
+            self.output = output.clone();
+            self.revert_reason = decode_revert_reason(output);
+        }
+    }
+
+    /// Removes logs of failed frames and of all their descendants.
+    fn clear_failed_logs(&mut self, parent_failed: bool) {
+        let failed = parent_failed || self.is_failed();
+        if failed {
+            self.logs.clear();
+        }
+        for call in &mut self.calls {
+            call.clear_failed_logs(failed);
+        }
+    }
+}
+
+/// Geth `callTracer` compatible [Inspector].
+///
+/// Builds a tree of [CallFrame]s for the executed transaction. The tree is
+/// available after execution through [CallTracer::call_frame] and is reset when
+/// the next transaction starts.
+#[derive(Clone, Debug, Default)]
+pub struct CallTracer {
+    config: CallTracerConfig,
+    /// Frames that are still executing. The last one is the innermost.
+    stack: Vec<CallFrame>,
+    /// Number of frames entered and not yet exited, including skipped ones.
+    depth: usize,
+    /// Finished top-level frame.
+    result: Option<CallFrame>,
+}
+
+impl CallTracer {
+    /// Creates a new tracer with the given config.
+    pub fn new(config: CallTracerConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the config of the tracer.
+    pub fn config(&self) -> &CallTracerConfig {
+        &self.config
+    }
+
+    /// Returns the top-level frame of the last traced transaction.
+    pub fn call_frame(&self) -> Option<&CallFrame> {
+        self.result.as_ref()
+    }
+
+    /// Takes the top-level frame of the last traced transaction.
+    pub fn take_call_frame(&mut self) -> Option<CallFrame> {
+        self.result.take()
+    }
+
+    /// Overrides gas used of the top-level frame.
+    ///
+    /// Geth reports the receipt gas used for the top call. The tracer derives it
+    /// from the frame gas and refunds, this can be used to also account for the
+    /// EIP-7623 floor, by passing `ExecutionResult::gas_used`.
+    pub fn set_gas_used(&mut self, gas_used: u64) {
+        if let Some(frame) = &mut self.result {
+            frame.gas_used = gas_used;
+        }
+    }
+
+    /// Resets the tracer, dropping any recorded frames.
+    pub fn clear(&mut self) {
+        self.stack.clear();
+        self.depth = 0;
+        self.result = None;
+    }
+
+    /// Returns true if the frame at the current depth should be recorded.
+    fn is_recorded(&self) -> bool {
+        !self.config.only_top_call || self.depth == 0
+    }
+
+    fn start_frame<CTX: TransactionGetter>(&mut self, context: &CTX, mut frame: CallFrame) {
+        if self.depth == 0 {
+            self.clear();
+            // Top-level frame reports the gas limit of the transaction.
+            frame.gas = context.tx().gas_limit();
+        }
+        if self.is_recorded() {
+            self.stack.push(frame);
+        }
+        self.depth += 1;
+    }
+
+    fn end_frame<CTX: TransactionGetter + CfgGetter>(
+        &mut self,
+        context: &CTX,
+        result: InstructionResult,
+        output: &Bytes,
+        gas: Gas,
+        created_address: Option<Address>,
+    ) {
+        self.depth -= 1;
+        if !self.is_recorded() {
+            return;
+        }
+        let Some(mut frame) = self.stack.pop() else {
+            return;
+        };
+
+        let gas_used = if self.depth == 0 {
+            let mut gas = gas;
+            gas.set_final_refund(context.cfg().spec().into().is_enabled_in(SpecId::LONDON));
+            (frame.gas - gas.remaining()).saturating_sub(gas.refunded() as u64)
+        } else if result.is_ok_or_revert() {
+            frame.gas - gas.remaining()
+        } else {
+            // Halted frames consume all of their gas.
+            frame.gas
+        };
+
+        if created_address.is_some() {
+            frame.to = created_address;
+        }
+        frame.fill_result(result, output, gas_used);
+
+        match self.stack.last_mut() {
+            Some(parent) => parent.calls.push(frame),
+            None => {
+                frame.clear_failed_logs(false);
+                self.result = Some(frame);
+            }
+        }
+    }
+}
+
+impl<CTX, INTR> Inspector<CTX, INTR> for CallTracer
+where
+    CTX: TransactionGetter + CfgGetter,
+    INTR: InterpreterTypes,
+{
+    fn log(&mut self, _interp: &mut Interpreter<INTR>, _context: &mut CTX, log: &Log) {
+        if !self.config.with_log {
+            return;
+        }
+        // Logs are emitted by the innermost frame, skip them if it is not recorded.
+        if self.config.only_top_call && self.depth > 1 {
+            return;
+        }
+        let Some(frame) = self.stack.last_mut() else {
+            return;
+        };
+        frame.logs.push(CallLog {
+            address: log.address,
+            topics: log.topics().to_vec(),
+            data: log.data.data.clone(),
+            position: frame.calls.len() as u64,
+        });
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let kind = CallKind::from(inputs.scheme);
+        // Delegate calls keep the target, the frame is entered from the current contract.
+        let from = match inputs.scheme {
+            CallScheme::DelegateCall | CallScheme::ExtDelegateCall => inputs.target_address,
+            _ => inputs.caller,
+        };
+        let value = match inputs.scheme {
+            CallScheme::StaticCall | CallScheme::ExtStaticCall => None,
+            _ => Some(inputs.call_value()),
+        };
+        let frame = CallFrame::new(
+            kind,
+            from,
+            Some(inputs.bytecode_address),
+            inputs.input.clone(),
+            inputs.gas_limit,
+            value,
+        );
+        self.start_frame(context, frame);
+        None
+    }
+
+    fn call_end(&mut self, context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.end_frame(
+            context,
+            outcome.result.result,
+            &outcome.result.output,
+            outcome.result.gas,
+            None,
+        );
+    }
+
+    fn create(&mut self, context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        let frame = CallFrame::new(
+            inputs.scheme.into(),
+            inputs.caller,
+            None,
+            inputs.init_code.clone(),
+            inputs.gas_limit,
+            Some(inputs.value),
+        );
+        self.start_frame(context, frame);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.end_frame(
+            context,
+            outcome.result.result,
+            &outcome.result.output,
+            outcome.result.gas,
+            outcome.address,
+        );
+    }
+
+    fn eofcreate(
+        &mut self,
+        context: &mut CTX,
+        inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        let (to, input) = match &inputs.kind {
+            EOFCreateKind::Opcode {
+                initcode,
+                created_address,
+                ..
+            } => (Some(*created_address), initcode.raw.clone()),
+            EOFCreateKind::Tx { initdata } => (None, initdata.clone()),
+        };
+        let frame = CallFrame::new(
+            CallKind::EofCreate,
+            inputs.caller,
+            to,
+            input,
+            inputs.gas_limit,
+            Some(inputs.value),
+        );
+        self.start_frame(context, frame);
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        context: &mut CTX,
+        _inputs: &EOFCreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.end_frame(
+            context,
+            outcome.result.result,
+            &outcome.result.output,
+            outcome.result.gas,
+            outcome.address,
+        );
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        if self.config.only_top_call {
+            return;
+        }
+        let Some(parent) = self.stack.last_mut() else {
+            return;
+        };
+        parent.calls.push(CallFrame::new(
+            CallKind::SelfDestruct,
+            contract,
+            Some(target),
+            Bytes::new(),
+            0,
+            Some(value),
+        ));
+    }
+}
+
+/// Returns the geth error message for a failed frame.
+fn error_message(result: InstructionResult) -> String {
+    match result {
+        InstructionResult::Revert => "execution reverted",
+        InstructionResult::CallTooDeep => "max call depth exceeded",
+        InstructionResult::OutOfFunds => "insufficient balance for transfer",
+        InstructionResult::OutOfGas
+        | InstructionResult::MemoryOOG
+        | InstructionResult::MemoryLimitOOG
+        | InstructionResult::PrecompileOOG
+        | InstructionResult::InvalidOperandOOG
+        | InstructionResult::ReentrancySentryOOG => "out of gas",
+        InstructionResult::OpcodeNotFound | InstructionResult::EOFOpcodeDisabledInLegacy => {
+            "invalid opcode"
+        }
+        InstructionResult::InvalidFEOpcode => "invalid opcode: INVALID",
+        InstructionResult::InvalidJump => "invalid jump destination",
+        InstructionResult::StackUnderflow => "stack underflow",
+        InstructionResult::StackOverflow => "stack limit reached 1024",
+        InstructionResult::CallNotAllowedInsideStatic
+        | InstructionResult::StateChangeDuringStaticCall => "write protection",
+        InstructionResult::OutOfOffset => "return data out of bounds",
+        InstructionResult::CreateCollision => "contract address collision",
+        InstructionResult::NonceOverflow => "nonce uint64 overflow",
+        InstructionResult::CreateContractSizeLimit => "max code size exceeded",
+        InstructionResult::CreateInitCodeSizeLimit => "max initcode size exceeded",
+        InstructionResult::CreateContractStartingWithEF => "invalid code: must not begin with 0xef",
+        InstructionResult::OverflowPayment => "gas uint64 overflow",
+        InstructionResult::PrecompileError => "precompile failed",
+        other => return format!("{other:?}"),
+    }
+    .to_string()
+}
+
+/// Selector of `Error(string)`.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Selector of `Panic(uint256)`.
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Decodes Solidity `Error(string)` and `Panic(uint256)` revert data.
+///
+/// Panic codes are described the same way as geth does.
+pub fn decode_revert_reason(output: &[u8]) -> Option<String> {
+    let (selector, data) = output.split_first_chunk::<4>()?;
+    match *selector {
+        ERROR_SELECTOR => {
+            let offset = read_usize(data, 0)?;
+            let len = read_usize(data, offset)?;
+            let start = offset.checked_add(32)?;
+            let bytes = data.get(start..start.checked_add(len)?)?;
+            String::from_utf8(bytes.to_vec()).ok()
+        }
+        PANIC_SELECTOR => {
+            let code = U256::from_be_slice(data.get(..32)?);
+            let reason = match code.try_into().unwrap_or(u64::MAX) {
+                0x00 => "generic panic",
+                0x01 => "assert(false)",
+                0x11 => "arithmetic underflow or overflow",
+                0x12 => "division or modulo by zero",
+                0x21 => "enum overflow",
+                0x22 => "invalid encoded storage byte array accessed",
+                0x31 => "out-of-bounds array access; popping on an empty array",
+                0x32 => "out-of-bounds access of an array or bytesN",
+                0x41 => "out of memory",
+                0x51 => "uninitialized function",
+                _ => return Some(format!("unknown panic code: {code:#x}")),
+            };
+            Some(reason.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Reads an ABI word at `offset` and converts it to `usize`.
+fn read_usize(data: &[u8], offset: usize) -> Option<usize> {
+    let word = data.get(offset..offset.checked_add(32)?)?;
+    U256::from_be_slice(word).try_into().ok()
+}
+
+/// Serde helper for `u64` hex quantities, e.g. `0x5208`.
+mod quantity {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::{borrow::Cow, format};
+
+    pub(super) fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{value:#x}"))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        let s = <Cow<'de, str>>::deserialize(deserializer)?;
+        let digits = s
+            .strip_prefix("0x")
+            .ok_or_else(|| D::Error::custom("missing 0x prefix"))?;
+        u64::from_str_radix(digits, 16).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{inspector_context::InspectorContext, inspector_handler, InspectorMainEvm};
+    use database::BenchmarkDB;
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        primitives::{hex, TxKind},
+        Context, EvmExec,
+    };
+
+    fn trace(code: Vec<u8>, config: CallTracerConfig) -> CallFrame {
+        let ctx = Context::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code.into())))
+            .modify_tx_chained(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.kind = TxKind::Call(Address::ZERO);
+                tx.gas_limit = 100_000;
+            });
+        let mut evm = InspectorMainEvm::new(
+            InspectorContext::new(ctx, CallTracer::new(config)),
+            inspector_handler(),
+        );
+        evm.exec().unwrap();
+        evm.context.inspector.take_call_frame().unwrap()
+    }
+
+    /// Emits a LOG0 and calls the identity precompile with a single byte of input.
+    fn log_and_call_code() -> Vec<u8> {
+        vec![
+            opcode::PUSH1,
+            0x00,
+            opcode::PUSH1,
+            0x00,
+            opcode::LOG0,
+            // STATICCALL(gas, 0x04, 0, 1, 0, 0)
+            opcode::PUSH1,
+            0x00,
+            opcode::PUSH1,
+            0x00,
+            opcode::PUSH1,
+            0x01,
+            opcode::PUSH1,
+            0x00,
+            opcode::PUSH1,
+            0x04,
+            opcode::GAS,
+            opcode::STATICCALL,
+            opcode::STOP,
+        ]
+    }
+
+    #[test]
+    fn nested_calls_and_logs() {
+        let frame = trace(
+            log_and_call_code(),
+            CallTracerConfig::default().with_log(true),
+        );
+        assert_eq!(frame.kind, CallKind::Call);
+        assert_eq!(frame.gas, 100_000);
+        assert!(frame.gas_used > 21_000);
+        assert_eq!(frame.logs.len(), 1);
+        assert_eq!(frame.logs[0].position, 0);
+        assert_eq!(frame.calls.len(), 1);
+
+        let call = &frame.calls[0];
+        assert_eq!(call.kind, CallKind::StaticCall);
+        assert_eq!(call.from, Address::ZERO);
+        assert_eq!(call.to, Some(Address::with_last_byte(4)));
+        assert_eq!(call.value, None);
+        assert_eq!(call.output, Bytes::from_static(&[0]));
+    }
+
+    #[test]
+    fn only_top_call() {
+        let frame = trace(log_and_call_code(), CallTracerConfig::default());
+        assert!(frame.logs.is_empty());
+        assert_eq!(frame.calls.len(), 1);
+
+        let frame = trace(
+            log_and_call_code(),
+            CallTracerConfig::default().only_top_call(true),
+        );
+        assert!(frame.calls.is_empty());
+    }
+
+    #[test]
+    fn revert_reason() {
+        // Error("revert")
+        let output = hex!("08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000067265766572740000000000000000000000000000000000000000000000000000");
+        assert_eq!(decode_revert_reason(&output).as_deref(), Some("revert"));
+
+        // Panic(0x11)
+        let output =
+            hex!("4e487b710000000000000000000000000000000000000000000000000000000000000011");
+        assert_eq!(
+            decode_revert_reason(&output).as_deref(),
+            Some("arithmetic underflow or overflow")
+        );
+
+        assert_eq!(decode_revert_reason(&[0xde, 0xad]), None);
+    }
+
+    #[cfg(feature = "serde-json")]
+    #[test]
+    fn geth_json_format() {
+        let frame = trace(
+            vec![opcode::PUSH1, 0x00, opcode::DUP1, opcode::REVERT],
+            CallTracerConfig::default(),
+        );
+        let json = serde_json::to_value(&frame).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "from": "0x0000000000000000000000000000000000000001",
+                "gas": "0x186a0",
+                "gasUsed": "0x520e",
+                "to": "0x0000000000000000000000000000000000000000",
+                "input": "0x",
+                "error": "execution reverted",
+                "value": "0x0",
+                "type": "CALL"
+            })
+        );
+        assert_eq!(serde_json::from_value::<CallFrame>(json).unwrap(), frame);
+    }
+}
@@ -0,0 +1,459 @@
+//! Code coverage [Inspector].
+use crate::Inspector;
+use revm::{
+    bytecode::{opcode, Bytecode},
+    interpreter::{
+        interpreter::EthInterpreter, interpreter_types::Jumps, CallInputs, CallOutcome,
+        CreateInputs, CreateOutcome, Interpreter,
+    },
+    primitives::{HashMap, B256},
+};
+use std::{collections::BTreeMap, vec, vec::Vec};
+#[cfg(feature = "serde-json")]
+use {
+    crate::source_map::{SourceLocation, SourceMaps},
+    core::fmt::Write,
+    std::string::{String, ToString},
+};
+
+/// Number of times the conditional jump was taken and not taken.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BranchCoverage {
+    /// Executions jumping to the destination.
+    pub taken: u64,
+    /// Executions continuing with the next instruction.
+    pub not_taken: u64,
+}
+
+/// Coverage of a code section, legacy bytecode has a single section.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SectionCoverage {
+    /// Number of executions of the instruction at the pc, relative to the section start.
+    pub hits: Vec<u64>,
+    /// `JUMPI` and `RJUMPI` branches by their pc, relative to the section start.
+    pub branches: BTreeMap<usize, BranchCoverage>,
+}
+
+#[cfg(feature = "serde-json")]
+impl SectionCoverage {
+    /// Adds the hits and branches of the other section.
+    fn merge(&mut self, other: &Self) {
+        if self.hits.len() < other.hits.len() {
+            self.hits.resize(other.hits.len(), 0);
+        }
+        for (hits, other) in self.hits.iter_mut().zip(&other.hits) {
+            *hits += other;
+        }
+        for (pc, other) in &other.branches {
+            let branch = self.branches.entry(*pc).or_default();
+            branch.taken += other.taken;
+            branch.not_taken += other.not_taken;
+        }
+    }
+}
+
+/// Coverage of the executed bytecode.
+#[derive(Clone, Debug)]
+pub struct CodeCoverage {
+    /// The executed bytecode.
+    pub bytecode: Bytecode,
+    /// Coverage of the code sections, in the order of the EOF container.
+    pub sections: Vec<SectionCoverage>,
+    /// End offset of every code section.
+    section_ends: Vec<usize>,
+}
+
+impl CodeCoverage {
+    fn new(bytecode: Bytecode) -> Self {
+        let section_ends = match &bytecode {
+            Bytecode::Eof(eof) => eof.body.code_section.clone(),
+            _ => vec![bytecode.original_byte_slice().len()],
+        };
+        let sections = section_ends
+            .iter()
+            .scan(0, |start, &end| {
+                let len = end.saturating_sub(*start);
+                *start = end;
+                Some(SectionCoverage {
+                    hits: vec![0; len],
+                    branches: BTreeMap::new(),
+                })
+            })
+            .collect();
+        Self {
+            bytecode,
+            sections,
+            section_ends,
+        }
+    }
+
+    /// Returns the code section and the pc relative to it.
+    ///
+    /// EOF pcs are offsets in the concatenated code sections.
+    fn section_pc(&self, pc: usize) -> (usize, usize) {
+        let section = self.section_ends.partition_point(|&end| end <= pc);
+        let start = match section {
+            0 => 0,
+            _ => self.section_ends[section - 1],
+        };
+        (section, pc - start)
+    }
+}
+
+/// [Inspector] recording the executed instructions and branches of every bytecode.
+///
+/// Coverage is keyed by the code hash, EOF containers are tracked per code section. With
+/// the [`SourceMaps`](crate::inspectors::SourceMaps) of the contracts it is exported as LCOV.
+#[derive(Clone, Debug, Default)]
+pub struct CoverageInspector {
+    codes: HashMap<B256, CodeCoverage>,
+    /// Code hash of every active frame, `None` until the interpreter is initialized.
+    frames: Vec<Option<B256>>,
+}
+
+impl CoverageInspector {
+    /// Creates the inspector without any coverage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the coverage by the code hash.
+    pub fn coverage(&self) -> &HashMap<B256, CodeCoverage> {
+        &self.codes
+    }
+
+    /// Clears the recorded coverage.
+    pub fn clear(&mut self) {
+        self.codes.clear();
+        self.frames.clear();
+    }
+}
+
+#[cfg(feature = "serde-json")]
+impl CoverageInspector {
+    /// Exports the coverage of the source files in the LCOV tracefile format.
+    ///
+    /// Every loaded contract is reported, contracts that were not executed have no hits.
+    /// Only legacy bytecode is matched to the source maps.
+    pub fn lcov(&self, maps: &SourceMaps) -> String {
+        // Deployments of the same contract with different immutables are merged.
+        let mut contracts = vec![SectionCoverage::default(); maps.contract_count()];
+        for code in self.codes.values() {
+            if let (Some(contract), [section]) =
+                (maps.find_contract(&code.bytecode), &code.sections[..])
+            {
+                contracts[contract].merge(section);
+            }
+        }
+
+        let mut files = BTreeMap::<String, FileCoverage>::new();
+        for (contract, coverage) in contracts.iter().enumerate() {
+            for (pc, opcode) in maps.instructions(contract) {
+                let Some(SourceLocation {
+                    path,
+                    line: Some(line),
+                    ..
+                }) = maps.locate(contract, pc)
+                else {
+                    continue;
+                };
+                let hits = coverage.hits.get(pc).copied().unwrap_or_default();
+                let file = files.entry(path).or_default();
+                let line_hits = file.lines.entry(line).or_default();
+                *line_hits = (*line_hits).max(hits);
+                if opcode == opcode::JUMPI {
+                    let branch = coverage.branches.get(&pc).copied().unwrap_or_default();
+                    file.branches
+                        .insert((line, contract, pc), (hits > 0).then_some(branch));
+                }
+            }
+        }
+
+        let mut out = String::new();
+        for (path, file) in files {
+            file.write(&path, &mut out);
+        }
+        out
+    }
+}
+
+/// Coverage of a source file.
+#[cfg(feature = "serde-json")]
+#[derive(Debug, Default)]
+struct FileCoverage {
+    /// Hits of the source line, the maximum over its instructions.
+    lines: BTreeMap<usize, u64>,
+    /// Branches by the line, contract and pc, `None` if the jump was not executed.
+    branches: BTreeMap<(usize, usize, usize), Option<BranchCoverage>>,
+}
+
+#[cfg(feature = "serde-json")]
+impl FileCoverage {
+    fn write(&self, path: &str, out: &mut String) {
+        let count = |count: Option<u64>| count.map_or("-".into(), |count| count.to_string());
+        let _ = writeln!(out, "SF:{path}");
+        for (block, (&(line, _, _), branch)) in self.branches.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "BRDA:{line},{block},0,{}",
+                count(branch.map(|branch| branch.taken))
+            );
+            let _ = writeln!(
+                out,
+                "BRDA:{line},{block},1,{}",
+                count(branch.map(|branch| branch.not_taken))
+            );
+        }
+        let branches_hit = self
+            .branches
+            .values()
+            .flatten()
+            .map(|branch| (branch.taken > 0) as usize + (branch.not_taken > 0) as usize)
+            .sum::<usize>();
+        let _ = writeln!(out, "BRF:{}", self.branches.len() * 2);
+        let _ = writeln!(out, "BRH:{branches_hit}");
+        for (line, hits) in &self.lines {
+            let _ = writeln!(out, "DA:{line},{hits}");
+        }
+        let _ = writeln!(out, "LF:{}", self.lines.len());
+        let _ = writeln!(
+            out,
+            "LH:{}",
+            self.lines.values().filter(|&&hits| hits > 0).count()
+        );
+        out.push_str("end_of_record\n");
+    }
+}
+
+impl<CTX> Inspector<CTX, EthInterpreter> for CoverageInspector {
+    fn initialize_interp(&mut self, interp: &mut Interpreter<EthInterpreter>, _: &mut CTX) {
+        let hash = match interp.bytecode.hash() {
+            Some(hash) => hash,
+            None => interp.bytecode.hash_slow(),
+        };
+        self.codes
+            .entry(hash)
+            .or_insert_with(|| CodeCoverage::new((*interp.bytecode).clone()));
+        if let Some(frame) = self.frames.last_mut() {
+            *frame = Some(hash);
+        }
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _: &mut CTX) {
+        let Some(code) = self
+            .frames
+            .last()
+            .copied()
+            .flatten()
+            .and_then(|hash| self.codes.get_mut(&hash))
+        else {
+            return;
+        };
+        let (section, pc) = code.section_pc(interp.bytecode.pc());
+        let Some(section) = code.sections.get_mut(section) else {
+            return;
+        };
+        if let Some(hits) = section.hits.get_mut(pc) {
+            *hits += 1;
+        }
+        let stack = interp.stack.data();
+        let condition = match interp.bytecode.opcode() {
+            // The destination is on the top of the stack, the condition below it.
+            opcode::JUMPI => stack.len().checked_sub(2).map(|i| stack[i]),
+            opcode::RJUMPI => stack.last().copied(),
+            _ => None,
+        };
+        if let Some(condition) = condition {
+            let branch = section.branches.entry(pc).or_default();
+            if condition.is_zero() {
+                branch.not_taken += 1;
+            } else {
+                branch.taken += 1;
+            }
+        }
+    }
+
+    fn call(&mut self, _: &mut CTX, _: &mut CallInputs) -> Option<CallOutcome> {
+        self.frames.push(None);
+        None
+    }
+
+    fn call_end(&mut self, _: &mut CTX, _: &CallInputs, _: &mut CallOutcome) {
+        self.frames.pop();
+    }
+
+    fn create(&mut self, _: &mut CTX, _: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.frames.push(None);
+        None
+    }
+
+    fn create_end(&mut self, _: &mut CTX, _: &CreateInputs, _: &mut CreateOutcome) {
+        self.frames.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{inspector_context::InspectorContext, inspector_handler, InspectorMainEvm};
+    use database::BenchmarkDB;
+    use revm::{
+        bytecode::{
+            eof::{EofBody, TypesSection},
+            Eof,
+        },
+        primitives::{address, Address, Bytes, TxKind, U256},
+        specification::hardfork::SpecId,
+        Context, EvmExec,
+    };
+
+    fn run(bytecode: Bytecode, data: Bytes) -> CoverageInspector {
+        let ctx = Context::builder()
+            .with_db(BenchmarkDB::new_bytecode(bytecode))
+            .modify_cfg_chained(|cfg| cfg.spec = SpecId::OSAKA)
+            .modify_tx_chained(|tx| {
+                tx.caller = address!("0000000000000000000000000000000000000001");
+                tx.kind = TxKind::Call(Address::ZERO);
+                tx.data = data;
+            });
+        let mut evm = InspectorMainEvm::new(
+            InspectorContext::new(ctx, CoverageInspector::new()),
+            inspector_handler(),
+        );
+        assert!(evm.exec().unwrap().result.is_success());
+        evm.context.inspector
+    }
+
+    /// `if (calldata[0] != 0) jump to 8`, with an unreachable `STOP` at 7.
+    fn branching_code() -> Vec<u8> {
+        vec![
+            opcode::PUSH1,
+            0,
+            opcode::CALLDATALOAD,
+            opcode::PUSH1,
+            8,
+            opcode::JUMPI,
+            opcode::STOP,
+            opcode::STOP,
+            opcode::JUMPDEST,
+            opcode::STOP,
+        ]
+    }
+
+    #[test]
+    fn legacy_branches() {
+        let coverage = run(
+            Bytecode::new_raw(branching_code().into()),
+            U256::from(1).to_be_bytes_vec().into(),
+        );
+        let [code] = &coverage.coverage().values().collect::<Vec<_>>()[..] else {
+            panic!("expected a single bytecode");
+        };
+        let [section] = &code.sections[..] else {
+            panic!("expected a single section");
+        };
+        assert_eq!(section.hits, [1, 0, 1, 1, 0, 1, 0, 0, 1, 1]);
+        assert_eq!(
+            section.branches.get(&5),
+            Some(&BranchCoverage {
+                taken: 1,
+                not_taken: 0
+            })
+        );
+    }
+
+    #[test]
+    fn eof_sections() {
+        // Section 0 calls section 1, which does not take the branch and stops.
+        let eof = Eof::new(EofBody {
+            types_section: vec![
+                TypesSection {
+                    inputs: 0,
+                    outputs: 0x80,
+                    max_stack_size: 0,
+                },
+                TypesSection {
+                    inputs: 0,
+                    outputs: 0,
+                    max_stack_size: 1,
+                },
+            ],
+            code_section: vec![4, 11],
+            code: Bytes::from_static(&[
+                opcode::CALLF,
+                0,
+                1,
+                opcode::STOP,
+                opcode::PUSH1,
+                0,
+                opcode::RJUMPI,
+                0,
+                1,
+                opcode::STOP,
+                opcode::RETF,
+            ]),
+            data_section: Bytes::new(),
+            ..Default::default()
+        });
+        let coverage = run(Bytecode::Eof(eof.into()), Bytes::new());
+        let code = coverage.coverage().values().next().unwrap();
+        assert_eq!(code.sections.len(), 2);
+        assert_eq!(code.sections[0].hits, [1, 0, 0, 0]);
+        assert_eq!(code.sections[1].hits, [1, 0, 1, 0, 0, 1, 0]);
+        assert_eq!(
+            code.sections[1].branches.get(&2),
+            Some(&BranchCoverage {
+                taken: 0,
+                not_taken: 1
+            })
+        );
+    }
+
+    #[cfg(feature = "serde-json")]
+    #[test]
+    fn lcov_export() {
+        const SOURCE: &str = "contract C {\n    fallback() external {\n        if (msg.data.length > 0) {\n            return;\n        }\n    }\n}\n";
+        let at = |pattern: &str| SOURCE.find(pattern).unwrap();
+        // The condition is on line 3, the not taken branch ends on line 6 and the taken on line 4.
+        let source_map = format!(
+            "{}:24:0;;;;{}:1:0;0:0:-1;{}:7:0;",
+            at("msg"),
+            at("    }\n}") + 4,
+            at("return")
+        );
+        let artifact = serde_json::json!({
+            "id": 0,
+            "deployedBytecode": {
+                "object": revm::primitives::hex::encode(branching_code()),
+                "sourceMap": source_map,
+            },
+        });
+        let mut maps = SourceMaps::new();
+        maps.add_foundry_artifact("C", &artifact.to_string())
+            .unwrap();
+        maps.add_source(0, "src/C.sol", SOURCE);
+
+        let coverage = run(
+            Bytecode::new_raw(branching_code().into()),
+            U256::from(1).to_be_bytes_vec().into(),
+        );
+        assert_eq!(
+            coverage.lcov(&maps),
+            "SF:src/C.sol\n\
+             BRDA:3,0,0,1\n\
+             BRDA:3,0,1,0\n\
+             BRF:2\n\
+             BRH:1\n\
+             DA:3,1\n\
+             DA:4,1\n\
+             DA:6,0\n\
+             LF:3\n\
+             LH:2\n\
+             end_of_record\n"
+        );
+        // Contracts that were not executed have no hits.
+        assert!(CoverageInspector::new()
+            .lcov(&maps)
+            .contains("BRDA:3,0,0,-\n"));
+    }
+}
@@ -11,7 +11,7 @@ use revm::{
     },
     database_interface::{Database, EmptyDB},
     handler::{
-        EthExecution, EthFrame, EthHandler, EthPostExecution, EthPreExecution,
+        EthExecution, EthFrame, EthFrameError, EthHandler, EthPostExecution, EthPreExecution,
         EthPrecompileProvider, EthValidation, FrameResult,
     },
     handler_interface::{Frame, FrameOrResultGen, PrecompileProvider},
@@ -22,7 +22,6 @@ use revm::{
         CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, FrameInput, Host,
         Instruction, InstructionResult, Interpreter, InterpreterResult, InterpreterTypes,
     },
-    precompile::PrecompileErrors,
     primitives::{Address, Log, U256},
     Context, Error, Evm,
 };
@@ -194,6 +193,8 @@ impl<BLOCK, TX, CFG, DB: Database, JOURNAL: Journal<Database = DB> + JournalExt,
 #[derive(Clone)]
 pub struct InspectorInstruction<IT: InterpreterTypes, HOST> {
     pub instruction: fn(&mut Interpreter<IT>, &mut HOST),
+    /// Static gas cost, charged after the step hook.
+    pub static_gas: u64,
 }
 
 impl<IT: InterpreterTypes, HOST> CustomInstruction for InspectorInstruction<IT, HOST>
@@ -218,14 +219,23 @@ where
         interpreter.bytecode.relative_jump(1);
 
         // Execute instruction.
-        (self.instruction)(interpreter, host);
+        if interpreter.control.gas().record_cost(self.static_gas) {
+            (self.instruction)(interpreter, host);
+        } else {
+            interpreter
+                .control
+                .set_instruction_result(InstructionResult::OutOfGas);
+        }
 
         // Call step_end.
         host.step_end(interpreter);
     }
 
     fn from_base(instruction: Instruction<Self::Wire, Self::Host>) -> Self {
-        Self { instruction }
+        Self {
+            instruction,
+            static_gas: 0,
+        }
     }
 }
 
@@ -253,7 +263,7 @@ where
         + JournalExtGetter
         + Host
         + InspectorCtx<IT = EthInterpreter>,
-    ERROR: From<JournalDBError<CTX>> + From<PrecompileErrors>,
+    ERROR: EthFrameError<CTX>,
     PRECOMPILE: PrecompileProvider<Context = CTX, Error = ERROR, Output = InterpreterResult>,
 {
     type Context = CTX;
@@ -1,7 +1,7 @@
 use core::mem::MaybeUninit;
 use revm::{
     bytecode::opcode::OpCode,
-    context_interface::JournalGetter,
+    context_interface::{Cfg, JournalGetter},
     interpreter::{
         instructions::host::{log, selfdestruct},
         interpreter::InstructionProvider,
@@ -41,14 +41,16 @@ where
     type WIRE = WIRE;
     type Host = HOST;
 
-    fn new(_context: &mut Self::Host) -> Self {
-        let main_table = table::make_instruction_table::<WIRE, HOST>();
+    fn new(context: &mut Self::Host) -> Self {
+        let main_table =
+            table::make_metered_instruction_table::<WIRE, HOST>(context.cfg().gas_schedule());
         let mut table: [MaybeUninit<InspectorInstruction<WIRE, HOST>>; 256] =
             unsafe { MaybeUninit::uninit().assume_init() };
 
         for (i, element) in table.iter_mut().enumerate() {
             let function = InspectorInstruction {
-                instruction: main_table[i],
+                instruction: main_table[i].instruction,
+                static_gas: main_table[i].static_gas,
             };
             *element = MaybeUninit::new(function);
         }
@@ -75,54 +77,42 @@ where
         }
 
         /* LOG and Selfdestruct instructions */
-        table[OpCode::LOG0.as_usize()] = InspectorInstruction {
-            instruction: |interp, context| {
-                inspector_log(interp, context, log::<0, HOST>);
-            },
+        table[OpCode::LOG0.as_usize()].instruction = |interp, context| {
+            inspector_log(interp, context, log::<0, HOST>);
         };
-        table[OpCode::LOG1.as_usize()] = InspectorInstruction {
-            instruction: |interp, context| {
-                inspector_log(interp, context, log::<1, HOST>);
-            },
+        table[OpCode::LOG1.as_usize()].instruction = |interp, context| {
+            inspector_log(interp, context, log::<1, HOST>);
         };
-        table[OpCode::LOG2.as_usize()] = InspectorInstruction {
-            instruction: |interp, context| {
-                inspector_log(interp, context, log::<2, HOST>);
-            },
+        table[OpCode::LOG2.as_usize()].instruction = |interp, context| {
+            inspector_log(interp, context, log::<2, HOST>);
         };
-        table[OpCode::LOG3.as_usize()] = InspectorInstruction {
-            instruction: |interp, context| {
-                inspector_log(interp, context, log::<3, HOST>);
-            },
+        table[OpCode::LOG3.as_usize()].instruction = |interp, context| {
+            inspector_log(interp, context, log::<3, HOST>);
         };
-        table[OpCode::LOG4.as_usize()] = InspectorInstruction {
-            instruction: |interp, context| {
-                inspector_log(interp, context, log::<4, HOST>);
-            },
+        table[OpCode::LOG4.as_usize()].instruction = |interp, context| {
+            inspector_log(interp, context, log::<4, HOST>);
         };
 
-        table[OpCode::SELFDESTRUCT.as_usize()] = InspectorInstruction {
-            instruction: |interp, context| {
-                selfdestruct::<Self::WIRE, HOST>(interp, context);
-                if interp.control.instruction_result() == InstructionResult::SelfDestruct {
-                    match context.journal_ext().last_journal().last() {
-                        Some(JournalEntry::AccountDestroyed {
-                            address,
-                            target,
-                            had_balance,
-                            ..
-                        }) => {
-                            context.inspector_selfdestruct(*address, *target, *had_balance);
-                        }
-                        Some(JournalEntry::BalanceTransfer {
-                            from, to, balance, ..
-                        }) => {
-                            context.inspector_selfdestruct(*from, *to, *balance);
-                        }
-                        _ => {}
+        table[OpCode::SELFDESTRUCT.as_usize()].instruction = |interp, context| {
+            selfdestruct::<Self::WIRE, HOST>(interp, context);
+            if interp.control.instruction_result() == InstructionResult::SelfDestruct {
+                match context.journal_ext().last_journal().last() {
+                    Some(JournalEntry::AccountDestroyed {
+                        address,
+                        target,
+                        had_balance,
+                        ..
+                    }) => {
+                        context.inspector_selfdestruct(*address, *target, *had_balance);
                     }
+                    Some(JournalEntry::BalanceTransfer {
+                        from, to, balance, ..
+                    }) => {
+                        context.inspector_selfdestruct(*from, *to, *balance);
+                    }
+                    _ => {}
                 }
-            },
+            }
         };
 
         Self {
@@ -5,6 +5,10 @@
 #[cfg(not(feature = "std"))]
 extern crate alloc as std;
 
+mod access_list;
+#[cfg(feature = "serde")]
+mod call_tracer;
+mod coverage;
 #[cfg(all(feature = "std", feature = "serde-json"))]
 mod eip3155;
 mod gas;
@@ -13,13 +17,36 @@ pub mod inspector_context;
 pub mod inspector_instruction;
 pub mod journal;
 mod noop;
+#[cfg(feature = "serde")]
+mod prestate;
+#[cfg(feature = "std")]
+mod profiler;
+#[cfg(feature = "serde-json")]
+mod source_map;
 
 pub use inspector::*;
 
 /// [Inspector] implementations.
 pub mod inspectors {
+    pub use super::access_list::{create_access_list, AccessListInspector};
+    #[cfg(feature = "serde")]
+    pub use super::call_tracer::{
+        decode_revert_reason, CallFrame, CallKind, CallLog, CallTracer, CallTracerConfig,
+    };
+    pub use super::coverage::{BranchCoverage, CodeCoverage, CoverageInspector, SectionCoverage};
     #[cfg(all(feature = "std", feature = "serde-json"))]
     pub use super::eip3155::TracerEip3155;
     pub use super::gas::GasInspector;
     pub use super::noop::NoOpInspector;
+    #[cfg(feature = "serde")]
+    pub use super::prestate::{
+        PrestateAccount, PrestateAccounts, PrestateDiff, PrestateFrame, PrestateTracer,
+        PrestateTracerConfig,
+    };
+    #[cfg(feature = "std")]
+    pub use super::profiler::{ContractStats, OpcodeStats, ProfileMetric, Profiler};
+    #[cfg(feature = "serde-json")]
+    pub use super::source_map::{
+        LineGas, SourceLocation, SourceMapError, SourceMapTracer, SourceMaps, TraceFrame,
+    };
 }
@@ -0,0 +1,431 @@
+//! Prestate [Inspector] producing output compatible with geth `prestateTracer`.
+use crate::{
+    journal::{JournalExt, JournalExtGetter},
+    Inspector,
+};
+use revm::{
+    context_interface::{Block, BlockGetter, DatabaseGetter, Journal, JournalGetter},
+    database_interface::Database,
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, InterpreterTypes,
+    },
+    primitives::{Address, Bytes, B256, KECCAK_EMPTY, U256},
+    state::{AccountInfo, EvmState},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, vec::Vec};
+
+/// Options of the [PrestateTracer].
+///
+/// Mirrors the `tracerConfig` object accepted by geth `prestateTracer`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PrestateTracerConfig {
+    /// Output the state before and after the transaction, limited to changed values.
+    pub diff_mode: bool,
+    /// Skip contract code in the output.
+    pub disable_code: bool,
+    /// Skip contract storage in the output.
+    pub disable_storage: bool,
+}
+
+impl PrestateTracerConfig {
+    /// Sets the `diffMode` option.
+    pub fn diff_mode(mut self, diff_mode: bool) -> Self {
+        self.diff_mode = diff_mode;
+        self
+    }
+
+    /// Sets the `disableCode` option.
+    pub fn disable_code(mut self, disable_code: bool) -> Self {
+        self.disable_code = disable_code;
+        self
+    }
+
+    /// Sets the `disableStorage` option.
+    pub fn disable_storage(mut self, disable_storage: bool) -> Self {
+        self.disable_storage = disable_storage;
+        self
+    }
+}
+
+/// State of a single account as reported by geth `prestateTracer`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrestateAccount {
+    /// Account balance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    /// Account code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Account nonce.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    /// Accessed storage slots.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<B256, B256>,
+}
+
+impl PrestateAccount {
+    /// Creates a full account entry, omitting zero nonce and empty code like geth does.
+    fn from_info(info: &AccountInfo, code: Option<Bytes>) -> Self {
+        Self {
+            balance: Some(info.balance),
+            code: code.filter(|code| !code.is_empty()),
+            nonce: (info.nonce != 0).then_some(info.nonce),
+            storage: BTreeMap::new(),
+        }
+    }
+}
+
+/// Accounts keyed by address.
+pub type PrestateAccounts = BTreeMap<Address, PrestateAccount>;
+
+/// Pre and post state of the changed accounts, produced in diff mode.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrestateDiff {
+    /// Changed accounts before the transaction.
+    pub pre: PrestateAccounts,
+    /// Changed values after the transaction.
+    pub post: PrestateAccounts,
+}
+
+/// Output of the [PrestateTracer].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PrestateFrame {
+    /// Diff mode, see [PrestateTracerConfig::diff_mode].
+    Diff(PrestateDiff),
+    /// Default mode, all touched accounts before the transaction.
+    Default(PrestateAccounts),
+}
+
+/// Geth `prestateTracer` compatible [Inspector].
+///
+/// The tracer snapshots the database values of every account loaded by the journal
+/// when the top-level frame ends, before the state is committed. Combined with the
+/// final [EvmState] returned in `ResultAndState` it produces the prestate, or the
+/// pre/post diff, with [PrestateTracer::geth_trace].
+#[derive(Clone, Debug, Default)]
+pub struct PrestateTracer {
+    config: PrestateTracerConfig,
+    /// Account info and code as found in the database, `None` if the account did not exist.
+    pre: BTreeMap<Address, Option<(AccountInfo, Bytes)>>,
+}
+
+impl PrestateTracer {
+    /// Creates a new tracer with the given config.
+    pub fn new(config: PrestateTracerConfig) -> Self {
+        Self {
+            config,
+            pre: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the config of the tracer.
+    pub fn config(&self) -> &PrestateTracerConfig {
+        &self.config
+    }
+
+    /// Resets the tracer, dropping the recorded accounts.
+    pub fn clear(&mut self) {
+        self.pre.clear();
+    }
+
+    /// Returns the database values of accounts touched by the last transaction.
+    ///
+    /// `None` is stored for accounts that did not exist before the transaction.
+    pub fn pre_accounts(&self) -> impl Iterator<Item = (&Address, Option<&AccountInfo>)> {
+        self.pre
+            .iter()
+            .map(|(address, account)| (address, account.as_ref().map(|(info, _)| info)))
+    }
+
+    /// Builds the geth `prestateTracer` output.
+    ///
+    /// `state` is the final state of the transaction from `ResultAndState::state`.
+    pub fn geth_trace(&self, state: &EvmState) -> PrestateFrame {
+        if self.config.diff_mode {
+            PrestateFrame::Diff(self.diff(state))
+        } else {
+            PrestateFrame::Default(self.prestate(state))
+        }
+    }
+
+    /// Returns the pre state of all touched accounts.
+    fn prestate(&self, state: &EvmState) -> PrestateAccounts {
+        let mut accounts = PrestateAccounts::new();
+        for (address, pre) in &self.pre {
+            let account = state.get(address);
+            let pre_info = pre.as_ref().map(|(info, code)| (info, code.clone()));
+            // Contracts created by the transaction had no prestate.
+            if pre_info.is_none() && account.is_some_and(|account| account.is_created()) {
+                continue;
+            }
+            let mut entry = match pre_info {
+                Some((info, code)) => PrestateAccount::from_info(info, self.code(code)),
+                None => PrestateAccount::from_info(&AccountInfo::default(), None),
+            };
+            if let (Some(account), false) = (account, self.config.disable_storage) {
+                entry.storage = account
+                    .storage
+                    .iter()
+                    .map(|(key, slot)| (B256::from(*key), B256::from(slot.original_value)))
+                    .collect();
+            }
+            accounts.insert(*address, entry);
+        }
+        accounts
+    }
+
+    /// Returns the changed accounts before and after the transaction.
+    fn diff(&self, state: &EvmState) -> PrestateDiff {
+        let mut diff = PrestateDiff::default();
+        for (address, pre) in &self.pre {
+            let Some(account) = state.get(address) else {
+                continue;
+            };
+            let (pre_info, pre_code) = match pre {
+                Some((info, code)) => (info.clone(), code.clone()),
+                None => (AccountInfo::default(), Bytes::new()),
+            };
+            let mut pre_entry = PrestateAccount::from_info(&pre_info, self.code(pre_code.clone()));
+
+            // Destructed accounts are kept in the prestate but pruned from the post state.
+            if account.is_selfdestructed() {
+                diff.pre.insert(*address, pre_entry);
+                continue;
+            }
+
+            let mut post_entry = PrestateAccount::default();
+            let mut modified = false;
+            if account.info.balance != pre_info.balance {
+                modified = true;
+                post_entry.balance = Some(account.info.balance);
+            }
+            if account.info.nonce != pre_info.nonce {
+                modified = true;
+                post_entry.nonce = Some(account.info.nonce);
+            }
+            let post_code = account
+                .info
+                .code
+                .as_ref()
+                .map(|code| code.original_bytes())
+                .unwrap_or_default();
+            if post_code != pre_code {
+                modified = true;
+                post_entry.code = self.code(post_code).filter(|code| !code.is_empty());
+            }
+            if !self.config.disable_storage {
+                for (key, slot) in &account.storage {
+                    if slot.original_value == slot.present_value {
+                        continue;
+                    }
+                    modified = true;
+                    let key = B256::from(*key);
+                    if !slot.original_value.is_zero() {
+                        pre_entry
+                            .storage
+                            .insert(key, B256::from(slot.original_value));
+                    }
+                    if !slot.present_value.is_zero() {
+                        post_entry
+                            .storage
+                            .insert(key, B256::from(slot.present_value));
+                    }
+                }
+            }
+
+            // Created contracts had no prestate.
+            let created = pre.is_none() && account.is_created();
+            if modified {
+                if !created {
+                    diff.pre.insert(*address, pre_entry);
+                }
+                diff.post.insert(*address, post_entry);
+            }
+        }
+        diff
+    }
+
+    /// Returns the code unless it is disabled by the config.
+    fn code(&self, code: Bytes) -> Option<Bytes> {
+        (!self.config.disable_code).then_some(code)
+    }
+
+    /// Records the database value of an account if it is not recorded yet.
+    fn record<DB: Database>(&mut self, db: &mut DB, address: Address) {
+        if self.pre.contains_key(&address) {
+            return;
+        }
+        // Database errors are surfaced by the execution, skip the account here.
+        let Ok(info) = db.basic(address) else {
+            return;
+        };
+        let pre = match info {
+            Some(info) => {
+                let code = match &info.code {
+                    Some(code) => code.original_bytes(),
+                    None if info.code_hash != KECCAK_EMPTY => db
+                        .code_by_hash(info.code_hash)
+                        .map(|code| code.original_bytes())
+                        .unwrap_or_default(),
+                    None => Bytes::new(),
+                };
+                Some((info, code))
+            }
+            None => None,
+        };
+        self.pre.insert(address, pre);
+    }
+
+    /// Snapshots all accounts loaded by the journal when the top-level frame ends.
+    fn frame_end<CTX>(&mut self, context: &mut CTX)
+    where
+        CTX: JournalGetter + JournalExtGetter + DatabaseGetter + BlockGetter,
+    {
+        if context.journal().depth() != 0 {
+            return;
+        }
+        let addresses: Vec<Address> = context
+            .journal_ext()
+            .evm_state()
+            .keys()
+            .copied()
+            .chain([context.block().beneficiary()])
+            .collect();
+        let db = context.db();
+        for address in addresses {
+            self.record(db, address);
+        }
+    }
+}
+
+impl<CTX, INTR> Inspector<CTX, INTR> for PrestateTracer
+where
+    CTX: JournalGetter + JournalExtGetter + DatabaseGetter + BlockGetter,
+    INTR: InterpreterTypes,
+{
+    fn call(&mut self, context: &mut CTX, _inputs: &mut CallInputs) -> Option<CallOutcome> {
+        if context.journal().depth() == 0 {
+            self.clear();
+        }
+        None
+    }
+
+    fn call_end(&mut self, context: &mut CTX, _inputs: &CallInputs, _outcome: &mut CallOutcome) {
+        self.frame_end(context);
+    }
+
+    fn create(&mut self, context: &mut CTX, _inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        if context.journal().depth() == 0 {
+            self.clear();
+        }
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut CTX,
+        _inputs: &CreateInputs,
+        _outcome: &mut CreateOutcome,
+    ) {
+        self.frame_end(context);
+    }
+
+    fn eofcreate(
+        &mut self,
+        context: &mut CTX,
+        _inputs: &mut EOFCreateInputs,
+    ) -> Option<CreateOutcome> {
+        if context.journal().depth() == 0 {
+            self.clear();
+        }
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        context: &mut CTX,
+        _inputs: &EOFCreateInputs,
+        _outcome: &mut CreateOutcome,
+    ) {
+        self.frame_end(context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{inspector_context::InspectorContext, inspector_handler, InspectorMainEvm};
+    use database::BenchmarkDB;
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        primitives::TxKind,
+        Context, EvmExec,
+    };
+
+    fn trace(config: PrestateTracerConfig) -> PrestateFrame {
+        // SSTORE(0, 1)
+        let code = vec![
+            opcode::PUSH1,
+            0x01,
+            opcode::PUSH1,
+            0x00,
+            opcode::SSTORE,
+            opcode::STOP,
+        ];
+        let ctx = Context::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code.into())))
+            .modify_tx_chained(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.kind = TxKind::Call(Address::ZERO);
+                tx.gas_limit = 100_000;
+            });
+        let mut evm = InspectorMainEvm::new(
+            InspectorContext::new(ctx, PrestateTracer::new(config)),
+            inspector_handler(),
+        );
+        let output = evm.exec().unwrap();
+        evm.context.inspector.geth_trace(&output.state)
+    }
+
+    #[test]
+    fn prestate() {
+        let PrestateFrame::Default(accounts) = trace(PrestateTracerConfig::default()) else {
+            panic!("default mode expected");
+        };
+        // Caller, contract and the zero beneficiary that is the contract itself.
+        assert_eq!(accounts.len(), 2);
+
+        let contract = &accounts[&Address::ZERO];
+        assert_eq!(contract.nonce, Some(1));
+        assert_eq!(contract.code.as_ref().map(|code| code.len()), Some(6));
+        assert_eq!(contract.storage[&B256::ZERO], B256::ZERO);
+
+        let caller = &accounts[&Address::with_last_byte(1)];
+        assert_eq!(caller.nonce, None);
+        assert_eq!(caller.balance, Some(U256::from(10000000)));
+    }
+
+    #[test]
+    fn diff_mode() {
+        let PrestateFrame::Diff(diff) = trace(PrestateTracerConfig::default().diff_mode(true))
+        else {
+            panic!("diff mode expected");
+        };
+
+        // Zero slot is not included in the prestate.
+        assert!(diff.pre[&Address::ZERO].storage.is_empty());
+        assert_eq!(
+            diff.post[&Address::ZERO].storage[&B256::ZERO],
+            B256::from(U256::from(1))
+        );
+        assert_eq!(diff.post[&Address::ZERO].balance, None);
+
+        // Caller nonce was bumped.
+        assert_eq!(diff.pre[&Address::with_last_byte(1)].nonce, None);
+        assert_eq!(diff.post[&Address::with_last_byte(1)].nonce, Some(1));
+    }
+}
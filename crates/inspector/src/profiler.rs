@@ -0,0 +1,427 @@
+//! Gas and time profiling [Inspector].
+use crate::Inspector;
+use revm::{
+    bytecode::opcode::OpCode,
+    interpreter::{
+        interpreter_types::{Jumps, LoopControl},
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter, InterpreterTypes,
+    },
+    primitives::{Address, HashMap},
+};
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+    string::String,
+    time::{Duration, Instant},
+    vec::Vec,
+};
+
+/// Measure of the folded stacks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfileMetric {
+    /// Gas spent.
+    Gas,
+    /// Wall-clock time in nanoseconds.
+    Time,
+}
+
+/// Gas and time spent by the executions of an opcode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OpcodeStats {
+    /// The opcode.
+    pub opcode: u8,
+    /// Number of executions.
+    pub count: u64,
+    /// Gas spent, excluding the gas spent by the called contracts.
+    pub gas: u64,
+    /// Wall-clock time spent in the instruction.
+    pub time: Duration,
+}
+
+impl OpcodeStats {
+    /// Returns the name of the opcode.
+    pub fn name(&self) -> String {
+        opcode_name(self.opcode)
+    }
+
+    fn add(&mut self, gas: u64, time: Duration) {
+        self.count += 1;
+        self.gas += gas;
+        self.time += time;
+    }
+}
+
+/// Gas and time spent by the code of a contract.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ContractStats {
+    /// Address of the executed code.
+    pub address: Address,
+    /// Number of executed instructions.
+    pub instructions: u64,
+    /// Gas spent by the code itself, the calls it makes are attributed to the called code.
+    pub gas: u64,
+    /// Wall-clock time spent in the instructions.
+    pub time: Duration,
+}
+
+/// Frame of the profiled call stack.
+#[derive(Clone, Copy, Debug)]
+struct Frame {
+    /// Interned call stack of the frame.
+    stack: usize,
+    address: Address,
+    steps: u64,
+}
+
+/// [Inspector] attributing gas and wall-clock time to opcodes, contracts and call stacks.
+///
+/// Gas of the calls is attributed to the called code, the calling instruction is charged
+/// only with its own cost. Time is measured between [Inspector::step] and
+/// [Inspector::step_end], so it includes the overhead of the inspector itself.
+///
+/// The stacks can be exported in the folded format of `inferno` and `flamegraph.pl`.
+#[derive(Clone, Debug, Default)]
+pub struct Profiler {
+    labels: HashMap<Address, String>,
+    /// Interned call stacks, `;`-separated frame labels.
+    stacks: Vec<String>,
+    stack_ids: HashMap<(Option<usize>, Address), usize>,
+    frames: Vec<Frame>,
+    opcodes: Vec<OpcodeStats>,
+    contracts: HashMap<Address, ContractStats>,
+    /// Stats by the call stack and opcode, `None` for the code of precompiles.
+    folded: HashMap<(usize, Option<u8>), OpcodeStats>,
+    /// Gas before the current instruction, its start time and opcode.
+    current: Option<(u64, Instant, u8)>,
+    /// Key of the last charged instruction, the gas passed to the calls is deducted from it.
+    last: Option<(usize, u8)>,
+}
+
+impl Profiler {
+    /// Creates the profiler.
+    pub fn new() -> Self {
+        Self {
+            opcodes: (0..=u8::MAX)
+                .map(|opcode| OpcodeStats {
+                    opcode,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Names the contract in the call stacks instead of its address.
+    pub fn with_label(mut self, address: Address, label: impl Into<String>) -> Self {
+        self.labels.insert(address, label.into());
+        self
+    }
+
+    /// Returns the stats of the executed opcodes, sorted by the spent gas.
+    pub fn opcodes(&self) -> Vec<OpcodeStats> {
+        let mut opcodes = self
+            .opcodes
+            .iter()
+            .filter(|stats| stats.count > 0)
+            .copied()
+            .collect::<Vec<_>>();
+        opcodes.sort_by(|a, b| b.gas.cmp(&a.gas).then(b.time.cmp(&a.time)));
+        opcodes
+    }
+
+    /// Returns the stats of the executed contracts, sorted by the spent gas.
+    pub fn contracts(&self) -> Vec<ContractStats> {
+        let mut contracts = self.contracts.values().cloned().collect::<Vec<_>>();
+        contracts.sort_by(|a, b| b.gas.cmp(&a.gas).then(a.address.cmp(&b.address)));
+        contracts
+    }
+
+    /// Returns the per-opcode histogram as a text table.
+    pub fn histogram(&self) -> String {
+        let opcodes = self.opcodes();
+        let total_gas = opcodes.iter().map(|stats| stats.gas).sum::<u64>().max(1);
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:<16} {:>12} {:>14} {:>7} {:>14}",
+            "opcode", "count", "gas", "gas %", "time"
+        );
+        for stats in opcodes {
+            let _ = writeln!(
+                out,
+                "{:<16} {:>12} {:>14} {:>6.2}% {:>14?}",
+                stats.name(),
+                stats.count,
+                stats.gas,
+                stats.gas as f64 * 100.0 / total_gas as f64,
+                stats.time
+            );
+        }
+        out
+    }
+
+    /// Writes the call stacks in the folded format, one `stack;OPCODE value` line each.
+    pub fn write_folded(&self, metric: ProfileMetric, mut out: impl Write) -> io::Result<()> {
+        let mut lines = self
+            .folded
+            .iter()
+            .map(|(&(stack, opcode), stats)| {
+                let value = match metric {
+                    ProfileMetric::Gas => stats.gas,
+                    ProfileMetric::Time => stats.time.as_nanos() as u64,
+                };
+                let name = opcode.map_or_else(|| "PRECOMPILE".into(), opcode_name);
+                (format!("{};{name}", self.stacks[stack]), value)
+            })
+            .filter(|(_, value)| *value > 0)
+            .collect::<Vec<_>>();
+        lines.sort_unstable();
+        for (stack, value) in lines {
+            writeln!(out, "{stack} {value}")?;
+        }
+        Ok(())
+    }
+
+    /// Returns the call stacks in the folded format.
+    pub fn folded(&self, metric: ProfileMetric) -> String {
+        let mut out = Vec::new();
+        self.write_folded(metric, &mut out)
+            .expect("writing to a vector never fails");
+        String::from_utf8(out).expect("labels are valid UTF-8")
+    }
+
+    /// Pushes the frame of the new call, `forwarded` is the gas passed to it by the caller.
+    fn frame_start(&mut self, address: Address, forwarded: u64) {
+        // The calling instruction was charged with the gas passed to the call.
+        if let Some((stack, opcode)) = self.last.take() {
+            self.opcodes[opcode as usize].gas =
+                self.opcodes[opcode as usize].gas.saturating_sub(forwarded);
+            if let Some(stats) = self.folded.get_mut(&(stack, Some(opcode))) {
+                stats.gas = stats.gas.saturating_sub(forwarded);
+            }
+            let caller = self
+                .frames
+                .last()
+                .map_or(Address::ZERO, |frame| frame.address);
+            if let Some(stats) = self.contracts.get_mut(&caller) {
+                stats.gas = stats.gas.saturating_sub(forwarded);
+            }
+        }
+        let parent = self.frames.last().map(|frame| frame.stack);
+        let stack = match self.stack_ids.get(&(parent, address)) {
+            Some(stack) => *stack,
+            None => {
+                let label = self
+                    .labels
+                    .get(&address)
+                    .cloned()
+                    .unwrap_or_else(|| address.to_string());
+                let path = match parent {
+                    Some(parent) => format!("{};{label}", self.stacks[parent]),
+                    None => label,
+                };
+                self.stacks.push(path);
+                self.stack_ids
+                    .insert((parent, address), self.stacks.len() - 1);
+                self.stacks.len() - 1
+            }
+        };
+        self.frames.push(Frame {
+            stack,
+            address,
+            steps: 0,
+        });
+    }
+
+    /// Attributes the gas spent by the precompiles to their own frames.
+    fn frame_end(&mut self, spent: u64) {
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+        if frame.steps == 0 && spent > 0 {
+            self.folded
+                .entry((frame.stack, None))
+                .or_default()
+                .add(spent, Duration::ZERO);
+            let contract = self.contracts.entry(frame.address).or_default();
+            contract.address = frame.address;
+            contract.gas += spent;
+        }
+        self.last = None;
+    }
+}
+
+impl<CTX, INTR: InterpreterTypes> Inspector<CTX, INTR> for Profiler {
+    fn step(&mut self, interp: &mut Interpreter<INTR>, _: &mut CTX) {
+        self.current = Some((
+            interp.control.gas().remaining(),
+            Instant::now(),
+            interp.bytecode.opcode(),
+        ));
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter<INTR>, _: &mut CTX) {
+        let Some((gas, started, opcode)) = self.current.take() else {
+            return;
+        };
+        let time = started.elapsed();
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        let gas = gas.saturating_sub(interp.control.gas().remaining());
+        frame.steps += 1;
+        self.opcodes[opcode as usize].add(gas, time);
+        self.folded
+            .entry((frame.stack, Some(opcode)))
+            .or_default()
+            .add(gas, time);
+        let contract = self.contracts.entry(frame.address).or_default();
+        contract.address = frame.address;
+        contract.instructions += 1;
+        contract.gas += gas;
+        contract.time += time;
+        self.last = Some((frame.stack, opcode));
+    }
+
+    fn call(&mut self, _: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.frame_start(inputs.bytecode_address, inputs.forwarded_gas());
+        None
+    }
+
+    fn call_end(&mut self, _: &mut CTX, _: &CallInputs, outcome: &mut CallOutcome) {
+        self.frame_end(outcome.result.gas.spent());
+    }
+
+    fn create(&mut self, _: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        // Init code runs before the address is known, it is labelled by the creator.
+        self.frame_start(inputs.caller, inputs.gas_limit);
+        None
+    }
+
+    fn create_end(&mut self, _: &mut CTX, _: &CreateInputs, outcome: &mut CreateOutcome) {
+        self.frame_end(outcome.result.gas.spent());
+    }
+}
+
+fn opcode_name(opcode: u8) -> String {
+    OpCode::new(opcode).map_or_else(|| format!("0x{opcode:02X}"), |op| op.as_str().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{inspector_context::InspectorContext, inspector_handler, InspectorMainEvm};
+    use database::BenchmarkDB;
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        primitives::{address, TxKind},
+        Context, EvmExec,
+    };
+
+    #[test]
+    fn gas_attribution() {
+        // Calls the identity precompile with 32 bytes of input and stops.
+        let code = vec![
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            32,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            4,
+            opcode::GAS,
+            opcode::CALL,
+            opcode::STOP,
+        ];
+        let ctx = Context::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code.into())))
+            .modify_tx_chained(|tx| {
+                tx.caller = address!("0000000000000000000000000000000000000001");
+                tx.kind = TxKind::Call(Address::ZERO);
+            });
+        let profiler = Profiler::new().with_label(Address::ZERO, "main");
+        let mut evm =
+            InspectorMainEvm::new(InspectorContext::new(ctx, profiler), inspector_handler());
+        let gas_used = evm.exec().unwrap().result.gas_used();
+        let profiler = &evm.context.inspector;
+
+        let opcodes = profiler.opcodes();
+        let push1 = opcodes
+            .iter()
+            .find(|stats| stats.opcode == opcode::PUSH1)
+            .unwrap();
+        assert_eq!((push1.count, push1.gas), (6, 18));
+        // Warm precompile access and memory expansion, without the gas passed to the precompile.
+        let call = opcodes
+            .iter()
+            .find(|stats| stats.opcode == opcode::CALL)
+            .unwrap();
+        assert_eq!((call.count, call.gas), (1, 103));
+
+        let identity = address!("0000000000000000000000000000000000000004");
+        let contracts = profiler.contracts();
+        assert_eq!(contracts[0].address, Address::ZERO);
+        assert_eq!(contracts[0].instructions, 9);
+        assert_eq!(contracts[1].address, identity);
+        assert_eq!((contracts[1].instructions, contracts[1].gas), (0, 18));
+
+        let folded = profiler.folded(ProfileMetric::Gas);
+        assert!(folded.contains(&format!("main;{identity};PRECOMPILE 18\n")));
+        assert!(folded.contains("main;CALL 103\n"));
+        let total = folded
+            .lines()
+            .map(|line| line.rsplit_once(' ').unwrap().1.parse::<u64>().unwrap())
+            .sum::<u64>();
+        assert_eq!(total, gas_used - 21_000);
+        assert!(profiler
+            .histogram()
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("CALL"));
+    }
+
+    #[test]
+    fn value_call_stipend() {
+        // Sends 1 wei to an empty account without passing gas, the callee gets the stipend.
+        let mut code = vec![
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH1,
+            1,
+            opcode::PUSH20,
+        ];
+        code.extend_from_slice(Address::with_last_byte(0xaa).as_slice());
+        code.extend_from_slice(&[opcode::PUSH0, opcode::CALL, opcode::STOP]);
+        let ctx = Context::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code.into())))
+            .modify_tx_chained(|tx| {
+                tx.caller = address!("0000000000000000000000000000000000000001");
+                tx.kind = TxKind::Call(Address::ZERO);
+            });
+        let mut evm = InspectorMainEvm::new(
+            InspectorContext::new(ctx, Profiler::new()),
+            inspector_handler(),
+        );
+        let gas_used = evm.exec().unwrap().result.gas_used();
+        let profiler = &evm.context.inspector;
+
+        // Cold account access, value transfer and new account, the stipend is not paid by the
+        // caller.
+        let call = profiler
+            .opcodes()
+            .into_iter()
+            .find(|stats| stats.opcode == opcode::CALL)
+            .unwrap();
+        assert_eq!(call.gas, 2_600 + 9_000 + 25_000);
+        // The unused stipend is returned to the caller on top of the gas it paid.
+        assert_eq!(profiler.contracts()[0].gas, gas_used - 21_000 + 2_300);
+    }
+}
@@ -0,0 +1,748 @@
+//! Source-map aware [Inspector] for the Solidity compiler artifacts.
+use crate::Inspector;
+use core::fmt;
+use revm::{
+    bytecode::{opcode, Bytecode, JumpTable},
+    interpreter::{
+        interpreter::EthInterpreter,
+        interpreter_types::{InputsTrait, Jumps, LoopControl},
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter,
+    },
+    primitives::{hex, Address, HashMap, B256},
+};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Error of loading the compiler artifacts.
+#[derive(Debug)]
+pub enum SourceMapError {
+    /// The artifact is not a valid JSON.
+    Json(serde_json::Error),
+    /// The field is missing or has an unexpected type.
+    MissingField(&'static str),
+    /// The bytecode is not a valid hex string, unlinked libraries are not supported.
+    InvalidBytecode,
+    /// The source map entry is malformed.
+    InvalidSourceMap(String),
+}
+
+impl fmt::Display for SourceMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "invalid artifact: {e}"),
+            Self::MissingField(field) => write!(f, "missing artifact field `{field}`"),
+            Self::InvalidBytecode => f.write_str("invalid or unlinked bytecode"),
+            Self::InvalidSourceMap(entry) => write!(f, "invalid source map entry `{entry}`"),
+        }
+    }
+}
+
+impl core::error::Error for SourceMapError {}
+
+impl From<serde_json::Error> for SourceMapError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+/// Location in the source code of the executed instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// Path of the source file.
+    pub path: String,
+    /// Byte offset of the source range.
+    pub offset: usize,
+    /// Length of the source range.
+    pub length: usize,
+    /// 1-based line of the range start, `None` if the source content is not known.
+    pub line: Option<usize>,
+    /// Innermost function or modifier containing the range, as `Contract.function`.
+    pub function: Option<String>,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)?;
+        match self.line {
+            Some(line) => write!(f, ":{line}")?,
+            None => write!(f, "@{}", self.offset)?,
+        }
+        if let Some(function) = &self.function {
+            write!(f, " in {function}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Frame of the revert stack trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceFrame {
+    /// Address of the executed contract.
+    pub address: Address,
+    /// Name of the matched contract artifact.
+    pub contract: Option<String>,
+    /// Location of the last executed instruction.
+    pub location: Option<SourceLocation>,
+}
+
+impl fmt::Display for TraceFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at {}", self.contract.as_deref().unwrap_or("<unknown>"))?;
+        write!(f, " ({})", self.address)?;
+        if let Some(location) = &self.location {
+            write!(f, " {location}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Gas spent by the instructions of a source line.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineGas {
+    /// Path of the source file.
+    pub path: String,
+    /// 1-based line.
+    pub line: usize,
+    /// Gas charged by the instructions of the line, without the gas they passed to calls.
+    pub gas: u64,
+    /// Number of executed instructions.
+    pub instructions: u64,
+}
+
+/// Element of the decompressed source map.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct SourceElement {
+    offset: usize,
+    length: usize,
+    /// Index of the source file, `-1` for the compiler generated code.
+    file: i64,
+}
+
+/// Deployed bytecode of the contract with its source map.
+#[derive(Clone, Debug)]
+struct Contract {
+    name: String,
+    /// Bytecode without the metadata trailer, immutables are zeroed.
+    code: Vec<u8>,
+    /// Byte ranges of the immutables filled in at deployment.
+    immutables: Vec<(usize, usize)>,
+    /// Index of the instruction starting at the pc, `None` for the immediates.
+    instructions: Vec<Option<usize>>,
+    elements: Vec<SourceElement>,
+}
+
+impl Contract {
+    /// Returns `true` if the runtime bytecode is the deployment of this contract.
+    ///
+    /// The metadata trailer is ignored and the immutables may have any value. The jump
+    /// destinations of the contract have to be valid in the jump table of the runtime code.
+    fn matches(&self, code: &[u8], jump_table: &JumpTable) -> bool {
+        let code = strip_metadata(code);
+        if code.len() != self.code.len() {
+            return false;
+        }
+        let jumpdests_match = self
+            .instructions
+            .iter()
+            .enumerate()
+            .filter(|(pc, instruction)| instruction.is_some() && self.code[*pc] == opcode::JUMPDEST)
+            .all(|(pc, _)| jump_table.is_valid(pc));
+        if !jumpdests_match {
+            return false;
+        }
+        let mut start = 0;
+        for &(offset, length) in &self.immutables {
+            if code[start..offset] != self.code[start..offset] {
+                return false;
+            }
+            start = offset + length;
+        }
+        code[start..] == self.code[start..]
+    }
+}
+
+/// Function or modifier definition from the AST.
+#[derive(Clone, Debug)]
+struct Function {
+    file: i64,
+    start: usize,
+    end: usize,
+    name: String,
+}
+
+/// Source file with the offsets of its lines.
+#[derive(Clone, Debug, Default)]
+struct Source {
+    path: String,
+    line_starts: Option<Vec<usize>>,
+}
+
+/// Source maps of the loaded compiler artifacts.
+#[derive(Clone, Debug, Default)]
+pub struct SourceMaps {
+    contracts: Vec<Contract>,
+    sources: HashMap<i64, Source>,
+    functions: Vec<Function>,
+}
+
+impl SourceMaps {
+    /// Creates empty source maps.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the foundry artifact of the contract, `out/<File>.sol/<Contract>.json`.
+    ///
+    /// Foundry artifacts contain the AST of the source file, the source content has to be
+    /// added with [Self::add_source] to resolve the lines.
+    pub fn add_foundry_artifact(&mut self, name: &str, json: &str) -> Result<(), SourceMapError> {
+        let artifact: Value = serde_json::from_str(json)?;
+        let deployed = artifact
+            .get("deployedBytecode")
+            .ok_or(SourceMapError::MissingField("deployedBytecode"))?;
+        self.add_contract(name, deployed)?;
+        if let (Some(id), Some(ast)) = (artifact["id"].as_i64(), artifact.get("ast")) {
+            self.add_ast(id, ast);
+        }
+        Ok(())
+    }
+
+    /// Loads the contracts and ASTs of the solc standard JSON output.
+    ///
+    /// Source contents are part of the standard JSON input, they have to be added with
+    /// [Self::add_source] to resolve the lines.
+    pub fn add_solc_output(&mut self, json: &str) -> Result<(), SourceMapError> {
+        let output: Value = serde_json::from_str(json)?;
+        if let Some(sources) = output["sources"].as_object() {
+            for source in sources.values() {
+                if let (Some(id), Some(ast)) = (source["id"].as_i64(), source.get("ast")) {
+                    self.add_ast(id, ast);
+                }
+            }
+        }
+        let files = output["contracts"]
+            .as_object()
+            .ok_or(SourceMapError::MissingField("contracts"))?;
+        for contracts in files.values() {
+            for (name, contract) in contracts.as_object().into_iter().flatten() {
+                let deployed = &contract["evm"]["deployedBytecode"];
+                if deployed.is_object() {
+                    self.add_contract(name, deployed)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the content of the source file with the id from the compiler output.
+    pub fn add_source(&mut self, id: i64, path: &str, content: &str) {
+        let line_starts = core::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        self.sources.insert(
+            id,
+            Source {
+                path: path.to_string(),
+                line_starts: Some(line_starts),
+            },
+        );
+    }
+
+    /// Adds the deployed bytecode object with its source map and immutable references.
+    fn add_contract(&mut self, name: &str, deployed: &Value) -> Result<(), SourceMapError> {
+        let object = deployed["object"]
+            .as_str()
+            .ok_or(SourceMapError::MissingField("deployedBytecode.object"))?;
+        let source_map = deployed["sourceMap"]
+            .as_str()
+            .ok_or(SourceMapError::MissingField("deployedBytecode.sourceMap"))?;
+        let code = hex::decode(object).map_err(|_| SourceMapError::InvalidBytecode)?;
+        let mut code = strip_metadata(&code).to_vec();
+
+        let mut immutables = deployed["immutableReferences"]
+            .as_object()
+            .into_iter()
+            .flat_map(|references| references.values())
+            .flat_map(|ranges| ranges.as_array().into_iter().flatten())
+            .filter_map(|range| {
+                let start = range["start"].as_u64()? as usize;
+                let length = range["length"].as_u64()? as usize;
+                (start + length <= code.len()).then_some((start, length))
+            })
+            .collect::<Vec<_>>();
+        immutables.sort_unstable();
+        for &(start, length) in &immutables {
+            code[start..start + length].fill(0);
+        }
+
+        // Source map elements are indexed by the instructions, not by the pc.
+        let mut instructions = vec![None; code.len()];
+        let mut instruction = 0;
+        let mut pc = 0;
+        while pc < code.len() {
+            instructions[pc] = Some(instruction);
+            instruction += 1;
+            pc += 1 + push_size(code[pc]);
+        }
+
+        self.contracts.push(Contract {
+            name: name.to_string(),
+            code,
+            immutables,
+            instructions,
+            elements: decompress_source_map(source_map)?,
+        });
+        Ok(())
+    }
+
+    /// Collects the functions and modifiers of the AST and the path of the source unit.
+    fn add_ast(&mut self, id: i64, ast: &Value) {
+        if let Some(path) = ast["absolutePath"].as_str() {
+            self.sources.entry(id).or_default().path = path.to_string();
+        }
+        let mut stack = vec![(ast, None::<&str>)];
+        while let Some((node, contract)) = stack.pop() {
+            let contract = match node["nodeType"].as_str() {
+                Some("ContractDefinition") => node["name"].as_str(),
+                Some("FunctionDefinition" | "ModifierDefinition") => {
+                    if let Some((start, length, file)) = node["src"].as_str().and_then(parse_src) {
+                        let name = match node["name"].as_str() {
+                            Some(name) if !name.is_empty() => name,
+                            _ => node["kind"].as_str().unwrap_or("function"),
+                        };
+                        self.functions.push(Function {
+                            file,
+                            start,
+                            end: start + length,
+                            name: match contract {
+                                Some(contract) => format!("{contract}.{name}"),
+                                None => name.to_string(),
+                            },
+                        });
+                    }
+                    contract
+                }
+                _ => contract,
+            };
+            let children = node.as_object().into_iter().flat_map(|node| node.values());
+            for child in children {
+                match child {
+                    Value::Array(items) => stack.extend(items.iter().map(|item| (item, contract))),
+                    Value::Object(_) => stack.push((child, contract)),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Returns the number of the loaded contracts.
+    pub(crate) fn contract_count(&self) -> usize {
+        self.contracts.len()
+    }
+
+    /// Returns the pc and opcode of every instruction of the contract.
+    pub(crate) fn instructions(&self, contract: usize) -> impl Iterator<Item = (usize, u8)> + '_ {
+        let contract = &self.contracts[contract];
+        contract
+            .instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, instruction)| instruction.is_some())
+            .map(|(pc, _)| (pc, contract.code[pc]))
+    }
+
+    /// Returns the index of the contract deployed as the bytecode.
+    pub(crate) fn find_contract(&self, bytecode: &Bytecode) -> Option<usize> {
+        let Bytecode::LegacyAnalyzed(analyzed) = bytecode else {
+            return None;
+        };
+        let code = analyzed.original_byte_slice();
+        self.contracts
+            .iter()
+            .position(|contract| contract.matches(code, analyzed.jump_table()))
+    }
+
+    /// Returns the source location of the instruction at the pc of the contract.
+    pub(crate) fn locate(&self, contract: usize, pc: usize) -> Option<SourceLocation> {
+        let contract = &self.contracts[contract];
+        let element = *contract.elements.get((*contract.instructions.get(pc)?)?)?;
+        let source = self.sources.get(&element.file)?;
+        let line = source
+            .line_starts
+            .as_ref()
+            .map(|line_starts| line_starts.partition_point(|&start| start <= element.offset));
+        let end = element.offset + element.length;
+        let function = self
+            .functions
+            .iter()
+            .filter(|function| {
+                function.file == element.file
+                    && function.start <= element.offset
+                    && end <= function.end
+            })
+            .min_by_key(|function| function.end - function.start)
+            .map(|function| function.name.clone());
+        Some(SourceLocation {
+            path: source.path.clone(),
+            offset: element.offset,
+            length: element.length,
+            line,
+            function,
+        })
+    }
+}
+
+/// Call frame tracked by the [SourceMapTracer].
+#[derive(Clone, Debug, Default)]
+struct Frame {
+    address: Address,
+    contract: Option<usize>,
+    location: Option<SourceLocation>,
+    /// Gas before the executed instruction.
+    gas: u64,
+    /// Line charged with the gas of the last instruction.
+    charged: Option<(String, usize)>,
+}
+
+/// [Inspector] mapping the executed instructions to the Solidity sources.
+///
+/// Produces the stack trace of the failed transaction and the gas profile of the source
+/// lines. Contracts are matched by their deployed bytecode, see [SourceMaps].
+#[derive(Clone, Debug, Default)]
+pub struct SourceMapTracer {
+    maps: SourceMaps,
+    /// Matched contract by the bytecode hash.
+    matches: HashMap<B256, Option<usize>>,
+    frames: Vec<Frame>,
+    revert_trace: Vec<TraceFrame>,
+    gas_profile: BTreeMap<(String, usize), (u64, u64)>,
+}
+
+impl SourceMapTracer {
+    /// Creates the tracer with the loaded source maps.
+    pub fn new(maps: SourceMaps) -> Self {
+        Self {
+            maps,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the stack trace of the failed call, innermost frame first.
+    ///
+    /// Empty if the transaction succeeded or the failure was handled by a caller.
+    pub fn revert_trace(&self) -> &[TraceFrame] {
+        &self.revert_trace
+    }
+
+    /// Returns the gas spent by every executed source line, sorted by the path and line.
+    pub fn gas_profile(&self) -> Vec<LineGas> {
+        self.gas_profile
+            .iter()
+            .map(|((path, line), (gas, instructions))| LineGas {
+                path: path.clone(),
+                line: *line,
+                gas: *gas,
+                instructions: *instructions,
+            })
+            .collect()
+    }
+
+    /// Clears the results of the previous transaction.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.revert_trace.clear();
+        self.gas_profile.clear();
+    }
+
+    fn frame_end(&mut self, success: bool) {
+        let depth = self.frames.len();
+        if success {
+            // The failure of the inner call was handled.
+            if self.revert_trace.len() >= depth {
+                self.revert_trace.clear();
+            }
+        } else if self.revert_trace.len() < depth {
+            // Record the innermost failure, the callers revert with it.
+            self.revert_trace = self
+                .frames
+                .iter()
+                .rev()
+                .map(|frame| TraceFrame {
+                    address: frame.address,
+                    contract: frame
+                        .contract
+                        .map(|contract| self.maps.contracts[contract].name.clone()),
+                    location: frame.location.clone(),
+                })
+                .collect();
+        }
+        self.frames.pop();
+    }
+
+    /// Deducts the gas passed to the new call from the instruction creating it.
+    fn frame_start(&mut self, forwarded: u64) {
+        if let Some((path, line)) = self.frames.last().and_then(|frame| frame.charged.clone()) {
+            if let Some((gas, _)) = self.gas_profile.get_mut(&(path, line)) {
+                *gas = gas.saturating_sub(forwarded);
+            }
+        }
+        if self.frames.is_empty() {
+            self.clear();
+        }
+        self.frames.push(Frame::default());
+    }
+}
+
+impl<CTX> Inspector<CTX, EthInterpreter> for SourceMapTracer {
+    fn initialize_interp(&mut self, interp: &mut Interpreter<EthInterpreter>, _: &mut CTX) {
+        let hash = match interp.bytecode.hash() {
+            Some(hash) => hash,
+            None => interp.bytecode.hash_slow(),
+        };
+        let maps = &self.maps;
+        let contract = *self
+            .matches
+            .entry(hash)
+            .or_insert_with(|| maps.find_contract(&interp.bytecode));
+        if let Some(frame) = self.frames.last_mut() {
+            frame.address = interp.input.target_address();
+            frame.contract = contract;
+        }
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _: &mut CTX) {
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        frame.gas = interp.control.gas().remaining();
+        frame.location = frame
+            .contract
+            .and_then(|contract| self.maps.locate(contract, interp.bytecode.pc()));
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter<EthInterpreter>, _: &mut CTX) {
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        frame.charged = None;
+        let Some(SourceLocation {
+            path,
+            line: Some(line),
+            ..
+        }) = &frame.location
+        else {
+            return;
+        };
+        let cost = frame.gas.saturating_sub(interp.control.gas().remaining());
+        let entry = self.gas_profile.entry((path.clone(), *line)).or_default();
+        entry.0 += cost;
+        entry.1 += 1;
+        frame.charged = Some((path.clone(), *line));
+    }
+
+    fn call(&mut self, _: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.frame_start(inputs.forwarded_gas());
+        if let Some(frame) = self.frames.last_mut() {
+            frame.address = inputs.target_address;
+        }
+        None
+    }
+
+    fn call_end(&mut self, _: &mut CTX, _: &CallInputs, outcome: &mut CallOutcome) {
+        self.frame_end(outcome.result.is_ok());
+    }
+
+    fn create(&mut self, _: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.frame_start(inputs.gas_limit);
+        None
+    }
+
+    fn create_end(&mut self, _: &mut CTX, _: &CreateInputs, outcome: &mut CreateOutcome) {
+        self.frame_end(outcome.result.is_ok());
+    }
+}
+
+/// Returns the code without the CBOR-encoded metadata appended by solc.
+///
+/// The last two bytes are the big-endian length of the metadata, which is a CBOR map.
+fn strip_metadata(code: &[u8]) -> &[u8] {
+    let Some((rest, &[high, low])) = code.split_last_chunk::<2>() else {
+        return code;
+    };
+    let length = u16::from_be_bytes([high, low]) as usize;
+    match rest.len().checked_sub(length) {
+        // Major type 5 is a map.
+        Some(start) if length > 0 && rest[start] >> 5 == 5 => &rest[..start],
+        _ => code,
+    }
+}
+
+/// Returns the size of the immediate of the PUSH opcode.
+fn push_size(opcode: u8) -> usize {
+    if (opcode::PUSH1..=opcode::PUSH32).contains(&opcode) {
+        (opcode - opcode::PUSH1 + 1) as usize
+    } else {
+        0
+    }
+}
+
+/// Parses the `start:length:file` source range of the AST node.
+fn parse_src(src: &str) -> Option<(usize, usize, i64)> {
+    let mut parts = src.split(':');
+    let start = parts.next()?.parse().ok()?;
+    let length = parts.next()?.parse().ok()?;
+    let file = parts.next()?.parse().ok()?;
+    Some((start, length, file))
+}
+
+/// Decompresses the `s:l:f:j:m` source map, empty fields repeat the previous element.
+fn decompress_source_map(source_map: &str) -> Result<Vec<SourceElement>, SourceMapError> {
+    let mut elements = Vec::new();
+    let mut last = SourceElement::default();
+    for entry in source_map.split(';') {
+        let invalid = || SourceMapError::InvalidSourceMap(entry.to_string());
+        let mut fields = entry.split(':');
+        if let Some(offset) = fields.next().filter(|field| !field.is_empty()) {
+            last.offset = offset.parse().map_err(|_| invalid())?;
+        }
+        if let Some(length) = fields.next().filter(|field| !field.is_empty()) {
+            last.length = length.parse().map_err(|_| invalid())?;
+        }
+        if let Some(file) = fields.next().filter(|field| !field.is_empty()) {
+            last.file = file.parse().map_err(|_| invalid())?;
+        }
+        elements.push(last);
+    }
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{inspector_context::InspectorContext, inspector_handler, InspectorMainEvm};
+    use database::BenchmarkDB;
+    use revm::{primitives::TxKind, Context, EvmExec};
+
+    const SOURCE: &str = "contract C {\n    function f() public {\n        revert();\n    }\n}\n";
+
+    /// `PUSH32 <immutable> POP PUSH1 0 PUSH1 0 REVERT` with the metadata trailer.
+    fn code(immutable: u8, metadata: u8) -> Vec<u8> {
+        let mut code = vec![opcode::PUSH32];
+        code.extend([immutable; 32]);
+        code.extend([
+            opcode::POP,
+            opcode::PUSH1,
+            0,
+            opcode::PUSH1,
+            0,
+            opcode::REVERT,
+        ]);
+        code.extend([0xa1, 0x01, metadata, 0x00, 0x03]);
+        code
+    }
+
+    fn artifact() -> String {
+        serde_json::json!({
+            "id": 0,
+            "deployedBytecode": {
+                "object": hex::encode_prefixed(code(0, 0)),
+                "sourceMap": "17:45:0:-:0;;47:8;;",
+                "immutableReferences": { "5": [{ "start": 1, "length": 32 }] }
+            },
+            "ast": {
+                "nodeType": "SourceUnit",
+                "absolutePath": "src/C.sol",
+                "nodes": [{
+                    "nodeType": "ContractDefinition",
+                    "name": "C",
+                    "src": "0:63:0",
+                    "nodes": [{
+                        "nodeType": "FunctionDefinition",
+                        "name": "f",
+                        "kind": "function",
+                        "id": 4,
+                        "src": "17:45:0"
+                    }]
+                }]
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn revert_trace_and_gas_profile() {
+        let mut maps = SourceMaps::new();
+        maps.add_foundry_artifact("C", &artifact()).unwrap();
+        maps.add_source(0, "src/C.sol", SOURCE);
+
+        // Deployed with the immutable set and a different metadata hash.
+        let bytecode = Bytecode::new_raw(code(0x11, 0x22).into());
+        let ctx = Context::builder()
+            .with_db(BenchmarkDB::new_bytecode(bytecode))
+            .modify_tx_chained(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.kind = TxKind::Call(Address::ZERO);
+                tx.gas_limit = 100_000;
+            });
+        let mut evm = InspectorMainEvm::new(
+            InspectorContext::new(ctx, SourceMapTracer::new(maps)),
+            inspector_handler(),
+        );
+        assert!(!evm.exec().unwrap().result.is_success());
+        let tracer = &evm.context.inspector;
+
+        let [frame] = tracer.revert_trace() else {
+            panic!("single frame expected");
+        };
+        assert_eq!(frame.contract.as_deref(), Some("C"));
+        assert_eq!(
+            frame.to_string(),
+            "at C (0x0000000000000000000000000000000000000000) src/C.sol:3 in C.f"
+        );
+
+        let profile = tracer.gas_profile();
+        assert_eq!(profile.len(), 2);
+        assert_eq!(
+            (profile[0].line, profile[0].gas, profile[0].instructions),
+            (2, 5, 2)
+        );
+        assert_eq!(
+            (profile[1].line, profile[1].gas, profile[1].instructions),
+            (3, 6, 3)
+        );
+    }
+
+    #[test]
+    fn unmatched_code() {
+        let mut maps = SourceMaps::new();
+        maps.add_foundry_artifact("C", &artifact()).unwrap();
+        let mut other = code(0, 0);
+        other[33] = opcode::JUMPDEST;
+        let bytecode = Bytecode::new_raw(other.into());
+        assert_eq!(maps.find_contract(&bytecode), None);
+        assert_eq!(
+            maps.find_contract(&Bytecode::new_raw(code(7, 7).into())),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn source_map_compression() {
+        let elements = decompress_source_map("1:2:0:-:0;;3;:4:-1").unwrap();
+        assert_eq!(elements.len(), 4);
+        assert_eq!(elements[1], elements[0]);
+        assert_eq!((elements[2].offset, elements[2].length), (3, 2));
+        assert_eq!(
+            (elements[3].offset, elements[3].length, elements[3].file),
+            (3, 4, -1)
+        );
+        assert_eq!(strip_metadata(&[0x00, 0xa1, 0x00, 0x01]), &[0x00]);
+        assert_eq!(strip_metadata(&[0x00, 0x00, 0x01]), &[0x00, 0x00, 0x01]);
+    }
+}
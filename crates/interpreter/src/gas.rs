@@ -145,6 +145,13 @@ impl Gas {
         success
     }
 
+    /// Sets the cost of the memory, see [`memory_gas`].
+    #[inline]
+    pub fn set_memory_cost(&mut self, word_cost: u64, quadratic_divisor: u64) {
+        self.memory.word_cost = word_cost;
+        self.memory.quadratic_divisor = quadratic_divisor;
+    }
+
     /// Record memory expansion
     #[inline]
     #[must_use = "internally uses record_cost that flags out of gas error"]
@@ -174,13 +181,23 @@ pub enum MemoryExtensionResult {
 /// It contains the current memory length and its memory expansion cost.
 ///
 /// It allows us to split gas accounting from memory structure.
-#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MemoryGas {
     /// Current memory length
     pub words_num: usize,
     /// Current memory expansion cost
     pub expansion_cost: u64,
+    /// Linear cost of a word of memory
+    pub word_cost: u64,
+    /// Divisor of the quadratic cost of the memory
+    pub quadratic_divisor: u64,
+}
+
+impl Default for MemoryGas {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MemoryGas {
@@ -188,6 +205,8 @@ impl MemoryGas {
         Self {
             words_num: 0,
             expansion_cost: 0,
+            word_cost: MEMORY,
+            quadratic_divisor: 512,
         }
     }
 
@@ -197,7 +216,7 @@ impl MemoryGas {
             return None;
         }
         self.words_num = new_num;
-        let mut cost = crate::gas::calc::memory_gas(new_num);
+        let mut cost = memory_gas(new_num, self.word_cost, self.quadratic_divisor);
         core::mem::swap(&mut self.expansion_cost, &mut cost);
         // Safe to subtract because we know that new_len > length
         // Notice the swap above.
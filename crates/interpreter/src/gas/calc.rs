@@ -1,21 +1,16 @@
 use super::constants::*;
 use crate::{num_words, tri, SStoreResult, SelfDestructResult, StateLoad};
-use context_interface::journaled_state::AccountLoad;
+use context_interface::{journaled_state::AccountLoad, GasSchedule};
 use handler_interface::InitialAndFloorGas;
 use primitives::U256;
-use specification::{eip7702, hardfork::SpecId};
+use specification::hardfork::SpecId;
 
 /// `SSTORE` opcode refund calculation.
 #[allow(clippy::collapsible_else_if)]
 #[inline]
-pub fn sstore_refund(spec_id: SpecId, vals: &SStoreResult) -> i64 {
-    if spec_id.is_enabled_in(SpecId::ISTANBUL) {
-        // EIP-3529: Reduction in refunds
-        let sstore_clears_schedule = if spec_id.is_enabled_in(SpecId::LONDON) {
-            (SSTORE_RESET - COLD_SLOAD_COST + ACCESS_LIST_STORAGE_KEY) as i64
-        } else {
-            REFUND_SSTORE_CLEARS
-        };
+pub fn sstore_refund(schedule: &GasSchedule, vals: &SStoreResult) -> i64 {
+    let sstore_clears_schedule = schedule.sstore_clears_refund as i64;
+    if schedule.sstore_net_metering {
         if vals.is_new_eq_present() {
             0
         } else {
@@ -33,15 +28,11 @@ pub fn sstore_refund(spec_id: SpecId, vals: &SStoreResult) -> i64 {
                 }
 
                 if vals.is_original_eq_new() {
-                    let (gas_sstore_reset, gas_sload) = if spec_id.is_enabled_in(SpecId::BERLIN) {
-                        (SSTORE_RESET - COLD_SLOAD_COST, WARM_STORAGE_READ_COST)
-                    } else {
-                        (SSTORE_RESET, sload_cost(spec_id, false))
-                    };
+                    let gas_sload = warm_sload_cost(schedule) as i64;
                     if vals.is_original_zero() {
-                        refund += (SSTORE_SET - gas_sload) as i64;
+                        refund += schedule.sstore_set_cost as i64 - gas_sload;
                     } else {
-                        refund += (gas_sstore_reset - gas_sload) as i64;
+                        refund += schedule.sstore_reset_cost as i64 - gas_sload;
                     }
                 }
 
@@ -50,7 +41,7 @@ pub fn sstore_refund(spec_id: SpecId, vals: &SStoreResult) -> i64 {
         }
     } else {
         if !vals.is_present_zero() && vals.is_new_zero() {
-            REFUND_SSTORE_CLEARS
+            sstore_clears_schedule
         } else {
             0
         }
@@ -86,22 +77,13 @@ const fn log2floor(value: U256) -> u64 {
     l
 }
 
-/// `EXP` opcode cost calculation.
+/// `EXP` opcode cost calculation, without the static cost.
 #[inline]
-pub fn exp_cost(spec_id: SpecId, power: U256) -> Option<u64> {
+pub fn exp_cost(schedule: &GasSchedule, power: U256) -> Option<u64> {
     if power.is_zero() {
-        Some(EXP)
+        Some(0)
     } else {
-        // EIP-160: EXP cost increase
-        let gas_byte = U256::from(if spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON) {
-            50
-        } else {
-            10
-        });
-        let gas = U256::from(EXP)
-            .checked_add(gas_byte.checked_mul(U256::from(log2floor(power) / 8 + 1))?)?;
-
-        u64::try_from(gas).ok()
+        schedule.exp_byte_cost.checked_mul(log2floor(power) / 8 + 1)
     }
 }
 
@@ -111,17 +93,10 @@ pub const fn copy_cost_verylow(len: usize) -> Option<u64> {
     copy_cost(VERYLOW, len)
 }
 
-/// `EXTCODECOPY` opcode cost calculation.
+/// `EXTCODECOPY` opcode cost calculation, without the static cost.
 #[inline]
-pub const fn extcodecopy_cost(spec_id: SpecId, len: usize, is_cold: bool) -> Option<u64> {
-    let base_gas = if spec_id.is_enabled_in(SpecId::BERLIN) {
-        warm_cold_cost(is_cold)
-    } else if spec_id.is_enabled_in(SpecId::TANGERINE) {
-        700
-    } else {
-        20
-    };
-    copy_cost(base_gas, len)
+pub const fn extcodecopy_cost(schedule: &GasSchedule, len: usize, is_cold: bool) -> Option<u64> {
+    copy_cost(warm_cold_cost(schedule, is_cold), len)
 }
 
 #[inline]
@@ -149,86 +124,77 @@ pub const fn cost_per_word(len: usize, multiple: u64) -> Option<u64> {
 
 /// EIP-3860: Limit and meter initcode
 ///
-/// Apply extra gas cost of 2 for every 32-byte chunk of initcode.
-///
-/// This cannot overflow as the initcode length is assumed to be checked.
+/// Apply extra gas cost for every 32-byte chunk of initcode, saturating on overflow.
 #[inline]
-pub const fn initcode_cost(len: usize) -> u64 {
-    let Some(cost) = cost_per_word(len, INITCODE_WORD_COST) else {
-        panic!("initcode cost overflow")
-    };
-    cost
+pub const fn initcode_cost(schedule: &GasSchedule, len: usize) -> u64 {
+    match cost_per_word(len, schedule.initcode_word_cost) {
+        Some(cost) => cost,
+        None => u64::MAX,
+    }
 }
 
-/// `SLOAD` opcode cost calculation.
+/// `SLOAD` opcode cost calculation, without the static cost.
 #[inline]
-pub const fn sload_cost(spec_id: SpecId, is_cold: bool) -> u64 {
-    if spec_id.is_enabled_in(SpecId::BERLIN) {
-        if is_cold {
-            COLD_SLOAD_COST
-        } else {
-            WARM_STORAGE_READ_COST
-        }
-    } else if spec_id.is_enabled_in(SpecId::ISTANBUL) {
-        // EIP-1884: Repricing for trie-size-dependent opcodes
-        ISTANBUL_SLOAD_GAS
-    } else if spec_id.is_enabled_in(SpecId::TANGERINE) {
-        // EIP-150: Gas cost changes for IO-heavy operations
-        200
+pub const fn sload_cost(schedule: &GasSchedule, is_cold: bool) -> u64 {
+    if is_cold {
+        schedule.cold_sload_cost
     } else {
-        50
+        schedule.warm_storage_read_cost
     }
 }
 
-/// `SSTORE` opcode cost calculation.
+/// Cost of an `SLOAD` of a warm slot, including the static cost.
 #[inline]
-pub fn sstore_cost(spec_id: SpecId, vals: &SStoreResult, is_cold: bool) -> u64 {
-    if spec_id.is_enabled_in(SpecId::BERLIN) {
-        // Berlin specification logic
-        let mut gas_cost = istanbul_sstore_cost::<WARM_STORAGE_READ_COST, WARM_SSTORE_RESET>(vals);
+const fn warm_sload_cost(schedule: &GasSchedule) -> u64 {
+    schedule.static_costs[bytecode::opcode::SLOAD as usize] + schedule.warm_storage_read_cost
+}
 
-        if is_cold {
-            gas_cost += COLD_SLOAD_COST;
-        }
-        gas_cost
-    } else if spec_id.is_enabled_in(SpecId::ISTANBUL) {
-        // Istanbul logic
-        istanbul_sstore_cost::<ISTANBUL_SLOAD_GAS, SSTORE_RESET>(vals)
+/// `SSTORE` opcode cost calculation, without the static cost.
+#[inline]
+pub fn sstore_cost(schedule: &GasSchedule, vals: &SStoreResult, is_cold: bool) -> u64 {
+    let gas_cost = if schedule.sstore_net_metering {
+        istanbul_sstore_cost(schedule, vals)
     } else {
-        // Frontier logic
-        frontier_sstore_cost(vals)
+        frontier_sstore_cost(schedule, vals)
+    };
+    if is_cold {
+        gas_cost + schedule.cold_sload_cost
+    } else {
+        gas_cost
     }
 }
 
 /// EIP-2200: Structured Definitions for Net Gas Metering
 #[inline]
-fn istanbul_sstore_cost<const SLOAD_GAS: u64, const SSTORE_RESET_GAS: u64>(
-    vals: &SStoreResult,
-) -> u64 {
+fn istanbul_sstore_cost(schedule: &GasSchedule, vals: &SStoreResult) -> u64 {
     if vals.is_new_eq_present() {
-        SLOAD_GAS
+        warm_sload_cost(schedule)
     } else if vals.is_original_eq_present() && vals.is_original_zero() {
-        SSTORE_SET
+        schedule.sstore_set_cost
     } else if vals.is_original_eq_present() {
-        SSTORE_RESET_GAS
+        schedule.sstore_reset_cost
     } else {
-        SLOAD_GAS
+        warm_sload_cost(schedule)
     }
 }
 
 /// Frontier sstore cost just had two cases set and reset values.
 #[inline]
-fn frontier_sstore_cost(vals: &SStoreResult) -> u64 {
+fn frontier_sstore_cost(schedule: &GasSchedule, vals: &SStoreResult) -> u64 {
     if vals.is_present_zero() && !vals.is_new_zero() {
-        SSTORE_SET
+        schedule.sstore_set_cost
     } else {
-        SSTORE_RESET
+        schedule.sstore_reset_cost
     }
 }
 
-/// `SELFDESTRUCT` opcode cost calculation.
+/// `SELFDESTRUCT` opcode cost calculation, without the static cost.
 #[inline]
-pub const fn selfdestruct_cost(spec_id: SpecId, res: StateLoad<SelfDestructResult>) -> u64 {
+pub const fn selfdestruct_cost(
+    spec_id: SpecId,
+    schedule: &GasSchedule,
+    res: StateLoad<SelfDestructResult>,
+) -> u64 {
     // EIP-161: State trie clearing (invariant-preserving alternative)
     let should_charge_topup = if spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON) {
         res.data.had_value && !res.data.target_exists
@@ -244,21 +210,14 @@ pub const fn selfdestruct_cost(spec_id: SpecId, res: StateLoad<SelfDestructResul
         0
     };
 
-    // EIP-150: Gas cost changes for IO-heavy operations
-    let selfdestruct_gas = if spec_id.is_enabled_in(SpecId::TANGERINE) {
-        5000
-    } else {
-        0
-    };
-
-    let mut gas = selfdestruct_gas + selfdestruct_gas_topup;
-    if spec_id.is_enabled_in(SpecId::BERLIN) && res.is_cold {
-        gas += COLD_ACCOUNT_ACCESS_COST
+    let mut gas = selfdestruct_gas_topup;
+    if res.is_cold {
+        gas += schedule.cold_account_access_cost
     }
     gas
 }
 
-/// Calculate call gas cost for the call instruction.
+/// Calculate call gas cost for the call instruction, without the static cost.
 ///
 /// There is three types of gas.
 /// * Account access gas. after berlin it can be cold or warm.
@@ -281,19 +240,13 @@ pub const fn selfdestruct_cost(spec_id: SpecId, res: StateLoad<SelfDestructResul
 #[inline]
 pub const fn call_cost(
     spec_id: SpecId,
+    schedule: &GasSchedule,
     transfers_value: bool,
     account_load: StateLoad<AccountLoad>,
 ) -> u64 {
     let is_empty = account_load.data.is_empty;
     // Account access.
-    let mut gas = if spec_id.is_enabled_in(SpecId::BERLIN) {
-        warm_cold_cost_with_delegation(account_load)
-    } else if spec_id.is_enabled_in(SpecId::TANGERINE) {
-        // EIP-150: Gas cost changes for IO-heavy operations
-        700
-    } else {
-        40
-    };
+    let mut gas = warm_cold_cost_with_delegation(schedule, account_load);
 
     // Transfer value cost
     if transfers_value {
@@ -318,11 +271,11 @@ pub const fn call_cost(
 
 /// Berlin warm and cold storage access cost for account access.
 #[inline]
-pub const fn warm_cold_cost(is_cold: bool) -> u64 {
+pub const fn warm_cold_cost(schedule: &GasSchedule, is_cold: bool) -> u64 {
     if is_cold {
-        COLD_ACCOUNT_ACCESS_COST
+        schedule.cold_account_access_cost
     } else {
-        WARM_STORAGE_READ_COST
+        schedule.warm_storage_read_cost
     }
 }
 
@@ -330,21 +283,33 @@ pub const fn warm_cold_cost(is_cold: bool) -> u64 {
 ///
 /// If delegation is Some, add additional cost for delegation account load.
 #[inline]
-pub const fn warm_cold_cost_with_delegation(load: StateLoad<AccountLoad>) -> u64 {
-    let mut gas = warm_cold_cost(load.is_cold);
+pub const fn warm_cold_cost_with_delegation(
+    schedule: &GasSchedule,
+    load: StateLoad<AccountLoad>,
+) -> u64 {
+    let mut gas = warm_cold_cost(schedule, load.is_cold);
     if let Some(is_cold) = load.data.is_delegate_account_cold {
-        gas += warm_cold_cost(is_cold);
+        gas += warm_cold_cost(schedule, is_cold);
     }
     gas
 }
 
 /// Memory expansion cost calculation for a given number of words.
+///
+/// A zero `quadratic_divisor` disables the quadratic cost.
 #[inline]
-pub const fn memory_gas(num_words: usize) -> u64 {
+pub const fn memory_gas(num_words: usize, word_cost: u64, quadratic_divisor: u64) -> u64 {
     let num_words = num_words as u64;
-    MEMORY
+    let quadratic_cost = match num_words
+        .saturating_mul(num_words)
+        .checked_div(quadratic_divisor)
+    {
+        Some(cost) => cost,
+        None => 0,
+    };
+    word_cost
         .saturating_mul(num_words)
-        .saturating_add(num_words.saturating_mul(num_words) / 512)
+        .saturating_add(quadratic_cost)
 }
 
 /// Initial gas that is deducted for transaction to be included.
@@ -355,7 +320,7 @@ pub const fn memory_gas(num_words: usize) -> u64 {
 /// - Intrinsic gas
 /// - Number of tokens in calldata
 pub fn calculate_initial_tx_gas(
-    spec_id: SpecId,
+    schedule: &GasSchedule,
     input: &[u8],
     is_create: bool,
     access_list_accounts: u64,
@@ -365,58 +330,47 @@ pub fn calculate_initial_tx_gas(
     let mut gas = InitialAndFloorGas::default();
 
     // Initdate stipend
-    let tokens_in_calldata = get_tokens_in_calldata(input, spec_id.is_enabled_in(SpecId::ISTANBUL));
-    gas.initial_gas += tokens_in_calldata * STANDARD_TOKEN_COST;
+    let tokens_in_calldata = get_tokens_in_calldata(input, schedule.tx_non_zero_byte_tokens);
+    gas.initial_gas += tokens_in_calldata * schedule.tx_token_cost;
 
     // Get number of access list account and storages.
-    gas.initial_gas += access_list_accounts * ACCESS_LIST_ADDRESS;
-    gas.initial_gas += access_list_storages * ACCESS_LIST_STORAGE_KEY;
+    gas.initial_gas += access_list_accounts * schedule.access_list_address_cost;
+    gas.initial_gas += access_list_storages * schedule.access_list_storage_key_cost;
 
     // Base stipend
-    gas.initial_gas += if is_create {
-        if spec_id.is_enabled_in(SpecId::HOMESTEAD) {
-            // EIP-2: Homestead Hard-fork Changes
-            53000
-        } else {
-            21000
-        }
-    } else {
-        21000
-    };
-
-    // EIP-3860: Limit and meter initcode
-    // Init code stipend for bytecode analysis
-    if spec_id.is_enabled_in(SpecId::SHANGHAI) && is_create {
-        gas.initial_gas += initcode_cost(input.len())
+    gas.initial_gas += schedule.tx_base_cost;
+    if is_create {
+        // EIP-2: Homestead Hard-fork Changes
+        gas.initial_gas += schedule.tx_create_cost;
+
+        // EIP-3860: Limit and meter initcode
+        // Init code stipend for bytecode analysis
+        gas.initial_gas += initcode_cost(schedule, input.len());
     }
 
     // EIP-7702
-    if spec_id.is_enabled_in(SpecId::PRAGUE) {
-        gas.initial_gas += authorization_list_num * eip7702::PER_EMPTY_ACCOUNT_COST;
+    gas.initial_gas += authorization_list_num * schedule.authorization_cost;
 
-        // Calculate gas floor for EIP-7623
-        gas.floor_gas = calc_tx_floor_cost(tokens_in_calldata);
+    // Calculate gas floor for EIP-7623
+    if schedule.tx_floor_token_cost.is_some() {
+        gas.floor_gas = calc_tx_floor_cost(schedule, tokens_in_calldata);
     }
 
     gas
 }
 
 /// Retrieve the total number of tokens in calldata.
+///
+/// Zero bytes are one token, non-zero bytes are `non_zero_byte_tokens` tokens.
 #[inline]
-pub fn get_tokens_in_calldata(input: &[u8], is_istanbul: bool) -> u64 {
+pub fn get_tokens_in_calldata(input: &[u8], non_zero_byte_tokens: u64) -> u64 {
     let zero_data_len = input.iter().filter(|v| **v == 0).count() as u64;
     let non_zero_data_len = input.len() as u64 - zero_data_len;
-    let non_zero_data_multiplier = if is_istanbul {
-        // EIP-2028: Transaction data gas cost reduction
-        NON_ZERO_BYTE_MULTIPLIER_ISTANBUL
-    } else {
-        NON_ZERO_BYTE_MULTIPLIER
-    };
-    zero_data_len + non_zero_data_len * non_zero_data_multiplier
+    zero_data_len + non_zero_data_len * non_zero_byte_tokens
 }
 
 /// Calculate the transaction cost floor as specified in EIP-7623.
 #[inline]
-pub fn calc_tx_floor_cost(tokens_in_calldata: u64) -> u64 {
-    tokens_in_calldata * TOTAL_COST_FLOOR_PER_TOKEN + 21_000
+pub fn calc_tx_floor_cost(schedule: &GasSchedule, tokens_in_calldata: u64) -> u64 {
+    tokens_in_calldata * schedule.tx_floor_token_cost.unwrap_or_default() + schedule.tx_base_cost
 }
@@ -98,6 +98,10 @@ pub enum InstructionResult {
     EofAuxDataTooSmall,
     /// `EXT*CALL` target address needs to be padded with 0s.
     InvalidEXTCALLTarget,
+    /// Stylus program is not activated.
+    ProgramNotActivated,
+    /// Stylus program was activated with an older version and needs to be reactivated.
+    ProgramVersionMismatch,
 }
 
 impl From<TransferError> for InstructionResult {
@@ -156,6 +160,8 @@ impl From<HaltReason> for InstructionResult {
             HaltReason::EofAuxDataTooSmall => Self::EofAuxDataTooSmall,
             HaltReason::SubRoutineStackOverflow => Self::SubRoutineStackOverflow,
             HaltReason::InvalidEXTCALLTarget => Self::InvalidEXTCALLTarget,
+            HaltReason::ProgramNotActivated => Self::ProgramNotActivated,
+            HaltReason::ProgramVersionMismatch => Self::ProgramVersionMismatch,
         }
     }
 }
@@ -215,6 +221,8 @@ macro_rules! return_error {
             | $crate::InstructionResult::EofAuxDataTooSmall
             | $crate::InstructionResult::EofAuxDataOverflow
             | $crate::InstructionResult::InvalidEXTCALLTarget
+            | $crate::InstructionResult::ProgramNotActivated
+            | $crate::InstructionResult::ProgramVersionMismatch
     };
 }
 
@@ -392,6 +400,12 @@ impl<HaltReasonT: HaltReasonTrait> From<InstructionResult> for SuccessOrHalt<Hal
             InstructionResult::InvalidExtDelegateCallTarget => {
                 Self::Internal(InternalResult::InvalidExtDelegateCallTarget)
             }
+            InstructionResult::ProgramNotActivated => {
+                Self::Halt(HaltReason::ProgramNotActivated.into())
+            }
+            InstructionResult::ProgramVersionMismatch => {
+                Self::Halt(HaltReason::ProgramVersionMismatch.into())
+            }
         }
     }
 }
@@ -460,6 +474,8 @@ mod tests {
             InstructionResult::CreateContractStartingWithEF,
             InstructionResult::CreateInitCodeSizeLimit,
             InstructionResult::FatalExternalError,
+            InstructionResult::ProgramNotActivated,
+            InstructionResult::ProgramVersionMismatch,
         ];
 
         for result in error_results {
@@ -1,4 +1,11 @@
 //! EVM opcode implementations.
+//!
+//! # Gas
+//!
+//! Instructions charge only their dynamic gas. The static gas cost of every opcode comes from
+//! the [`GasSchedule`][context_interface::GasSchedule] and is charged by
+//! [`MeteredInstruction`][crate::table::MeteredInstruction], executing the instructions without
+//! it runs them **without their static gas cost**.
 
 #[macro_use]
 pub mod macros;
@@ -2,16 +2,16 @@ use super::i256::{i256_div, i256_mod};
 use crate::{
     gas,
     interpreter::Interpreter,
-    interpreter_types::{InterpreterTypes, LoopControl, RuntimeFlag, StackTrait},
+    interpreter_types::{InterpreterTypes, LoopControl, StackTrait},
     Host,
 };
+use context_interface::Cfg;
 use primitives::U256;
 
 pub fn add<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([op1], op2, interpreter);
     *op2 = op1.wrapping_add(*op2);
 }
@@ -20,7 +20,6 @@ pub fn mul<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::LOW);
     popn_top!([op1], op2, interpreter);
     *op2 = op1.wrapping_mul(*op2);
 }
@@ -29,7 +28,6 @@ pub fn sub<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([op1], op2, interpreter);
     *op2 = op1.wrapping_sub(*op2);
 }
@@ -38,7 +36,6 @@ pub fn div<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::LOW);
     popn_top!([op1], op2, interpreter);
     if !op2.is_zero() {
         *op2 = op1.wrapping_div(*op2);
@@ -49,7 +46,6 @@ pub fn sdiv<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::LOW);
     popn_top!([op1], op2, interpreter);
     *op2 = i256_div(op1, *op2);
 }
@@ -58,7 +54,6 @@ pub fn rem<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::LOW);
     popn_top!([op1], op2, interpreter);
     if !op2.is_zero() {
         *op2 = op1.wrapping_rem(*op2);
@@ -69,7 +64,6 @@ pub fn smod<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::LOW);
     popn_top!([op1], op2, interpreter);
     *op2 = i256_mod(op1, *op2)
 }
@@ -78,7 +72,6 @@ pub fn addmod<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::MID);
     popn_top!([op1, op2], op3, interpreter);
     *op3 = op1.add_mod(op2, *op3)
 }
@@ -87,18 +80,16 @@ pub fn mulmod<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::MID);
     popn_top!([op1, op2], op3, interpreter);
     *op3 = op1.mul_mod(op2, *op3)
 }
 
 pub fn exp<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
-    _host: &mut H,
+    host: &mut H,
 ) {
-    let spec_id = interpreter.runtime_flag.spec_id();
     popn_top!([op1], op2, interpreter);
-    gas_or_fail!(interpreter, gas::exp_cost(spec_id, *op2));
+    gas_or_fail!(interpreter, gas::exp_cost(host.cfg().gas_schedule(), *op2));
     *op2 = op1.pow(*op2);
 }
 
@@ -135,7 +126,6 @@ pub fn signextend<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::LOW);
     popn_top!([ext], x, interpreter);
     // For 31 we also don't need to do anything.
     if ext < U256::from(31) {
@@ -1,6 +1,5 @@
 use super::i256::i256_cmp;
 use crate::{
-    gas,
     interpreter::Interpreter,
     interpreter_types::{InterpreterTypes, LoopControl, RuntimeFlag, StackTrait},
     Host,
@@ -12,7 +11,6 @@ pub fn lt<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([op1], op2, interpreter);
     *op2 = U256::from(op1 < *op2);
 }
@@ -21,7 +19,6 @@ pub fn gt<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([op1], op2, interpreter);
 
     *op2 = U256::from(op1 > *op2);
@@ -31,7 +28,6 @@ pub fn slt<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([op1], op2, interpreter);
 
     *op2 = U256::from(i256_cmp(&op1, op2) == Ordering::Less);
@@ -41,7 +37,6 @@ pub fn sgt<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([op1], op2, interpreter);
 
     *op2 = U256::from(i256_cmp(&op1, op2) == Ordering::Greater);
@@ -51,7 +46,6 @@ pub fn eq<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([op1], op2, interpreter);
 
     *op2 = U256::from(op1 == *op2);
@@ -61,7 +55,6 @@ pub fn iszero<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([], op1, interpreter);
     *op1 = U256::from(op1.is_zero());
 }
@@ -70,7 +63,6 @@ pub fn bitand<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([op1], op2, interpreter);
     *op2 = op1 & *op2;
 }
@@ -79,7 +71,6 @@ pub fn bitor<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([op1], op2, interpreter);
 
     *op2 = op1 | *op2;
@@ -89,7 +80,6 @@ pub fn bitxor<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([op1], op2, interpreter);
 
     *op2 = op1 ^ *op2;
@@ -99,7 +89,6 @@ pub fn not<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([], op1, interpreter);
 
     *op1 = !*op1;
@@ -109,7 +98,6 @@ pub fn byte<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([op1], op2, interpreter);
 
     let o1 = as_usize_saturated!(op1);
@@ -127,7 +115,6 @@ pub fn shl<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     check!(interpreter, CONSTANTINOPLE);
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([op1], op2, interpreter);
 
     let shift = as_usize_saturated!(op1);
@@ -144,7 +131,6 @@ pub fn shr<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     check!(interpreter, CONSTANTINOPLE);
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([op1], op2, interpreter);
 
     let shift = as_usize_saturated!(op1);
@@ -161,7 +147,6 @@ pub fn sar<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     check!(interpreter, CONSTANTINOPLE);
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([op1], op2, interpreter);
 
     let shift = as_usize_saturated!(op1);
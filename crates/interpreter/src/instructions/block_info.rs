@@ -1,5 +1,4 @@
 use crate::{
-    gas,
     instructions::utility::IntoU256,
     interpreter::Interpreter,
     interpreter_types::{InterpreterTypes, LoopControl, RuntimeFlag, StackTrait},
@@ -15,7 +14,6 @@ pub fn chainid<WIRE: InterpreterTypes, H: Host + ?Sized>(
     host: &mut H,
 ) {
     check!(interpreter, ISTANBUL);
-    gas!(interpreter, gas::BASE);
     push!(interpreter, U256::from(host.cfg().chain_id()));
 }
 
@@ -23,7 +21,6 @@ pub fn coinbase<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     push!(interpreter, host.block().beneficiary().into_word().into());
 }
 
@@ -31,7 +28,6 @@ pub fn timestamp<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     push!(interpreter, U256::from(host.block().timestamp()));
 }
 
@@ -39,7 +35,6 @@ pub fn block_number<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     push!(interpreter, U256::from(host.block().number()));
 }
 
@@ -47,7 +42,6 @@ pub fn difficulty<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     if interpreter.runtime_flag.spec_id().is_enabled_in(MERGE) {
         // Unwrap is safe as this fields is checked in validation handler.
         push!(
@@ -63,7 +57,6 @@ pub fn gaslimit<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     push!(interpreter, U256::from(host.block().gas_limit()));
 }
 
@@ -73,7 +66,6 @@ pub fn basefee<WIRE: InterpreterTypes, H: Host + ?Sized>(
     host: &mut H,
 ) {
     check!(interpreter, LONDON);
-    gas!(interpreter, gas::BASE);
     push!(interpreter, U256::from(host.block().basefee()));
 }
 
@@ -83,7 +75,6 @@ pub fn blob_basefee<WIRE: InterpreterTypes, H: Host + ?Sized>(
     host: &mut H,
 ) {
     check!(interpreter, CANCUN);
-    gas!(interpreter, gas::BASE);
     push!(
         interpreter,
         U256::from(host.block().blob_gasprice().unwrap_or_default())
@@ -3,7 +3,7 @@ mod call_helpers;
 pub use call_helpers::{calc_call_gas, get_memory_input_and_out_ranges, resize_memory};
 
 use crate::{
-    gas::{self, cost_per_word, EOF_CREATE_GAS, KECCAK256WORD, MIN_CALLEE_GAS},
+    gas::{self, cost_per_word, KECCAK256WORD, MIN_CALLEE_GAS},
     instructions::utility::IntoAddress,
     interpreter::Interpreter,
     interpreter_action::FrameInput,
@@ -28,7 +28,6 @@ pub fn eofcreate<WIRE: InterpreterTypes, H: Host + ?Sized>(
 ) {
     require_eof!(interpreter);
     require_non_staticcall!(interpreter);
-    gas!(interpreter, EOF_CREATE_GAS);
     let initcontainer_index = interpreter.bytecode.read_u8();
 
     popn!([value, salt, data_offset, data_size], interpreter);
@@ -190,6 +189,7 @@ pub fn extcall_gas_calc<WIRE: InterpreterTypes, H: Host + ?Sized>(
     // Berlin can be hardcoded as extcall came after berlin.
     let call_cost = gas::call_cost(
         interpreter.runtime_flag.spec_id(),
+        host.cfg().gas_schedule(),
         transfers_value,
         account_load,
     );
@@ -395,7 +395,10 @@ pub fn create<WIRE: InterpreterTypes, const IS_CREATE2: bool, H: Host + ?Sized>(
                     .set_instruction_result(InstructionResult::CreateInitCodeSizeLimit);
                 return;
             }
-            gas!(interpreter, gas::initcode_cost(len));
+            gas!(
+                interpreter,
+                gas::initcode_cost(host.cfg().gas_schedule(), len)
+            );
         }
 
         let code_offset = as_usize_or_fail!(interpreter, code_offset);
@@ -407,10 +410,9 @@ pub fn create<WIRE: InterpreterTypes, const IS_CREATE2: bool, H: Host + ?Sized>(
     let scheme = if IS_CREATE2 {
         popn!([salt], interpreter);
         // SAFETY: `len` is reasonable in size as gas for it is already deducted.
-        gas_or_fail!(interpreter, gas::create2_cost(len));
+        gas_or_fail!(interpreter, cost_per_word(len, KECCAK256WORD));
         CreateScheme::Create2 { salt }
     } else {
-        gas!(interpreter, gas::CREATE);
         CreateScheme::Create
     };
 
@@ -467,9 +469,13 @@ pub fn call<WIRE: InterpreterTypes, H: Host + ?Sized>(
             .set_instruction_result(InstructionResult::FatalExternalError);
         return;
     };
-    let Some(mut gas_limit) =
-        calc_call_gas(interpreter, account_load, has_transfer, local_gas_limit)
-    else {
+    let Some(mut gas_limit) = calc_call_gas(
+        interpreter,
+        host.cfg().gas_schedule(),
+        account_load,
+        has_transfer,
+        local_gas_limit,
+    ) else {
         return;
     };
 
@@ -520,8 +526,13 @@ pub fn call_code<WIRE: InterpreterTypes, H: Host + ?Sized>(
     };
     // Set `is_empty` to false as we are not creating this account.
     load.is_empty = false;
-    let Some(mut gas_limit) = calc_call_gas(interpreter, load, !value.is_zero(), local_gas_limit)
-    else {
+    let Some(mut gas_limit) = calc_call_gas(
+        interpreter,
+        host.cfg().gas_schedule(),
+        load,
+        !value.is_zero(),
+        local_gas_limit,
+    ) else {
         return;
     };
 
@@ -572,7 +583,13 @@ pub fn delegate_call<WIRE: InterpreterTypes, H: Host + ?Sized>(
     };
     // Set is_empty to false as we are not creating this account.
     load.is_empty = false;
-    let Some(gas_limit) = calc_call_gas(interpreter, load, false, local_gas_limit) else {
+    let Some(gas_limit) = calc_call_gas(
+        interpreter,
+        host.cfg().gas_schedule(),
+        load,
+        false,
+        local_gas_limit,
+    ) else {
         return;
     };
 
@@ -618,7 +635,13 @@ pub fn static_call<WIRE: InterpreterTypes, H: Host + ?Sized>(
     };
     // Set `is_empty` to false as we are not creating this account.
     load.is_empty = false;
-    let Some(gas_limit) = calc_call_gas(interpreter, load, false, local_gas_limit) else {
+    let Some(gas_limit) = calc_call_gas(
+        interpreter,
+        host.cfg().gas_schedule(),
+        load,
+        false,
+        local_gas_limit,
+    ) else {
         return;
     };
     gas!(interpreter, gas_limit);
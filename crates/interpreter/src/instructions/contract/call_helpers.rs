@@ -3,7 +3,7 @@ use crate::{
     interpreter::Interpreter,
     interpreter_types::{InterpreterTypes, LoopControl, MemoryTrait, RuntimeFlag, StackTrait},
 };
-use context_interface::{host::StateLoad, journaled_state::AccountLoad};
+use context_interface::{host::StateLoad, journaled_state::AccountLoad, GasSchedule};
 use core::{cmp::min, ops::Range};
 use primitives::{Bytes, U256};
 use specification::hardfork::SpecId::*;
@@ -47,12 +47,14 @@ pub fn resize_memory(
 #[inline]
 pub fn calc_call_gas(
     interpreter: &mut Interpreter<impl InterpreterTypes>,
+    schedule: &GasSchedule,
     account_load: StateLoad<AccountLoad>,
     has_transfer: bool,
     local_gas_limit: u64,
 ) -> Option<u64> {
     let call_cost = gas::call_cost(
         interpreter.runtime_flag.spec_id(),
+        schedule,
         has_transfer,
         account_load,
     );
@@ -1,5 +1,4 @@
 use crate::{
-    gas,
     interpreter::Interpreter,
     interpreter_types::{
         EofCodeInfo, Immediates, InterpreterTypes, Jumps, LoopControl, MemoryTrait, RuntimeFlag,
@@ -14,7 +13,6 @@ pub fn rjump<WIRE: InterpreterTypes, H: ?Sized>(
     _host: &mut H,
 ) {
     require_eof!(interpreter);
-    gas!(interpreter, gas::BASE);
     let offset = interpreter.bytecode.read_i16() as isize;
     // In spec it is +3 but pointer is already incremented in
     // `Interpreter::step` so for revm is +2.
@@ -26,7 +24,6 @@ pub fn rjumpi<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     require_eof!(interpreter);
-    gas!(interpreter, gas::CONDITION_JUMP_GAS);
     popn!([condition], interpreter);
     // In spec it is +3 but pointer is already incremented in
     // `Interpreter::step` so for revm is +2.
@@ -43,7 +40,6 @@ pub fn rjumpv<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     require_eof!(interpreter);
-    gas!(interpreter, gas::CONDITION_JUMP_GAS);
     popn!([case], interpreter);
     let case = as_isize_saturated!(case);
 
@@ -62,7 +58,6 @@ pub fn jump<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::MID);
     popn!([target], interpreter);
     jump_inner(interpreter, target);
 }
@@ -71,7 +66,6 @@ pub fn jumpi<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::HIGH);
     popn!([target, cond], interpreter);
 
     if !cond.is_zero() {
@@ -93,10 +87,9 @@ fn jump_inner<WIRE: InterpreterTypes>(interpreter: &mut Interpreter<WIRE>, targe
 }
 
 pub fn jumpdest_or_nop<WIRE: InterpreterTypes, H: Host + ?Sized>(
-    interpreter: &mut Interpreter<WIRE>,
+    _interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::JUMPDEST);
 }
 
 pub fn callf<WIRE: InterpreterTypes, H: Host + ?Sized>(
@@ -104,7 +97,6 @@ pub fn callf<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     require_eof!(interpreter);
-    gas!(interpreter, gas::LOW);
 
     let idx = interpreter.bytecode.read_u16() as usize;
 
@@ -145,7 +137,6 @@ pub fn retf<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     require_eof!(interpreter);
-    gas!(interpreter, gas::RETF_GAS);
 
     let Some(jump) = interpreter.sub_routine.pop() else {
         panic!("Expected function frame")
@@ -159,7 +150,6 @@ pub fn jumpf<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     require_eof!(interpreter);
-    gas!(interpreter, gas::LOW);
 
     let idx = interpreter.bytecode.read_u16() as usize;
 
@@ -189,7 +179,6 @@ pub fn pc<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     // - 1 because we have already advanced the instruction pointer in `Interpreter::step`
     push!(interpreter, U256::from(interpreter.bytecode.pc() - 1));
 }
@@ -1,5 +1,5 @@
 use crate::{
-    gas::{cost_per_word, BASE, DATA_LOAD_GAS, VERYLOW},
+    gas::{cost_per_word, VERYLOW},
     interpreter::Interpreter,
     interpreter_types::{
         EofData, Immediates, InterpreterTypes, Jumps, LoopControl, MemoryTrait, RuntimeFlag,
@@ -14,7 +14,6 @@ pub fn data_load<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     require_eof!(interpreter);
-    gas!(interpreter, DATA_LOAD_GAS);
     popn_top!([], offset, interpreter);
 
     let offset_usize = as_usize_saturated!(offset);
@@ -32,7 +31,6 @@ pub fn data_loadn<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     require_eof!(interpreter);
-    gas!(interpreter, VERYLOW);
     let offset = interpreter.bytecode.read_u16() as usize;
 
     let slice = interpreter.bytecode.data_slice(offset, 32);
@@ -51,7 +49,6 @@ pub fn data_size<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     require_eof!(interpreter);
-    gas!(interpreter, BASE);
 
     push!(interpreter, U256::from(interpreter.bytecode.data_size()));
 }
@@ -61,7 +58,6 @@ pub fn data_copy<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     require_eof!(interpreter);
-    gas!(interpreter, VERYLOW);
     popn!([mem_offset, offset, size], interpreter);
 
     // Sizes more than u64::MAX will spend all the gas in memory resize.
@@ -7,6 +7,7 @@ use crate::{
     },
     Host, InstructionResult,
 };
+use context_interface::Cfg;
 use core::cmp::min;
 use primitives::{Bytes, Log, LogData, B256, U256};
 use specification::hardfork::SpecId::*;
@@ -23,19 +24,9 @@ pub fn balance<WIRE: InterpreterTypes, H: Host + ?Sized>(
             .set_instruction_result(InstructionResult::FatalExternalError);
         return;
     };
-    let spec_id = interpreter.runtime_flag.spec_id();
     gas!(
         interpreter,
-        if spec_id.is_enabled_in(BERLIN) {
-            warm_cold_cost(balance.is_cold)
-        } else if spec_id.is_enabled_in(ISTANBUL) {
-            // EIP-1884: Repricing for trie-size-dependent opcodes
-            700
-        } else if spec_id.is_enabled_in(TANGERINE) {
-            400
-        } else {
-            20
-        }
+        warm_cold_cost(host.cfg().gas_schedule(), balance.is_cold)
     );
     *top = balance.data;
 }
@@ -46,7 +37,6 @@ pub fn selfbalance<WIRE: InterpreterTypes, H: Host + ?Sized>(
     host: &mut H,
 ) {
     check!(interpreter, ISTANBUL);
-    gas!(interpreter, gas::LOW);
     let Some(balance) = host.balance(interpreter.input.target_address()) else {
         interpreter
             .control
@@ -68,14 +58,10 @@ pub fn extcodesize<WIRE: InterpreterTypes, H: Host + ?Sized>(
             .set_instruction_result(InstructionResult::FatalExternalError);
         return;
     };
-    let spec_id = interpreter.runtime_flag.spec_id();
-    if spec_id.is_enabled_in(BERLIN) {
-        gas!(interpreter, warm_cold_cost(code.is_cold));
-    } else if spec_id.is_enabled_in(TANGERINE) {
-        gas!(interpreter, 700);
-    } else {
-        gas!(interpreter, 20);
-    }
+    gas!(
+        interpreter,
+        warm_cold_cost(host.cfg().gas_schedule(), code.is_cold)
+    );
 
     *top = U256::from(code.len());
 }
@@ -94,14 +80,10 @@ pub fn extcodehash<WIRE: InterpreterTypes, H: Host + ?Sized>(
             .set_instruction_result(InstructionResult::FatalExternalError);
         return;
     };
-    let spec_id = interpreter.runtime_flag.spec_id();
-    if spec_id.is_enabled_in(BERLIN) {
-        gas!(interpreter, warm_cold_cost(code_hash.is_cold));
-    } else if spec_id.is_enabled_in(ISTANBUL) {
-        gas!(interpreter, 700);
-    } else {
-        gas!(interpreter, 400);
-    }
+    gas!(
+        interpreter,
+        warm_cold_cost(host.cfg().gas_schedule(), code_hash.is_cold)
+    );
     *top = code_hash.into_u256();
 }
 
@@ -121,7 +103,7 @@ pub fn extcodecopy<WIRE: InterpreterTypes, H: Host + ?Sized>(
     let len = as_usize_or_fail!(interpreter, len_u256);
     gas_or_fail!(
         interpreter,
-        gas::extcodecopy_cost(interpreter.runtime_flag.spec_id(), len, code.is_cold)
+        gas::extcodecopy_cost(host.cfg().gas_schedule(), len, code.is_cold)
     );
     if len == 0 {
         return;
@@ -140,7 +122,6 @@ pub fn blockhash<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     host: &mut H,
 ) {
-    gas!(interpreter, gas::BLOCKHASH);
     popn_top!([], number, interpreter);
 
     let number_u64 = as_u64_saturated!(number);
@@ -166,7 +147,7 @@ pub fn sload<WIRE: InterpreterTypes, H: Host + ?Sized>(
     };
     gas!(
         interpreter,
-        gas::sload_cost(interpreter.runtime_flag.spec_id(), value.is_cold)
+        gas::sload_cost(host.cfg().gas_schedule(), value.is_cold)
     );
     *index = value.data;
 }
@@ -194,19 +175,16 @@ pub fn sstore<WIRE: InterpreterTypes, H: Host + ?Sized>(
             .set_instruction_result(InstructionResult::ReentrancySentryOOG);
         return;
     }
+    let schedule = host.cfg().gas_schedule();
     gas!(
         interpreter,
-        gas::sstore_cost(
-            interpreter.runtime_flag.spec_id(),
-            &state_load.data,
-            state_load.is_cold
-        )
+        gas::sstore_cost(schedule, &state_load.data, state_load.is_cold)
     );
 
-    interpreter.control.gas().record_refund(gas::sstore_refund(
-        interpreter.runtime_flag.spec_id(),
-        &state_load.data,
-    ));
+    interpreter
+        .control
+        .gas()
+        .record_refund(gas::sstore_refund(schedule, &state_load.data));
 }
 
 /// EIP-1153: Transient storage opcodes
@@ -217,7 +195,6 @@ pub fn tstore<WIRE: InterpreterTypes, H: Host + ?Sized>(
 ) {
     check!(interpreter, CANCUN);
     require_non_staticcall!(interpreter);
-    gas!(interpreter, gas::WARM_STORAGE_READ_COST);
 
     popn!([index, value], interpreter);
 
@@ -231,7 +208,6 @@ pub fn tload<WIRE: InterpreterTypes, H: Host + ?Sized>(
     host: &mut H,
 ) {
     check!(interpreter, CANCUN);
-    gas!(interpreter, gas::WARM_STORAGE_READ_COST);
 
     popn_top!([], index, interpreter);
 
@@ -246,7 +222,7 @@ pub fn log<const N: usize, H: Host + ?Sized>(
 
     popn!([offset, len], interpreter);
     let len = as_usize_or_fail!(interpreter, len);
-    gas_or_fail!(interpreter, gas::log_cost(N as u8, len as u64));
+    gas_or_fail!(interpreter, gas::LOGDATA.checked_mul(len as u64));
     let data = if len == 0 {
         Bytes::new()
     } else {
@@ -296,7 +272,11 @@ pub fn selfdestruct<WIRE: InterpreterTypes, H: Host + ?Sized>(
     }
     gas!(
         interpreter,
-        gas::selfdestruct_cost(interpreter.runtime_flag.spec_id(), res)
+        gas::selfdestruct_cost(
+            interpreter.runtime_flag.spec_id(),
+            host.cfg().gas_schedule(),
+            res
+        )
     );
 
     interpreter
@@ -11,7 +11,6 @@ pub fn mload<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([], top, interpreter);
     let offset = as_usize_or_fail!(interpreter, top);
     resize_memory!(interpreter, offset, 32);
@@ -22,7 +21,6 @@ pub fn mstore<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn!([offset, value], interpreter);
     let offset = as_usize_or_fail!(interpreter, offset);
     resize_memory!(interpreter, offset, 32);
@@ -33,7 +31,6 @@ pub fn mstore8<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     popn!([offset, value], interpreter);
     let offset = as_usize_or_fail!(interpreter, offset);
     resize_memory!(interpreter, offset, 1);
@@ -44,7 +41,6 @@ pub fn msize<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     push!(interpreter, U256::from(interpreter.memory.size()));
 }
 
@@ -59,7 +55,7 @@ pub fn mcopy<WIRE: InterpreterTypes, H: Host + ?Sized>(
     // Into usize or fail
     let len = as_usize_or_fail!(interpreter, len);
     // Deduce gas
-    gas_or_fail!(interpreter, gas::copy_cost_verylow(len));
+    gas_or_fail!(interpreter, gas::cost_per_word(len, gas::COPY));
     if len == 0 {
         return;
     }
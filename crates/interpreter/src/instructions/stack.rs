@@ -1,5 +1,4 @@
 use crate::{
-    gas,
     instructions::utility::cast_slice_to_u256,
     interpreter::Interpreter,
     interpreter_types::{
@@ -13,7 +12,6 @@ pub fn pop<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     // Can ignore return. as relative N jump is safe operation.
     popn!([_i], interpreter);
 }
@@ -26,7 +24,6 @@ pub fn push0<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     check!(interpreter, SHANGHAI);
-    gas!(interpreter, gas::BASE);
     push!(interpreter, U256::ZERO);
 }
 
@@ -34,7 +31,6 @@ pub fn push<const N: usize, WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     // TODO : Check performance degradation.
     push!(interpreter, U256::ZERO);
     popn_top!([], top, interpreter);
@@ -50,7 +46,6 @@ pub fn dup<const N: usize, WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     if !interpreter.stack.dup(N) {
         interpreter
             .control
@@ -62,7 +57,6 @@ pub fn swap<const N: usize, WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     assert!(N != 0);
     if !interpreter.stack.exchange(0, N) {
         interpreter
@@ -76,7 +70,6 @@ pub fn dupn<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     require_eof!(interpreter);
-    gas!(interpreter, gas::VERYLOW);
     let imm = interpreter.bytecode.read_u8();
     if !interpreter.stack.dup(imm as usize + 1) {
         interpreter
@@ -91,7 +84,6 @@ pub fn swapn<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     require_eof!(interpreter);
-    gas!(interpreter, gas::VERYLOW);
     let imm = interpreter.bytecode.read_u8();
     if !interpreter.stack.exchange(0, imm as usize + 1) {
         interpreter
@@ -106,7 +98,6 @@ pub fn exchange<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     require_eof!(interpreter);
-    gas!(interpreter, gas::VERYLOW);
     let imm = interpreter.bytecode.read_u8();
     let n = (imm >> 4) + 1;
     let m = (imm & 0x0F) + 1;
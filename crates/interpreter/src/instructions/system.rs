@@ -16,7 +16,7 @@ pub fn keccak256<WIRE: InterpreterTypes, H: Host + ?Sized>(
 ) {
     popn_top!([offset], top, interpreter);
     let len = as_usize_or_fail!(interpreter, top);
-    gas_or_fail!(interpreter, gas::keccak256_cost(len));
+    gas_or_fail!(interpreter, gas::cost_per_word(len, gas::KECCAK256WORD));
     let hash = if len == 0 {
         KECCAK_EMPTY
     } else {
@@ -31,7 +31,6 @@ pub fn address<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     push!(
         interpreter,
         interpreter.input.target_address().into_word().into()
@@ -42,7 +41,6 @@ pub fn caller<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     push!(
         interpreter,
         interpreter.input.caller_address().into_word().into()
@@ -53,7 +51,6 @@ pub fn codesize<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     push!(interpreter, U256::from(interpreter.bytecode.bytecode_len()));
 }
 
@@ -81,7 +78,6 @@ pub fn calldataload<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::VERYLOW);
     //pop_top!(interpreter, offset_ptr);
     popn_top!([], offset_ptr, interpreter);
     let mut word = B256::ZERO;
@@ -104,7 +100,6 @@ pub fn calldatasize<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     push!(interpreter, U256::from(interpreter.input.input().len()));
 }
 
@@ -112,7 +107,6 @@ pub fn callvalue<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     push!(interpreter, interpreter.input.call_value());
 }
 
@@ -139,7 +133,6 @@ pub fn returndatasize<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     check!(interpreter, BYZANTIUM);
-    gas!(interpreter, gas::BASE);
     push!(
         interpreter,
         U256::from(interpreter.return_data.buffer().len())
@@ -186,7 +179,6 @@ pub fn returndataload<WIRE: InterpreterTypes, H: Host + ?Sized>(
     _host: &mut H,
 ) {
     require_eof!(interpreter);
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([], offset, interpreter);
     let offset_usize = as_usize_saturated!(offset);
 
@@ -210,7 +202,6 @@ pub fn gas<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     _host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     push!(
         interpreter,
         U256::from(interpreter.control.gas().remaining())
@@ -224,7 +215,7 @@ pub fn memory_resize(
     len: usize,
 ) -> Option<usize> {
     // Safe to cast usize to u64
-    gas_or_fail!(interpreter, gas::copy_cost_verylow(len), None);
+    gas_or_fail!(interpreter, gas::cost_per_word(len, gas::COPY), None);
     if len == 0 {
         return None;
     }
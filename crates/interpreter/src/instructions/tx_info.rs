@@ -1,5 +1,4 @@
 use crate::{
-    gas,
     interpreter::Interpreter,
     interpreter_types::{InterpreterTypes, LoopControl, RuntimeFlag, StackTrait},
     Host,
@@ -11,7 +10,6 @@ pub fn gasprice<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     let basefee = host.block().basefee();
     push!(
         interpreter,
@@ -23,7 +21,6 @@ pub fn origin<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     host: &mut H,
 ) {
-    gas!(interpreter, gas::BASE);
     push!(interpreter, host.tx().caller().into_word().into());
 }
 
@@ -33,7 +30,6 @@ pub fn blob_hash<WIRE: InterpreterTypes, H: Host + ?Sized>(
     host: &mut H,
 ) {
     check!(interpreter, CANCUN);
-    gas!(interpreter, gas::VERYLOW);
     popn_top!([], index, interpreter);
     let i = as_usize_saturated!(index);
     let tx = &host.tx();
@@ -10,9 +10,15 @@ mod stack;
 mod subroutine_stack;
 
 use crate::{
-    interpreter_types::*, table::CustomInstruction, Gas, Host, Instruction, InstructionResult,
-    InterpreterAction,
+    interpreter_types::*,
+    table::{make_metered_instruction_table, CustomInstruction, MeteredInstruction},
+    Gas, Host, Instruction, InstructionResult, InterpreterAction,
 };
+use bytecode::{
+    legacy::{BlockAnalysis, Step},
+    opcode,
+};
+use context_interface::Cfg;
 use core::cell::RefCell;
 pub use ext_bytecode::ExtBytecode;
 pub use input::InputsImpl;
@@ -72,6 +78,7 @@ impl<EXT: Default, MG: MemoryGetter> Interpreter<EthInterpreter<EXT, MG>> {
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct EthInterpreter<EXT = (), MG = SharedMemory> {
     _phantom: core::marker::PhantomData<fn() -> (EXT, MG)>,
 }
@@ -95,10 +102,18 @@ pub trait InstructionProvider: Clone {
     fn new(context: &mut Self::Host) -> Self;
 
     fn table(&mut self) -> &[impl CustomInstruction<Wire = Self::WIRE, Host = Self::Host>; 256];
+
+    /// Returns the table for block metered execution, see [`Interpreter::run_blocks`].
+    ///
+    /// Providers that have to execute every instruction separately, like inspectors, return
+    /// `None`.
+    fn metered_table(&mut self) -> Option<&[MeteredInstruction<Self::WIRE, Self::Host>; 256]> {
+        None
+    }
 }
 
 pub struct EthInstructionProvider<WIRE: InterpreterTypes, HOST> {
-    instruction_table: Rc<[Instruction<WIRE, HOST>; 256]>,
+    instruction_table: Rc<[MeteredInstruction<WIRE, HOST>; 256]>,
 }
 
 impl<WIRE, HOST> Clone for EthInstructionProvider<WIRE, HOST>
@@ -120,9 +135,11 @@ where
     type WIRE = WIRE;
     type Host = HOST;
 
-    fn new(_context: &mut Self::Host) -> Self {
+    fn new(context: &mut Self::Host) -> Self {
         Self {
-            instruction_table: Rc::new(crate::table::make_instruction_table::<WIRE, HOST>()),
+            instruction_table: Rc::new(make_metered_instruction_table(
+                context.cfg().gas_schedule(),
+            )),
         }
     }
 
@@ -131,6 +148,10 @@ where
     fn table(&mut self) -> &[impl CustomInstruction<Wire = Self::WIRE, Host = Self::Host>; 256] {
         self.instruction_table.as_ref()
     }
+
+    fn metered_table(&mut self) -> Option<&[MeteredInstruction<Self::WIRE, Self::Host>; 256]> {
+        Some(self.instruction_table.as_ref())
+    }
 }
 
 impl<IW: InterpreterTypes, H: Host> CustomInstruction for Instruction<IW, H> {
@@ -153,7 +174,7 @@ impl<IW: InterpreterTypes> Interpreter<IW> {
     ///
     /// Internally it will increment instruction pointer by one.
     #[inline]
-    pub(crate) fn step<FN, H: Host>(&mut self, instruction_table: &[FN; 256], host: &mut H)
+    pub fn step<FN, H: Host>(&mut self, instruction_table: &[FN; 256], host: &mut H)
     where
         FN: CustomInstruction<Wire = IW, Host = H>,
     {
@@ -186,6 +207,118 @@ impl<IW: InterpreterTypes> Interpreter<IW> {
             self.step(instruction_table, host);
         }
 
+        self.take_action()
+    }
+
+    /// Executes the interpreter until it returns or stops, charging the static gas once per
+    /// basic block of the analysis.
+    ///
+    /// The analysis has to be created with the static gas costs of the table. Superinstructions
+    /// execute `PUSH`, `JUMP` and `JUMPI` without the table, so the table has to implement them
+    /// as the mainnet table does.
+    ///
+    /// Gas usage is the same as with [`Interpreter::run`], but a block is not entered if its
+    /// static gas exceeds the remaining gas, even if one of its instructions would halt earlier.
+    pub fn run_blocks<H: Host>(
+        &mut self,
+        instruction_table: &[MeteredInstruction<IW, H>; 256],
+        analysis: &BlockAnalysis,
+        host: &mut H,
+    ) -> InterpreterAction {
+        self.control
+            .set_next_action(InterpreterAction::None, InstructionResult::Continue);
+
+        while self.control.instruction_result().is_continue() {
+            let Some(block) = analysis.block_at(self.bytecode.pc()) else {
+                // Blocks are left after their last instruction, this is only reached if the
+                // analysis does not match the bytecode.
+                self.step(instruction_table, host);
+                continue;
+            };
+            if !self.control.gas().record_cost(block.static_gas) {
+                self.control
+                    .set_instruction_result(InstructionResult::OutOfGas);
+                break;
+            }
+            for &step in &analysis.steps()[block.steps.clone()] {
+                self.block_step(step, instruction_table, host);
+                if !self.control.instruction_result().is_continue() {
+                    break;
+                }
+            }
+        }
+
+        self.take_action()
+    }
+
+    /// Executes the step of a basic block without charging the static gas.
+    #[inline]
+    fn block_step<H: Host>(
+        &mut self,
+        step: Step,
+        instruction_table: &[MeteredInstruction<IW, H>; 256],
+        host: &mut H,
+    ) {
+        let opcode = self.bytecode.opcode();
+        match step {
+            Step::Instruction => {
+                self.bytecode.relative_jump(1);
+                (instruction_table[opcode as usize].instruction)(self, host);
+            }
+            Step::PushJump(target) => {
+                if self.stack.len() >= STACK_LIMIT {
+                    self.control
+                        .set_instruction_result(InstructionResult::StackOverflow);
+                    return;
+                }
+                self.block_jump(target);
+            }
+            Step::PushJumpi(target) => {
+                if self.stack.len() >= STACK_LIMIT {
+                    self.control
+                        .set_instruction_result(InstructionResult::StackOverflow);
+                    return;
+                }
+                let Some(cond) = self.stack.pop() else {
+                    self.control
+                        .set_instruction_result(InstructionResult::StackUnderflow);
+                    return;
+                };
+                if cond.is_zero() {
+                    // Skip the push, its immediate and the jump.
+                    self.bytecode
+                        .relative_jump((opcode - opcode::PUSH0) as isize + 2);
+                } else {
+                    self.block_jump(target);
+                }
+            }
+            Step::DupSwap => {
+                for _ in 0..2 {
+                    let opcode = self.bytecode.opcode();
+                    self.bytecode.relative_jump(1);
+                    (instruction_table[opcode as usize].instruction)(self, host);
+                    if !self.control.instruction_result().is_continue() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Jumps to the target of a superinstruction, halts if it is not a valid jump destination.
+    #[inline]
+    fn block_jump(&mut self, target: Option<u32>) {
+        match target {
+            Some(target) => self.bytecode.absolute_jump(target as usize),
+            None => self
+                .control
+                .set_instruction_result(InstructionResult::InvalidJump),
+        }
+    }
+
+    /// Returns the next action, or the halt result if there is none.
+    #[inline]
+    pub fn take_action(&mut self) -> InterpreterAction {
         // Return next action if it is some.
         let action = self.control.take_next_action();
         if action.is_some() {
@@ -19,6 +19,19 @@ pub struct ExtBytecode {
     instruction_pointer: *const u8,
 }
 
+impl Clone for ExtBytecode {
+    fn clone(&self) -> Self {
+        let mut bytecode = Self {
+            base: self.base.clone(),
+            bytecode_hash: self.bytecode_hash,
+            instruction_pointer: self.instruction_pointer,
+        };
+        // Instruction pointer has to point into the bytecode of the clone.
+        bytecode.absolute_jump(self.pc());
+        bytecode
+    }
+}
+
 impl Deref for ExtBytecode {
     type Target = Bytecode;
 
@@ -4,6 +4,7 @@ use super::RuntimeFlag;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RuntimeFlags {
     pub is_static: bool,
@@ -48,6 +48,21 @@ impl CallInputs {
         self.value.transfer().is_some_and(|x| x > U256::ZERO)
     }
 
+    /// Returns the gas the caller passed to the call.
+    ///
+    /// `CALL` and `CALLCODE` transferring value add the [`CALL_STIPEND`] to the gas limit, the
+    /// caller does not pay for it.
+    ///
+    /// [`CALL_STIPEND`]: crate::gas::CALL_STIPEND
+    #[inline]
+    pub fn forwarded_gas(&self) -> u64 {
+        if self.transfers_value() && !self.scheme.is_ext() {
+            self.gas_limit.saturating_sub(crate::gas::CALL_STIPEND)
+        } else {
+            self.gas_limit
+        }
+    }
+
     /// Returns the transfer value.
     ///
     /// This is the value that is transferred from caller to callee, see [`CallValue`].
@@ -182,3 +197,44 @@ impl CallValue {
         matches!(self, Self::Apparent(_))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::CALL_STIPEND;
+
+    fn inputs(scheme: CallScheme, value: CallValue) -> CallInputs {
+        CallInputs {
+            input: Bytes::new(),
+            return_memory_offset: 0..0,
+            gas_limit: 10_000 + CALL_STIPEND,
+            bytecode_address: Address::ZERO,
+            target_address: Address::ZERO,
+            caller: Address::ZERO,
+            value,
+            scheme,
+            is_static: false,
+            is_eof: scheme.is_ext(),
+        }
+    }
+
+    #[test]
+    fn forwarded_gas_excludes_the_stipend() {
+        let value = CallValue::Transfer(U256::from(1));
+        for scheme in [CallScheme::Call, CallScheme::CallCode] {
+            assert_eq!(inputs(scheme, value.clone()).forwarded_gas(), 10_000);
+        }
+        assert_eq!(
+            inputs(CallScheme::ExtCall, value).forwarded_gas(),
+            10_000 + CALL_STIPEND
+        );
+        assert_eq!(
+            inputs(CallScheme::Call, CallValue::default()).forwarded_gas(),
+            10_000 + CALL_STIPEND
+        );
+        assert_eq!(
+            inputs(CallScheme::DelegateCall, CallValue::Apparent(U256::from(1))).forwarded_gas(),
+            10_000 + CALL_STIPEND
+        );
+    }
+}
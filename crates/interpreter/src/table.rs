@@ -3,12 +3,15 @@
 use crate::{
     instructions::{control, instruction},
     interpreter::Interpreter,
-    interpreter_types::InterpreterTypes,
-    Host,
+    interpreter_types::{InterpreterTypes, LoopControl},
+    Host, InstructionResult,
 };
+use context_interface::GasSchedule;
 use std::boxed::Box;
 
 /// EVM opcode function signature.
+///
+/// Instructions do **not** charge their static gas cost, see [`MeteredInstruction`].
 pub type Instruction<W, H> = for<'a> fn(&'a mut Interpreter<W>, &'a mut H);
 
 /// Instruction table is list of instruction function pointers mapped to 256 EVM opcodes.
@@ -29,6 +32,48 @@ pub trait CustomInstruction {
     fn from_base(instruction: Instruction<Self::Wire, Self::Host>) -> Self;
 }
 
+/// Instruction that charges its static gas cost before it is executed.
+///
+/// This is the only place the static gas costs of the [`GasSchedule`] are charged, tables
+/// executing bytecode have to be made with [`make_metered_instruction_table`].
+pub struct MeteredInstruction<W: InterpreterTypes, H: ?Sized> {
+    pub instruction: Instruction<W, H>,
+    pub static_gas: u64,
+}
+
+impl<W: InterpreterTypes, H: ?Sized> Clone for MeteredInstruction<W, H> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<W: InterpreterTypes, H: ?Sized> Copy for MeteredInstruction<W, H> {}
+
+impl<W: InterpreterTypes, H: Host> CustomInstruction for MeteredInstruction<W, H> {
+    type Wire = W;
+    type Host = H;
+
+    #[inline]
+    fn exec(&self, interpreter: &mut Interpreter<Self::Wire>, host: &mut Self::Host) {
+        if !interpreter.control.gas().record_cost(self.static_gas) {
+            interpreter
+                .control
+                .set_instruction_result(InstructionResult::OutOfGas);
+            return;
+        }
+        (self.instruction)(interpreter, host);
+    }
+
+    /// Instructions inserted into the table without a cost do not charge static gas.
+    #[inline]
+    fn from_base(instruction: Instruction<Self::Wire, Self::Host>) -> Self {
+        Self {
+            instruction,
+            static_gas: 0,
+        }
+    }
+}
+
 /// Either a plain, static instruction table, or a boxed, dynamic instruction table.
 ///
 /// Note that `Plain` variant is about 10-20% faster in Interpreter execution.
@@ -112,6 +157,13 @@ where
 }
 
 /// Make instruction table.
+///
+/// # Gas
+///
+/// The table does **not** charge the static gas costs, executing bytecode with it directly
+/// runs every instruction without its static cost. Use [`make_metered_instruction_table`] with
+/// the [`Cfg::gas_schedule`][context_interface::Cfg::gas_schedule] of the configuration to
+/// execute bytecode, this table is meant for wrapping the instructions.
 #[inline]
 pub const fn make_instruction_table<WIRE: InterpreterTypes, H: Host + ?Sized>(
 ) -> InstructionTable<WIRE, H> {
@@ -126,6 +178,18 @@ pub const fn make_instruction_table<WIRE: InterpreterTypes, H: Host + ?Sized>(
     }
 }
 
+/// Make instruction table that charges the static costs of the gas schedule.
+#[inline]
+pub fn make_metered_instruction_table<WIRE: InterpreterTypes, H: Host + ?Sized>(
+    schedule: &GasSchedule,
+) -> [MeteredInstruction<WIRE, H>; 256] {
+    let table = make_instruction_table::<WIRE, H>();
+    core::array::from_fn(|i| MeteredInstruction {
+        instruction: table[i],
+        static_gas: schedule.static_costs[i],
+    })
+}
+
 /// Make boxed instruction table that calls `f` closure for every instruction.
 #[inline]
 pub fn make_custom_instruction_table<W, H, FN, CI: CustomInstruction<Wire = W, Host = H>>(
@@ -0,0 +1,272 @@
+//! Cache of compiled code shared between frames and EVM instances.
+use crate::{compile, CompiledCode};
+use bytecode::LegacyAnalyzedBytecode;
+use core::num::NonZeroUsize;
+use lru::LruCache;
+use primitives::{keccak256, Bytes, B256};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+/// Default number of code hashes tracked by the [`JitCache`].
+pub const DEFAULT_JIT_CACHE_SIZE: usize = 1024;
+
+/// Compilation state of a code hash.
+#[derive(Debug)]
+enum Entry {
+    /// Number of executions before the code is compiled.
+    Executed(u64),
+    /// Code is compiled by another thread, it is interpreted meanwhile.
+    Compiling,
+    Compiled(Arc<CompiledCode>),
+    /// Code can not be compiled and is always interpreted.
+    Failed,
+}
+
+/// Compilation state with the padded bytecode of the code hash.
+#[derive(Debug)]
+struct CachedCode {
+    bytecode: Bytes,
+    entry: Entry,
+}
+
+/// Counts executions of legacy bytecode and compiles it once it is hot.
+///
+/// Code is only tracked if the code hash is the hash of the bytecode, and only returned for the
+/// bytecode it was compiled from. The cache tracks a bounded number of code hashes, the least
+/// recently used one is evicted first. Use [`JitCache::global`] to share compiled code between
+/// EVM instances.
+#[derive(Debug)]
+pub struct JitCache {
+    entries: Mutex<LruCache<B256, CachedCode>>,
+}
+
+impl Default for JitCache {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_JIT_CACHE_SIZE)
+    }
+}
+
+impl JitCache {
+    /// Creates an empty cache with the [`DEFAULT_JIT_CACHE_SIZE`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty cache tracking at most `capacity` code hashes, at least one.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Returns the cache shared by all EVM instances.
+    pub fn global() -> &'static Self {
+        static INSTANCE: OnceLock<JitCache> = OnceLock::new();
+        INSTANCE.get_or_init(Self::new)
+    }
+
+    /// Returns the compiled code if it was compiled from the bytecode with the given static gas
+    /// costs.
+    pub fn get(
+        &self,
+        code_hash: B256,
+        bytecode: &LegacyAnalyzedBytecode,
+        static_costs: &[u64; 256],
+    ) -> Option<Arc<CompiledCode>> {
+        match self
+            .entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&code_hash)
+        {
+            Some(CachedCode {
+                bytecode: cached,
+                entry: Entry::Compiled(code),
+            }) if cached == bytecode.bytecode() && code.static_costs() == static_costs => {
+                Some(code.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Records an execution of the bytecode and returns its compiled code.
+    ///
+    /// Bytecode is compiled when it was executed `threshold` times before, code compiled with
+    /// other static gas costs is recompiled. Returns `None` if the bytecode is not hot yet, is
+    /// compiled by another thread, can not be compiled, or if the code hash is not its hash.
+    pub fn get_or_compile(
+        &self,
+        code_hash: B256,
+        bytecode: &LegacyAnalyzedBytecode,
+        static_costs: &[u64; 256],
+        threshold: u64,
+    ) -> Option<Arc<CompiledCode>> {
+        {
+            let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+            if !entries.contains(&code_hash) {
+                // The hash is checked once, later executions compare the bytecode.
+                if keccak256(bytecode.original_byte_slice()) != code_hash {
+                    return None;
+                }
+                entries.put(
+                    code_hash,
+                    CachedCode {
+                        bytecode: bytecode.bytecode().clone(),
+                        entry: Entry::Executed(0),
+                    },
+                );
+            }
+            let cached = entries.get_mut(&code_hash).expect("entry is inserted");
+            if cached.bytecode != *bytecode.bytecode() {
+                return None;
+            }
+            match &mut cached.entry {
+                Entry::Executed(executed) if *executed < threshold => {
+                    *executed += 1;
+                    return None;
+                }
+                Entry::Compiled(code) if code.static_costs() == static_costs => {
+                    return Some(code.clone())
+                }
+                Entry::Compiling | Entry::Failed => return None,
+                entry => *entry = Entry::Compiling,
+            }
+        }
+        self.compile(code_hash, bytecode, static_costs)
+    }
+
+    /// Compiles the bytecode and replaces the [`Entry::Compiling`] with the result.
+    ///
+    /// Compiles without holding the lock, other threads interpret the code meanwhile.
+    fn compile(
+        &self,
+        code_hash: B256,
+        bytecode: &LegacyAnalyzedBytecode,
+        static_costs: &[u64; 256],
+    ) -> Option<Arc<CompiledCode>> {
+        let (entry, code) = match compile(bytecode, static_costs) {
+            Ok(code) => {
+                let code = Arc::new(code);
+                (Entry::Compiled(code.clone()), Some(code))
+            }
+            Err(_) => (Entry::Failed, None),
+        };
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .put(
+                code_hash,
+                CachedCode {
+                    bytecode: bytecode.bytecode().clone(),
+                    entry,
+                },
+            );
+        code
+    }
+
+    /// Returns the number of compiled codes.
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|(_, cached)| matches!(cached.entry, Entry::Compiled(_)))
+            .count()
+    }
+
+    /// Returns `true` if no code is compiled.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all compiled codes and execution counts.
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytecode::{opcode::*, LegacyRawBytecode};
+
+    fn legacy(code: &[u8]) -> (B256, LegacyAnalyzedBytecode) {
+        (
+            keccak256(code),
+            LegacyRawBytecode(code.to_vec().into()).into_analyzed(),
+        )
+    }
+
+    #[test]
+    fn compiles_hot_code() {
+        let cache = JitCache::new();
+        let (hash, bytecode) = legacy(&[PUSH0, PUSH0, ADD, POP]);
+        let costs = [3; 256];
+        // Compiled on the third execution.
+        assert!(cache.get_or_compile(hash, &bytecode, &costs, 2).is_none());
+        assert!(cache.get_or_compile(hash, &bytecode, &costs, 2).is_none());
+        let code = cache.get_or_compile(hash, &bytecode, &costs, 2).unwrap();
+        assert!(Arc::ptr_eq(
+            &code,
+            &cache.get(hash, &bytecode, &costs).unwrap()
+        ));
+        // Code compiled with other costs is recompiled.
+        assert!(cache.get(hash, &bytecode, &[2; 256]).is_none());
+        let code = cache.get_or_compile(hash, &bytecode, &[2; 256], 2).unwrap();
+        assert_eq!(code.static_costs(), &[2; 256]);
+        assert_eq!(cache.len(), 1);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn compiles_once() {
+        let cache = JitCache::new();
+        let (hash, bytecode) = legacy(&[PUSH0, POP]);
+        assert!(cache
+            .get_or_compile(hash, &bytecode, &[3; 256], 1)
+            .is_none());
+        // Another thread crossed the threshold and compiles the code.
+        cache.entries.lock().unwrap().get_mut(&hash).unwrap().entry = Entry::Compiling;
+        assert!(cache
+            .get_or_compile(hash, &bytecode, &[3; 256], 1)
+            .is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn checks_code_hash() {
+        let cache = JitCache::new();
+        let (hash, bytecode) = legacy(&[PUSH0, POP]);
+        let (_, other) = legacy(&[PUSH0, PUSH0, ADD, POP]);
+        // Bytecode under a wrong hash is not tracked.
+        assert!(cache.get_or_compile(hash, &other, &[3; 256], 0).is_none());
+        assert!(cache.entries.lock().unwrap().is_empty());
+
+        // Code compiled for the hash is not returned for other bytecode.
+        assert!(cache
+            .get_or_compile(hash, &bytecode, &[3; 256], 0)
+            .is_some());
+        assert!(cache.get(hash, &other, &[3; 256]).is_none());
+        assert!(cache.get_or_compile(hash, &other, &[3; 256], 0).is_none());
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = JitCache::with_capacity(2);
+        let [a, b, c] = [ADD, MUL, SUB].map(|op| legacy(&[PUSH0, PUSH0, op, POP]));
+        let costs = [3; 256];
+        cache.get_or_compile(a.0, &a.1, &costs, 0).unwrap();
+        cache.get_or_compile(b.0, &b.1, &costs, 0).unwrap();
+        // Use `a`, so that `b` is evicted.
+        assert!(cache.get(a.0, &a.1, &costs).is_some());
+        cache.get_or_compile(c.0, &c.1, &costs, 0).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(a.0, &a.1, &costs).is_some());
+        assert!(cache.get(b.0, &b.1, &costs).is_none());
+        assert!(cache.get(c.0, &c.1, &costs).is_some());
+    }
+}
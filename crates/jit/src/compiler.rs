@@ -0,0 +1,745 @@
+//! Compilation of legacy bytecode with Cranelift.
+use crate::runtime::{
+    CompiledFn, Env, EXIT_FALLBACK, EXIT_INVALID_JUMP, EXIT_OUT_OF_GAS, EXIT_STACK_OVERFLOW,
+    EXIT_STACK_UNDERFLOW, EXIT_STOP, STEP_CONTINUE,
+};
+use bytecode::{opcode::*, JumpTable, LegacyAnalyzedBytecode};
+use core::{fmt, mem::offset_of, mem::ManuallyDrop};
+use cranelift_codegen::{
+    ir::{
+        condcodes::IntCC, types, AbiParam, Block, InstBuilder, MemFlags, SigRef, Signature,
+        UserFuncName, Value,
+    },
+    settings::{self, Configurable},
+};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Switch, Variable};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, Linkage, Module};
+use interpreter::STACK_LIMIT;
+use primitives::{HashMap, U256};
+
+/// Legacy bytecode compiled to native code.
+///
+/// Executed with [`CompiledCode::run`].
+pub struct CompiledCode {
+    module: ManuallyDrop<JITModule>,
+    pub(crate) function: CompiledFn,
+    static_costs: Box<[u64; 256]>,
+}
+
+// SAFETY: The module is not modified after the code is finalized and the code does not have
+// any state of its own.
+unsafe impl Send for CompiledCode {}
+unsafe impl Sync for CompiledCode {}
+
+impl CompiledCode {
+    /// Returns the static gas costs of the instructions the code was compiled with.
+    pub fn static_costs(&self) -> &[u64; 256] {
+        &self.static_costs
+    }
+}
+
+impl fmt::Debug for CompiledCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompiledCode").finish_non_exhaustive()
+    }
+}
+
+impl Drop for CompiledCode {
+    fn drop(&mut self) {
+        // SAFETY: The function is not called after the code is dropped.
+        unsafe { ManuallyDrop::take(&mut self.module).free_memory() }
+    }
+}
+
+/// Errors that make bytecode not compilable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CompileError {
+    /// Host is not a 64-bit little endian target supported by Cranelift.
+    UnsupportedHost,
+    /// Cranelift failed to compile the code.
+    Codegen,
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::UnsupportedHost => "Unsupported host for native compilation",
+            Self::Codegen => "Failed to compile bytecode",
+        };
+        f.write_str(s)
+    }
+}
+
+impl core::error::Error for CompileError {}
+
+/// Compiles the bytecode with the static gas costs of the instruction table.
+///
+/// Stack, control flow and arithmetic instructions are compiled to native code and charge the
+/// static gas of the instruction, other instructions are executed with the instruction table.
+pub fn compile(
+    bytecode: &LegacyAnalyzedBytecode,
+    static_costs: &[u64; 256],
+) -> Result<CompiledCode, CompileError> {
+    if !cfg!(all(target_endian = "little", target_pointer_width = "64")) {
+        return Err(CompileError::UnsupportedHost);
+    }
+    let mut flags = settings::builder();
+    flags
+        .set("opt_level", "speed")
+        .map_err(|_| CompileError::UnsupportedHost)?;
+    let isa = cranelift_native::builder()
+        .map_err(|_| CompileError::UnsupportedHost)?
+        .finish(settings::Flags::new(flags))
+        .map_err(|_| CompileError::UnsupportedHost)?;
+    let mut module = JITModule::new(JITBuilder::with_isa(isa, default_libcall_names()));
+
+    match define(&mut module, bytecode, static_costs) {
+        Ok(function) => Ok(CompiledCode {
+            module: ManuallyDrop::new(module),
+            function,
+            static_costs: Box::new(*static_costs),
+        }),
+        Err(err) => {
+            // SAFETY: No function of the module was returned.
+            unsafe { module.free_memory() };
+            Err(err)
+        }
+    }
+}
+
+/// Defines and finalizes the function of the bytecode.
+fn define(
+    module: &mut JITModule,
+    bytecode: &LegacyAnalyzedBytecode,
+    static_costs: &[u64; 256],
+) -> Result<CompiledFn, CompileError> {
+    let mut signature = module.make_signature();
+    signature.params.push(AbiParam::new(types::I64));
+    signature.params.push(AbiParam::new(types::I64));
+    signature.returns.push(AbiParam::new(types::I32));
+
+    let id = module
+        .declare_function("evm", Linkage::Local, &signature)
+        .map_err(|_| CompileError::Codegen)?;
+    let mut context = module.make_context();
+    context.func.signature = signature.clone();
+    context.func.name = UserFuncName::user(0, id.as_u32());
+    let mut function_context = FunctionBuilderContext::new();
+    Translator::new(
+        FunctionBuilder::new(&mut context.func, &mut function_context),
+        signature,
+        bytecode,
+        static_costs,
+    )
+    .translate();
+
+    module
+        .define_function(id, &mut context)
+        .map_err(|_| CompileError::Codegen)?;
+    module.clear_context(&mut context);
+    module
+        .finalize_definitions()
+        .map_err(|_| CompileError::Codegen)?;
+    let function = module.get_finalized_function(id);
+    // SAFETY: The function was declared with the signature of `CompiledFn`.
+    Ok(unsafe { core::mem::transmute::<*const u8, CompiledFn>(function) })
+}
+
+/// Flags of stack and environment accesses, the stack is only aligned to 8 bytes.
+fn flags() -> MemFlags {
+    MemFlags::new().with_notrap()
+}
+
+/// 256-bit word as low and high 128-bit halves.
+#[derive(Clone, Copy)]
+struct Word {
+    lo: Value,
+    hi: Value,
+}
+
+/// Translates the bytecode to a Cranelift function.
+///
+/// The stack length and the remaining gas are kept in variables and written to the [`Env`]
+/// before the instruction table is called and when the function returns.
+struct Translator<'a> {
+    builder: FunctionBuilder<'a>,
+    code: &'a [u8],
+    jump_table: &'a JumpTable,
+    static_costs: &'a [u64; 256],
+    step_signature: SigRef,
+    env: Value,
+    /// Program counter the function is called with.
+    entry_pc: Value,
+    stack: Value,
+    step: Value,
+    len: Variable,
+    gas: Variable,
+    /// Blocks of the jump destinations, entry points and `JUMPI` fallthroughs.
+    labels: HashMap<usize, Block>,
+    jump_destinations: Vec<usize>,
+    exits: Vec<(u32, Block)>,
+    /// Block that jumps to the jump destination of its argument.
+    dispatch: Option<Block>,
+}
+
+impl<'a> Translator<'a> {
+    /// Creates the entry block that loads the environment.
+    fn new(
+        mut builder: FunctionBuilder<'a>,
+        step_signature: Signature,
+        bytecode: &'a LegacyAnalyzedBytecode,
+        static_costs: &'a [u64; 256],
+    ) -> Self {
+        let entry = builder.create_block();
+        builder.append_block_params_for_function_params(entry);
+        builder.switch_to_block(entry);
+        let &[env, entry_pc] = builder.block_params(entry) else {
+            unreachable!("function has two parameters")
+        };
+        let load = |builder: &mut FunctionBuilder<'_>, offset: usize| {
+            builder.ins().load(types::I64, flags(), env, offset as i32)
+        };
+        let stack = load(&mut builder, offset_of!(Env, stack));
+        let step = load(&mut builder, offset_of!(Env, step));
+        let (len, gas) = (Variable::from_u32(0), Variable::from_u32(1));
+        builder.declare_var(len, types::I64);
+        builder.declare_var(gas, types::I64);
+        let value = load(&mut builder, offset_of!(Env, stack_len));
+        builder.def_var(len, value);
+        let value = load(&mut builder, offset_of!(Env, gas_remaining));
+        builder.def_var(gas, value);
+        let step_signature = builder.import_signature(step_signature);
+        Self {
+            builder,
+            code: bytecode.bytecode(),
+            jump_table: bytecode.jump_table(),
+            static_costs,
+            step_signature,
+            env,
+            entry_pc,
+            stack,
+            step,
+            len,
+            gas,
+            labels: HashMap::default(),
+            jump_destinations: Vec::new(),
+            exits: Vec::new(),
+            dispatch: None,
+        }
+    }
+
+    fn translate(mut self) {
+        // Execution starts at the first instruction and resumes after calls and creates.
+        let mut entries = vec![0];
+        let mut pc = 0;
+        while pc < self.code.len() {
+            let opcode = self.code[pc];
+            if matches!(
+                opcode,
+                CALL | CALLCODE | DELEGATECALL | STATICCALL | CREATE | CREATE2
+            ) {
+                entries.push(pc + 1);
+            }
+            if self.jump_table.is_valid(pc) {
+                self.jump_destinations.push(pc);
+            }
+            pc = next_pc(pc, opcode);
+        }
+        let mut switch = Switch::new();
+        for &entry in &entries {
+            let block = self.label(entry);
+            switch.set_entry(entry as u128, block);
+        }
+        for pc in self.jump_destinations.clone() {
+            self.label(pc);
+        }
+        let fallback = self.exit(EXIT_FALLBACK);
+        switch.emit(&mut self.builder, self.entry_pc, fallback);
+
+        let mut reachable = false;
+        let mut pushed = None;
+        let mut pc = 0;
+        while pc < self.code.len() {
+            let opcode = self.code[pc];
+            if let Some(&block) = self.labels.get(&pc) {
+                if reachable {
+                    self.builder.ins().jump(block, &[]);
+                }
+                self.builder.switch_to_block(block);
+                reachable = true;
+                pushed = None;
+            }
+            if reachable {
+                (reachable, pushed) = self.instruction(pc, opcode, pushed);
+            }
+            pc = next_pc(pc, opcode);
+        }
+        if reachable {
+            // Padding ends with `STOP`, this is not reached.
+            self.exit_here(EXIT_STOP);
+        }
+
+        self.finish();
+    }
+
+    /// Translates the instruction.
+    ///
+    /// `pushed` is the value of the previous instruction if it was a `PUSH`. Returns whether the
+    /// next instruction is reachable and the value if this is a `PUSH`.
+    fn instruction(&mut self, pc: usize, opcode: u8, pushed: Option<U256>) -> (bool, Option<U256>) {
+        let native = matches!(
+            opcode,
+            STOP | ADD
+                | SUB
+                | LT
+                | GT
+                | EQ
+                | ISZERO
+                | AND
+                | OR
+                | XOR
+                | NOT
+                | POP
+                | JUMP
+                | JUMPI
+                | PC
+                | GAS
+                | JUMPDEST
+                | PUSH1..=PUSH32
+                | DUP1..=DUP16
+                | SWAP1..=SWAP16
+        );
+        if !native {
+            self.call_step(pc);
+            return (true, None);
+        }
+        self.charge(self.static_costs[opcode as usize]);
+        match opcode {
+            STOP => {
+                self.exit_here(EXIT_STOP);
+                return (false, None);
+            }
+            ADD => self.binary(|b, x, y| {
+                let lo = b.ins().iadd(x.lo, y.lo);
+                let carry = b.ins().icmp(IntCC::UnsignedLessThan, lo, x.lo);
+                let carry = b.ins().uextend(types::I128, carry);
+                let hi = b.ins().iadd(x.hi, y.hi);
+                let hi = b.ins().iadd(hi, carry);
+                Word { lo, hi }
+            }),
+            SUB => self.binary(|b, x, y| {
+                let lo = b.ins().isub(x.lo, y.lo);
+                let borrow = b.ins().icmp(IntCC::UnsignedLessThan, x.lo, y.lo);
+                let borrow = b.ins().uextend(types::I128, borrow);
+                let hi = b.ins().isub(x.hi, y.hi);
+                let hi = b.ins().isub(hi, borrow);
+                Word { lo, hi }
+            }),
+            LT => self.binary(|b, x, y| {
+                let lt = less_than(b, x, y);
+                bool_word(b, lt)
+            }),
+            GT => self.binary(|b, x, y| {
+                let gt = less_than(b, y, x);
+                bool_word(b, gt)
+            }),
+            EQ => self.binary(|b, x, y| {
+                let lo = b.ins().icmp(IntCC::Equal, x.lo, y.lo);
+                let hi = b.ins().icmp(IntCC::Equal, x.hi, y.hi);
+                let eq = b.ins().band(lo, hi);
+                bool_word(b, eq)
+            }),
+            AND => self.binary(|b, x, y| Word {
+                lo: b.ins().band(x.lo, y.lo),
+                hi: b.ins().band(x.hi, y.hi),
+            }),
+            OR => self.binary(|b, x, y| Word {
+                lo: b.ins().bor(x.lo, y.lo),
+                hi: b.ins().bor(x.hi, y.hi),
+            }),
+            XOR => self.binary(|b, x, y| Word {
+                lo: b.ins().bxor(x.lo, y.lo),
+                hi: b.ins().bxor(x.hi, y.hi),
+            }),
+            ISZERO => self.unary(|b, x| {
+                let is_zero = is_zero(b, x);
+                bool_word(b, is_zero)
+            }),
+            NOT => self.unary(|b, x| Word {
+                lo: b.ins().bnot(x.lo),
+                hi: b.ins().bnot(x.hi),
+            }),
+            POP => {
+                self.require_len(1, EXIT_STACK_UNDERFLOW);
+                self.add_len(-1);
+            }
+            JUMP => {
+                self.require_len(1, EXIT_STACK_UNDERFLOW);
+                let target = self.target(pushed);
+                self.add_len(-1);
+                self.jump(target);
+                return (false, None);
+            }
+            JUMPI => {
+                self.require_len(2, EXIT_STACK_UNDERFLOW);
+                let target = self.target(pushed);
+                let top = self.top();
+                let condition = self.load_word(top, 2);
+                self.add_len(-2);
+                let is_zero = is_zero(&mut self.builder, condition);
+                let fallthrough = self.label(next_pc(pc, opcode));
+                let taken = self.builder.create_block();
+                self.builder
+                    .ins()
+                    .brif(is_zero, fallthrough, &[], taken, &[]);
+                self.builder.switch_to_block(taken);
+                self.jump(target);
+                return (false, None);
+            }
+            PC => self.push(U256::from(pc)),
+            GAS => {
+                self.require_space();
+                let gas = self.builder.use_var(self.gas);
+                let zero = self.builder.ins().iconst(types::I64, 0);
+                let top = self.top();
+                self.store_limbs(top, [gas, zero, zero, zero]);
+                self.add_len(1);
+            }
+            JUMPDEST => {}
+            PUSH1..=PUSH32 => {
+                // Immediates are read from the padded bytecode, missing bytes are zero.
+                let size = (opcode - PUSH0) as usize;
+                let immediate = &self.code[(pc + 1).min(self.code.len())..];
+                let mut bytes = [0; 32];
+                let len = size.min(immediate.len());
+                bytes[..len].copy_from_slice(&immediate[..len]);
+                let value = U256::from_be_slice(&bytes[..size]);
+                self.push(value);
+                return (true, Some(value));
+            }
+            DUP1..=DUP16 => {
+                let n = (opcode - DUP1 + 1) as i64;
+                let len = self.builder.use_var(self.len);
+                let missing = self.builder.ins().icmp_imm(IntCC::UnsignedLessThan, len, n);
+                self.fail_if(missing, EXIT_STACK_OVERFLOW);
+                self.require_space();
+                let top = self.top();
+                let value = self.load_word(top, n as i32);
+                self.store_word(top, 0, value);
+                self.add_len(1);
+            }
+            SWAP1..=SWAP16 => {
+                let n = (opcode - SWAP1 + 1) as i64;
+                let len = self.builder.use_var(self.len);
+                let missing = self
+                    .builder
+                    .ins()
+                    .icmp_imm(IntCC::UnsignedLessThanOrEqual, len, n);
+                self.fail_if(missing, EXIT_STACK_OVERFLOW);
+                let top = self.top();
+                let a = self.load_word(top, 1);
+                let b = self.load_word(top, n as i32 + 1);
+                self.store_word(top, 1, b);
+                self.store_word(top, n as i32 + 1, a);
+            }
+            _ => unreachable!("opcode is compiled natively"),
+        }
+        (true, None)
+    }
+
+    /// Returns the block of the instruction at `pc`.
+    fn label(&mut self, pc: usize) -> Block {
+        *self
+            .labels
+            .entry(pc)
+            .or_insert_with(|| self.builder.create_block())
+    }
+
+    /// Returns the block that returns the exit code.
+    fn exit(&mut self, code: u32) -> Block {
+        if let Some(&(_, block)) = self.exits.iter().find(|(exit, _)| *exit == code) {
+            return block;
+        }
+        let block = self.builder.create_block();
+        self.exits.push((code, block));
+        block
+    }
+
+    /// Returns the exit code from the current block.
+    fn exit_here(&mut self, code: u32) {
+        let block = self.exit(code);
+        self.builder.ins().jump(block, &[]);
+    }
+
+    /// Returns the exit code if `condition` is true and continues in a new block otherwise.
+    fn fail_if(&mut self, condition: Value, code: u32) {
+        let exit = self.exit(code);
+        let next = self.builder.create_block();
+        self.builder.ins().brif(condition, exit, &[], next, &[]);
+        self.builder.switch_to_block(next);
+    }
+
+    /// Translates the dispatch and the exit blocks.
+    fn finish(mut self) {
+        if let Some(dispatch) = self.dispatch {
+            self.builder.switch_to_block(dispatch);
+            let target = self.builder.block_params(dispatch)[0];
+            let mut switch = Switch::new();
+            for pc in core::mem::take(&mut self.jump_destinations) {
+                switch.set_entry(pc as u128, self.labels[&pc]);
+            }
+            let invalid = self.exit(EXIT_INVALID_JUMP);
+            switch.emit(&mut self.builder, target, invalid);
+        }
+        for (code, block) in core::mem::take(&mut self.exits) {
+            self.builder.switch_to_block(block);
+            self.store_state();
+            let code = self.builder.ins().iconst(types::I32, code as i64);
+            self.builder.ins().return_(&[code]);
+        }
+        self.builder.seal_all_blocks();
+        self.builder.finalize();
+    }
+
+    /// Writes the stack length and the remaining gas to the environment.
+    fn store_state(&mut self) {
+        let len = self.builder.use_var(self.len);
+        self.builder
+            .ins()
+            .store(flags(), len, self.env, offset_of!(Env, stack_len) as i32);
+        let gas = self.builder.use_var(self.gas);
+        self.builder.ins().store(
+            flags(),
+            gas,
+            self.env,
+            offset_of!(Env, gas_remaining) as i32,
+        );
+    }
+
+    /// Reads the stack length and the remaining gas from the environment.
+    fn load_state(&mut self) {
+        let len = self.builder.ins().load(
+            types::I64,
+            flags(),
+            self.env,
+            offset_of!(Env, stack_len) as i32,
+        );
+        self.builder.def_var(self.len, len);
+        let gas = self.builder.ins().load(
+            types::I64,
+            flags(),
+            self.env,
+            offset_of!(Env, gas_remaining) as i32,
+        );
+        self.builder.def_var(self.gas, gas);
+    }
+
+    /// Executes the instruction with the instruction table.
+    fn call_step(&mut self, pc: usize) {
+        self.store_state();
+        let pc = self.builder.ins().iconst(types::I64, pc as i64);
+        let call =
+            self.builder
+                .ins()
+                .call_indirect(self.step_signature, self.step, &[self.env, pc]);
+        let result = self.builder.inst_results(call)[0];
+        self.load_state();
+        let is_continue = self
+            .builder
+            .ins()
+            .icmp_imm(IntCC::Equal, result, STEP_CONTINUE as i64);
+        let next = self.builder.create_block();
+        let exit = self.builder.create_block();
+        self.builder.ins().brif(is_continue, next, &[], exit, &[]);
+        // The step function already wrote the state to the environment.
+        self.builder.switch_to_block(exit);
+        self.builder.ins().return_(&[result]);
+        self.builder.switch_to_block(next);
+    }
+
+    /// Charges the static gas of the instruction.
+    fn charge(&mut self, cost: u64) {
+        if cost == 0 {
+            return;
+        }
+        let gas = self.builder.use_var(self.gas);
+        let out_of_gas = self
+            .builder
+            .ins()
+            .icmp_imm(IntCC::UnsignedLessThan, gas, cost as i64);
+        self.fail_if(out_of_gas, EXIT_OUT_OF_GAS);
+        let gas = self
+            .builder
+            .ins()
+            .iadd_imm(gas, (cost as i64).wrapping_neg());
+        self.builder.def_var(self.gas, gas);
+    }
+
+    /// Exits with `code` if the stack has less than `n` values.
+    fn require_len(&mut self, n: i64, code: u32) {
+        let len = self.builder.use_var(self.len);
+        let missing = self.builder.ins().icmp_imm(IntCC::UnsignedLessThan, len, n);
+        self.fail_if(missing, code);
+    }
+
+    /// Exits with stack overflow if the stack is full.
+    fn require_space(&mut self) {
+        let len = self.builder.use_var(self.len);
+        let full =
+            self.builder
+                .ins()
+                .icmp_imm(IntCC::UnsignedGreaterThanOrEqual, len, STACK_LIMIT as i64);
+        self.fail_if(full, EXIT_STACK_OVERFLOW);
+    }
+
+    fn add_len(&mut self, n: i64) {
+        let len = self.builder.use_var(self.len);
+        let len = self.builder.ins().iadd_imm(len, n);
+        self.builder.def_var(self.len, len);
+    }
+
+    /// Returns the address after the top of the stack.
+    fn top(&mut self) -> Value {
+        let len = self.builder.use_var(self.len);
+        let offset = self.builder.ins().ishl_imm(len, 5);
+        self.builder.ins().iadd(self.stack, offset)
+    }
+
+    /// Loads the value at `depth` from the top, the top value is at depth 1.
+    fn load_word(&mut self, top: Value, depth: i32) -> Word {
+        let offset = -32 * depth;
+        let lo = self.builder.ins().load(types::I128, flags(), top, offset);
+        let hi = self
+            .builder
+            .ins()
+            .load(types::I128, flags(), top, offset + 16);
+        Word { lo, hi }
+    }
+
+    fn store_word(&mut self, top: Value, depth: i32, word: Word) {
+        let offset = -32 * depth;
+        self.builder.ins().store(flags(), word.lo, top, offset);
+        self.builder.ins().store(flags(), word.hi, top, offset + 16);
+    }
+
+    /// Stores the little endian limbs above the top of the stack.
+    fn store_limbs(&mut self, top: Value, limbs: [Value; 4]) {
+        for (i, limb) in limbs.into_iter().enumerate() {
+            self.builder.ins().store(flags(), limb, top, 8 * i as i32);
+        }
+    }
+
+    fn push(&mut self, value: U256) {
+        self.require_space();
+        let limbs = value
+            .as_limbs()
+            .map(|limb| self.builder.ins().iconst(types::I64, limb as i64));
+        let top = self.top();
+        self.store_limbs(top, limbs);
+        self.add_len(1);
+    }
+
+    /// Applies `f` to the top value and the value below it, replaces them with the result.
+    fn binary(&mut self, f: impl FnOnce(&mut FunctionBuilder<'_>, Word, Word) -> Word) {
+        self.require_len(2, EXIT_STACK_UNDERFLOW);
+        let top = self.top();
+        let x = self.load_word(top, 1);
+        let y = self.load_word(top, 2);
+        let result = f(&mut self.builder, x, y);
+        self.store_word(top, 2, result);
+        self.add_len(-1);
+    }
+
+    /// Replaces the top value with the result of `f`.
+    fn unary(&mut self, f: impl FnOnce(&mut FunctionBuilder<'_>, Word) -> Word) {
+        self.require_len(1, EXIT_STACK_UNDERFLOW);
+        let top = self.top();
+        let x = self.load_word(top, 1);
+        let result = f(&mut self.builder, x);
+        self.store_word(top, 1, result);
+    }
+
+    /// Returns the jump target on top of the stack, `pushed` if it was pushed by the previous
+    /// instruction.
+    fn target(&mut self, pushed: Option<U256>) -> Target {
+        match pushed {
+            Some(target) => Target::Constant(target),
+            None => {
+                let top = self.top();
+                Target::Dynamic(self.load_word(top, 1))
+            }
+        }
+    }
+
+    /// Jumps to the target or exits with invalid jump.
+    fn jump(&mut self, target: Target) {
+        match target {
+            Target::Constant(target) => match usize::try_from(target) {
+                Ok(target) if self.jump_table.is_valid(target) => {
+                    let block = self.labels[&target];
+                    self.builder.ins().jump(block, &[]);
+                }
+                _ => self.exit_here(EXIT_INVALID_JUMP),
+            },
+            Target::Dynamic(target) => {
+                let dispatch = match self.dispatch {
+                    Some(dispatch) => dispatch,
+                    None => {
+                        let dispatch = self.builder.create_block();
+                        self.builder.append_block_param(dispatch, types::I64);
+                        *self.dispatch.insert(dispatch)
+                    }
+                };
+                let (lo, hi) = self.builder.ins().isplit(target.lo);
+                let high = self.builder.ins().isplit(target.hi);
+                let high = self.builder.ins().bor(high.0, high.1);
+                let high = self.builder.ins().bor(high, hi);
+                let fits = self.builder.ins().icmp_imm(IntCC::Equal, high, 0);
+                let invalid = self.exit(EXIT_INVALID_JUMP);
+                self.builder.ins().brif(fits, dispatch, &[lo], invalid, &[]);
+            }
+        }
+    }
+}
+
+/// Jump target of `JUMP` and `JUMPI`.
+enum Target {
+    /// Target pushed by the previous instruction.
+    Constant(U256),
+    Dynamic(Word),
+}
+
+/// Returns the program counter of the next instruction.
+fn next_pc(pc: usize, opcode: u8) -> usize {
+    match opcode {
+        PUSH1..=PUSH32 => pc + 1 + (opcode - PUSH0) as usize,
+        _ => pc + 1,
+    }
+}
+
+/// Returns whether `x` is less than `y`.
+fn less_than(builder: &mut FunctionBuilder<'_>, x: Word, y: Word) -> Value {
+    let hi_lt = builder.ins().icmp(IntCC::UnsignedLessThan, x.hi, y.hi);
+    let hi_eq = builder.ins().icmp(IntCC::Equal, x.hi, y.hi);
+    let lo_lt = builder.ins().icmp(IntCC::UnsignedLessThan, x.lo, y.lo);
+    let lo_lt = builder.ins().band(hi_eq, lo_lt);
+    builder.ins().bor(hi_lt, lo_lt)
+}
+
+fn is_zero(builder: &mut FunctionBuilder<'_>, x: Word) -> Value {
+    let (lo, hi) = builder.ins().isplit(x.lo);
+    let lo = builder.ins().bor(lo, hi);
+    let (a, b) = builder.ins().isplit(x.hi);
+    let hi = builder.ins().bor(a, b);
+    let any = builder.ins().bor(lo, hi);
+    builder.ins().icmp_imm(IntCC::Equal, any, 0)
+}
+
+/// Returns the word of the boolean.
+fn bool_word(builder: &mut FunctionBuilder<'_>, value: Value) -> Word {
+    let lo = builder.ins().uextend(types::I128, value);
+    let zero = builder.ins().iconst(types::I64, 0);
+    let hi = builder.ins().uextend(types::I128, zero);
+    Word { lo, hi }
+}
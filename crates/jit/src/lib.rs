@@ -0,0 +1,17 @@
+//! Native compilation of hot legacy bytecode.
+//!
+//! Legacy bytecode that is executed often is compiled with Cranelift to [`CompiledCode`] that
+//! runs instead of the [`Interpreter`][interpreter::Interpreter] run loop. Stack, control flow
+//! and arithmetic instructions are compiled to native code, other instructions call the
+//! instruction table, so results, gas usage and journal effects are the same as with the
+//! interpreter.
+//!
+//! Compiled code is shared by code hash through the [`JitCache`].
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+
+pub mod cache;
+pub mod compiler;
+pub mod runtime;
+
+pub use cache::{JitCache, DEFAULT_JIT_CACHE_SIZE};
+pub use compiler::{compile, CompileError, CompiledCode};
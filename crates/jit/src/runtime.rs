@@ -0,0 +1,259 @@
+//! Execution of compiled code.
+//!
+//! Compiled code keeps the stack length and the remaining gas in registers and exchanges them
+//! with the interpreter through the [`Env`] when it calls the instruction table or returns.
+use crate::CompiledCode;
+use interpreter::{
+    interpreter::EthInterpreter,
+    interpreter_types::{Jumps, LoopControl},
+    table::{CustomInstruction, MeteredInstruction},
+    Host, InstructionResult, Interpreter, InterpreterAction, STACK_LIMIT,
+};
+use primitives::U256;
+
+/// Compiled code called the instruction table, which suspended or halted the execution.
+pub(crate) const EXIT_SUSPEND: u32 = 0;
+/// Execution reached a `STOP`.
+pub(crate) const EXIT_STOP: u32 = 1;
+pub(crate) const EXIT_OUT_OF_GAS: u32 = 2;
+pub(crate) const EXIT_STACK_UNDERFLOW: u32 = 3;
+pub(crate) const EXIT_STACK_OVERFLOW: u32 = 4;
+pub(crate) const EXIT_INVALID_JUMP: u32 = 5;
+/// Execution has to continue in the interpreter at its program counter.
+pub(crate) const EXIT_FALLBACK: u32 = 6;
+
+/// Returned by [`step`] if the compiled code continues after the instruction.
+pub(crate) const STEP_CONTINUE: u32 = 7;
+
+/// Signature of the compiled function, called with the program counter to start at.
+pub(crate) type CompiledFn = unsafe extern "C" fn(env: *mut Env, pc: u64) -> u32;
+
+/// Signature of the function that executes one instruction with the instruction table.
+pub(crate) type StepFn = unsafe extern "C" fn(env: *mut Env, pc: u64) -> u32;
+
+/// State shared between compiled code and the interpreter.
+///
+/// Compiled code accesses the fields by their offsets.
+#[repr(C)]
+pub(crate) struct Env {
+    /// Pointer to the stack buffer with capacity for [`STACK_LIMIT`] values.
+    pub(crate) stack: *mut U256,
+    pub(crate) stack_len: u64,
+    pub(crate) gas_remaining: u64,
+    /// Pointer to the [`StepContext`].
+    pub(crate) context: *mut (),
+    pub(crate) step: StepFn,
+}
+
+/// Interpreter, instruction table and host used by [`step`].
+struct StepContext<'a, H: Host> {
+    interpreter: &'a mut Interpreter<EthInterpreter>,
+    table: &'a [MeteredInstruction<EthInterpreter, H>; 256],
+    host: &'a mut H,
+}
+
+impl CompiledCode {
+    /// Executes the compiled code until it returns or stops.
+    ///
+    /// Results, gas usage and host effects are the same as with [`Interpreter::run`] with the
+    /// same table. The table has to have the static gas costs the code was compiled with and has
+    /// to implement the compiled instructions as the mainnet table does.
+    pub fn run<H: Host>(
+        &self,
+        interpreter: &mut Interpreter<EthInterpreter>,
+        table: &[MeteredInstruction<EthInterpreter, H>; 256],
+        host: &mut H,
+    ) -> InterpreterAction {
+        let stack = interpreter.stack.data_mut();
+        if stack.capacity() < STACK_LIMIT {
+            // Compiled code writes to the stack buffer without growing it.
+            return interpreter.run(table, host);
+        }
+        let stack = stack.as_mut_ptr();
+
+        interpreter
+            .control
+            .set_next_action(InterpreterAction::None, InstructionResult::Continue);
+        let pc = interpreter.bytecode.pc() as u64;
+        let mut env = Env {
+            stack,
+            stack_len: interpreter.stack.len() as u64,
+            gas_remaining: interpreter.control.gas().remaining(),
+            context: core::ptr::null_mut(),
+            step: step::<H>,
+        };
+        let mut context = StepContext {
+            interpreter,
+            table,
+            host,
+        };
+        env.context = (&mut context as *mut StepContext<'_, H>).cast();
+
+        // SAFETY: The stack buffer has capacity for `STACK_LIMIT` values and the context outlives
+        // the call.
+        let exit = unsafe { (self.function)(&mut env, pc) };
+
+        let StepContext {
+            interpreter, host, ..
+        } = context;
+        sync_interpreter(interpreter, &env);
+        let result = match exit {
+            EXIT_SUSPEND => None,
+            EXIT_STOP => Some(InstructionResult::Stop),
+            EXIT_OUT_OF_GAS => Some(InstructionResult::OutOfGas),
+            EXIT_STACK_UNDERFLOW => Some(InstructionResult::StackUnderflow),
+            EXIT_STACK_OVERFLOW => Some(InstructionResult::StackOverflow),
+            EXIT_INVALID_JUMP => Some(InstructionResult::InvalidJump),
+            _ => return interpreter.run(table, host),
+        };
+        if let Some(result) = result {
+            interpreter.control.set_instruction_result(result);
+        }
+        interpreter.take_action()
+    }
+}
+
+/// Writes the stack length and the remaining gas of the compiled code to the interpreter.
+#[inline]
+fn sync_interpreter(interpreter: &mut Interpreter<EthInterpreter>, env: &Env) {
+    // SAFETY: Compiled code only pushes values it wrote and stays within the stack limit.
+    unsafe { interpreter.stack.data_mut().set_len(env.stack_len as usize) };
+    let gas = interpreter.control.gas();
+    // Compiled code only spends gas, this can not fail.
+    let _ = gas.record_cost(gas.remaining() - env.gas_remaining);
+}
+
+/// Executes the instruction at `pc` with the instruction table.
+///
+/// Returns [`STEP_CONTINUE`] if the compiled code continues with the next instruction,
+/// [`EXIT_SUSPEND`] if the instruction suspended or halted the execution and [`EXIT_FALLBACK`]
+/// if the instruction jumped.
+///
+/// # Safety
+///
+/// `env` has to be the environment created by [`CompiledCode::run`] with the same host type.
+unsafe extern "C" fn step<H: Host>(env: *mut Env, pc: u64) -> u32 {
+    let env = &mut *env;
+    let context = &mut *env.context.cast::<StepContext<'_, H>>();
+    let interpreter = &mut *context.interpreter;
+    sync_interpreter(interpreter, env);
+
+    interpreter.bytecode.absolute_jump(pc as usize);
+    let opcode = interpreter.bytecode.opcode();
+    interpreter.bytecode.relative_jump(1);
+    context.table[opcode as usize].exec(interpreter, context.host);
+
+    env.stack_len = interpreter.stack.len() as u64;
+    env.gas_remaining = interpreter.control.gas().remaining();
+    if !interpreter.control.instruction_result().is_continue() {
+        EXIT_SUSPEND
+    } else if interpreter.bytecode.pc() as u64 != pc + 1 {
+        EXIT_FALLBACK
+    } else {
+        STEP_CONTINUE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compile;
+    use bytecode::{opcode::*, Bytecode};
+    use database::CacheDB;
+    use primitives::{Address, TxKind, U256};
+    use revm::{
+        context_interface::{
+            result::{HaltReason, ResultAndState},
+            Cfg,
+        },
+        database_interface::EmptyDB,
+        handler::EthHandler,
+        state::AccountInfo,
+        Context, EvmExec, MainEvm,
+    };
+
+    const CONTRACT: Address = Address::with_last_byte(0xa0);
+    const CALLEE: Address = Address::with_last_byte(0xb0);
+
+    /// Executes the code with the interpreter and compiled, asserts that results and state are
+    /// the same for all gas limits.
+    fn assert_same(
+        code: &[u8],
+        gas_limits: impl IntoIterator<Item = u64>,
+    ) -> ResultAndState<HaltReason> {
+        let code = Bytecode::new_raw(code.to_vec().into());
+        // Stores the call value and returns the caller.
+        let callee = Bytecode::new_raw(
+            [
+                CALLVALUE, PUSH0, SSTORE, CALLER, PUSH0, MSTORE, PUSH1, 0x20, PUSH0, RETURN,
+            ]
+            .into(),
+        );
+        let mut db = CacheDB::new(EmptyDB::default());
+        let info = AccountInfo::new(U256::from(10), 1, code.hash_slow(), code.clone());
+        db.insert_account_info(CONTRACT, info);
+        let info = AccountInfo::new(U256::ZERO, 1, callee.hash_slow(), callee);
+        db.insert_account_info(CALLEE, info);
+        let mut evm = MainEvm::new(
+            Context::builder().with_db(db).modify_tx_chained(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.kind = TxKind::Call(CONTRACT);
+            }),
+            EthHandler::default(),
+        );
+
+        let mut last = None;
+        for gas_limit in gas_limits {
+            evm.context.tx.gas_limit = gas_limit;
+            evm.context.cfg.jit_threshold = None;
+            let expected = evm.exec().unwrap();
+            evm.context.cfg.jit_threshold = Some(0);
+            let result = evm.exec().unwrap();
+            assert_eq!(result, expected, "gas limit {gas_limit}");
+            last = Some(result);
+        }
+        // Frames use the cache of the handler, the code has to be compilable to be compared.
+        let Bytecode::LegacyAnalyzed(legacy) = &code else {
+            unreachable!("raw bytecode is analyzed")
+        };
+        assert!(compile(legacy, &evm.context.cfg.gas_schedule().static_costs).is_ok());
+        last.unwrap()
+    }
+
+    #[test]
+    fn loop_and_call() {
+        let code = [
+            PUSH1, 0x0a, // Loop: decrement the counter and accumulate values.
+            JUMPDEST, PUSH1, 0x01, SWAP1, SUB, DUP1, DUP1, PUSH1, 0x05, LT, SWAP1, PUSH1, 0x03, GT,
+            OR, PUSH1, 0xff, AND, PUSH1, 0x0f, XOR, NOT, ISZERO, PC, EQ, ADD, DUP1, ISZERO, ISZERO,
+            PUSH1, 0x02, JUMPI, // CALL(GAS, CALLEE, 1, 0, 0, 0, 0x20)
+            POP, PUSH1, 0x20, PUSH0, PUSH0, PUSH0, PUSH1, 0x01, PUSH1, 0xb0, GAS, CALL,
+            // Resumes after the call and stores the returned caller.
+            PUSH0, MLOAD, ADD, PUSH1, 0x01, SSTORE, GAS, PUSH1, 0x02, SSTORE, STOP,
+        ];
+        let result = assert_same(&code, (21_000..120_000).step_by(131));
+        assert!(result.result.is_success());
+    }
+
+    #[test]
+    fn halts() {
+        // Stack underflow.
+        assert_same(&[PUSH0, ADD], [30_000]);
+        // Stack overflow of a push loop.
+        assert_same(&[JUMPDEST, PUSH0, PUSH0, PUSH0, JUMP], [30_000, 1_000_000]);
+        // Duplicating or swapping missing values.
+        assert_same(&[PUSH0, DUP2], [30_000]);
+        assert_same(&[PUSH0, PUSH0, SWAP2], [30_000]);
+        // Jump into push data, to a large target and a not taken invalid jump.
+        assert_same(&[PUSH1, 0x5b, PUSH1, 0x01, JUMP], [30_000]);
+        assert_same(&[PUSH1, 0x5b, PUSH1, 0x01, DUP1, POP, JUMP], [30_000]);
+        assert_same(&[PUSH0, NOT, JUMP], [30_000]);
+        assert_same(
+            &[PUSH0, PUSH1, 0x01, JUMPI, PUSH0, JUMPDEST, STOP],
+            [30_000],
+        );
+        // Push data is truncated at the end of the code.
+        assert_same(&[PUSH32, 0x01, 0x02, PUSH0, SSTORE], [30_000, 50_000]);
+        // Reverts through the instruction table.
+        assert_same(&[PUSH0, PUSH0, REVERT], [30_000]);
+    }
+}
@@ -6,7 +6,7 @@ use revm::{
     handler_interface::PrecompileProvider, interpreter::InterpreterResult,
     specification::hardfork::SpecId,
 };
-use std::boxed::Box;
+use std::{boxed::Box, vec::Vec};
 
 pub struct OpPrecompileProvider<CTX, ERROR> {
     precompile_provider: EthPrecompileProvider<CTX, ERROR>,
@@ -25,6 +25,7 @@ impl<CTX, ERROR> OpPrecompileProvider<CTX, ERROR> {
         Self {
             precompile_provider: EthPrecompileProvider {
                 precompiles,
+                moves: Vec::new(),
                 _phantom: core::marker::PhantomData,
             },
         }
@@ -66,7 +67,7 @@ where
     #[inline]
     fn new(context: &mut Self::Context) -> Self {
         let spec = context.cfg().spec();
-        match spec {
+        let mut provider = match spec {
             // No changes
             spec @ (OpSpec::Eth(
                 SpecId::FRONTIER
@@ -99,7 +100,9 @@ where
             OpSpec::Op(OpSpecId::FJORD) => Self::new(fjord()),
             OpSpec::Op(OpSpecId::GRANITE)
             | OpSpec::Eth(SpecId::PRAGUE | SpecId::OSAKA | SpecId::LATEST) => Self::new(granite()),
-        }
+        };
+        provider.precompile_provider.moves = context.cfg().precompile_moves().to_vec();
+        provider
     }
 
     #[inline]
@@ -0,0 +1,124 @@
+//! Database view of the transaction over the multi-version memory.
+use crate::{
+    mv_memory::{Location, MemoryRead, MemoryValue, MvMemory, ReadSet},
+    scheduler::TxIndex,
+};
+use core::fmt;
+use revm::{
+    bytecode::Bytecode,
+    database_interface::DBErrorMarker,
+    primitives::{Address, B256, U256},
+    state::AccountInfo,
+    Database, DatabaseRef,
+};
+
+/// Database of the single transaction execution.
+///
+/// Reads the values written by the lower transactions of the block from the [`MvMemory`]
+/// and falls back to the underlying [`DatabaseRef`], every read location is recorded
+/// in the read set used for validation.
+#[derive(Debug)]
+pub struct VersionedDb<'a, DB> {
+    db: &'a DB,
+    memory: &'a MvMemory,
+    tx_index: TxIndex,
+    read_set: ReadSet,
+}
+
+impl<'a, DB> VersionedDb<'a, DB> {
+    /// Creates the database of the transaction.
+    pub fn new(db: &'a DB, memory: &'a MvMemory, tx_index: TxIndex) -> Self {
+        Self {
+            db,
+            memory,
+            tx_index,
+            read_set: ReadSet::default(),
+        }
+    }
+
+    /// Takes the locations read so far.
+    pub fn take_read_set(&mut self) -> ReadSet {
+        core::mem::take(&mut self.read_set)
+    }
+
+    fn read<E>(&mut self, location: Location) -> Result<MemoryRead, VersionedDbError<E>> {
+        let read = self
+            .memory
+            .read(&location, self.tx_index)
+            .map_err(VersionedDbError::Blocked)?;
+        self.read_set.insert(location, read.origins.clone());
+        Ok(read)
+    }
+}
+
+impl<DB: DatabaseRef> Database for VersionedDb<'_, DB> {
+    type Error = VersionedDbError<DB::Error>;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let read = self.read(Location::Basic(address))?;
+        let mut info = match read.value {
+            Some(MemoryValue::Basic(info)) => info,
+            _ => self.db.basic_ref(address)?,
+        };
+        increment_balance(&mut info, read.increment);
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        // Bytecode is addressed by its hash and can't conflict.
+        Ok(self.db.code_by_hash_ref(code_hash)?)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let slot = self.read(Location::Storage(address, index))?;
+        let reset = self.read(Location::StorageReset(address))?;
+        // Storage wiped after the last write of the slot reads as zero.
+        if reset.tx_index() > slot.tx_index() {
+            return Ok(U256::ZERO);
+        }
+        match slot.value {
+            Some(MemoryValue::Storage(value)) => Ok(value),
+            _ => Ok(self.db.storage_ref(address, index)?),
+        }
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        Ok(self.db.block_hash_ref(number)?)
+    }
+}
+
+/// Adds the balance to the account, creating it if it does not exist.
+pub(crate) fn increment_balance(info: &mut Option<AccountInfo>, increment: U256) {
+    if !increment.is_zero() {
+        let info = info.get_or_insert_with(AccountInfo::default);
+        info.balance = info.balance.saturating_add(increment);
+    }
+}
+
+/// Error of the [`VersionedDb`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VersionedDbError<E> {
+    /// Read the estimate of the lower transaction, execution has to wait for it.
+    Blocked(TxIndex),
+    /// Error of the underlying database.
+    Database(E),
+}
+
+impl<E> From<E> for VersionedDbError<E> {
+    fn from(value: E) -> Self {
+        Self::Database(value)
+    }
+}
+
+impl<E> DBErrorMarker for VersionedDbError<E> {}
+
+impl<E: fmt::Display> fmt::Display for VersionedDbError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Blocked(tx_index) => write!(f, "blocked by transaction {tx_index}"),
+            Self::Database(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: core::error::Error> core::error::Error for VersionedDbError<E> {}
@@ -0,0 +1,504 @@
+//! Parallel block executor.
+use crate::{
+    db::{increment_balance, VersionedDb, VersionedDbError},
+    handler::ParallelPostExecution,
+    mv_memory::{Location, MemoryValue, MvMemory, WriteSet},
+    scheduler::{lock, Scheduler, Task, TxIndex, Version},
+};
+use core::{fmt, num::NonZeroUsize};
+use database::{states::bundle_state::BundleRetention, BundleState, State};
+use revm::{
+    context::{block::BlockEnv, tx::TxEnv, CfgEnv, Context},
+    context_interface::{
+        result::{EVMError, ExecutionResult, HaltReason, InvalidTransaction},
+        Transaction,
+    },
+    handler::{EthExecution, EthHandler, EthPreExecution, EthValidation},
+    primitives::{HashMap, U256},
+    specification::hardfork::SpecId,
+    state::{Account, EvmState},
+    Database, DatabaseCommit, DatabaseRef, EthContext, Evm,
+};
+use std::{sync::Mutex, thread, vec::Vec};
+
+type ParallelContext<'a, DB> = EthContext<VersionedDb<'a, DB>>;
+
+type ParallelEvmError<DB> =
+    EVMError<VersionedDbError<<DB as DatabaseRef>::Error>, InvalidTransaction>;
+
+type ParallelEvm<'a, DB> = Evm<
+    ParallelEvmError<DB>,
+    ParallelContext<'a, DB>,
+    EthHandler<
+        ParallelContext<'a, DB>,
+        ParallelEvmError<DB>,
+        EthValidation<ParallelContext<'a, DB>, ParallelEvmError<DB>>,
+        EthPreExecution<ParallelContext<'a, DB>, ParallelEvmError<DB>>,
+        EthExecution<ParallelContext<'a, DB>, ParallelEvmError<DB>>,
+        ParallelPostExecution<ParallelContext<'a, DB>, ParallelEvmError<DB>>,
+    >,
+>;
+
+/// Output of the validated transaction execution.
+#[derive(Debug)]
+struct TxOutput {
+    result: ExecutionResult<HaltReason>,
+    state: EvmState,
+    reward: U256,
+}
+
+type TxOutcome<E> = Result<TxOutput, EVMError<E, InvalidTransaction>>;
+
+/// Output of the block execution.
+#[derive(Debug)]
+pub struct BlockOutput {
+    /// Results of the transactions in the block order.
+    pub results: Vec<ExecutionResult<HaltReason>>,
+    /// State changes of the whole block.
+    pub bundle: BundleState,
+}
+
+/// Error of the block execution, the first transaction that failed in the block order.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BlockExecutionError<E> {
+    /// Index of the failed transaction.
+    pub tx_index: TxIndex,
+    /// Error of the transaction.
+    pub error: EVMError<E, InvalidTransaction>,
+}
+
+impl<E: fmt::Display> fmt::Display for BlockExecutionError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "transaction {} failed: {}", self.tx_index, self.error)
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> core::error::Error for BlockExecutionError<E> {}
+
+/// Block executor that runs transactions speculatively on multiple threads.
+///
+/// Implements Block-STM: transactions are executed optimistically against the [`MvMemory`],
+/// their read sets are validated after execution and conflicting transactions are
+/// re-executed. The output is the same as executing the transactions sequentially with
+/// [`State`] on top of the same database.
+#[derive(Debug)]
+pub struct ParallelExecutor<DB> {
+    db: DB,
+    concurrency: NonZeroUsize,
+}
+
+impl<DB> ParallelExecutor<DB> {
+    /// Creates the executor with a worker thread per available CPU.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            concurrency: thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+        }
+    }
+
+    /// Sets the number of worker threads.
+    pub fn with_concurrency(self, concurrency: NonZeroUsize) -> Self {
+        Self {
+            concurrency,
+            ..self
+        }
+    }
+
+    /// Returns the underlying database.
+    pub fn db(&self) -> &DB {
+        &self.db
+    }
+}
+
+impl<DB> ParallelExecutor<DB>
+where
+    DB: DatabaseRef + Sync,
+    DB::Error: Send,
+{
+    /// Executes the transactions of the block.
+    pub fn execute(
+        &self,
+        cfg: &CfgEnv,
+        block: &BlockEnv,
+        txs: &[TxEnv],
+    ) -> Result<BlockOutput, BlockExecutionError<DB::Error>> {
+        let scheduler = Scheduler::new(txs.len());
+        let memory = MvMemory::new(txs.len());
+        let outputs: Vec<Mutex<Option<TxOutcome<DB::Error>>>> =
+            (0..txs.len()).map(|_| Mutex::default()).collect();
+
+        let worker = Worker {
+            db: &self.db,
+            cfg,
+            block,
+            txs,
+            scheduler: &scheduler,
+            memory: &memory,
+            outputs: &outputs,
+        };
+        let workers = self.concurrency.get().min(txs.len());
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| worker.run());
+            }
+        });
+
+        self.finalize(cfg, block, outputs)
+    }
+
+    /// Commits the validated outputs in the block order to build the bundle.
+    fn finalize(
+        &self,
+        cfg: &CfgEnv,
+        block: &BlockEnv,
+        outputs: Vec<Mutex<Option<TxOutcome<DB::Error>>>>,
+    ) -> Result<BlockOutput, BlockExecutionError<DB::Error>> {
+        let mut state = State::builder()
+            .with_database_ref(&self.db)
+            .with_bundle_update()
+            .build();
+        state.set_state_clear_flag(cfg.spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+
+        let mut results = Vec::with_capacity(outputs.len());
+        for (tx_index, output) in outputs.into_iter().enumerate() {
+            let error = |error| BlockExecutionError { tx_index, error };
+            let output = output
+                .into_inner()
+                .unwrap_or_else(|e| e.into_inner())
+                .expect("validated transaction has an output")
+                .map_err(error)?;
+
+            for address in output.state.keys() {
+                state
+                    .load_cache_account(*address)
+                    .map_err(|e| error(EVMError::Database(e)))?;
+            }
+            state.commit(output.state);
+
+            let beneficiary = block.beneficiary;
+            let mut account = state
+                .basic(beneficiary)
+                .map_err(|e| error(EVMError::Database(e)))?
+                .map(Account::from)
+                .unwrap_or_else(Account::new_not_existing);
+            account.info.balance = account.info.balance.saturating_add(output.reward);
+            account.mark_touch();
+            state.commit(HashMap::from_iter([(beneficiary, account)]));
+
+            results.push(output.result);
+        }
+
+        state.merge_transitions(BundleRetention::Reverts);
+        Ok(BlockOutput {
+            results,
+            bundle: state.take_bundle(),
+        })
+    }
+}
+
+/// State shared by the worker threads.
+struct Worker<'a, DB: DatabaseRef> {
+    db: &'a DB,
+    cfg: &'a CfgEnv,
+    block: &'a BlockEnv,
+    txs: &'a [TxEnv],
+    scheduler: &'a Scheduler,
+    memory: &'a MvMemory,
+    outputs: &'a [Mutex<Option<TxOutcome<DB::Error>>>],
+}
+
+impl<DB: DatabaseRef> Worker<'_, DB> {
+    fn run(&self) {
+        let mut task = None;
+        while !self.scheduler.done() {
+            task = match task {
+                Some(Task::Execution(version)) => self.try_execute(version),
+                Some(Task::Validation(version)) => self.validate(version),
+                None => self.scheduler.next_task(),
+            };
+        }
+    }
+
+    fn try_execute(&self, version: Version) -> Option<Task> {
+        let tx_index = version.tx_index;
+        loop {
+            let mut evm = self.evm(tx_index);
+            let result = evm.transact();
+            let read_set = evm.context.journaled_state.database.take_read_set();
+
+            let (outcome, write_set) = match result {
+                Ok(result) => {
+                    let reward = self.reward(tx_index, result.result.gas_used());
+                    let write_set = self.write_set(&result.state, reward);
+                    let output = TxOutput {
+                        result: result.result,
+                        state: result.state,
+                        reward,
+                    };
+                    (Ok(output), write_set)
+                }
+                Err(EVMError::Database(VersionedDbError::Blocked(blocking))) => {
+                    if self.scheduler.add_dependency(tx_index, blocking) {
+                        return None;
+                    }
+                    // Blocking transaction finished in the meantime.
+                    continue;
+                }
+                Err(error) => {
+                    let error = error.map_db_err(|e| match e {
+                        VersionedDbError::Database(e) => e,
+                        VersionedDbError::Blocked(_) => unreachable!(),
+                    });
+                    (Err(error), WriteSet::new())
+                }
+            };
+
+            *lock(&self.outputs[tx_index]) = Some(outcome);
+            let wrote_new_location = self.memory.record(version, read_set, write_set);
+            return self.scheduler.finish_execution(version, wrote_new_location);
+        }
+    }
+
+    fn validate(&self, version: Version) -> Option<Task> {
+        let valid = self.memory.validate_read_set(version.tx_index);
+        let aborted = !valid && self.scheduler.try_validation_abort(version);
+        if aborted {
+            self.memory.convert_writes_to_estimates(version.tx_index);
+        }
+        self.scheduler.finish_validation(version.tx_index, aborted)
+    }
+
+    fn evm(&self, tx_index: TxIndex) -> ParallelEvm<'_, DB> {
+        let db = VersionedDb::new(self.db, self.memory, tx_index);
+        Evm::new(
+            Context::builder()
+                .with_db(db)
+                .with_block(self.block.clone())
+                .with_tx(self.txs[tx_index].clone())
+                .with_cfg(self.cfg.clone()),
+            EthHandler::new(
+                EthValidation::new(),
+                EthPreExecution::new(),
+                EthExecution::new(),
+                ParallelPostExecution::new(),
+            ),
+        )
+    }
+
+    /// Returns the beneficiary reward deferred by [`ParallelPostExecution`].
+    fn reward(&self, tx_index: TxIndex, gas_used: u64) -> U256 {
+        let basefee = self.block.basefee as u128;
+        let effective_gas_price = self.txs[tx_index].effective_gas_price(basefee);
+        // EIP-1559 discard basefee for coinbase transfer.
+        let coinbase_gas_price = if self.cfg.spec.is_enabled_in(SpecId::LONDON) {
+            effective_gas_price.saturating_sub(basefee)
+        } else {
+            effective_gas_price
+        };
+        U256::from(coinbase_gas_price * gas_used as u128)
+    }
+
+    /// Returns the locations written by the transaction, mirroring how [`State`] applies it.
+    fn write_set(&self, state: &EvmState, reward: U256) -> WriteSet {
+        let has_state_clear = self.cfg.spec.is_enabled_in(SpecId::SPURIOUS_DRAGON);
+        let beneficiary = self.block.beneficiary;
+        let mut write_set = WriteSet::new();
+        let mut rewarded = false;
+
+        for (address, account) in state {
+            if !account.is_touched() {
+                continue;
+            }
+            let address = *address;
+            let mut info = if account.is_selfdestructed()
+                || (!account.is_created() && account.is_empty() && has_state_clear)
+            {
+                write_set.push((Location::StorageReset(address), MemoryValue::StorageReset));
+                None
+            } else {
+                if account.is_created() {
+                    write_set.push((Location::StorageReset(address), MemoryValue::StorageReset));
+                }
+                write_set.extend(account.changed_storage_slots().map(|(index, slot)| {
+                    (
+                        Location::Storage(address, *index),
+                        MemoryValue::Storage(slot.present_value),
+                    )
+                }));
+                Some(account.info.clone())
+            };
+            if address == beneficiary {
+                rewarded = true;
+                increment_balance(&mut info, reward);
+            }
+            write_set.push((Location::Basic(address), MemoryValue::Basic(info)));
+        }
+
+        if !rewarded {
+            write_set.push((
+                Location::Basic(beneficiary),
+                MemoryValue::BalanceIncrement(reward),
+            ));
+        }
+        write_set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::CacheDB;
+    use revm::{
+        bytecode::{opcode, Bytecode},
+        database_interface::EmptyDB,
+        handler::EthHandler,
+        primitives::{Address, Bytes, TxKind},
+        state::AccountInfo,
+        EvmCommit, EvmExec, MainEvm,
+    };
+
+    const COUNTER: Address = Address::with_last_byte(0xc0);
+    const BENEFICIARY: Address = Address::with_last_byte(0xbe);
+
+    fn block() -> BlockEnv {
+        BlockEnv {
+            beneficiary: BENEFICIARY,
+            basefee: 1,
+            ..Default::default()
+        }
+    }
+
+    fn db(senders: usize) -> CacheDB<EmptyDB> {
+        // Increments the first storage slot.
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            opcode::PUSH0,
+            opcode::SLOAD,
+            opcode::PUSH1,
+            0x01,
+            opcode::ADD,
+            opcode::PUSH0,
+            opcode::SSTORE,
+            opcode::STOP,
+        ]));
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(
+            COUNTER,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        for sender in 0..senders {
+            let info = AccountInfo::from_balance(U256::from(1_000_000_000u64));
+            db.insert_account_info(Address::with_last_byte(sender as u8 + 1), info);
+        }
+        db
+    }
+
+    fn tx(caller: u8, nonce: u64, kind: TxKind, value: u64) -> TxEnv {
+        TxEnv {
+            caller: Address::with_last_byte(caller),
+            nonce,
+            kind,
+            value: U256::from(value),
+            gas_limit: 100_000,
+            gas_price: 2,
+            ..Default::default()
+        }
+    }
+
+    fn execute_sequential(db: &CacheDB<EmptyDB>, txs: &[TxEnv]) -> BlockOutput {
+        let mut state = State::builder()
+            .with_database_ref(db)
+            .with_bundle_update()
+            .build();
+        let mut evm = MainEvm::new(
+            Context::builder().with_db(&mut state).with_block(block()),
+            EthHandler::default(),
+        );
+        let results = txs
+            .iter()
+            .map(|tx| {
+                evm.set_tx(tx.clone());
+                evm.exec_commit().unwrap()
+            })
+            .collect();
+        drop(evm);
+        state.merge_transitions(BundleRetention::Reverts);
+        BlockOutput {
+            results,
+            bundle: state.take_bundle(),
+        }
+    }
+
+    fn assert_same_as_sequential(db: CacheDB<EmptyDB>, txs: Vec<TxEnv>) {
+        let expected = execute_sequential(&db, &txs);
+        for concurrency in [1, 2, 4, 8] {
+            let output = ParallelExecutor::new(&db)
+                .with_concurrency(NonZeroUsize::new(concurrency).unwrap())
+                .execute(&CfgEnv::default(), &block(), &txs)
+                .unwrap();
+            assert_eq!(output.results, expected.results);
+            assert_eq!(output.bundle, expected.bundle);
+        }
+    }
+
+    #[test]
+    fn empty_block() {
+        let output = ParallelExecutor::new(db(0))
+            .execute(&CfgEnv::default(), &block(), &[])
+            .unwrap();
+        assert!(output.results.is_empty());
+        assert!(output.bundle.is_empty());
+    }
+
+    #[test]
+    fn independent_transfers() {
+        let txs = (1..=32)
+            .map(|sender| {
+                let recipient = Address::with_last_byte(0x80 + sender);
+                tx(sender, 0, TxKind::Call(recipient), 1_000)
+            })
+            .collect();
+        assert_same_as_sequential(db(32), txs);
+    }
+
+    #[test]
+    fn conflicting_transactions() {
+        let mut txs = Vec::new();
+        for nonce in 0..8 {
+            for sender in 1..=8 {
+                // Every transaction increments the same counter slot.
+                txs.push(tx(sender, nonce, TxKind::Call(COUNTER), 0));
+                // And pays the sender of the next transaction.
+                let recipient = Address::with_last_byte(sender % 8 + 1);
+                txs.push(tx(sender, nonce + 8, TxKind::Call(recipient), 100));
+            }
+        }
+        txs.sort_by_key(|tx| (tx.nonce, tx.caller));
+        assert_same_as_sequential(db(8), txs);
+    }
+
+    #[test]
+    fn beneficiary_transfers() {
+        let mut txs = Vec::new();
+        for sender in 1..=8 {
+            txs.push(tx(sender, 0, TxKind::Call(BENEFICIARY), 1_000));
+            txs.push(tx(sender, 1, TxKind::Call(COUNTER), 0));
+        }
+        assert_same_as_sequential(db(8), txs);
+    }
+
+    #[test]
+    fn invalid_transaction() {
+        let txs = [
+            tx(1, 0, TxKind::Call(COUNTER), 0),
+            tx(2, 1, TxKind::Call(COUNTER), 0),
+        ];
+        let error = ParallelExecutor::new(db(2))
+            .execute(&CfgEnv::default(), &block(), &txs)
+            .unwrap_err();
+        assert_eq!(error.tx_index, 1);
+        assert!(matches!(
+            error.error,
+            EVMError::Transaction(InvalidTransaction::NonceTooHigh { .. })
+        ));
+    }
+}
@@ -0,0 +1,87 @@
+//! Post execution handler that defers the beneficiary reward.
+use revm::{
+    context_interface::result::{HaltReason, ResultAndState},
+    handler::{EthPostExecution, EthPostExecutionContext, EthPostExecutionError, FrameResult},
+    handler_interface::{InitialAndFloorGas, PostExecutionHandler},
+};
+
+/// Post execution handler that does not reward the beneficiary.
+///
+/// Every transaction of the block pays the beneficiary, loading its account would make all of
+/// them conflict. The reward is instead applied by the executor as a balance increment.
+pub struct ParallelPostExecution<CTX, ERROR> {
+    pub eth: EthPostExecution<CTX, ERROR, HaltReason>,
+}
+
+impl<CTX, ERROR> ParallelPostExecution<CTX, ERROR> {
+    /// Create new instance of post execution handler.
+    pub fn new() -> Self {
+        Self {
+            eth: EthPostExecution::new(),
+        }
+    }
+}
+
+impl<CTX, ERROR> Default for ParallelPostExecution<CTX, ERROR> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<CTX, ERROR> PostExecutionHandler for ParallelPostExecution<CTX, ERROR>
+where
+    CTX: EthPostExecutionContext,
+    ERROR: EthPostExecutionError<CTX>,
+{
+    type Context = CTX;
+    type Error = ERROR;
+    type ExecResult = FrameResult;
+    type Output = ResultAndState<HaltReason>;
+
+    fn eip7623_check_gas_floor(
+        &self,
+        context: &mut Self::Context,
+        exec_result: &mut Self::ExecResult,
+        init_and_floor_gas: InitialAndFloorGas,
+    ) {
+        self.eth
+            .eip7623_check_gas_floor(context, exec_result, init_and_floor_gas);
+    }
+
+    fn refund(
+        &self,
+        context: &mut Self::Context,
+        exec_result: &mut Self::ExecResult,
+        eip7702_refund: i64,
+    ) {
+        self.eth.refund(context, exec_result, eip7702_refund);
+    }
+
+    fn reimburse_caller(
+        &self,
+        context: &mut Self::Context,
+        exec_result: &mut Self::ExecResult,
+    ) -> Result<(), Self::Error> {
+        self.eth.reimburse_caller(context, exec_result)
+    }
+
+    fn reward_beneficiary(
+        &self,
+        _context: &mut Self::Context,
+        _exec_result: &mut Self::ExecResult,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn output(
+        &self,
+        context: &mut Self::Context,
+        result: Self::ExecResult,
+    ) -> Result<Self::Output, Self::Error> {
+        self.eth.output(context, result)
+    }
+
+    fn clear(&self, context: &mut Self::Context) {
+        self.eth.clear(context);
+    }
+}
@@ -0,0 +1,18 @@
+//! Parallel block execution.
+//!
+//! [`ParallelExecutor`] executes the transactions of the block speculatively on multiple
+//! threads using Block-STM and produces the same [`BundleState`][database::BundleState]
+//! as the sequential execution with [`State`][database::State].
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+
+pub mod db;
+pub mod executor;
+pub mod handler;
+pub mod mv_memory;
+pub mod scheduler;
+
+pub use db::{VersionedDb, VersionedDbError};
+pub use executor::{BlockExecutionError, BlockOutput, ParallelExecutor};
+pub use handler::ParallelPostExecution;
+pub use mv_memory::{Location, MemoryValue, MvMemory, ReadOrigin};
+pub use scheduler::{Incarnation, Scheduler, Task, TxIndex, Version};
@@ -0,0 +1,198 @@
+//! Multi-version memory of Block-STM.
+//!
+//! Every location keeps the values written by the transactions of the block ordered by the
+//! transaction index, so a transaction reads the value written by the closest lower
+//! transaction or falls back to the underlying database.
+use crate::scheduler::{lock, Incarnation, TxIndex, Version};
+use core::hash::{Hash, Hasher};
+use revm::{
+    primitives::{Address, HashMap, U256},
+    state::AccountInfo,
+};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    sync::{Mutex, MutexGuard},
+    vec::Vec,
+};
+
+/// Number of shards the locations are spread over to reduce lock contention.
+const SHARDS: usize = 64;
+
+/// Location of the state that transactions read and write.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Location {
+    /// Account information.
+    Basic(Address),
+    /// Storage slot of the account.
+    Storage(Address, U256),
+    /// Wipe of the whole storage of the account, written on creation and destruction.
+    StorageReset(Address),
+}
+
+/// Value written to the location.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MemoryValue {
+    /// Account information, `None` if the account does not exist.
+    Basic(Option<AccountInfo>),
+    /// Balance added to the account without reading it, used for the beneficiary reward.
+    BalanceIncrement(U256),
+    /// Storage slot value.
+    Storage(U256),
+    /// Storage of the account was wiped.
+    StorageReset,
+}
+
+/// Origin of the value read by the transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadOrigin {
+    /// Value written by the lower transaction.
+    MvMemory(Version),
+    /// Value of the underlying database.
+    Storage,
+}
+
+/// Result of the read from the multi-version memory.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryRead {
+    /// Origins of the read value, starting at the closest lower transaction.
+    pub origins: Vec<ReadOrigin>,
+    /// Value written by the lower transaction, `None` if it has to be read from the database.
+    pub value: Option<MemoryValue>,
+    /// Sum of the balance increments on top of the value.
+    pub increment: U256,
+}
+
+impl MemoryRead {
+    /// Returns the index of the transaction that wrote the value.
+    pub fn tx_index(&self) -> Option<TxIndex> {
+        match self.origins.last() {
+            Some(ReadOrigin::MvMemory(version)) => Some(version.tx_index),
+            _ => None,
+        }
+    }
+}
+
+/// Locations read by the transaction together with the origins of the read values.
+pub type ReadSet = HashMap<Location, Vec<ReadOrigin>>;
+
+/// Locations written by the transaction together with the written values.
+pub type WriteSet = Vec<(Location, MemoryValue)>;
+
+#[derive(Clone, Debug)]
+enum Entry {
+    Data(Incarnation, MemoryValue),
+    /// Value of the aborted incarnation that is likely to be written again.
+    Estimate,
+}
+
+type Shard = HashMap<Location, BTreeMap<TxIndex, Entry>>;
+
+/// Multi-version memory shared by the worker threads.
+#[derive(Debug)]
+pub struct MvMemory {
+    shards: Vec<Mutex<Shard>>,
+    last_written_locations: Vec<Mutex<Vec<Location>>>,
+    last_read_set: Vec<Mutex<ReadSet>>,
+}
+
+impl MvMemory {
+    /// Creates the memory for the block with the given number of transactions.
+    pub fn new(block_size: usize) -> Self {
+        Self {
+            shards: (0..SHARDS).map(|_| Mutex::default()).collect(),
+            last_written_locations: (0..block_size).map(|_| Mutex::default()).collect(),
+            last_read_set: (0..block_size).map(|_| Mutex::default()).collect(),
+        }
+    }
+
+    /// Records the read and write sets of the executed incarnation.
+    ///
+    /// Returns `true` if the incarnation wrote a location that the previous one did not.
+    pub fn record(&self, version: Version, read_set: ReadSet, write_set: WriteSet) -> bool {
+        let Version {
+            tx_index,
+            incarnation,
+        } = version;
+        let mut last_written = lock(&self.last_written_locations[tx_index]);
+
+        for location in last_written.iter() {
+            if !write_set.iter().any(|(written, _)| written == location) {
+                if let Some(entries) = self.shard(location).get_mut(location) {
+                    entries.remove(&tx_index);
+                }
+            }
+        }
+
+        let mut wrote_new_location = false;
+        let mut locations = Vec::with_capacity(write_set.len());
+        for (location, value) in write_set {
+            wrote_new_location |= !last_written.contains(&location);
+            self.shard(&location)
+                .entry(location)
+                .or_default()
+                .insert(tx_index, Entry::Data(incarnation, value));
+            locations.push(location);
+        }
+
+        *last_written = locations;
+        *lock(&self.last_read_set[tx_index]) = read_set;
+        wrote_new_location
+    }
+
+    /// Marks the values written by the aborted transaction as estimates.
+    pub fn convert_writes_to_estimates(&self, tx_index: TxIndex) {
+        for location in lock(&self.last_written_locations[tx_index]).iter() {
+            if let Some(entry) = self
+                .shard(location)
+                .get_mut(location)
+                .and_then(|entries| entries.get_mut(&tx_index))
+            {
+                *entry = Entry::Estimate;
+            }
+        }
+    }
+
+    /// Reads the location as seen by the transaction.
+    ///
+    /// Returns the index of the blocking transaction if the value is an estimate.
+    pub fn read(&self, location: &Location, tx_index: TxIndex) -> Result<MemoryRead, TxIndex> {
+        let mut read = MemoryRead::default();
+        let shard = self.shard(location);
+        if let Some(entries) = shard.get(location) {
+            for (&index, entry) in entries.range(..tx_index).rev() {
+                let Entry::Data(incarnation, value) = entry else {
+                    return Err(index);
+                };
+                read.origins.push(ReadOrigin::MvMemory(Version {
+                    tx_index: index,
+                    incarnation: *incarnation,
+                }));
+                if let MemoryValue::BalanceIncrement(increment) = value {
+                    read.increment = read.increment.saturating_add(*increment);
+                    continue;
+                }
+                read.value = Some(value.clone());
+                return Ok(read);
+            }
+        }
+        read.origins.push(ReadOrigin::Storage);
+        Ok(read)
+    }
+
+    /// Returns `true` if the values read by the last incarnation of the transaction are
+    /// still the ones it would read now.
+    pub fn validate_read_set(&self, tx_index: TxIndex) -> bool {
+        lock(&self.last_read_set[tx_index])
+            .iter()
+            .all(|(location, origins)| {
+                self.read(location, tx_index)
+                    .is_ok_and(|read| read.origins == *origins)
+            })
+    }
+
+    fn shard(&self, location: &Location) -> MutexGuard<'_, Shard> {
+        let mut hasher = DefaultHasher::new();
+        location.hash(&mut hasher);
+        lock(&self.shards[hasher.finish() as usize % SHARDS])
+    }
+}
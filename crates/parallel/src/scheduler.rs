@@ -0,0 +1,241 @@
+//! Collaborative scheduler of Block-STM.
+//!
+//! Execution and validation tasks are handed out in transaction order. Validation of a
+//! transaction that fails aborts it and schedules its re-execution, transactions that read
+//! an estimate of a lower transaction wait until that transaction is executed again.
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::{
+    sync::{Mutex, MutexGuard, PoisonError},
+    vec::Vec,
+};
+
+/// Index of the transaction in the block.
+pub type TxIndex = usize;
+
+/// Incarnation of the transaction, increased on every re-execution.
+pub type Incarnation = usize;
+
+/// Version of the transaction execution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Version {
+    /// Index of the transaction.
+    pub tx_index: TxIndex,
+    /// Incarnation of the transaction.
+    pub incarnation: Incarnation,
+}
+
+/// Task of the worker thread.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Task {
+    /// Execute the transaction.
+    Execution(Version),
+    /// Validate the read set of the executed transaction.
+    Validation(Version),
+}
+
+/// Execution status of the transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Status {
+    ReadyToExecute,
+    Executing,
+    Executed,
+    Aborting,
+}
+
+/// Scheduler shared by the worker threads.
+#[derive(Debug)]
+pub struct Scheduler {
+    block_size: usize,
+    execution_index: AtomicUsize,
+    validation_index: AtomicUsize,
+    decrease_count: AtomicUsize,
+    active_tasks: AtomicUsize,
+    done: AtomicBool,
+    status: Vec<Mutex<(Incarnation, Status)>>,
+    dependencies: Vec<Mutex<Vec<TxIndex>>>,
+}
+
+impl Scheduler {
+    /// Creates the scheduler for the block with the given number of transactions.
+    pub fn new(block_size: usize) -> Self {
+        Self {
+            block_size,
+            execution_index: AtomicUsize::new(0),
+            validation_index: AtomicUsize::new(0),
+            decrease_count: AtomicUsize::new(0),
+            active_tasks: AtomicUsize::new(0),
+            done: AtomicBool::new(block_size == 0),
+            status: (0..block_size)
+                .map(|_| Mutex::new((0, Status::ReadyToExecute)))
+                .collect(),
+            dependencies: (0..block_size).map(|_| Mutex::new(Vec::new())).collect(),
+        }
+    }
+
+    /// Returns `true` if all transactions are executed and validated.
+    #[inline]
+    pub fn done(&self) -> bool {
+        self.done.load(Ordering::Acquire)
+    }
+
+    /// Returns the next task, validation tasks take priority over execution of higher
+    /// transactions.
+    pub fn next_task(&self) -> Option<Task> {
+        if self.validation_index.load(Ordering::Acquire)
+            < self.execution_index.load(Ordering::Acquire)
+        {
+            self.next_version_to_validate().map(Task::Validation)
+        } else {
+            self.next_version_to_execute().map(Task::Execution)
+        }
+    }
+
+    /// Marks the transaction as waiting for the execution of the blocking transaction.
+    ///
+    /// Returns `false` if the blocking transaction was executed in the meantime and the
+    /// transaction can be executed again right away.
+    pub fn add_dependency(&self, tx_index: TxIndex, blocking: TxIndex) -> bool {
+        let mut dependencies = lock(&self.dependencies[blocking]);
+        if lock(&self.status[blocking]).1 == Status::Executed {
+            return false;
+        }
+        lock(&self.status[tx_index]).1 = Status::Aborting;
+        dependencies.push(tx_index);
+        self.active_tasks.fetch_sub(1, Ordering::AcqRel);
+        true
+    }
+
+    /// Finishes the execution, returns the validation of the transaction if it is not
+    /// covered by the validation index.
+    pub fn finish_execution(&self, version: Version, wrote_new_location: bool) -> Option<Task> {
+        let tx_index = version.tx_index;
+        lock(&self.status[tx_index]).1 = Status::Executed;
+
+        let dependencies = core::mem::take(&mut *lock(&self.dependencies[tx_index]));
+        self.resume_dependencies(dependencies);
+
+        if self.validation_index.load(Ordering::Acquire) > tx_index {
+            if !wrote_new_location {
+                return Some(Task::Validation(version));
+            }
+            // Higher transactions may have read the previous value.
+            self.decrease_validation_index(tx_index);
+        }
+        self.active_tasks.fetch_sub(1, Ordering::AcqRel);
+        None
+    }
+
+    /// Aborts the executed incarnation, returns `false` if it was already aborted.
+    pub fn try_validation_abort(&self, version: Version) -> bool {
+        let mut status = lock(&self.status[version.tx_index]);
+        if *status == (version.incarnation, Status::Executed) {
+            status.1 = Status::Aborting;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Finishes the validation, returns the re-execution of the aborted transaction.
+    pub fn finish_validation(&self, tx_index: TxIndex, aborted: bool) -> Option<Task> {
+        if aborted {
+            self.set_ready_status(tx_index);
+            self.decrease_validation_index(tx_index + 1);
+            if self.execution_index.load(Ordering::Acquire) > tx_index {
+                if let Some(version) = self.try_incarnate(tx_index) {
+                    return Some(Task::Execution(version));
+                }
+            }
+        }
+        self.active_tasks.fetch_sub(1, Ordering::AcqRel);
+        None
+    }
+
+    fn next_version_to_execute(&self) -> Option<Version> {
+        if self.execution_index.load(Ordering::Acquire) >= self.block_size {
+            self.check_done();
+            return None;
+        }
+        self.active_tasks.fetch_add(1, Ordering::AcqRel);
+        let tx_index = self.execution_index.fetch_add(1, Ordering::AcqRel);
+        let version = self.try_incarnate(tx_index);
+        if version.is_none() {
+            self.active_tasks.fetch_sub(1, Ordering::AcqRel);
+        }
+        version
+    }
+
+    fn next_version_to_validate(&self) -> Option<Version> {
+        if self.validation_index.load(Ordering::Acquire) >= self.block_size {
+            self.check_done();
+            return None;
+        }
+        self.active_tasks.fetch_add(1, Ordering::AcqRel);
+        let tx_index = self.validation_index.fetch_add(1, Ordering::AcqRel);
+        if tx_index < self.block_size {
+            let (incarnation, status) = *lock(&self.status[tx_index]);
+            if status == Status::Executed {
+                return Some(Version {
+                    tx_index,
+                    incarnation,
+                });
+            }
+        }
+        self.active_tasks.fetch_sub(1, Ordering::AcqRel);
+        None
+    }
+
+    fn try_incarnate(&self, tx_index: TxIndex) -> Option<Version> {
+        if tx_index >= self.block_size {
+            return None;
+        }
+        let mut status = lock(&self.status[tx_index]);
+        if status.1 != Status::ReadyToExecute {
+            return None;
+        }
+        status.1 = Status::Executing;
+        Some(Version {
+            tx_index,
+            incarnation: status.0,
+        })
+    }
+
+    fn set_ready_status(&self, tx_index: TxIndex) {
+        let mut status = lock(&self.status[tx_index]);
+        *status = (status.0 + 1, Status::ReadyToExecute);
+    }
+
+    fn resume_dependencies(&self, dependencies: Vec<TxIndex>) {
+        let Some(&min) = dependencies.iter().min() else {
+            return;
+        };
+        for tx_index in dependencies {
+            self.set_ready_status(tx_index);
+        }
+        self.execution_index.fetch_min(min, Ordering::AcqRel);
+        self.decrease_count.fetch_add(1, Ordering::AcqRel);
+    }
+
+    fn decrease_validation_index(&self, target: TxIndex) {
+        self.validation_index.fetch_min(target, Ordering::AcqRel);
+        self.decrease_count.fetch_add(1, Ordering::AcqRel);
+    }
+
+    fn check_done(&self) {
+        let observed = self.decrease_count.load(Ordering::Acquire);
+        if self
+            .execution_index
+            .load(Ordering::Acquire)
+            .min(self.validation_index.load(Ordering::Acquire))
+            >= self.block_size
+            && self.active_tasks.load(Ordering::Acquire) == 0
+            && observed == self.decrease_count.load(Ordering::Acquire)
+        {
+            self.done.store(true, Ordering::Release);
+        }
+    }
+}
+
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,226 @@
+use crate::{evm::Evm, exec::EvmExec};
+use context::{tx::TxEnv, CfgEnv, Context};
+use context_interface::{
+    result::{ExecutionResult, HaltReasonTrait, ResultAndState},
+    Block, Journal,
+};
+use core::fmt;
+use database_interface::Database;
+use primitives::{Address, HashSet, TxKind, B256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+/// Access list in the form of [`TxEnv::access_list`].
+pub type AccessList = Vec<(Address, Vec<B256>)>;
+
+/// Maximum number of executions of [`Evm::create_access_list`].
+pub const MAX_ACCESS_LIST_ITERATIONS: usize = 16;
+
+/// Output of [`Evm::create_access_list`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessListResult<HALT: HaltReasonTrait> {
+    /// Generated access list.
+    pub access_list: AccessList,
+    /// Gas used by the transaction with the generated access list.
+    pub gas_used: u64,
+    /// Result of the transaction with the generated access list.
+    pub result: ExecutionResult<HALT>,
+}
+
+/// Error of the access list generation, see [`Evm::create_access_list`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CreateAccessListError<ERROR> {
+    /// Access list still changed after [`MAX_ACCESS_LIST_ITERATIONS`] executions.
+    NotConverged {
+        /// Access list generated by the last execution.
+        access_list: AccessList,
+    },
+    /// Transaction is invalid or the database failed.
+    Evm(ERROR),
+}
+
+impl<ERROR: fmt::Display> fmt::Display for CreateAccessListError<ERROR> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConverged { .. } => write!(
+                f,
+                "access list did not converge after {MAX_ACCESS_LIST_ITERATIONS} executions"
+            ),
+            Self::Evm(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<ERROR: core::error::Error> core::error::Error for CreateAccessListError<ERROR> {}
+
+impl<ERROR, BLOCK, DB, JOURNAL, CHAIN, HANDLER, HALT>
+    Evm<ERROR, Context<BLOCK, TxEnv, CfgEnv, DB, JOURNAL, CHAIN>, HANDLER>
+where
+    BLOCK: Block,
+    DB: Database,
+    JOURNAL: Journal<Database = DB>,
+    HALT: HaltReasonTrait,
+    Self: EvmExec<Output = Result<ResultAndState<HALT>, ERROR>>,
+{
+    /// Generates the access list of the transaction, like geth `eth_createAccessList`.
+    ///
+    /// Every account and storage slot loaded by the execution is recorded. Accounts that are
+    /// warm anyway are skipped unless their storage is accessed: the caller, the target, the
+    /// precompiles, the beneficiary, the EIP-7702 authorities and the accounts created by the
+    /// transaction.
+    ///
+    /// The transaction is executed with the access list of the previous run until the list no
+    /// longer changes, as the access list can change the execution paths through the gas it
+    /// saves. Returns [`CreateAccessListError::NotConverged`] if it still changes after
+    /// [`MAX_ACCESS_LIST_ITERATIONS`] executions. The initial access list of the transaction is
+    /// extended, and restored after the generation.
+    pub fn create_access_list(
+        &mut self,
+    ) -> Result<AccessListResult<HALT>, CreateAccessListError<ERROR>> {
+        let initial = self.context.tx.access_list.clone();
+        let output = self.create_access_list_inner();
+        self.context.tx.access_list = initial;
+        output
+    }
+
+    fn create_access_list_inner(
+        &mut self,
+    ) -> Result<AccessListResult<HALT>, CreateAccessListError<ERROR>> {
+        let tx = &self.context.tx;
+        let target = match tx.kind {
+            TxKind::Call(address) => address,
+            TxKind::Create => tx.caller.create(tx.nonce),
+        };
+        let mut warm: HashSet<Address> = [tx.caller, target, self.context.block.beneficiary()]
+            .into_iter()
+            .chain(tx.authorization_list.iter().filter_map(|item| item.0))
+            .collect();
+
+        let mut access_list = self.context.tx.access_list.clone();
+        for _ in 0..MAX_ACCESS_LIST_ITERATIONS {
+            self.context.tx.access_list = access_list.clone();
+            let ResultAndState { result, state } =
+                self.exec().map_err(CreateAccessListError::Evm)?;
+            // Precompiles are known after the execution loaded them.
+            warm.extend(self.context.journaled_state.precompile_addresses());
+
+            let mut generated: BTreeMap<Address, BTreeSet<B256>> = access_list
+                .iter()
+                .map(|(address, keys)| (*address, keys.iter().copied().collect()))
+                .collect();
+            for (address, account) in state {
+                if account.storage.is_empty() && (warm.contains(&address) || account.is_created()) {
+                    continue;
+                }
+                generated
+                    .entry(address)
+                    .or_default()
+                    .extend(account.storage.keys().map(|key| B256::from(*key)));
+            }
+            let generated: AccessList = generated
+                .into_iter()
+                .map(|(address, keys)| (address, keys.into_iter().collect()))
+                .collect();
+
+            if generated == access_list {
+                return Ok(AccessListResult {
+                    access_list,
+                    gas_used: result.gas_used(),
+                    result,
+                });
+            }
+            access_list = generated;
+        }
+        Err(CreateAccessListError::NotConverged { access_list })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MainEvm;
+    use bytecode::{opcode, Bytecode};
+    use database::BenchmarkDB;
+    use handler::EthHandler;
+    use primitives::U256;
+
+    #[test]
+    fn create_access_list() {
+        let other = Address::with_last_byte(0xaa);
+        let mut code = vec![
+            // SLOAD(2)
+            opcode::PUSH1,
+            0x02,
+            opcode::SLOAD,
+            opcode::POP,
+            // BALANCE(other)
+            opcode::PUSH20,
+        ];
+        code.extend_from_slice(other.as_slice());
+        code.extend_from_slice(&[
+            opcode::BALANCE,
+            opcode::POP,
+            // STATICCALL(gas, 0x01, 0, 0, 0, 0) to the ecrecover precompile.
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH1,
+            0x01,
+            opcode::GAS,
+            opcode::STATICCALL,
+            opcode::STOP,
+        ]);
+        let mut evm = MainEvm::new(
+            Context::builder()
+                .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(code.into())))
+                .modify_block_chained(|block| block.beneficiary = Address::with_last_byte(0xcb))
+                .modify_tx_chained(|tx| {
+                    tx.caller = Address::with_last_byte(1);
+                    tx.kind = TxKind::Call(Address::ZERO);
+                    tx.gas_limit = 100_000;
+                }),
+            EthHandler::default(),
+        );
+        let output = evm.create_access_list().unwrap();
+
+        // Caller, beneficiary, target and precompile are excluded, but the slots of the target
+        // are not.
+        let expected = vec![
+            (Address::ZERO, vec![B256::from(U256::from(2))]),
+            (other, vec![]),
+        ];
+        assert_eq!(output.access_list, expected);
+        assert!(output.result.is_success());
+        assert!(evm.context.tx.access_list.is_empty());
+
+        // Gas used matches the execution with the generated access list.
+        evm.context.tx.access_list = expected;
+        assert_eq!(evm.exec().unwrap().result.gas_used(), output.gas_used);
+    }
+
+    #[test]
+    fn create_access_list_not_converged() {
+        // BALANCE(GAS), the accessed address changes with the gas saved by the access list.
+        let code = [opcode::GAS, opcode::BALANCE, opcode::STOP];
+        let mut evm = MainEvm::new(
+            Context::builder()
+                .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(
+                    code.to_vec().into(),
+                )))
+                .modify_tx_chained(|tx| {
+                    tx.caller = Address::with_last_byte(1);
+                    tx.kind = TxKind::Call(Address::ZERO);
+                    tx.gas_limit = 100_000;
+                }),
+            EthHandler::default(),
+        );
+        let Err(CreateAccessListError::NotConverged { access_list }) = evm.create_access_list()
+        else {
+            panic!("access list converged");
+        };
+        assert_eq!(access_list.len(), MAX_ACCESS_LIST_ITERATIONS);
+    }
+}
@@ -0,0 +1,313 @@
+use crate::{evm::Evm, exec::EvmExec};
+use context::{tx::TxEnv, CfgEnv, Context};
+use context_interface::{
+    result::{ExecutionResult, HaltReasonTrait, InvalidTransaction, ResultAndState},
+    transaction::TransactionType,
+    Block, Cfg, Journal, Transaction,
+};
+use core::fmt;
+use database_interface::Database;
+use handler::validate_initial_tx_gas;
+use interpreter::gas::CALL_STIPEND;
+use primitives::{Bytes, U256};
+
+/// Error of the gas estimation, see [`Evm::estimate_gas`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EstimateGasError<HALT, ERROR> {
+    /// Transaction reverts with the highest gas limit.
+    Revert {
+        /// Output of the reverted transaction.
+        output: Bytes,
+        /// Gas used by the reverted transaction.
+        gas_used: u64,
+    },
+    /// Transaction halts with the highest gas limit, e.g. runs out of gas.
+    Halt {
+        /// Reason of the halt.
+        reason: HALT,
+        /// Gas used by the halted transaction.
+        gas_used: u64,
+    },
+    /// Transaction is invalid or the database failed.
+    Evm(ERROR),
+}
+
+impl<HALT: fmt::Debug, ERROR: fmt::Display> fmt::Display for EstimateGasError<HALT, ERROR> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Revert { output, .. } => write!(f, "execution reverted: {output}"),
+            Self::Halt { reason, .. } => write!(f, "execution halted: {reason:?}"),
+            Self::Evm(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<HALT: fmt::Debug, ERROR: core::error::Error> core::error::Error
+    for EstimateGasError<HALT, ERROR>
+{
+}
+
+impl<ERROR, BLOCK, DB, JOURNAL, CHAIN, HANDLER, HALT>
+    Evm<ERROR, Context<BLOCK, TxEnv, CfgEnv, DB, JOURNAL, CHAIN>, HANDLER>
+where
+    BLOCK: Block,
+    DB: Database,
+    JOURNAL: Journal<Database = DB>,
+    HALT: HaltReasonTrait,
+    ERROR: From<InvalidTransaction> + From<DB::Error>,
+    Self: EvmExec<Output = Result<ResultAndState<HALT>, ERROR>>,
+{
+    /// Estimates the lowest gas limit the transaction succeeds with.
+    ///
+    /// Performs the binary search of geth `eth_estimateGas` between the intrinsic gas, or the
+    /// EIP-7623 floor, and the gas limit of the transaction. Like geth, the upper bound is capped
+    /// at the gas the caller can pay for after the value transfer, if the gas price is not zero.
+    /// The block gas limit check is disabled if the `optional_block_gas_limit` feature is
+    /// enabled.
+    ///
+    /// Gas limit of the transaction is restored after the estimation.
+    pub fn estimate_gas(&mut self) -> Result<u64, EstimateGasError<HALT, ERROR>> {
+        let gas_limit = self.context.tx.gas_limit;
+        #[cfg(feature = "optional_block_gas_limit")]
+        let disable_block_gas_limit =
+            core::mem::replace(&mut self.context.cfg.disable_block_gas_limit, true);
+
+        let estimate = self.estimate_gas_inner();
+
+        self.context.tx.gas_limit = gas_limit;
+        #[cfg(feature = "optional_block_gas_limit")]
+        {
+            self.context.cfg.disable_block_gas_limit = disable_block_gas_limit;
+        }
+        estimate
+    }
+
+    fn estimate_gas_inner(&mut self) -> Result<u64, EstimateGasError<HALT, ERROR>> {
+        let mut hi = self.context.tx.gas_limit;
+        let gas = validate_initial_tx_gas::<_, ERROR>(&self.context, self.context.cfg.spec)
+            .map_err(EstimateGasError::Evm)?;
+        // Transaction is invalid below the intrinsic gas and the EIP-7623 floor.
+        let mut lo = gas.initial_gas.max(gas.floor_gas) - 1;
+
+        // Caller that can't pay for the intrinsic gas gets the error of the upper bound.
+        let allowance = self.gas_allowance().map_err(EstimateGasError::Evm)?;
+        if allowance > lo {
+            hi = hi.min(allowance);
+        }
+
+        let (gas_used, gas_refunded) = match self.exec_with_gas_limit(hi)? {
+            ExecutionResult::Success {
+                gas_used,
+                gas_refunded,
+                ..
+            } => (gas_used, gas_refunded),
+            ExecutionResult::Revert { output, gas_used } => {
+                return Err(EstimateGasError::Revert { output, gas_used })
+            }
+            ExecutionResult::Halt { reason, gas_used } => {
+                return Err(EstimateGasError::Halt { reason, gas_used })
+            }
+        };
+
+        // Gas used by the unconstrained execution is the lower bound for almost any
+        // transaction, except those that explicitly check the remaining gas.
+        lo = lo.max(gas_used - 1);
+
+        // Most transactions succeed with the spent gas, the stipend of the value transfer and
+        // the 1/64th of the gas retained by the caller on every call (EIP-150).
+        let optimistic = (gas_used + gas_refunded + CALL_STIPEND) * 64 / 63;
+        if optimistic < hi {
+            if self.exec_with_gas_limit(optimistic)?.is_success() {
+                hi = optimistic;
+            } else {
+                lo = optimistic;
+            }
+        }
+
+        while lo + 1 < hi {
+            // Bias the search towards the lower bound, as it is usually close to the result.
+            let mid = (lo + (hi - lo) / 2).min(lo * 2);
+            if self.exec_with_gas_limit(mid)?.is_success() {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        Ok(hi)
+    }
+
+    /// Returns the gas the caller can pay for after the value transfer and the blob fee.
+    fn gas_allowance(&mut self) -> Result<u64, ERROR> {
+        let tx = &self.context.tx;
+        let fee_cap = tx.max_fee_per_gas();
+        if fee_cap == 0 || self.context.cfg.is_balance_check_disabled() {
+            return Ok(u64::MAX);
+        }
+        let mut cost = tx.value();
+        if tx.tx_type() == TransactionType::Eip4844 {
+            cost = cost.saturating_add(tx.calc_max_data_fee());
+        }
+        let caller = tx.caller();
+        let balance = self
+            .context
+            .journaled_state
+            .db()
+            .basic(caller)?
+            .map(|info| info.balance)
+            .unwrap_or_default();
+        Ok((balance.saturating_sub(cost) / U256::from(fee_cap)).saturating_to())
+    }
+
+    fn exec_with_gas_limit(
+        &mut self,
+        gas_limit: u64,
+    ) -> Result<ExecutionResult<HALT>, EstimateGasError<HALT, ERROR>> {
+        self.context.tx.gas_limit = gas_limit;
+        self.exec()
+            .map(|output| output.result)
+            .map_err(EstimateGasError::Evm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MainEvm;
+    use bytecode::{opcode, Bytecode};
+    use context_interface::result::{EVMError, HaltReason, OutOfGasError};
+    use database::BenchmarkDB;
+    use handler::EthHandler;
+    use primitives::{Address, TxKind};
+
+    fn new_evm(
+        code: &[u8],
+        gas_limit: u64,
+    ) -> MainEvm<BenchmarkDB, context::BlockEnv, TxEnv, CfgEnv> {
+        MainEvm::new(
+            Context::builder()
+                .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(
+                    code.to_vec().into(),
+                )))
+                .modify_tx_chained(|tx| {
+                    tx.caller = Address::with_last_byte(1);
+                    tx.kind = TxKind::Call(Address::ZERO);
+                    tx.gas_limit = gas_limit;
+                }),
+            EthHandler::default(),
+        )
+    }
+
+    #[test]
+    fn estimate_gas() {
+        // Calls itself with half of the gas, the inner call needs more gas than the outer
+        // one spends.
+        let code = [
+            opcode::CALLDATASIZE,
+            opcode::PUSH1,
+            0x18,
+            opcode::JUMPI,
+            // CALL(GAS / 2, ADDRESS, 0, 0, 1, 0, 0)
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH1,
+            0x01,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::ADDRESS,
+            opcode::PUSH1,
+            0x02,
+            opcode::GAS,
+            opcode::DIV,
+            opcode::CALL,
+            // Revert if the inner call failed.
+            opcode::PUSH1,
+            0x16,
+            opcode::JUMPI,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::REVERT,
+            opcode::JUMPDEST,
+            opcode::STOP,
+            // Inner call: SSTORE(1, 1)
+            opcode::JUMPDEST,
+            opcode::PUSH1,
+            0x01,
+            opcode::DUP1,
+            opcode::SSTORE,
+            opcode::STOP,
+        ];
+        let mut evm = new_evm(&code, 1_000_000);
+        let estimate = evm.estimate_gas().unwrap();
+        assert_eq!(evm.context.tx.gas_limit, 1_000_000);
+
+        evm.context.tx.gas_limit = estimate;
+        let result = evm.exec().unwrap().result;
+        assert!(result.is_success());
+        assert!(estimate > result.gas_used());
+
+        evm.context.tx.gas_limit = estimate - 1;
+        assert!(!evm.exec().unwrap().result.is_success());
+    }
+
+    #[test]
+    fn estimate_gas_errors() {
+        let mut evm = new_evm(&[opcode::PUSH0, opcode::PUSH0, opcode::REVERT], 100_000);
+        assert!(matches!(
+            evm.estimate_gas(),
+            Err(EstimateGasError::Revert { .. })
+        ));
+
+        // SSTORE needs more than the gas limit.
+        let mut evm = new_evm(
+            &[opcode::PUSH1, 0x01, opcode::PUSH0, opcode::SSTORE],
+            30_000,
+        );
+        assert_eq!(
+            evm.estimate_gas(),
+            Err(EstimateGasError::Halt {
+                reason: HaltReason::OutOfGas(OutOfGasError::Basic),
+                gas_used: 30_000,
+            })
+        );
+
+        let mut evm = new_evm(&[opcode::STOP], 20_000);
+        assert_eq!(
+            evm.estimate_gas(),
+            Err(EstimateGasError::Evm(EVMError::Transaction(
+                InvalidTransaction::CallGasCostMoreThanGasLimit
+            )))
+        );
+    }
+
+    #[test]
+    fn estimate_gas_allowance() {
+        // SSTORE(1, 1)
+        let code = [opcode::PUSH1, 0x01, opcode::DUP1, opcode::SSTORE];
+        // Caller has a balance of 10_000_000, enough for 100_000 gas.
+        let mut evm = new_evm(&code, 1_000_000);
+        evm.context.tx.gas_price = 100;
+        let estimate = evm.estimate_gas().unwrap();
+        evm.context.tx.gas_limit = estimate;
+        assert!(evm.exec().unwrap().result.is_success());
+
+        // SSTORE needs more than the gas the caller can pay for.
+        evm.context.tx.gas_limit = 1_000_000;
+        evm.context.tx.value = U256::from(7_500_000);
+        assert_eq!(
+            evm.estimate_gas(),
+            Err(EstimateGasError::Halt {
+                reason: HaltReason::OutOfGas(OutOfGasError::Basic),
+                gas_used: 25_000,
+            })
+        );
+
+        // Intrinsic gas is more than the caller can pay for.
+        evm.context.tx.value = U256::from(9_000_000);
+        assert!(matches!(
+            evm.estimate_gas(),
+            Err(EstimateGasError::Evm(EVMError::Transaction(
+                InvalidTransaction::LackOfFundForMaxFee { .. }
+            )))
+        ));
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{exec::EvmCommit, EvmExec};
+use crate::{
+    exec::EvmCommit,
+    suspend::{SuspendedTransaction, TransactSteps},
+    EvmExec,
+};
 use context::{block::BlockEnv, tx::TxEnv, CfgEnv, Context, JournaledState};
 use context_interface::{
     block::BlockSetter,
@@ -15,8 +19,8 @@ use context_interface::{
 use database_interface::{Database, DatabaseCommit};
 use handler::{EthHandler, FrameResult};
 use handler_interface::{
-    ExecutionHandler, Frame, FrameOrResultGen, Handler, InitialAndFloorGas, PostExecutionHandler,
-    PreExecutionHandler, ValidationHandler,
+    util::FrameOrFrameResult, ExecutionHandler, ExecutionStack, Frame, FrameOrResultGen, Handler,
+    InitialAndFloorGas, PostExecutionHandler, PreExecutionHandler, ValidationHandler,
 };
 use interpreter::Host;
 use precompile::PrecompileErrors;
@@ -206,13 +210,8 @@ where
             .inspect_err(|_| {
                 self.clear();
             })?;
-        let init_and_floor_gas = self.transact_preverified_inner(initial_gas_spend);
-        let output = self
-            .handler
-            .post_execution()
-            .end(&mut self.context, init_and_floor_gas);
-        self.clear();
-        output
+        let output = self.transact_preverified_inner(initial_gas_spend);
+        self.end(output)
     }
 
     /// Pre verify transaction inner.
@@ -238,13 +237,8 @@ where
             self.clear();
         })?;
 
-        let init_and_floor_gas = self.transact_preverified_inner(initial_gas_spend);
-        let output = self
-            .handler
-            .post_execution()
-            .end(&mut self.context, init_and_floor_gas);
-        self.clear();
-        output
+        let output = self.transact_preverified_inner(initial_gas_spend);
+        self.end(output)
     }
 
     /// Transact pre-verified transaction.
@@ -252,6 +246,80 @@ where
         &mut self,
         init_and_floor_gas: InitialAndFloorGas,
     ) -> Result<<POSTEXEC as PostExecutionHandler>::Output, ERROR> {
+        let (first_frame, eip7702_gas_refund) = self.start_execution(init_and_floor_gas)?;
+
+        let context = &mut self.context;
+        let exec = self.handler.execution();
+        let frame_result = match first_frame {
+            FrameOrResultGen::Frame(frame) => exec.run(context, frame)?,
+            FrameOrResultGen::Result(result) => result,
+        };
+
+        self.finish_execution(frame_result, init_and_floor_gas, eip7702_gas_refund)
+    }
+
+    /// Transact transaction step by step.
+    ///
+    /// Validates the transaction and executes at most `max_steps` steps of the frame loop, see
+    /// [`ExecutionHandler::step`]. The transaction is suspended if it did not finish, the context
+    /// keeps its journal and must not be used for other transactions until it is resumed with
+    /// [`Evm::resume`].
+    pub fn transact_steps(
+        &mut self,
+        max_steps: u64,
+    ) -> Result<TransactSteps<<POSTEXEC as PostExecutionHandler>::Output, EXEC::Frame>, ERROR> {
+        let init_and_floor_gas = self.preverify_transaction_inner().inspect_err(|_| {
+            self.clear();
+        })?;
+
+        let suspended = match self.start_execution(init_and_floor_gas) {
+            Ok((FrameOrResultGen::Frame(frame), eip7702_gas_refund)) => SuspendedTransaction {
+                init_and_floor_gas,
+                eip7702_gas_refund,
+                stack: ExecutionStack::new(frame),
+            },
+            Ok((FrameOrResultGen::Result(result), eip7702_gas_refund)) => {
+                let output = self.finish_execution(result, init_and_floor_gas, eip7702_gas_refund);
+                return self.end(output).map(TransactSteps::Finished);
+            }
+            Err(e) => return self.end(Err(e)).map(TransactSteps::Finished),
+        };
+        self.resume(suspended, max_steps)
+    }
+
+    /// Resumes the suspended transaction for at most `max_steps` steps of the frame loop.
+    pub fn resume(
+        &mut self,
+        mut suspended: SuspendedTransaction<EXEC::Frame>,
+        max_steps: u64,
+    ) -> Result<TransactSteps<<POSTEXEC as PostExecutionHandler>::Output, EXEC::Frame>, ERROR> {
+        for _ in 0..max_steps {
+            let frame_result = match self
+                .handler
+                .execution()
+                .step(&mut self.context, &mut suspended.stack)
+            {
+                Ok(None) => continue,
+                Ok(Some(frame_result)) => frame_result,
+                Err(e) => return self.end(Err(e)).map(TransactSteps::Finished),
+            };
+            let output = self.finish_execution(
+                frame_result,
+                suspended.init_and_floor_gas,
+                suspended.eip7702_gas_refund,
+            );
+            return self.end(output).map(TransactSteps::Finished);
+        }
+        Ok(TransactSteps::Suspended(suspended))
+    }
+
+    /// Runs pre-execution and creates the first frame.
+    ///
+    /// Returns the first frame or its result and the EIP-7702 gas refund.
+    fn start_execution(
+        &mut self,
+        init_and_floor_gas: InitialAndFloorGas,
+    ) -> Result<(FrameOrFrameResult<EXEC::Frame>, i64), ERROR> {
         let context = &mut self.context;
         let pre_exec = self.handler.pre_execution();
 
@@ -273,11 +341,18 @@ where
 
         // Create first frame action
         let first_frame = exec.init_first_frame(context, gas_limit)?;
-        let frame_result = match first_frame {
-            FrameOrResultGen::Frame(frame) => exec.run(context, frame)?,
-            FrameOrResultGen::Result(result) => result,
-        };
+        Ok((first_frame, eip7702_gas_refund))
+    }
 
+    /// Runs post-execution on the result of the first frame.
+    fn finish_execution(
+        &mut self,
+        frame_result: FrameResult,
+        init_and_floor_gas: InitialAndFloorGas,
+        eip7702_gas_refund: i64,
+    ) -> Result<<POSTEXEC as PostExecutionHandler>::Output, ERROR> {
+        let context = &mut self.context;
+        let exec = self.handler.execution();
         let mut exec_result = exec.last_frame_result(context, frame_result)?;
 
         let post_exec = self.handler.post_execution();
@@ -292,6 +367,16 @@ where
         // Returns output of transaction.
         post_exec.output(context, exec_result)
     }
+
+    /// Ends the transaction and clears the state for next execution.
+    fn end(
+        &mut self,
+        output: Result<<POSTEXEC as PostExecutionHandler>::Output, ERROR>,
+    ) -> Result<<POSTEXEC as PostExecutionHandler>::Output, ERROR> {
+        let output = self.handler.post_execution().end(&mut self.context, output);
+        self.clear();
+        output
+    }
 }
 
 /*
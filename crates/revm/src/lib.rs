@@ -20,13 +20,21 @@ pub use state;
 
 // Modules.
 
+mod access_list;
+mod estimate;
 mod evm;
 mod exec;
+mod suspend;
 
 // Export items.
 
+pub use access_list::{
+    AccessList, AccessListResult, CreateAccessListError, MAX_ACCESS_LIST_ITERATIONS,
+};
 pub use context::journaled_state::{JournalEntry, JournaledState};
 pub use context::Context;
 pub use database_interface::{Database, DatabaseCommit, DatabaseRef};
+pub use estimate::EstimateGasError;
 pub use evm::{Error, EthContext, Evm, MainEvm};
 pub use exec::{EvmCommit, EvmExec};
+pub use suspend::{SuspendedTransaction, TransactSteps, TransactionSnapshot};
@@ -0,0 +1,298 @@
+use context::{block::BlockEnv, tx::TxEnv, CfgEnv, Context, JournalInit, JournaledState};
+use database_interface::Database;
+use handler::{EthFrame, EthFrameContext, EthFrameError, ExecutionSnapshot, SnapshotError};
+use handler_interface::{ExecutionStack, Frame, InitialAndFloorGas, PrecompileProvider};
+use interpreter::{
+    interpreter::{EthInterpreter, InstructionProvider},
+    InterpreterResult,
+};
+
+/// Context with the mainnet journal.
+type JournaledContext<BLOCK, TX, CFG, DB, CHAIN> =
+    Context<BLOCK, TX, CFG, DB, JournaledState<DB>, CHAIN>;
+
+/// Suspended transaction of the mainnet frames of a [`JournaledContext`].
+type SuspendedEthTransaction<BLOCK, TX, CFG, DB, CHAIN, ERROR, PRECOMPILE, INSTRUCTION> =
+    SuspendedTransaction<
+        EthFrame<
+            JournaledContext<BLOCK, TX, CFG, DB, CHAIN>,
+            ERROR,
+            EthInterpreter,
+            PRECOMPILE,
+            INSTRUCTION,
+        >,
+    >;
+
+/// Transaction suspended between two steps of the frame loop, see [`Evm::transact_steps`].
+///
+/// [`Evm::transact_steps`]: crate::Evm::transact_steps
+pub struct SuspendedTransaction<FRAME: Frame> {
+    /// Initial and floor gas of the transaction.
+    pub init_and_floor_gas: InitialAndFloorGas,
+    /// Gas refund of the EIP-7702 authorization list.
+    pub eip7702_gas_refund: i64,
+    /// Frames of the execution.
+    pub stack: ExecutionStack<FRAME>,
+}
+
+/// Output of a transaction executed step by step.
+pub enum TransactSteps<OUTPUT, FRAME: Frame> {
+    /// Transaction finished with the output.
+    Finished(OUTPUT),
+    /// Transaction is suspended and can be resumed with [`Evm::resume`].
+    ///
+    /// [`Evm::resume`]: crate::Evm::resume
+    Suspended(SuspendedTransaction<FRAME>),
+}
+
+/// Serializable state of a [`SuspendedTransaction`] of the mainnet frames.
+///
+/// Contains the environment and the journal of the transaction but not the database, the
+/// snapshot has to be restored to a context with a database in the state the transaction
+/// started with.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransactionSnapshot<BLOCK = BlockEnv, TX = TxEnv, CFG = CfgEnv, CHAIN = ()> {
+    pub block: BLOCK,
+    pub tx: TX,
+    pub cfg: CFG,
+    pub chain: CHAIN,
+    /// Journal of the transaction without the database.
+    pub journal: JournalInit,
+    /// Initial and floor gas of the transaction.
+    pub init_and_floor_gas: InitialAndFloorGas,
+    /// Gas refund of the EIP-7702 authorization list.
+    pub eip7702_gas_refund: i64,
+    /// Frames of the execution.
+    pub execution: ExecutionSnapshot,
+}
+
+impl<BLOCK: Clone, TX: Clone, CFG: Clone, CHAIN: Clone> TransactionSnapshot<BLOCK, TX, CFG, CHAIN> {
+    /// Takes a snapshot of the transaction suspended in the context.
+    pub fn new<DB, ERROR, PRECOMPILE, INSTRUCTION>(
+        context: &JournaledContext<BLOCK, TX, CFG, DB, CHAIN>,
+        suspended: &SuspendedEthTransaction<
+            BLOCK,
+            TX,
+            CFG,
+            DB,
+            CHAIN,
+            ERROR,
+            PRECOMPILE,
+            INSTRUCTION,
+        >,
+    ) -> Result<Self, SnapshotError>
+    where
+        DB: Database,
+        JournaledContext<BLOCK, TX, CFG, DB, CHAIN>: EthFrameContext,
+        ERROR: EthFrameError<JournaledContext<BLOCK, TX, CFG, DB, CHAIN>>,
+        PRECOMPILE: PrecompileProvider<
+            Context = JournaledContext<BLOCK, TX, CFG, DB, CHAIN>,
+            Error = ERROR,
+            Output = InterpreterResult,
+        >,
+        INSTRUCTION: InstructionProvider<
+            WIRE = EthInterpreter,
+            Host = JournaledContext<BLOCK, TX, CFG, DB, CHAIN>,
+        >,
+    {
+        Ok(Self {
+            block: context.block.clone(),
+            tx: context.tx.clone(),
+            cfg: context.cfg.clone(),
+            chain: context.chain.clone(),
+            journal: context.journaled_state.to_init(),
+            init_and_floor_gas: suspended.init_and_floor_gas,
+            eip7702_gas_refund: suspended.eip7702_gas_refund,
+            execution: ExecutionSnapshot::new(&suspended.stack)?,
+        })
+    }
+
+    /// Restores the environment and the journal to the context and returns the suspended
+    /// transaction, which is resumed with [`Evm::resume`].
+    ///
+    /// [`Evm::resume`]: crate::Evm::resume
+    pub fn restore<DB, ERROR, PRECOMPILE, INSTRUCTION>(
+        self,
+        context: &mut JournaledContext<BLOCK, TX, CFG, DB, CHAIN>,
+    ) -> SuspendedEthTransaction<BLOCK, TX, CFG, DB, CHAIN, ERROR, PRECOMPILE, INSTRUCTION>
+    where
+        DB: Database,
+        JournaledContext<BLOCK, TX, CFG, DB, CHAIN>: EthFrameContext,
+        ERROR: EthFrameError<JournaledContext<BLOCK, TX, CFG, DB, CHAIN>>,
+        PRECOMPILE: PrecompileProvider<
+            Context = JournaledContext<BLOCK, TX, CFG, DB, CHAIN>,
+            Error = ERROR,
+            Output = InterpreterResult,
+        >,
+        INSTRUCTION: InstructionProvider<
+            WIRE = EthInterpreter,
+            Host = JournaledContext<BLOCK, TX, CFG, DB, CHAIN>,
+        >,
+    {
+        context.block = self.block;
+        context.tx = self.tx;
+        context.cfg = self.cfg;
+        context.chain = self.chain;
+        context.journaled_state.restore_init(self.journal);
+        SuspendedTransaction {
+            init_and_floor_gas: self.init_and_floor_gas,
+            eip7702_gas_refund: self.eip7702_gas_refund,
+            stack: self.execution.restore(context),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MainEvm, TransactSteps};
+    use bytecode::{opcode::*, Bytecode};
+    use context_interface::result::{HaltReason, ResultAndState};
+    use database::CacheDB;
+    use database_interface::EmptyDB;
+    use handler::EthHandler;
+    use primitives::{Address, TxKind, U256};
+    use state::AccountInfo;
+
+    const CONTRACT: Address = Address::with_last_byte(0xa0);
+    const CALLEE: Address = Address::with_last_byte(0xb0);
+
+    type TestEvm = MainEvm<CacheDB<EmptyDB>, BlockEnv, TxEnv, CfgEnv>;
+
+    /// Contract calls the callee, creates a contract and stores the results.
+    fn new_evm() -> TestEvm {
+        let code = Bytecode::new_raw(
+            [
+                // CALL(GAS, CALLEE, 1, 0, 0, 0, 0x20)
+                PUSH1, 0x20, PUSH0, PUSH0, PUSH0, PUSH1, 0x01, PUSH1, 0xb0, GAS, CALL, PUSH0, MLOAD,
+                PUSH1, 0x01, SSTORE, // CREATE(0, 29, 3) of init code `PUSH0 PUSH0 RETURN`.
+                PUSH3, PUSH0, PUSH0, RETURN, PUSH0, MSTORE, PUSH1, 0x03, PUSH1, 0x1d, PUSH0,
+                CREATE, PUSH1, 0x02, SSTORE, STOP,
+            ]
+            .into(),
+        );
+        // Stores the call value and returns the caller.
+        let callee = Bytecode::new_raw(
+            [
+                CALLVALUE, PUSH0, SSTORE, CALLER, PUSH0, MSTORE, PUSH1, 0x20, PUSH0, RETURN,
+            ]
+            .into(),
+        );
+        let mut db = CacheDB::new(EmptyDB::default());
+        let info = AccountInfo::new(U256::from(10), 1, code.hash_slow(), code);
+        db.insert_account_info(CONTRACT, info);
+        let info = AccountInfo::new(U256::ZERO, 1, callee.hash_slow(), callee);
+        db.insert_account_info(CALLEE, info);
+        MainEvm::new(
+            Context::builder().with_db(db).modify_tx_chained(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.kind = TxKind::Call(CONTRACT);
+                tx.gas_limit = 200_000;
+            }),
+            EthHandler::default(),
+        )
+    }
+
+    /// Transacts step by step and returns the output and the number of steps.
+    fn transact_steps(evm: &mut TestEvm) -> (ResultAndState<HaltReason>, u64) {
+        let mut steps = 1;
+        let mut output = evm.transact_steps(1).unwrap();
+        loop {
+            match output {
+                TransactSteps::Finished(output) => return (output, steps),
+                TransactSteps::Suspended(suspended) => {
+                    output = evm.resume(suspended, 1).unwrap();
+                    steps += 1;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn steps_match_transact() {
+        let mut evm = new_evm();
+        let expected = evm.transact().unwrap();
+        assert!(expected.result.is_success());
+        let (output, steps) = transact_steps(&mut evm);
+        assert_eq!(output, expected);
+        // Frames of the call and the create are created, run and returned to.
+        assert!(steps > 6, "{steps} steps");
+    }
+
+    #[test]
+    fn resumes_from_snapshot() {
+        let mut evm = new_evm();
+        let expected = evm.transact().unwrap();
+        let (_, steps) = transact_steps(&mut evm);
+
+        for suspend_at in 1..steps {
+            let TransactSteps::Suspended(suspended) = evm.transact_steps(suspend_at).unwrap()
+            else {
+                panic!("transaction finished before step {suspend_at}");
+            };
+            let snapshot = TransactionSnapshot::new(&evm.context, &suspended).unwrap();
+            #[cfg(feature = "serde")]
+            let snapshot: TransactionSnapshot =
+                serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+
+            // Suspended transaction resumes in place and from the snapshot in a new EVM.
+            let TransactSteps::Finished(output) = evm.resume(suspended, u64::MAX).unwrap() else {
+                panic!("transaction did not finish");
+            };
+            assert_eq!(output, expected, "suspended at {suspend_at}");
+
+            let mut restored = new_evm();
+            restored.context.tx = TxEnv::default();
+            let suspended = snapshot.restore(&mut restored.context);
+            let TransactSteps::Finished(output) = restored.resume(suspended, u64::MAX).unwrap()
+            else {
+                panic!("restored transaction did not finish");
+            };
+            assert_eq!(output, expected, "restored at {suspend_at}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn snapshot_serde_roundtrip() {
+        use handler_interface::FrameOrResultGen;
+
+        let mut evm = new_evm();
+        let (_, steps) = transact_steps(&mut evm);
+        let (mut frames, mut results) = (0, 0);
+        for suspend_at in 1..steps {
+            let TransactSteps::Suspended(suspended) = evm.transact_steps(suspend_at).unwrap()
+            else {
+                panic!("transaction finished before step {suspend_at}");
+            };
+            let snapshot = TransactionSnapshot::new(&evm.context, &suspended)
+                .unwrap()
+                .execution;
+            evm.resume(suspended, u64::MAX).unwrap();
+            match &snapshot.pending {
+                Some(FrameOrResultGen::Frame(_)) => frames += 1,
+                Some(FrameOrResultGen::Result(_)) => results += 1,
+                None => {}
+            }
+            let json = serde_json::to_value(&snapshot).unwrap();
+            // Stack is serialized as the array of its items.
+            if let Some(frame) = snapshot.frames.last() {
+                assert_eq!(
+                    json["frames"].as_array().unwrap().last().unwrap()["interpreter"]["stack"],
+                    serde_json::to_value(frame.interpreter.stack.data()).unwrap()
+                );
+            }
+            let decoded: ExecutionSnapshot = serde_json::from_value(json.clone()).unwrap();
+            assert_eq!(
+                serde_json::to_value(&decoded).unwrap(),
+                json,
+                "suspended at {suspend_at}"
+            );
+        }
+        assert!(
+            frames > 0 && results > 0,
+            "{frames} frames, {results} results"
+        );
+    }
+}
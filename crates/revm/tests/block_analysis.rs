@@ -0,0 +1,78 @@
+//! Block analysis charges the same gas as per instruction metering.
+use database::BenchmarkDB;
+use revm::{
+    bytecode::{legacy::BlockAnalysisCache, opcode, Bytecode},
+    context_interface::{
+        result::{ExecutionResult, HaltReason},
+        Cfg,
+    },
+    handler::EthHandler,
+    primitives::{Address, TxKind},
+    Context, EvmExec, MainEvm,
+};
+
+#[test]
+fn block_analysis() {
+    let code = [
+        opcode::PUSH1,
+        0x05,
+        // Loop: decrement the counter until it is zero.
+        opcode::JUMPDEST,
+        opcode::DUP1,
+        opcode::SWAP1,
+        opcode::POP,
+        opcode::PUSH1,
+        0x01,
+        opcode::SWAP1,
+        opcode::SUB,
+        opcode::DUP1,
+        opcode::PUSH1,
+        0x02,
+        opcode::JUMPI,
+        opcode::GAS,
+        opcode::POP,
+        opcode::PUSH1,
+        0x01,
+        opcode::PUSH0,
+        opcode::SSTORE,
+        // Jump into push data.
+        opcode::PUSH1,
+        0x01,
+        opcode::JUMP,
+    ];
+    let bytecode = Bytecode::new_raw(code.to_vec().into());
+    let mut evm = MainEvm::new(
+        Context::builder()
+            .with_db(BenchmarkDB::new_bytecode(bytecode.clone()))
+            .modify_tx_chained(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.kind = TxKind::Call(Address::ZERO);
+            }),
+        EthHandler::default(),
+    );
+    // Gas usage is the same for all gas limits, including the ones that run out of gas.
+    for gas_limit in (21_000..50_000).step_by(97) {
+        evm.context.tx.gas_limit = gas_limit;
+        evm.context.cfg.block_analysis = false;
+        let expected = evm.exec().unwrap().result;
+        evm.context.cfg.block_analysis = true;
+        let result = evm.exec().unwrap().result;
+        assert_eq!(result.gas_used(), expected.gas_used(), "{gas_limit}");
+        assert_eq!(result.is_success(), expected.is_success(), "{gas_limit}");
+    }
+    assert!(matches!(
+        evm.exec().unwrap().result,
+        ExecutionResult::Halt {
+            reason: HaltReason::InvalidJump,
+            ..
+        }
+    ));
+
+    let Bytecode::LegacyAnalyzed(legacy) = &bytecode else {
+        unreachable!()
+    };
+    let static_costs = &evm.context.cfg.gas_schedule().static_costs;
+    assert!(BlockAnalysisCache::global()
+        .get(bytecode.hash_slow(), legacy, static_costs)
+        .is_some());
+}
@@ -0,0 +1,58 @@
+//! Static gas costs charged by the instruction table of the handler.
+use database::BenchmarkDB;
+use revm::{
+    bytecode::{opcode, Bytecode},
+    handler::EthHandler,
+    primitives::{Address, TxKind},
+    specification::hardfork::SpecId,
+    Context, EvmExec, MainEvm,
+};
+
+/// Returns the gas used by the code, without the intrinsic gas.
+fn execution_gas(spec: SpecId, code: &[u8]) -> u64 {
+    let mut evm = MainEvm::new(
+        Context::builder()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(
+                code.to_vec().into(),
+            )))
+            .modify_cfg_chained(|cfg| cfg.spec = spec)
+            .modify_tx_chained(|tx| {
+                tx.caller = Address::with_last_byte(1);
+                tx.kind = TxKind::Call(Address::ZERO);
+                tx.gas_limit = 100_000;
+            }),
+        EthHandler::default(),
+    );
+    let result = evm.exec().unwrap().result;
+    assert!(result.is_success(), "{result:?}");
+    result.gas_used() - 21_000
+}
+
+#[test]
+fn static_gas_is_charged() {
+    // Instructions without dynamic gas, executing them gas-free would use no gas.
+    let code = [
+        opcode::PUSH0,
+        opcode::PUSH1,
+        0x01,
+        opcode::ADD,
+        opcode::POP,
+        opcode::STOP,
+    ];
+    assert_eq!(execution_gas(SpecId::PRAGUE, &code), 2 + 3 + 3 + 2);
+}
+
+#[test]
+fn static_gas_follows_the_spec() {
+    let code = [
+        opcode::PUSH1,
+        0xff,
+        opcode::EXTCODESIZE,
+        opcode::POP,
+        opcode::STOP,
+    ];
+    // EIP-1884: static cost of 700.
+    assert_eq!(execution_gas(SpecId::ISTANBUL, &code), 3 + 700 + 2);
+    // EIP-2929: cold account access is dynamic gas.
+    assert_eq!(execution_gas(SpecId::BERLIN, &code), 3 + 2_600 + 2);
+}
@@ -13,3 +13,10 @@ pub const MAX_INITCODE_SIZE: usize = 2 * MAX_CODE_SIZE;
 
 /// EVM call stack limit
 pub const CALL_STACK_LIMIT: u64 = 1024;
+
+/// Default price of one gas in ink, the unit of Stylus WASM execution.
+pub const DEFAULT_INK_PRICE: u32 = 10_000;
+
+/// Current version of Stylus programs, programs activated with another version have to be
+/// reactivated.
+pub const STYLUS_VERSION: u16 = 1;
@@ -0,0 +1,336 @@
+//! Activation of Stylus programs.
+//!
+//! Programs have to be activated before they are executed. Activation validates and
+//! compiles the WASM module and records the program version in the storage of the
+//! [`ARB_WASM_ADDRESS`] account, keyed by the code hash of the program. Compiled modules are
+//! kept in a [`ProgramCache`] that is shared by all EVM instances.
+use crate::{ProgramError, StylusProgram};
+use bytecode::StylusBytecode;
+use core::num::NonZeroUsize;
+use lru::LruCache;
+use primitives::{address, Address, B256, U256};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+/// Address of the account that activates programs and stores their activation.
+pub const ARB_WASM_ADDRESS: Address = address!("0000000000000000000000000000000000000071");
+
+/// Selector of `activateProgram(address)`, returns `(uint16 version, uint256 dataFee)`.
+pub const ACTIVATE_PROGRAM_SELECTOR: [u8; 4] = [0x58, 0xc7, 0x80, 0xc2];
+
+/// Base gas of the program activation.
+pub const ACTIVATION_GAS: u64 = 1_000_000;
+
+/// Gas of the program activation for every word of the uncompressed WASM module.
+pub const ACTIVATION_WORD_GAS: u64 = 1_000;
+
+/// Data fee of the program activation in wei for every byte of the uncompressed WASM module.
+pub const ACTIVATION_DATA_FEE_PER_BYTE: u64 = 1_000_000_000;
+
+/// Returns the storage slot of the [`ARB_WASM_ADDRESS`] account that holds the activated
+/// version of the program.
+#[inline]
+pub fn activation_slot(code_hash: B256) -> U256 {
+    U256::from_be_bytes(code_hash.0)
+}
+
+/// Returns the gas of activating the WASM module with the given size.
+#[inline]
+pub fn activation_gas(wasm_len: usize) -> u64 {
+    let words = (wasm_len as u64).div_ceil(32);
+    ACTIVATION_GAS.saturating_add(words.saturating_mul(ACTIVATION_WORD_GAS))
+}
+
+/// Returns the data fee of activating the WASM module with the given size.
+#[inline]
+pub fn activation_data_fee(wasm_len: usize) -> U256 {
+    U256::from(wasm_len as u64) * U256::from(ACTIVATION_DATA_FEE_PER_BYTE)
+}
+
+/// Default number of programs kept by the [`ProgramCache`].
+pub const DEFAULT_PROGRAM_CACHE_SIZE: usize = 1024;
+
+/// Cache of compiled Stylus programs keyed by code hash and program version.
+///
+/// The cache holds a bounded number of programs, the least recently used program is evicted
+/// when a new one is inserted into a full cache. Use [`ProgramCache::global`] to share
+/// compiled programs between EVM instances.
+#[derive(Debug)]
+pub struct ProgramCache {
+    programs: Mutex<LruCache<(B256, u16), Arc<StylusProgram>>>,
+}
+
+impl Default for ProgramCache {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_PROGRAM_CACHE_SIZE)
+    }
+}
+
+impl ProgramCache {
+    /// Creates an empty cache with the [`DEFAULT_PROGRAM_CACHE_SIZE`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty cache holding at most `capacity` programs, at least one.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            programs: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Returns the cache shared by all EVM instances.
+    pub fn global() -> &'static Self {
+        static INSTANCE: OnceLock<ProgramCache> = OnceLock::new();
+        INSTANCE.get_or_init(Self::new)
+    }
+
+    /// Returns the compiled program.
+    pub fn get(&self, code_hash: B256, version: u16) -> Option<Arc<StylusProgram>> {
+        self.programs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&(code_hash, version))
+            .cloned()
+    }
+
+    /// Inserts the compiled program and returns it.
+    pub fn insert(
+        &self,
+        code_hash: B256,
+        version: u16,
+        program: StylusProgram,
+    ) -> Arc<StylusProgram> {
+        let program = Arc::new(program);
+        self.programs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .put((code_hash, version), program.clone());
+        program
+    }
+
+    /// Returns the compiled program, compiles it if it is not cached.
+    ///
+    /// Programs are compiled again after a restart, activation has to be checked by the caller.
+    pub fn get_or_compile(
+        &self,
+        code_hash: B256,
+        version: u16,
+        bytecode: &StylusBytecode,
+    ) -> Result<Arc<StylusProgram>, ProgramError> {
+        if let Some(program) = self.get(code_hash, version) {
+            return Ok(program);
+        }
+        let program = StylusProgram::new(bytecode)?;
+        Ok(self.insert(code_hash, version, program))
+    }
+
+    /// Returns the number of cached programs.
+    pub fn len(&self) -> usize {
+        self.programs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns `true` if no program is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached programs.
+    pub fn clear(&self) {
+        self.programs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decompress,
+        runtime::tests::{activate, stylus_code},
+        STYLUS_VERSION,
+    };
+    use database::CacheDB;
+    use primitives::{keccak256, Bytes, TxKind};
+    use revm::{
+        context_interface::result::{ExecutionResult, HaltReason, Output},
+        database_interface::{DatabaseGetter, EmptyDB},
+        handler::EthHandler,
+        state::AccountInfo,
+        Context, EvmCommit, EvmExec, MainEvm,
+    };
+
+    #[test]
+    fn activate_program_selector() {
+        assert_eq!(
+            keccak256("activateProgram(address)")[..4],
+            ACTIVATE_PROGRAM_SELECTOR
+        );
+    }
+
+    const EMPTY_PROGRAM: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (func (export "user_entrypoint") (param i32) (result i32)
+                (i32.const 0)))
+    "#;
+
+    #[test]
+    fn cache_evicts_least_recently_used() {
+        let cache = ProgramCache::with_capacity(2);
+        let code = stylus_code(EMPTY_PROGRAM);
+        let program = || StylusProgram::new(code.stylus().unwrap()).unwrap();
+        let [a, b, c] = [1, 2, 3].map(B256::with_last_byte);
+        cache.insert(a, 1, program());
+        cache.insert(b, 1, program());
+        assert!(cache.get(a, 1).is_some());
+        cache.insert(c, 1, program());
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(b, 1).is_none());
+        assert!(cache.get(a, 1).is_some());
+        assert!(cache.get(c, 1).is_some());
+    }
+
+    #[test]
+    fn activation_read_is_charged() {
+        let program = Address::with_last_byte(0xb0);
+        let code = stylus_code(EMPTY_PROGRAM);
+        let mut db = CacheDB::<EmptyDB>::default();
+        activate(&mut db, &code);
+        db.insert_account_info(
+            program,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        let mut evm = MainEvm::new(
+            Context::builder()
+                .modify_cfg_chained(|cfg| cfg.stylus_version = STYLUS_VERSION)
+                .modify_tx_chained(|tx| tx.kind = TxKind::Call(program))
+                .with_db(db),
+            EthHandler::default(),
+        );
+        // Cold access of the `ArbWasm` account and of the activation slot.
+        let access_gas = 21_000 + 2_600 + 2_100;
+
+        evm.context.modify_tx(|tx| tx.gas_limit = access_gas - 1);
+        let result = evm.exec().unwrap().result;
+        assert!(
+            matches!(
+                result,
+                ExecutionResult::Halt {
+                    reason: HaltReason::OutOfGas(_),
+                    ..
+                }
+            ),
+            "{result:?}"
+        );
+
+        evm.context.modify_tx(|tx| tx.gas_limit = 100_000);
+        let result = evm.exec().unwrap().result;
+        assert!(result.is_success(), "{result:?}");
+        assert!(result.gas_used() > access_gas, "{result:?}");
+    }
+
+    #[test]
+    fn activate_and_reactivate() {
+        let caller = Address::with_last_byte(0xa0);
+        let program = Address::with_last_byte(0xb0);
+        let code = stylus_code(
+            r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "user_entrypoint") (param i32) (result i32)
+                    (i32.const 0)))
+            "#,
+        );
+        let code_hash = code.hash_slow();
+        let data_fee = activation_data_fee(decompress(code.stylus().unwrap()).unwrap().len());
+        let balance = U256::from(10).pow(U256::from(18));
+
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(caller, AccountInfo::from_balance(balance));
+        db.insert_account_info(program, AccountInfo::new(U256::ZERO, 1, code_hash, code));
+        let mut evm = MainEvm::new(
+            Context::builder()
+                .modify_cfg_chained(|cfg| cfg.disable_nonce_check = true)
+                .with_db(db),
+            EthHandler::default(),
+        );
+
+        let mut call = |version: u16, to: Address, data: Bytes, value: U256| {
+            evm.context.modify_cfg(|cfg| cfg.stylus_version = version);
+            evm.context.modify_tx(|tx| {
+                tx.caller = caller;
+                tx.kind = TxKind::Call(to);
+                tx.data = data;
+                tx.value = value;
+            });
+            let result = evm.exec_commit().unwrap();
+            (result, evm.context.db_ref().accounts.clone())
+        };
+        let activate = Bytes::from(
+            [
+                &ACTIVATE_PROGRAM_SELECTOR[..],
+                program.into_word().as_slice(),
+            ]
+            .concat(),
+        );
+
+        let (result, _) = call(1, program, Bytes::new(), U256::ZERO);
+        assert!(matches!(
+            result,
+            ExecutionResult::Halt {
+                reason: HaltReason::ProgramNotActivated,
+                ..
+            }
+        ));
+
+        for version in [1, 2] {
+            // Excess value is returned to the caller.
+            let (result, accounts) = call(
+                version,
+                ARB_WASM_ADDRESS,
+                activate.clone(),
+                balance / U256::from(2),
+            );
+            let ExecutionResult::Success {
+                output: Output::Call(output),
+                ..
+            } = result
+            else {
+                panic!("Activation failed: {result:?}");
+            };
+            assert_eq!(output[..32], U256::from(version).to_be_bytes::<32>());
+            assert_eq!(U256::from_be_slice(&output[32..]), data_fee);
+            let fees = data_fee * U256::from(version);
+            assert_eq!(accounts[&caller].info.balance, balance - fees);
+            assert_eq!(accounts[&ARB_WASM_ADDRESS].info.balance, fees);
+
+            // Program is already activated.
+            let (result, _) = call(
+                version,
+                ARB_WASM_ADDRESS,
+                activate.clone(),
+                balance / U256::from(2),
+            );
+            assert!(matches!(result, ExecutionResult::Revert { .. }));
+
+            let (result, _) = call(version, program, Bytes::new(), U256::ZERO);
+            assert!(result.is_success(), "{result:?}");
+        }
+
+        // Programs activated with an older version have to be reactivated.
+        let (result, _) = call(3, program, Bytes::new(), U256::ZERO);
+        assert!(matches!(
+            result,
+            ExecutionResult::Halt {
+                reason: HaltReason::ProgramVersionMismatch,
+                ..
+            }
+        ));
+    }
+}
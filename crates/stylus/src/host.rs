@@ -0,0 +1,679 @@
+//! Stylus host-io functions imported by programs from the `vm_hooks` module.
+//!
+//! Functions that only need the call environment are served directly from [`StylusData`].
+//! Functions that need the [`Host`][interpreter::Host] store a [`HostRequest`] and suspend
+//! the program, the request is served by the [`StylusRuntime`][crate::StylusRuntime]
+//! before execution resumes.
+use crate::{
+    meter::{INK_LEFT, INK_STATUS, STATUS_OUT_OF_INK},
+    program::MEMORY,
+};
+use core::fmt;
+use interpreter::{gas, CallScheme, InstructionResult};
+use primitives::{keccak256, Address, Bytes, B256, U256};
+use std::{collections::BTreeMap, sync::OnceLock, vec::Vec};
+use wasmi::{core::TrapCode, Caller, Error, Extern, Global, Linker, Memory, Val};
+
+/// Name of the module the host-io functions are imported from.
+pub const HOSTIO_MODULE: &str = "vm_hooks";
+
+/// Ink charged for every host-io call.
+pub const HOSTIO_INK: u64 = 8400;
+
+/// Environment of the executing Stylus program.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StylusEnv {
+    /// Call data of the program.
+    pub args: Bytes,
+    /// Address of the executing program.
+    pub contract_address: Address,
+    /// Caller of the program.
+    pub msg_sender: Address,
+    /// Value sent with the call.
+    pub msg_value: U256,
+    /// Whether state changes are disallowed.
+    pub is_static: bool,
+    /// Origin of the transaction.
+    pub tx_origin: Address,
+    /// Effective gas price of the transaction.
+    pub tx_gas_price: U256,
+    /// Price of one gas in ink.
+    pub ink_price: u32,
+    /// Block base fee.
+    pub block_basefee: U256,
+    /// Block beneficiary.
+    pub block_coinbase: Address,
+    /// Block gas limit.
+    pub block_gas_limit: u64,
+    /// Block number.
+    pub block_number: u64,
+    /// Block timestamp.
+    pub block_timestamp: u64,
+    /// Chain id.
+    pub chain_id: u64,
+}
+
+/// Storage slot cached by the program.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CachedSlot {
+    /// Current value of the slot.
+    pub value: U256,
+    /// Whether the value needs to be written to the host.
+    pub dirty: bool,
+}
+
+/// Request from the program that needs to be served by the host.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HostRequest {
+    /// Load storage slot and write it to `dest`.
+    StorageLoad { key: U256, dest: u32 },
+    /// Write dirty cached slots to the host.
+    StorageFlush { clear: bool },
+    /// Load transient storage slot and write it to `dest`.
+    TransientLoad { key: U256, dest: u32 },
+    /// Store transient storage slot.
+    TransientStore { key: U256, value: U256 },
+    /// Call another contract.
+    Call {
+        scheme: CallScheme,
+        address: Address,
+        input: Bytes,
+        value: U256,
+        gas_limit: u64,
+        return_data_len: u32,
+    },
+    /// Create a new contract.
+    Create {
+        code: Bytes,
+        endowment: U256,
+        salt: Option<U256>,
+        contract: u32,
+        revert_data_len: u32,
+    },
+    /// Emit log.
+    EmitLog { topics: Vec<B256>, data: Bytes },
+    /// Load balance of the account and write it to `dest`.
+    AccountBalance { address: Address, dest: u32 },
+    /// Load code of the account and write the requested part to `dest`.
+    AccountCode {
+        address: Address,
+        offset: u32,
+        size: u32,
+        dest: u32,
+    },
+    /// Load code size of the account.
+    AccountCodeSize { address: Address },
+    /// Load code hash of the account and write it to `dest`.
+    AccountCodeHash { address: Address, dest: u32 },
+    /// Halt execution with the given result.
+    Halt(InstructionResult),
+}
+
+/// Store data of the executing Stylus program.
+#[derive(Clone, Debug, Default)]
+pub struct StylusData {
+    /// Environment of the program.
+    pub env: StylusEnv,
+    /// Output set by `write_result`.
+    pub output: Bytes,
+    /// Return data of the last call or create.
+    pub return_data: Bytes,
+    /// Storage slots cached by the program.
+    pub storage_cache: BTreeMap<U256, CachedSlot>,
+    /// Pending host request.
+    pub request: Option<HostRequest>,
+}
+
+impl StylusData {
+    /// Creates new store data for the given environment.
+    pub fn new(env: StylusEnv) -> Self {
+        Self {
+            env,
+            ..Default::default()
+        }
+    }
+}
+
+/// Host error used to suspend the program until the pending [`HostRequest`] is served.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Suspend;
+
+impl fmt::Display for Suspend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Stylus program suspended on host request")
+    }
+}
+
+impl wasmi::core::HostError for Suspend {}
+
+/// Returns the linker with all Stylus host-io functions.
+pub fn linker() -> &'static Linker<StylusData> {
+    static LINKER: OnceLock<Linker<StylusData>> = OnceLock::new();
+    LINKER.get_or_init(|| {
+        let mut linker = Linker::new(crate::program::engine());
+        define_hostio(&mut linker).expect("Host-io functions are unique");
+        linker
+    })
+}
+
+type Ctx<'a> = Caller<'a, StylusData>;
+
+fn define_hostio(linker: &mut Linker<StylusData>) -> Result<(), wasmi::errors::LinkerError> {
+    let m = HOSTIO_MODULE;
+
+    // Call data and output.
+    linker.func_wrap(m, "read_args", |mut caller: Ctx<'_>, dest: u32| {
+        charge(&mut caller, HOSTIO_INK)?;
+        let args = caller.data().env.args.clone();
+        write(&mut caller, dest, &args)
+    })?;
+    linker.func_wrap(
+        m,
+        "write_result",
+        |mut caller: Ctx<'_>, ptr: u32, len: u32| {
+            charge(&mut caller, HOSTIO_INK)?;
+            caller.data_mut().output = read(&mut caller, ptr, len)?.into();
+            Ok(())
+        },
+    )?;
+
+    // Storage.
+    linker.func_wrap(
+        m,
+        "storage_load_bytes32",
+        |mut caller: Ctx<'_>, key: u32, dest: u32| {
+            charge(&mut caller, HOSTIO_INK)?;
+            let key = read_word(&mut caller, key)?;
+            if let Some(slot) = caller.data().storage_cache.get(&key) {
+                let value = slot.value.to_be_bytes::<32>();
+                return write(&mut caller, dest, &value);
+            }
+            Err(suspend(&mut caller, HostRequest::StorageLoad { key, dest }))
+        },
+    )?;
+    linker.func_wrap(
+        m,
+        "storage_cache_bytes32",
+        |mut caller: Ctx<'_>, key: u32, value: u32| {
+            charge(&mut caller, HOSTIO_INK)?;
+            let key = read_word(&mut caller, key)?;
+            let value = read_word(&mut caller, value)?;
+            caller
+                .data_mut()
+                .storage_cache
+                .insert(key, CachedSlot { value, dirty: true });
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        m,
+        "storage_flush_cache",
+        |mut caller: Ctx<'_>, clear: u32| {
+            charge(&mut caller, HOSTIO_INK)?;
+            let clear = clear != 0;
+            Err::<(), _>(suspend(&mut caller, HostRequest::StorageFlush { clear }))
+        },
+    )?;
+    linker.func_wrap(
+        m,
+        "transient_load_bytes32",
+        |mut caller: Ctx<'_>, key: u32, dest: u32| {
+            charge(&mut caller, HOSTIO_INK)?;
+            let key = read_word(&mut caller, key)?;
+            Err::<(), _>(suspend(
+                &mut caller,
+                HostRequest::TransientLoad { key, dest },
+            ))
+        },
+    )?;
+    linker.func_wrap(
+        m,
+        "transient_store_bytes32",
+        |mut caller: Ctx<'_>, key: u32, value: u32| {
+            charge(&mut caller, HOSTIO_INK)?;
+            let key = read_word(&mut caller, key)?;
+            let value = read_word(&mut caller, value)?;
+            Err::<(), _>(suspend(
+                &mut caller,
+                HostRequest::TransientStore { key, value },
+            ))
+        },
+    )?;
+
+    // Calls.
+    linker.func_wrap(
+        m,
+        "call_contract",
+        |mut caller: Ctx<'_>,
+         contract: u32,
+         data: u32,
+         data_len: u32,
+         value: u32,
+         gas_limit: u64,
+         return_data_len: u32| {
+            let value = read_word(&mut caller, value)?;
+            call(
+                &mut caller,
+                CallScheme::Call,
+                contract,
+                data,
+                data_len,
+                value,
+                gas_limit,
+                return_data_len,
+            )
+        },
+    )?;
+    linker.func_wrap(
+        m,
+        "delegate_call_contract",
+        |mut caller: Ctx<'_>,
+         contract: u32,
+         data: u32,
+         data_len: u32,
+         gas_limit: u64,
+         return_data_len: u32| {
+            call(
+                &mut caller,
+                CallScheme::DelegateCall,
+                contract,
+                data,
+                data_len,
+                U256::ZERO,
+                gas_limit,
+                return_data_len,
+            )
+        },
+    )?;
+    linker.func_wrap(
+        m,
+        "static_call_contract",
+        |mut caller: Ctx<'_>,
+         contract: u32,
+         data: u32,
+         data_len: u32,
+         gas_limit: u64,
+         return_data_len: u32| {
+            call(
+                &mut caller,
+                CallScheme::StaticCall,
+                contract,
+                data,
+                data_len,
+                U256::ZERO,
+                gas_limit,
+                return_data_len,
+            )
+        },
+    )?;
+    linker.func_wrap(
+        m,
+        "create1",
+        |mut caller: Ctx<'_>,
+         code: u32,
+         code_len: u32,
+         endowment: u32,
+         contract: u32,
+         revert_data_len: u32| {
+            create(
+                &mut caller,
+                code,
+                code_len,
+                endowment,
+                None,
+                contract,
+                revert_data_len,
+            )
+        },
+    )?;
+    linker.func_wrap(
+        m,
+        "create2",
+        |mut caller: Ctx<'_>,
+         code: u32,
+         code_len: u32,
+         endowment: u32,
+         salt: u32,
+         contract: u32,
+         revert_data_len: u32| {
+            create(
+                &mut caller,
+                code,
+                code_len,
+                endowment,
+                Some(salt),
+                contract,
+                revert_data_len,
+            )
+        },
+    )?;
+    linker.func_wrap(
+        m,
+        "read_return_data",
+        |mut caller: Ctx<'_>, dest: u32, offset: u32, size: u32| {
+            charge(&mut caller, HOSTIO_INK)?;
+            let return_data = caller.data().return_data.clone();
+            let start = (offset as usize).min(return_data.len());
+            let end = start.saturating_add(size as usize).min(return_data.len());
+            write(&mut caller, dest, &return_data[start..end])?;
+            Ok((end - start) as u32)
+        },
+    )?;
+    linker.func_wrap(m, "return_data_size", |mut caller: Ctx<'_>| {
+        charge(&mut caller, HOSTIO_INK)?;
+        Ok(caller.data().return_data.len() as u32)
+    })?;
+
+    // Logs.
+    linker.func_wrap(
+        m,
+        "emit_log",
+        |mut caller: Ctx<'_>, data: u32, len: u32, topics: u32| {
+            charge(&mut caller, HOSTIO_INK)?;
+            if caller.data().env.is_static {
+                return Err::<(), _>(halt(
+                    &mut caller,
+                    InstructionResult::StateChangeDuringStaticCall,
+                ));
+            }
+            let topics_len = topics as usize * 32;
+            if topics > 4 || (len as usize) < topics_len {
+                return Err(TrapCode::BadSignature.into());
+            }
+            let data = read(&mut caller, data, len)?;
+            let (topics, data) = data.split_at(topics_len);
+            let topics = topics.chunks_exact(32).map(B256::from_slice).collect();
+            let data = Bytes::copy_from_slice(data);
+            Err(suspend(&mut caller, HostRequest::EmitLog { topics, data }))
+        },
+    )?;
+
+    // Accounts.
+    linker.func_wrap(
+        m,
+        "account_balance",
+        |mut caller: Ctx<'_>, address: u32, dest: u32| {
+            charge(&mut caller, HOSTIO_INK)?;
+            let address = read_address(&mut caller, address)?;
+            Err::<(), _>(suspend(
+                &mut caller,
+                HostRequest::AccountBalance { address, dest },
+            ))
+        },
+    )?;
+    linker.func_wrap(
+        m,
+        "account_code",
+        |mut caller: Ctx<'_>, address: u32, offset: u32, size: u32, dest: u32| {
+            charge(&mut caller, HOSTIO_INK)?;
+            let address = read_address(&mut caller, address)?;
+            let request = HostRequest::AccountCode {
+                address,
+                offset,
+                size,
+                dest,
+            };
+            Err::<u32, _>(suspend(&mut caller, request))
+        },
+    )?;
+    linker.func_wrap(
+        m,
+        "account_code_size",
+        |mut caller: Ctx<'_>, address: u32| {
+            charge(&mut caller, HOSTIO_INK)?;
+            let address = read_address(&mut caller, address)?;
+            Err::<u32, _>(suspend(
+                &mut caller,
+                HostRequest::AccountCodeSize { address },
+            ))
+        },
+    )?;
+    linker.func_wrap(
+        m,
+        "account_codehash",
+        |mut caller: Ctx<'_>, address: u32, dest: u32| {
+            charge(&mut caller, HOSTIO_INK)?;
+            let address = read_address(&mut caller, address)?;
+            Err::<(), _>(suspend(
+                &mut caller,
+                HostRequest::AccountCodeHash { address, dest },
+            ))
+        },
+    )?;
+
+    // Block.
+    linker.func_wrap(m, "block_basefee", |mut caller: Ctx<'_>, dest: u32| {
+        charge(&mut caller, HOSTIO_INK)?;
+        let value = caller.data().env.block_basefee.to_be_bytes::<32>();
+        write(&mut caller, dest, &value)
+    })?;
+    linker.func_wrap(m, "block_coinbase", |mut caller: Ctx<'_>, dest: u32| {
+        charge(&mut caller, HOSTIO_INK)?;
+        let coinbase = caller.data().env.block_coinbase;
+        write(&mut caller, dest, coinbase.as_slice())
+    })?;
+    linker.func_wrap(m, "block_gas_limit", |mut caller: Ctx<'_>| {
+        charge(&mut caller, HOSTIO_INK)?;
+        Ok(caller.data().env.block_gas_limit)
+    })?;
+    linker.func_wrap(m, "block_number", |mut caller: Ctx<'_>| {
+        charge(&mut caller, HOSTIO_INK)?;
+        Ok(caller.data().env.block_number)
+    })?;
+    linker.func_wrap(m, "block_timestamp", |mut caller: Ctx<'_>| {
+        charge(&mut caller, HOSTIO_INK)?;
+        Ok(caller.data().env.block_timestamp)
+    })?;
+    linker.func_wrap(m, "chainid", |mut caller: Ctx<'_>| {
+        charge(&mut caller, HOSTIO_INK)?;
+        Ok(caller.data().env.chain_id)
+    })?;
+
+    // Message.
+    linker.func_wrap(m, "contract_address", |mut caller: Ctx<'_>, dest: u32| {
+        charge(&mut caller, HOSTIO_INK)?;
+        let address = caller.data().env.contract_address;
+        write(&mut caller, dest, address.as_slice())
+    })?;
+    linker.func_wrap(m, "msg_reentrant", |mut caller: Ctx<'_>| {
+        charge(&mut caller, HOSTIO_INK)?;
+        Ok(0u32)
+    })?;
+    linker.func_wrap(m, "msg_sender", |mut caller: Ctx<'_>, dest: u32| {
+        charge(&mut caller, HOSTIO_INK)?;
+        let sender = caller.data().env.msg_sender;
+        write(&mut caller, dest, sender.as_slice())
+    })?;
+    linker.func_wrap(m, "msg_value", |mut caller: Ctx<'_>, dest: u32| {
+        charge(&mut caller, HOSTIO_INK)?;
+        let value = caller.data().env.msg_value.to_be_bytes::<32>();
+        write(&mut caller, dest, &value)
+    })?;
+
+    // Transaction.
+    linker.func_wrap(m, "tx_gas_price", |mut caller: Ctx<'_>, dest: u32| {
+        charge(&mut caller, HOSTIO_INK)?;
+        let value = caller.data().env.tx_gas_price.to_be_bytes::<32>();
+        write(&mut caller, dest, &value)
+    })?;
+    linker.func_wrap(m, "tx_ink_price", |mut caller: Ctx<'_>| {
+        charge(&mut caller, HOSTIO_INK)?;
+        Ok(caller.data().env.ink_price)
+    })?;
+    linker.func_wrap(m, "tx_origin", |mut caller: Ctx<'_>, dest: u32| {
+        charge(&mut caller, HOSTIO_INK)?;
+        let origin = caller.data().env.tx_origin;
+        write(&mut caller, dest, origin.as_slice())
+    })?;
+
+    // Metering.
+    linker.func_wrap(m, "evm_gas_left", |mut caller: Ctx<'_>| {
+        charge(&mut caller, HOSTIO_INK)?;
+        let ink_price = caller.data().env.ink_price.max(1) as u64;
+        Ok(ink_left(&caller)? / ink_price)
+    })?;
+    linker.func_wrap(m, "evm_ink_left", |mut caller: Ctx<'_>| {
+        charge(&mut caller, HOSTIO_INK)?;
+        ink_left(&caller)
+    })?;
+    linker.func_wrap(
+        m,
+        "pay_for_memory_grow",
+        |mut caller: Ctx<'_>, _pages: u32| charge(&mut caller, HOSTIO_INK),
+    )?;
+
+    // Crypto.
+    linker.func_wrap(
+        m,
+        "native_keccak256",
+        |mut caller: Ctx<'_>, ptr: u32, len: u32, dest: u32| {
+            let gas = gas::keccak256_cost(len as usize).unwrap_or(u64::MAX);
+            let ink_price = caller.data().env.ink_price as u64;
+            charge(
+                &mut caller,
+                HOSTIO_INK.saturating_add(gas.saturating_mul(ink_price)),
+            )?;
+            let hash = keccak256(read(&mut caller, ptr, len)?);
+            write(&mut caller, dest, hash.as_slice())
+        },
+    )?;
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn call(
+    caller: &mut Ctx<'_>,
+    scheme: CallScheme,
+    contract: u32,
+    data: u32,
+    data_len: u32,
+    value: U256,
+    gas_limit: u64,
+    return_data_len: u32,
+) -> Result<u32, Error> {
+    charge(caller, HOSTIO_INK)?;
+    if caller.data().env.is_static && !value.is_zero() {
+        return Err(halt(caller, InstructionResult::CallNotAllowedInsideStatic));
+    }
+    let address = read_address(caller, contract)?;
+    let input = read(caller, data, data_len)?.into();
+    let request = HostRequest::Call {
+        scheme,
+        address,
+        input,
+        value,
+        gas_limit,
+        return_data_len,
+    };
+    Err(suspend(caller, request))
+}
+
+fn create(
+    caller: &mut Ctx<'_>,
+    code: u32,
+    code_len: u32,
+    endowment: u32,
+    salt: Option<u32>,
+    contract: u32,
+    revert_data_len: u32,
+) -> Result<(), Error> {
+    charge(caller, HOSTIO_INK)?;
+    if caller.data().env.is_static {
+        return Err(halt(caller, InstructionResult::StateChangeDuringStaticCall));
+    }
+    let code = read(caller, code, code_len)?.into();
+    let endowment = read_word(caller, endowment)?;
+    let salt = salt.map(|salt| read_word(caller, salt)).transpose()?;
+    let request = HostRequest::Create {
+        code,
+        endowment,
+        salt,
+        contract,
+        revert_data_len,
+    };
+    Err(suspend(caller, request))
+}
+
+/// Stores the request and returns the error that suspends the program.
+fn suspend(caller: &mut Ctx<'_>, request: HostRequest) -> Error {
+    caller.data_mut().request = Some(request);
+    Error::host(Suspend)
+}
+
+/// Returns the error that halts the program with the given result.
+fn halt(caller: &mut Ctx<'_>, result: InstructionResult) -> Error {
+    suspend(caller, HostRequest::Halt(result))
+}
+
+/// Consumes ink, traps with [`TrapCode::OutOfFuel`] if there is not enough ink left.
+fn charge(caller: &mut Ctx<'_>, ink: u64) -> Result<(), Error> {
+    let left = ink_left(caller)?;
+    match left.checked_sub(ink) {
+        Some(left) => set_global(caller, INK_LEFT, Val::I64(left as i64)),
+        None => {
+            set_global(caller, INK_STATUS, Val::I32(STATUS_OUT_OF_INK))?;
+            Err(TrapCode::OutOfFuel.into())
+        }
+    }
+}
+
+fn global(caller: &Ctx<'_>, name: &str) -> Result<Global, Error> {
+    caller
+        .get_export(name)
+        .and_then(Extern::into_global)
+        .ok_or_else(|| TrapCode::BadSignature.into())
+}
+
+fn ink_left(caller: &Ctx<'_>) -> Result<u64, Error> {
+    match global(caller, INK_LEFT)?.get(caller) {
+        Val::I64(ink) => Ok(ink as u64),
+        _ => Err(TrapCode::BadSignature.into()),
+    }
+}
+
+fn set_global(caller: &mut Ctx<'_>, name: &str, value: Val) -> Result<(), Error> {
+    global(caller, name)?
+        .set(caller, value)
+        .map_err(|_| TrapCode::BadSignature.into())
+}
+
+fn memory(caller: &Ctx<'_>) -> Result<Memory, Error> {
+    caller
+        .get_export(MEMORY)
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| TrapCode::MemoryOutOfBounds.into())
+}
+
+/// Reads the memory of the program, the copy is charged as the EVM copy of the same length.
+///
+/// Bounds are checked and the copy is charged before the buffer is allocated.
+fn read(caller: &mut Ctx<'_>, ptr: u32, len: u32) -> Result<Vec<u8>, Error> {
+    let memory = memory(caller)?;
+    if ptr as usize + len as usize > memory.data_size(&*caller) {
+        return Err(TrapCode::MemoryOutOfBounds.into());
+    }
+    let gas = gas::cost_per_word(len as usize, gas::COPY).unwrap_or(u64::MAX);
+    let ink_price = caller.data().env.ink_price as u64;
+    charge(caller, gas.saturating_mul(ink_price))?;
+
+    let mut buffer = vec![0; len as usize];
+    memory
+        .read(&*caller, ptr as usize, &mut buffer)
+        .map_err(|_| TrapCode::MemoryOutOfBounds)?;
+    Ok(buffer)
+}
+
+fn read_word(caller: &mut Ctx<'_>, ptr: u32) -> Result<U256, Error> {
+    Ok(U256::from_be_slice(&read(caller, ptr, 32)?))
+}
+
+fn read_address(caller: &mut Ctx<'_>, ptr: u32) -> Result<Address, Error> {
+    Ok(Address::from_slice(&read(caller, ptr, 20)?))
+}
+
+fn write(caller: &mut Ctx<'_>, ptr: u32, data: &[u8]) -> Result<(), Error> {
+    memory(caller)?
+        .write(caller, ptr as usize, data)
+        .map_err(|_| TrapCode::MemoryOutOfBounds.into())
+}
@@ -0,0 +1,22 @@
+//! Execution of Arbitrum Stylus WASM programs.
+//!
+//! Stylus programs are deployed as [`StylusBytecode`][bytecode::StylusBytecode] and
+//! executed by [`StylusRuntime`] that exposes the Stylus host-io ABI on top of the
+//! [`Host`][interpreter::Host].
+//!
+//! Programs are only deployed and executed if the `stylus` feature of revm is enabled and the
+//! configured Stylus version is not zero, see [`STYLUS_VERSION`].
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+
+pub mod activation;
+pub mod host;
+pub mod meter;
+pub mod program;
+pub mod runtime;
+
+pub use activation::{ProgramCache, ARB_WASM_ADDRESS, DEFAULT_PROGRAM_CACHE_SIZE};
+pub use host::{HostRequest, StylusData, StylusEnv, HOSTIO_INK, HOSTIO_MODULE};
+pub use meter::{instrument, Instrumented, MAX_MEMORY_PAGES, MAX_STACK_DEPTH, MEMORY_PAGE_GAS};
+pub use program::{decompress, ProgramError, StylusProgram, MAX_WASM_SIZE};
+pub use runtime::{StylusError, StylusRuntime};
+pub use specification::constants::{DEFAULT_INK_PRICE, STYLUS_VERSION};
@@ -0,0 +1,629 @@
+//! Deterministic metering of Stylus programs.
+//!
+//! Programs are instrumented before compilation:
+//! * Every basic block starts by charging the ink of its instructions from the
+//!   [`INK_LEFT`] global.
+//! * Calls to program functions reserve the worst-case frame size of the callee from the
+//!   [`STACK_LEFT`] global.
+//! * `memory.grow` charges [`PAGE_INK`] ink for every requested page.
+//!
+//! When a limit is exceeded [`INK_STATUS`] is set before trapping so the runtime can tell
+//! metering traps apart from other traps.
+use crate::ProgramError;
+use core::convert::Infallible;
+use std::vec::Vec;
+use wasm_encoder::{
+    reencode::{utils, Reencode},
+    BlockType, CodeSection, ConstExpr, ExportKind, ExportSection, Function, GlobalSection,
+    GlobalType, Instruction, MemorySection, Module, SectionId, ValType,
+};
+use wasmparser::{
+    FuncType, FunctionBody, Operator, Parser, Payload, TypeRef, ValidPayload, Validator,
+    WasmFeatures,
+};
+
+/// Exported global with the ink left.
+pub const INK_LEFT: &str = "stylus_ink_left";
+
+/// Exported global with the metering status, see [`STATUS_OUT_OF_INK`] and
+/// [`STATUS_STACK_OVERFLOW`].
+pub const INK_STATUS: &str = "stylus_ink_status";
+
+/// Exported global with the stack depth left, in words.
+pub const STACK_LEFT: &str = "stylus_stack_left";
+
+/// Exported global with the ink price of a memory page.
+pub const PAGE_INK: &str = "stylus_page_ink";
+
+/// Status of the program that ran out of ink.
+pub const STATUS_OUT_OF_INK: i32 = 1;
+
+/// Status of the program that exceeded the stack depth.
+pub const STATUS_STACK_OVERFLOW: i32 = 2;
+
+/// Maximum stack depth of the program, in words.
+pub const MAX_STACK_DEPTH: u32 = 256 * 1024;
+
+/// Maximum number of memory pages of the program.
+pub const MAX_MEMORY_PAGES: u32 = 128;
+
+/// Gas of one memory page, charged for the initial memory and for `memory.grow`.
+pub const MEMORY_PAGE_GAS: u64 = 1000;
+
+/// WASM features allowed in Stylus programs.
+///
+/// Floats are not supported as they are not deterministic.
+const FEATURES: WasmFeatures = WasmFeatures::MUTABLE_GLOBAL
+    .union(WasmFeatures::SIGN_EXTENSION)
+    .union(WasmFeatures::MULTI_VALUE)
+    .union(WasmFeatures::BULK_MEMORY)
+    .union(WasmFeatures::GC_TYPES);
+
+/// Instrumented WASM module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instrumented {
+    /// Instrumented module.
+    pub wasm: Vec<u8>,
+    /// Initial memory pages of the module.
+    pub memory_pages: u32,
+}
+
+/// Validates the module and instruments it with ink, stack and memory metering.
+pub fn instrument(wasm: &[u8]) -> Result<Instrumented, ProgramError> {
+    let info = ModuleInfo::new(wasm)?;
+
+    let mut instrumenter = Instrumenter {
+        info: &info,
+        defined_functions: 0,
+        globals_added: false,
+        exports_added: false,
+    };
+    let mut module = Module::new();
+    instrumenter
+        .parse_core_module(&mut module, Parser::new(0), wasm)
+        .map_err(|_| ProgramError::InvalidWasm)?;
+
+    Ok(Instrumented {
+        wasm: module.finish(),
+        memory_pages: info.memory_pages,
+    })
+}
+
+/// Returns the ink of the instruction.
+pub fn opcode_ink(op: &Operator<'_>) -> u64 {
+    use Operator::*;
+    match op {
+        Unreachable | Nop | Block { .. } | Loop { .. } | If { .. } | Else | End | Return => 1,
+        I32Const { .. } | I64Const { .. } | Drop => 1,
+        Br { .. } | BrIf { .. } => 765,
+        BrTable { targets } => 2400 + 325 * targets.len() as u64,
+        Call { .. } => 3800,
+        CallIndirect { .. } => 13610,
+        Select | TypedSelect { .. } => 1250,
+        LocalGet { .. } | LocalTee { .. } => 75,
+        LocalSet { .. } => 210,
+        GlobalGet { .. } => 225,
+        GlobalSet { .. } => 575,
+        I32Load { .. }
+        | I64Load { .. }
+        | I32Load8S { .. }
+        | I32Load8U { .. }
+        | I32Load16S { .. }
+        | I32Load16U { .. }
+        | I64Load8S { .. }
+        | I64Load8U { .. }
+        | I64Load16S { .. }
+        | I64Load16U { .. }
+        | I64Load32S { .. }
+        | I64Load32U { .. } => 670,
+        I32Store { .. }
+        | I64Store { .. }
+        | I32Store8 { .. }
+        | I32Store16 { .. }
+        | I64Store8 { .. }
+        | I64Store16 { .. }
+        | I64Store32 { .. } => 825,
+        MemorySize { .. } => 3000,
+        // Pages are charged separately.
+        MemoryGrow { .. } => 1,
+        MemoryCopy { .. } | MemoryFill { .. } | MemoryInit { .. } => 3100,
+        DataDrop { .. } => 1,
+        I32Mul | I64Mul => 160,
+        I32DivS | I32DivU | I32RemS | I32RemU => 1120,
+        I64DivS | I64DivU | I64RemS | I64RemU => 1270,
+        I32Clz | I32Ctz | I64Clz | I64Ctz => 210,
+        I32Popcnt | I64Popcnt => 2650,
+        // Remaining integer comparisons, arithmetic, bitwise and conversion operations.
+        _ => 70,
+    }
+}
+
+/// Returns `true` if the instruction ends a basic block.
+fn is_block_end(op: &Operator<'_>) -> bool {
+    use Operator::*;
+    matches!(
+        op,
+        Block { .. }
+            | Loop { .. }
+            | If { .. }
+            | Else
+            | End
+            | Br { .. }
+            | BrIf { .. }
+            | BrTable { .. }
+            | Return
+            | Call { .. }
+            | CallIndirect { .. }
+            | Unreachable
+    )
+}
+
+/// Information about the module collected during validation.
+#[derive(Debug, Default)]
+struct ModuleInfo {
+    types: Vec<FuncType>,
+    /// Type index of all functions, imported functions first.
+    functions: Vec<u32>,
+    imported_functions: u32,
+    /// Number of imported and defined globals.
+    globals: u32,
+    memory_pages: u32,
+    /// Worst-case frame size of defined functions, in words.
+    frame_sizes: Vec<u32>,
+    /// Whether defined functions use `memory.grow`.
+    grows_memory: Vec<bool>,
+}
+
+impl ModuleInfo {
+    fn new(wasm: &[u8]) -> Result<Self, ProgramError> {
+        let mut info = Self::default();
+        let mut has_memory = false;
+        let mut validator = Validator::new_with_features(FEATURES);
+        for payload in Parser::new(0).parse_all(wasm) {
+            let payload = payload.map_err(|_| ProgramError::InvalidWasm)?;
+            let valid = validator
+                .payload(&payload)
+                .map_err(|_| ProgramError::InvalidWasm)?;
+
+            match payload {
+                Payload::TypeSection(reader) => {
+                    for ty in reader.into_iter_err_on_gc_types() {
+                        info.types.push(ty.map_err(|_| ProgramError::InvalidWasm)?);
+                    }
+                }
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        match import.map_err(|_| ProgramError::InvalidWasm)?.ty {
+                            TypeRef::Func(ty) => {
+                                info.functions.push(ty);
+                                info.imported_functions += 1;
+                            }
+                            TypeRef::Global(_) => info.globals += 1,
+                            // Memory has to be owned by the program.
+                            TypeRef::Memory(_) => return Err(ProgramError::InvalidWasm),
+                            _ => {}
+                        }
+                    }
+                }
+                Payload::FunctionSection(reader) => {
+                    for ty in reader {
+                        info.functions
+                            .push(ty.map_err(|_| ProgramError::InvalidWasm)?);
+                    }
+                }
+                Payload::GlobalSection(reader) => info.globals += reader.count(),
+                Payload::MemorySection(reader) => {
+                    for memory in reader {
+                        let memory = memory.map_err(|_| ProgramError::InvalidWasm)?;
+                        if memory.initial > MAX_MEMORY_PAGES as u64 {
+                            return Err(ProgramError::MemoryTooLarge);
+                        }
+                        info.memory_pages = memory.initial as u32;
+                        has_memory = true;
+                    }
+                }
+                _ => {}
+            }
+
+            if let ValidPayload::Func(func, body) = valid {
+                let params = info
+                    .types
+                    .get(func.ty as usize)
+                    .map_or(0, |ty| ty.params().len() as u32);
+                let mut validator = func.into_validator(Default::default());
+                let (frame_size, grows_memory) = analyze_function(&mut validator, &body, params)
+                    .map_err(|_| ProgramError::InvalidWasm)?;
+                info.frame_sizes.push(frame_size);
+                info.grows_memory.push(grows_memory);
+            }
+        }
+        if !has_memory {
+            return Err(ProgramError::MissingMemory);
+        }
+        Ok(info)
+    }
+
+    /// Returns the worst-case frame size of the called function.
+    fn frame_size(&self, function: u32) -> Option<u32> {
+        let defined = function.checked_sub(self.imported_functions)?;
+        self.frame_sizes.get(defined as usize).copied()
+    }
+
+    /// Returns the worst-case frame size of the functions with the given type.
+    fn indirect_frame_size(&self, ty: u32) -> Option<u32> {
+        self.functions
+            .iter()
+            .enumerate()
+            .filter(|(_, function_ty)| **function_ty == ty)
+            .filter_map(|(index, _)| self.frame_size(index as u32))
+            .max()
+    }
+}
+
+/// Validates the function and returns its frame size and whether it grows memory.
+fn analyze_function(
+    validator: &mut wasmparser::FuncValidator<wasmparser::ValidatorResources>,
+    body: &FunctionBody<'_>,
+    params: u32,
+) -> wasmparser::Result<(u32, bool)> {
+    let mut locals = body.get_locals_reader()?;
+    let mut frame_size = 1 + params;
+    for _ in 0..locals.get_count() {
+        let offset = locals.original_position();
+        let (count, ty) = locals.read()?;
+        validator.define_locals(offset, count, ty)?;
+        frame_size = frame_size.saturating_add(count);
+    }
+
+    let mut operators = body.get_operators_reader()?;
+    let mut max_height = 0;
+    let mut grows_memory = false;
+    while !operators.eof() {
+        let (op, offset) = operators.read_with_offset()?;
+        grows_memory |= matches!(op, Operator::MemoryGrow { .. });
+        validator.op(offset, &op)?;
+        max_height = max_height.max(validator.operand_stack_height());
+    }
+    validator.finish(operators.original_position())?;
+
+    Ok((frame_size.saturating_add(max_height), grows_memory))
+}
+
+/// Re-encodes the module with metering.
+struct Instrumenter<'a> {
+    info: &'a ModuleInfo,
+    defined_functions: u32,
+    globals_added: bool,
+    exports_added: bool,
+}
+
+impl Instrumenter<'_> {
+    fn ink_left(&self) -> u32 {
+        self.info.globals
+    }
+
+    fn ink_status(&self) -> u32 {
+        self.info.globals + 1
+    }
+
+    fn stack_left(&self) -> u32 {
+        self.info.globals + 2
+    }
+
+    fn page_ink(&self) -> u32 {
+        self.info.globals + 3
+    }
+
+    fn add_globals(&mut self, globals: &mut GlobalSection) {
+        for (ty, init) in [
+            (ValType::I64, ConstExpr::i64_const(0)),
+            (ValType::I32, ConstExpr::i32_const(0)),
+            (ValType::I32, ConstExpr::i32_const(0)),
+            (ValType::I64, ConstExpr::i64_const(0)),
+        ] {
+            let ty = GlobalType {
+                val_type: ty,
+                mutable: true,
+                shared: false,
+            };
+            globals.global(ty, &init);
+        }
+        self.globals_added = true;
+    }
+
+    fn add_exports(&mut self, exports: &mut ExportSection) {
+        exports.export(INK_LEFT, ExportKind::Global, self.ink_left());
+        exports.export(INK_STATUS, ExportKind::Global, self.ink_status());
+        exports.export(STACK_LEFT, ExportKind::Global, self.stack_left());
+        exports.export(PAGE_INK, ExportKind::Global, self.page_ink());
+        self.exports_added = true;
+    }
+
+    /// Traps with the given status.
+    fn trap(&self, status: i32) -> [Instruction<'static>; 5] {
+        [
+            Instruction::If(BlockType::Empty),
+            Instruction::I32Const(status),
+            Instruction::GlobalSet(self.ink_status()),
+            Instruction::Unreachable,
+            Instruction::End,
+        ]
+    }
+
+    /// Charges constant ink.
+    fn charge_ink(&self, function: &mut Function, ink: u64) {
+        if ink == 0 {
+            return;
+        }
+        let ink = ink as i64;
+        function
+            .instruction(&Instruction::GlobalGet(self.ink_left()))
+            .instruction(&Instruction::I64Const(ink))
+            .instruction(&Instruction::I64LtU);
+        for instruction in self.trap(STATUS_OUT_OF_INK) {
+            function.instruction(&instruction);
+        }
+        function
+            .instruction(&Instruction::GlobalGet(self.ink_left()))
+            .instruction(&Instruction::I64Const(ink))
+            .instruction(&Instruction::I64Sub)
+            .instruction(&Instruction::GlobalSet(self.ink_left()));
+    }
+
+    /// Charges ink of the pages on top of the stack, `memory.grow` follows.
+    fn charge_pages(&self, block: &mut Vec<Instruction<'_>>, pages: u32, cost: u32) {
+        block.extend([
+            Instruction::LocalTee(pages),
+            Instruction::LocalGet(pages),
+            Instruction::I64ExtendI32U,
+            Instruction::GlobalGet(self.page_ink()),
+            Instruction::I64Mul,
+            Instruction::LocalSet(cost),
+            Instruction::GlobalGet(self.ink_left()),
+            Instruction::LocalGet(cost),
+            Instruction::I64LtU,
+        ]);
+        block.extend(self.trap(STATUS_OUT_OF_INK));
+        block.extend([
+            Instruction::GlobalGet(self.ink_left()),
+            Instruction::LocalGet(cost),
+            Instruction::I64Sub,
+            Instruction::GlobalSet(self.ink_left()),
+        ]);
+    }
+
+    /// Reserves the frame of the callee.
+    fn enter_frame(&self, block: &mut Vec<Instruction<'_>>, size: u32) {
+        let size = size as i32;
+        block.extend([
+            Instruction::GlobalGet(self.stack_left()),
+            Instruction::I32Const(size),
+            Instruction::I32LtU,
+        ]);
+        block.extend(self.trap(STATUS_STACK_OVERFLOW));
+        block.extend([
+            Instruction::GlobalGet(self.stack_left()),
+            Instruction::I32Const(size),
+            Instruction::I32Sub,
+            Instruction::GlobalSet(self.stack_left()),
+        ]);
+    }
+
+    /// Releases the frame of the callee.
+    fn exit_frame(&self, block: &mut Vec<Instruction<'_>>, size: u32) {
+        block.extend([
+            Instruction::GlobalGet(self.stack_left()),
+            Instruction::I32Const(size as i32),
+            Instruction::I32Add,
+            Instruction::GlobalSet(self.stack_left()),
+        ]);
+    }
+}
+
+/// Returns `true` if the section follows the global section.
+fn is_after_globals(section: SectionId) -> bool {
+    matches!(
+        section,
+        SectionId::Export
+            | SectionId::Start
+            | SectionId::Element
+            | SectionId::DataCount
+            | SectionId::Code
+            | SectionId::Data
+    )
+}
+
+/// Returns `true` if the section follows the export section.
+fn is_after_exports(section: SectionId) -> bool {
+    section != SectionId::Export && is_after_globals(section)
+}
+
+impl Reencode for Instrumenter<'_> {
+    type Error = Infallible;
+
+    fn intersperse_section_hook(
+        &mut self,
+        module: &mut Module,
+        _after: Option<SectionId>,
+        before: Option<SectionId>,
+    ) -> Result<(), wasm_encoder::reencode::Error<Self::Error>> {
+        if !self.globals_added && before.is_none_or(is_after_globals) {
+            let mut globals = GlobalSection::new();
+            self.add_globals(&mut globals);
+            module.section(&globals);
+        }
+        if !self.exports_added && before.is_none_or(is_after_exports) {
+            let mut exports = ExportSection::new();
+            self.add_exports(&mut exports);
+            module.section(&exports);
+        }
+        Ok(())
+    }
+
+    fn parse_global_section(
+        &mut self,
+        globals: &mut GlobalSection,
+        section: wasmparser::GlobalSectionReader<'_>,
+    ) -> Result<(), wasm_encoder::reencode::Error<Self::Error>> {
+        utils::parse_global_section(self, globals, section)?;
+        self.add_globals(globals);
+        Ok(())
+    }
+
+    fn parse_export_section(
+        &mut self,
+        exports: &mut ExportSection,
+        section: wasmparser::ExportSectionReader<'_>,
+    ) -> Result<(), wasm_encoder::reencode::Error<Self::Error>> {
+        utils::parse_export_section(self, exports, section)?;
+        self.add_exports(exports);
+        Ok(())
+    }
+
+    fn parse_memory_section(
+        &mut self,
+        memories: &mut MemorySection,
+        section: wasmparser::MemorySectionReader<'_>,
+    ) -> Result<(), wasm_encoder::reencode::Error<Self::Error>> {
+        for memory in section {
+            let mut memory = self.memory_type(memory?);
+            let max_pages = MAX_MEMORY_PAGES as u64;
+            memory.maximum = Some(memory.maximum.unwrap_or(max_pages).min(max_pages));
+            memories.memory(memory);
+        }
+        Ok(())
+    }
+
+    fn parse_function_body(
+        &mut self,
+        code: &mut CodeSection,
+        func: FunctionBody<'_>,
+    ) -> Result<(), wasm_encoder::reencode::Error<Self::Error>> {
+        let defined = self.defined_functions as usize;
+        self.defined_functions += 1;
+        let info = self.info;
+        let ty = info.functions[info.imported_functions as usize + defined];
+        let params = info.types[ty as usize].params().len() as u32;
+
+        let mut locals = Vec::new();
+        let mut locals_len = params;
+        let mut reader = func.get_locals_reader()?;
+        for _ in 0..reader.get_count() {
+            let (count, ty) = reader.read()?;
+            locals.push((count, self.val_type(ty)?));
+            locals_len += count;
+        }
+        // Scratch locals for the pages and ink of `memory.grow`.
+        let grows_memory = info.grows_memory[defined];
+        if grows_memory {
+            locals.push((1, ValType::I32));
+            locals.push((1, ValType::I64));
+        }
+        let (pages, cost) = (locals_len, locals_len + 1);
+
+        let mut function = Function::new(locals);
+        let mut block = Vec::new();
+        let mut ink = 0u64;
+        let mut operators = func.get_operators_reader()?;
+        while !operators.eof() {
+            let op = operators.read()?;
+            ink = ink.saturating_add(opcode_ink(&op));
+            let is_block_end = is_block_end(&op);
+            let frame_size = match op {
+                Operator::Call { function_index } => info.frame_size(function_index),
+                Operator::CallIndirect { type_index, .. } => info.indirect_frame_size(type_index),
+                _ => None,
+            };
+            if matches!(op, Operator::MemoryGrow { .. }) {
+                self.charge_pages(&mut block, pages, cost);
+            }
+
+            let instruction = self.instruction(op)?;
+            match frame_size {
+                Some(size) => {
+                    self.enter_frame(&mut block, size);
+                    block.push(instruction);
+                    self.exit_frame(&mut block, size);
+                }
+                None => block.push(instruction),
+            }
+
+            if is_block_end {
+                self.charge_ink(&mut function, ink);
+                for instruction in block.drain(..) {
+                    function.instruction(&instruction);
+                }
+                ink = 0;
+            }
+        }
+        code.function(&function);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instrument_module() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory (export "memory") 1)
+                (func $leaf (param i32) (result i32)
+                    (i32.add (local.get 0) (i32.const 1)))
+                (func (export "user_entrypoint") (param i32) (result i32)
+                    (drop (memory.grow (i32.const 1)))
+                    (call $leaf (local.get 0))))
+            "#,
+        )
+        .unwrap();
+
+        let info = ModuleInfo::new(&wasm).unwrap();
+        assert_eq!(info.frame_sizes.len(), 2);
+        // Return address, param and two operands.
+        assert_eq!(info.frame_sizes[0], 4);
+        assert_eq!(info.grows_memory, vec![false, true]);
+
+        let instrumented = instrument(&wasm).unwrap();
+        assert_eq!(instrumented.memory_pages, 1);
+        let exports: Vec<_> = Parser::new(0)
+            .parse_all(&instrumented.wasm)
+            .find_map(|payload| match payload.unwrap() {
+                Payload::ExportSection(reader) => {
+                    Some(reader.into_iter().map(|e| e.unwrap().name).collect())
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            exports,
+            vec![
+                "memory",
+                "user_entrypoint",
+                INK_LEFT,
+                INK_STATUS,
+                STACK_LEFT,
+                PAGE_INK
+            ]
+        );
+        Validator::new_with_features(FEATURES)
+            .validate_all(&instrumented.wasm)
+            .unwrap();
+    }
+
+    #[test]
+    fn reject_floats() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "user_entrypoint") (param i32) (result i32)
+                    (i32.trunc_f32_s (f32.const 1))))
+            "#,
+        )
+        .unwrap();
+        assert_eq!(instrument(&wasm), Err(ProgramError::InvalidWasm));
+    }
+}
@@ -0,0 +1,129 @@
+use crate::meter;
+use bytecode::{StylusBytecode, StylusDictionary};
+use core::fmt;
+use std::{io::Read, sync::OnceLock, vec::Vec};
+use wasmi::{Engine, Module};
+
+/// Maximum size of the decompressed WASM module.
+pub const MAX_WASM_SIZE: usize = 128 * 1024;
+
+/// Name of the exported entrypoint of a Stylus program.
+pub const ENTRYPOINT: &str = "user_entrypoint";
+
+/// Name of the exported linear memory of a Stylus program.
+pub const MEMORY: &str = "memory";
+
+/// Returns the global WASM engine used to compile and execute Stylus programs.
+///
+/// Programs are metered by their instrumentation, see [`meter`].
+pub fn engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(Engine::default)
+}
+
+/// Compiled Stylus program.
+#[derive(Debug)]
+pub struct StylusProgram {
+    module: Module,
+    memory_pages: u32,
+}
+
+impl StylusProgram {
+    /// Decompresses and compiles the WASM module of the Stylus bytecode.
+    pub fn new(bytecode: &StylusBytecode) -> Result<Self, ProgramError> {
+        let wasm = decompress(bytecode)?;
+        Self::from_wasm(&wasm)
+    }
+
+    /// Instruments and compiles the uncompressed WASM module.
+    pub fn from_wasm(wasm: &[u8]) -> Result<Self, ProgramError> {
+        let instrumented = meter::instrument(wasm)?;
+        let module =
+            Module::new(engine(), &instrumented.wasm).map_err(|_| ProgramError::InvalidWasm)?;
+
+        let has_entrypoint = module
+            .exports()
+            .any(|export| export.name() == ENTRYPOINT && export.ty().func().is_some());
+        if !has_entrypoint {
+            return Err(ProgramError::MissingEntrypoint);
+        }
+        let has_memory = module
+            .exports()
+            .any(|export| export.name() == MEMORY && export.ty().memory().is_some());
+        if !has_memory {
+            return Err(ProgramError::MissingMemory);
+        }
+
+        Ok(Self {
+            module,
+            memory_pages: instrumented.memory_pages,
+        })
+    }
+
+    /// Returns the compiled module.
+    #[inline]
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+
+    /// Returns the initial memory pages of the program.
+    #[inline]
+    pub fn memory_pages(&self) -> u32 {
+        self.memory_pages
+    }
+}
+
+/// Decompresses the WASM module of the Stylus bytecode.
+pub fn decompress(bytecode: &StylusBytecode) -> Result<Vec<u8>, ProgramError> {
+    if bytecode.dictionary != StylusDictionary::Empty {
+        return Err(ProgramError::UnsupportedDictionary);
+    }
+    let mut wasm = Vec::new();
+    brotli_decompressor::Decompressor::new(bytecode.compressed_wasm(), 4096)
+        .take(MAX_WASM_SIZE as u64 + 1)
+        .read_to_end(&mut wasm)
+        .map_err(|_| ProgramError::Decompression)?;
+    if wasm.len() > MAX_WASM_SIZE {
+        return Err(ProgramError::WasmTooLarge);
+    }
+    Ok(wasm)
+}
+
+/// Errors that make a Stylus program not executable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProgramError {
+    /// Brotli dictionary is not supported.
+    UnsupportedDictionary,
+    /// WASM module could not be decompressed.
+    Decompression,
+    /// Decompressed WASM module is larger than [`MAX_WASM_SIZE`].
+    WasmTooLarge,
+    /// WASM module is invalid.
+    InvalidWasm,
+    /// WASM module does not export the `user_entrypoint` function.
+    MissingEntrypoint,
+    /// WASM module does not export its memory.
+    MissingMemory,
+    /// WASM module starts with more than [`MAX_MEMORY_PAGES`][meter::MAX_MEMORY_PAGES] memory pages.
+    MemoryTooLarge,
+    /// WASM module could not be instantiated with the host-io functions.
+    Instantiation,
+}
+
+impl fmt::Display for ProgramError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::UnsupportedDictionary => "Unsupported brotli dictionary",
+            Self::Decompression => "Failed to decompress WASM module",
+            Self::WasmTooLarge => "WASM module is too large",
+            Self::InvalidWasm => "Invalid WASM module",
+            Self::MissingEntrypoint => "Missing user_entrypoint export",
+            Self::MissingMemory => "Missing memory export",
+            Self::MemoryTooLarge => "WASM memory is too large",
+            Self::Instantiation => "Failed to instantiate WASM module",
+        };
+        f.write_str(s)
+    }
+}
+
+impl core::error::Error for ProgramError {}
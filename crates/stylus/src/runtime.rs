@@ -0,0 +1,1110 @@
+use crate::{
+    host::{linker, CachedSlot, HostRequest, StylusData, StylusEnv, Suspend},
+    meter::{
+        INK_LEFT, INK_STATUS, MAX_STACK_DEPTH, MEMORY_PAGE_GAS, PAGE_INK, STACK_LEFT,
+        STATUS_OUT_OF_INK, STATUS_STACK_OVERFLOW,
+    },
+    program::{StylusProgram, ENTRYPOINT, MEMORY},
+    ProgramError,
+};
+use bytecode::opcode;
+use context_interface::{Block, Cfg, Transaction};
+use core::mem;
+use interpreter::{
+    gas, CallInputs, CallOutcome, CallScheme, CallValue, CreateInputs, CreateOutcome, CreateScheme,
+    FrameInput, Gas, Host, InstructionResult, InterpreterAction, InterpreterResult,
+};
+use primitives::{Address, Bytes, Log, LogData, U256};
+use specification::hardfork::SpecId;
+use std::{boxed::Box, vec, vec::Vec};
+use wasmi::{
+    Error, Global, Instance, Memory, Store, TypedFunc, TypedResumableCall,
+    TypedResumableInvocation, Val,
+};
+
+/// Executes a Stylus program on top of the [`Host`].
+///
+/// Host-io functions that need the host suspend the program. Requests that can be served
+/// directly are handled inside [`StylusRuntime::run`], calls and creates are returned as
+/// [`InterpreterAction::NewFrame`] and the program is resumed once their outcome is inserted.
+pub struct StylusRuntime {
+    store: Store<StylusData>,
+    memory: Memory,
+    entrypoint: TypedFunc<u32, u32>,
+    /// Ink left of the program.
+    ink_left: Global,
+    /// Metering status of the program.
+    ink_status: Global,
+    state: RuntimeState,
+    gas: Gas,
+    spec: SpecId,
+    /// Ink that does not add up to a whole gas.
+    ink_remainder: u64,
+}
+
+/// Execution state of the program.
+enum RuntimeState {
+    /// Entrypoint is not called yet.
+    Start,
+    /// Program is suspended and will be resumed with the given outputs.
+    Resume {
+        invocation: TypedResumableInvocation<u32>,
+        outputs: Vec<Val>,
+    },
+    /// Program waits for the outcome of a call or create.
+    Pending {
+        invocation: TypedResumableInvocation<u32>,
+        frame: PendingFrame,
+    },
+    /// Program is going to halt with the given result.
+    Halt(InstructionResult),
+    /// Program finished.
+    Done,
+}
+
+/// Output pointers of the pending call or create.
+#[derive(Clone, Copy, Debug)]
+enum PendingFrame {
+    Call { return_data_len: u32 },
+    Create { contract: u32, revert_data_len: u32 },
+}
+
+/// Misuse of the [`StylusRuntime`] state machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StylusError {
+    /// Program waits for the outcome of a call or create, or has finished.
+    NotRunnable,
+    /// Program is not waiting for the outcome of a call.
+    NotWaitingForCall,
+    /// Program is not waiting for the outcome of a create.
+    NotWaitingForCreate,
+    /// Program was suspended without a host request.
+    MissingRequest,
+}
+
+impl core::fmt::Display for StylusError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let s = match self {
+            Self::NotRunnable => "Stylus program is not runnable",
+            Self::NotWaitingForCall => "Stylus program is not waiting for a call",
+            Self::NotWaitingForCreate => "Stylus program is not waiting for a create",
+            Self::MissingRequest => "Suspended Stylus program has no request",
+        };
+        f.write_str(s)
+    }
+}
+
+impl core::error::Error for StylusError {}
+
+/// Result of serving a host request.
+enum Served {
+    Resume(Vec<Val>),
+    NewFrame(FrameInput, PendingFrame),
+    Halt(InstructionResult),
+}
+
+impl core::fmt::Debug for StylusRuntime {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("StylusRuntime")
+            .field("env", &self.store.data().env)
+            .field("gas", &self.gas)
+            .field("spec", &self.spec)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StylusRuntime {
+    /// Instantiates the program with the given environment and gas limit.
+    ///
+    /// Initial memory of the program is charged upfront, the program halts with
+    /// [`InstructionResult::OutOfGas`] if the gas limit does not cover it.
+    pub fn new(
+        program: &StylusProgram,
+        env: StylusEnv,
+        gas_limit: u64,
+        spec: SpecId,
+    ) -> Result<Self, ProgramError> {
+        let ink_price = env.ink_price.max(1) as u64;
+        let mut store = Store::new(crate::program::engine(), StylusData::new(env));
+        let instance = linker()
+            .instantiate(&mut store, program.module())
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|_| ProgramError::Instantiation)?;
+        let memory = instance
+            .get_memory(&store, MEMORY)
+            .ok_or(ProgramError::MissingMemory)?;
+        let entrypoint = instance
+            .get_typed_func::<u32, u32>(&store, ENTRYPOINT)
+            .map_err(|_| ProgramError::MissingEntrypoint)?;
+        let ink_left = meter_global(&instance, &store, INK_LEFT)?;
+        let ink_status = meter_global(&instance, &store, INK_STATUS)?;
+        let stack_left = meter_global(&instance, &store, STACK_LEFT)?;
+        let page_ink = meter_global(&instance, &store, PAGE_INK)?;
+        stack_left
+            .set(&mut store, Val::I32(MAX_STACK_DEPTH as i32))
+            .map_err(|_| ProgramError::Instantiation)?;
+        page_ink
+            .set(
+                &mut store,
+                Val::I64(MEMORY_PAGE_GAS.saturating_mul(ink_price) as i64),
+            )
+            .map_err(|_| ProgramError::Instantiation)?;
+
+        let mut runtime = Self {
+            store,
+            memory,
+            entrypoint,
+            ink_left,
+            ink_status,
+            state: RuntimeState::Start,
+            gas: Gas::new(gas_limit),
+            spec,
+            ink_remainder: 0,
+        };
+        let memory_gas = MEMORY_PAGE_GAS * program.memory_pages() as u64;
+        if runtime.charge(memory_gas).is_err() {
+            runtime.state = RuntimeState::Halt(InstructionResult::OutOfGas);
+        }
+        runtime.sync_ink();
+        Ok(runtime)
+    }
+
+    /// Creates the runtime for the given call, environment is read from the host.
+    pub fn new_call<H: Host + ?Sized>(
+        program: &StylusProgram,
+        inputs: &CallInputs,
+        host: &mut H,
+    ) -> Result<Self, ProgramError> {
+        let basefee = host.block().basefee();
+        let env = StylusEnv {
+            args: inputs.input.clone(),
+            contract_address: inputs.target_address,
+            msg_sender: inputs.caller,
+            msg_value: inputs.value.get(),
+            is_static: inputs.is_static,
+            tx_origin: host.tx().caller(),
+            tx_gas_price: U256::from(host.tx().effective_gas_price(basefee as u128)),
+            ink_price: host.cfg().ink_price(),
+            block_basefee: U256::from(basefee),
+            block_coinbase: host.block().beneficiary(),
+            block_gas_limit: host.block().gas_limit(),
+            block_number: host.block().number(),
+            block_timestamp: host.block().timestamp(),
+            chain_id: host.cfg().chain_id(),
+        };
+        let spec = host.cfg().spec().into();
+        Self::new(program, env, inputs.gas_limit, spec)
+    }
+
+    /// Returns the environment of the program.
+    #[inline]
+    pub fn env(&self) -> &StylusEnv {
+        &self.store.data().env
+    }
+
+    /// Returns the gas of the program.
+    #[inline]
+    pub fn gas(&self) -> &Gas {
+        &self.gas
+    }
+
+    /// Runs the program until it finishes or needs a new frame.
+    ///
+    /// Returns [`StylusError::NotRunnable`] if the program waits for the outcome of a frame or
+    /// has finished.
+    pub fn run<H: Host + ?Sized>(
+        &mut self,
+        host: &mut H,
+    ) -> Result<InterpreterAction, StylusError> {
+        loop {
+            let call = match mem::replace(&mut self.state, RuntimeState::Done) {
+                RuntimeState::Start => {
+                    let args_len = self.store.data().env.args.len() as u32;
+                    self.entrypoint.call_resumable(&mut self.store, args_len)
+                }
+                RuntimeState::Resume {
+                    invocation,
+                    outputs,
+                } => invocation.resume(&mut self.store, &outputs),
+                RuntimeState::Halt(result) => return Ok(self.halt(result)),
+                state @ (RuntimeState::Pending { .. } | RuntimeState::Done) => {
+                    self.state = state;
+                    return Err(StylusError::NotRunnable);
+                }
+            };
+
+            let invocation = match call {
+                Ok(TypedResumableCall::Finished(status)) => return Ok(self.finish(host, status)),
+                Ok(TypedResumableCall::Resumable(invocation)) => invocation,
+                Err(error) => return Ok(self.halt(self.trap_result(&error))),
+            };
+            if invocation.host_error().downcast_ref::<Suspend>().is_none() {
+                return Ok(self.halt(self.trap_result(invocation.host_error())));
+            }
+            let Some(request) = self.store.data_mut().request.take() else {
+                return Err(StylusError::MissingRequest);
+            };
+
+            self.sync_gas();
+            let served = self.serve(host, request);
+            self.sync_ink();
+            match served {
+                Served::Resume(outputs) => {
+                    self.state = RuntimeState::Resume {
+                        invocation,
+                        outputs,
+                    }
+                }
+                Served::NewFrame(frame_input, frame) => {
+                    self.state = RuntimeState::Pending { invocation, frame };
+                    return Ok(InterpreterAction::NewFrame(frame_input));
+                }
+                Served::Halt(result) => return Ok(self.halt(result)),
+            }
+        }
+    }
+
+    /// Inserts the outcome of the pending call.
+    ///
+    /// Returns [`StylusError::NotWaitingForCall`] if no call is pending.
+    pub fn insert_call_outcome(&mut self, outcome: CallOutcome) -> Result<(), StylusError> {
+        let (invocation, return_data_len) = match mem::replace(&mut self.state, RuntimeState::Done)
+        {
+            RuntimeState::Pending {
+                invocation,
+                frame: PendingFrame::Call { return_data_len },
+            } => (invocation, return_data_len),
+            state => {
+                self.state = state;
+                return Err(StylusError::NotWaitingForCall);
+            }
+        };
+        let result = outcome.result;
+        self.return_gas(&result);
+
+        let status = u32::from(!result.result.is_ok());
+        let len = result.output.len() as u32;
+        self.store.data_mut().return_data = result.output;
+
+        self.state = match self.write(return_data_len, &len.to_le_bytes()) {
+            Ok(()) => RuntimeState::Resume {
+                invocation,
+                outputs: vec![Val::I32(status as i32)],
+            },
+            Err(result) => RuntimeState::Halt(result),
+        };
+        Ok(())
+    }
+
+    /// Inserts the outcome of the pending create.
+    ///
+    /// Returns [`StylusError::NotWaitingForCreate`] if no create is pending.
+    pub fn insert_create_outcome(&mut self, outcome: CreateOutcome) -> Result<(), StylusError> {
+        let (invocation, contract, revert_data_len) =
+            match mem::replace(&mut self.state, RuntimeState::Done) {
+                RuntimeState::Pending {
+                    invocation,
+                    frame:
+                        PendingFrame::Create {
+                            contract,
+                            revert_data_len,
+                        },
+                } => (invocation, contract, revert_data_len),
+                state => {
+                    self.state = state;
+                    return Err(StylusError::NotWaitingForCreate);
+                }
+            };
+        let result = &outcome.result;
+        self.return_gas(result);
+
+        let address = if result.result.is_ok() {
+            outcome.address.unwrap_or_default()
+        } else {
+            Address::ZERO
+        };
+        let return_data = if result.result.is_revert() {
+            result.output.clone()
+        } else {
+            Bytes::new()
+        };
+        let len = return_data.len() as u32;
+        self.store.data_mut().return_data = return_data;
+
+        self.state = match self
+            .write(contract, address.as_slice())
+            .and_then(|()| self.write(revert_data_len, &len.to_le_bytes()))
+        {
+            Ok(()) => RuntimeState::Resume {
+                invocation,
+                outputs: Vec::new(),
+            },
+            Err(result) => RuntimeState::Halt(result),
+        };
+        Ok(())
+    }
+
+    fn serve<H: Host + ?Sized>(&mut self, host: &mut H, request: HostRequest) -> Served {
+        match self.try_serve(host, request) {
+            Ok(served) => served,
+            Err(result) => Served::Halt(result),
+        }
+    }
+
+    fn try_serve<H: Host + ?Sized>(
+        &mut self,
+        host: &mut H,
+        request: HostRequest,
+    ) -> Result<Served, InstructionResult> {
+        let contract = self.env().contract_address;
+        let served = match request {
+            HostRequest::StorageLoad { key, dest } => {
+                let load = host
+                    .sload(contract, key)
+                    .ok_or(InstructionResult::FatalExternalError)?;
+                let schedule = host.cfg().gas_schedule();
+                self.charge(
+                    schedule.static_costs[opcode::SLOAD as usize]
+                        + gas::sload_cost(schedule, load.is_cold),
+                )?;
+                let value = load.data;
+                self.store.data_mut().storage_cache.insert(
+                    key,
+                    CachedSlot {
+                        value,
+                        dirty: false,
+                    },
+                );
+                self.write(dest, &value.to_be_bytes::<32>())?;
+                Served::Resume(Vec::new())
+            }
+            HostRequest::StorageFlush { clear } => {
+                self.flush(host)?;
+                if clear {
+                    self.store.data_mut().storage_cache.clear();
+                }
+                Served::Resume(Vec::new())
+            }
+            HostRequest::TransientLoad { key, dest } => {
+                self.charge(static_cost(host, opcode::TLOAD))?;
+                let value = host.tload(contract, key);
+                self.write(dest, &value.to_be_bytes::<32>())?;
+                Served::Resume(Vec::new())
+            }
+            HostRequest::TransientStore { key, value } => {
+                if self.env().is_static {
+                    return Err(InstructionResult::StateChangeDuringStaticCall);
+                }
+                self.charge(static_cost(host, opcode::TSTORE))?;
+                host.tstore(contract, key, value);
+                Served::Resume(Vec::new())
+            }
+            HostRequest::Call {
+                scheme,
+                address,
+                input,
+                value,
+                gas_limit,
+                return_data_len,
+            } => {
+                self.flush(host)?;
+                let mut load = host
+                    .load_account_delegated(address)
+                    .ok_or(InstructionResult::FatalExternalError)?;
+                let transfers_value = scheme == CallScheme::Call && !value.is_zero();
+                if scheme != CallScheme::Call {
+                    load.is_empty = false;
+                }
+                let op = match scheme {
+                    CallScheme::Call => opcode::CALL,
+                    CallScheme::CallCode => opcode::CALLCODE,
+                    CallScheme::DelegateCall => opcode::DELEGATECALL,
+                    CallScheme::StaticCall => opcode::STATICCALL,
+                    CallScheme::ExtCall => opcode::EXTCALL,
+                    CallScheme::ExtStaticCall => opcode::EXTSTATICCALL,
+                    CallScheme::ExtDelegateCall => opcode::EXTDELEGATECALL,
+                };
+                let schedule = host.cfg().gas_schedule();
+                self.charge(
+                    schedule.static_costs[op as usize]
+                        + gas::call_cost(self.spec, schedule, transfers_value, load),
+                )?;
+
+                // EIP-150: all but one 64th of the remaining gas can be forwarded.
+                let remaining = self.gas.remaining();
+                let mut gas_limit = gas_limit.min(remaining - remaining / 64);
+                self.charge(gas_limit)?;
+                if transfers_value {
+                    gas_limit = gas_limit.saturating_add(gas::CALL_STIPEND);
+                }
+
+                let env = self.env();
+                let (target_address, caller, value) = match scheme {
+                    CallScheme::DelegateCall => (
+                        env.contract_address,
+                        env.msg_sender,
+                        CallValue::Apparent(env.msg_value),
+                    ),
+                    _ => (address, env.contract_address, CallValue::Transfer(value)),
+                };
+                let inputs = CallInputs {
+                    input,
+                    return_memory_offset: 0..0,
+                    gas_limit,
+                    bytecode_address: address,
+                    target_address,
+                    caller,
+                    value,
+                    scheme,
+                    is_static: env.is_static || scheme == CallScheme::StaticCall,
+                    is_eof: false,
+                };
+                Served::NewFrame(
+                    FrameInput::Call(Box::new(inputs)),
+                    PendingFrame::Call { return_data_len },
+                )
+            }
+            HostRequest::Create {
+                code,
+                endowment,
+                salt,
+                contract: contract_ptr,
+                revert_data_len,
+            } => {
+                self.flush(host)?;
+                if self.spec.is_enabled_in(SpecId::SHANGHAI) {
+                    // EIP-3860: Limit and meter initcode
+                    let max_initcode_size = host.cfg().max_code_size().saturating_mul(2);
+                    if code.len() > max_initcode_size {
+                        return Err(InstructionResult::CreateInitCodeSizeLimit);
+                    }
+                    self.charge(gas::initcode_cost(host.cfg().gas_schedule(), code.len()))?;
+                }
+                let (cost, scheme) = match salt {
+                    Some(salt) => (
+                        gas::cost_per_word(code.len(), gas::KECCAK256WORD)
+                            .and_then(|cost| cost.checked_add(static_cost(host, opcode::CREATE2)))
+                            .ok_or(InstructionResult::OutOfGas)?,
+                        CreateScheme::Create2 { salt },
+                    ),
+                    None => (static_cost(host, opcode::CREATE), CreateScheme::Create),
+                };
+                self.charge(cost)?;
+
+                // EIP-150: all but one 64th of the remaining gas is forwarded.
+                let remaining = self.gas.remaining();
+                let gas_limit = remaining - remaining / 64;
+                self.charge(gas_limit)?;
+
+                let inputs = CreateInputs {
+                    caller: contract,
+                    scheme,
+                    value: endowment,
+                    init_code: code,
+                    gas_limit,
+                };
+                Served::NewFrame(
+                    FrameInput::Create(Box::new(inputs)),
+                    PendingFrame::Create {
+                        contract: contract_ptr,
+                        revert_data_len,
+                    },
+                )
+            }
+            HostRequest::EmitLog { topics, data } => {
+                let cost = gas::LOGDATA
+                    .checked_mul(data.len() as u64)
+                    .and_then(|cost| {
+                        cost.checked_add(static_cost(host, opcode::LOG0 + topics.len() as u8))
+                    })
+                    .ok_or(InstructionResult::OutOfGas)?;
+                self.charge(cost)?;
+                host.log(Log {
+                    address: contract,
+                    data: LogData::new_unchecked(topics, data),
+                });
+                Served::Resume(Vec::new())
+            }
+            HostRequest::AccountBalance { address, dest } => {
+                let balance = host
+                    .balance(address)
+                    .ok_or(InstructionResult::FatalExternalError)?;
+                let schedule = host.cfg().gas_schedule();
+                self.charge(
+                    schedule.static_costs[opcode::BALANCE as usize]
+                        + gas::warm_cold_cost(schedule, balance.is_cold),
+                )?;
+                self.write(dest, &balance.data.to_be_bytes::<32>())?;
+                Served::Resume(Vec::new())
+            }
+            HostRequest::AccountCode {
+                address,
+                offset,
+                size,
+                dest,
+            } => {
+                let code = host
+                    .code(address)
+                    .ok_or(InstructionResult::FatalExternalError)?;
+                let schedule = host.cfg().gas_schedule();
+                self.charge(
+                    schedule.static_costs[opcode::EXTCODECOPY as usize]
+                        + gas::warm_cold_cost(schedule, code.is_cold),
+                )?;
+                let start = (offset as usize).min(code.data.len());
+                let end = start.saturating_add(size as usize).min(code.data.len());
+                self.charge(
+                    gas::copy_cost_verylow(end - start).ok_or(InstructionResult::OutOfGas)?,
+                )?;
+                self.write(dest, &code.data[start..end])?;
+                Served::Resume(vec![Val::I32((end - start) as i32)])
+            }
+            HostRequest::AccountCodeSize { address } => {
+                let code = host
+                    .code(address)
+                    .ok_or(InstructionResult::FatalExternalError)?;
+                let schedule = host.cfg().gas_schedule();
+                self.charge(
+                    schedule.static_costs[opcode::EXTCODESIZE as usize]
+                        + gas::warm_cold_cost(schedule, code.is_cold),
+                )?;
+                Served::Resume(vec![Val::I32(code.data.len() as i32)])
+            }
+            HostRequest::AccountCodeHash { address, dest } => {
+                let code_hash = host
+                    .code_hash(address)
+                    .ok_or(InstructionResult::FatalExternalError)?;
+                let schedule = host.cfg().gas_schedule();
+                self.charge(
+                    schedule.static_costs[opcode::EXTCODEHASH as usize]
+                        + gas::warm_cold_cost(schedule, code_hash.is_cold),
+                )?;
+                self.write(dest, code_hash.data.as_slice())?;
+                Served::Resume(Vec::new())
+            }
+            HostRequest::Halt(result) => Served::Halt(result),
+        };
+        Ok(served)
+    }
+
+    /// Writes dirty cached storage slots to the host.
+    fn flush<H: Host + ?Sized>(&mut self, host: &mut H) -> Result<(), InstructionResult> {
+        let data = self.store.data();
+        let dirty: Vec<_> = data
+            .storage_cache
+            .iter()
+            .filter(|(_, slot)| slot.dirty)
+            .map(|(key, slot)| (*key, slot.value))
+            .collect();
+        if dirty.is_empty() {
+            return Ok(());
+        }
+        if data.env.is_static {
+            return Err(InstructionResult::StateChangeDuringStaticCall);
+        }
+
+        let contract = data.env.contract_address;
+        for (key, value) in dirty {
+            // EIP-2200: If gasleft is less than or equal to gas stipend, fail.
+            if self.spec.is_enabled_in(SpecId::ISTANBUL)
+                && self.gas.remaining() <= gas::CALL_STIPEND
+            {
+                return Err(InstructionResult::ReentrancySentryOOG);
+            }
+            let load = host
+                .sstore(contract, key, value)
+                .ok_or(InstructionResult::FatalExternalError)?;
+            let schedule = host.cfg().gas_schedule();
+            self.charge(
+                schedule.static_costs[opcode::SSTORE as usize]
+                    + gas::sstore_cost(schedule, &load.data, load.is_cold),
+            )?;
+            self.gas
+                .record_refund(gas::sstore_refund(schedule, &load.data));
+            self.store.data_mut().storage_cache.insert(
+                key,
+                CachedSlot {
+                    value,
+                    dirty: false,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn finish<H: Host + ?Sized>(&mut self, host: &mut H, status: u32) -> InterpreterAction {
+        self.sync_gas();
+        let result = if status == 0 {
+            if let Err(result) = self.flush(host) {
+                return self.halt(result);
+            }
+            InstructionResult::Return
+        } else {
+            InstructionResult::Revert
+        };
+        let output = mem::take(&mut self.store.data_mut().output);
+        InterpreterAction::Return {
+            result: InterpreterResult {
+                result,
+                output,
+                gas: self.gas,
+            },
+        }
+    }
+
+    fn halt(&mut self, result: InstructionResult) -> InterpreterAction {
+        self.state = RuntimeState::Done;
+        self.gas.spend_all();
+        InterpreterAction::Return {
+            result: InterpreterResult {
+                result,
+                output: Bytes::new(),
+                gas: self.gas,
+            },
+        }
+    }
+
+    /// Returns unspent gas and refunds of the finished call or create.
+    fn return_gas(&mut self, result: &InterpreterResult) {
+        if result.result.is_ok_or_revert() {
+            self.gas.erase_cost(result.gas.remaining());
+        }
+        if result.result.is_ok() {
+            self.gas.record_refund(result.gas.refunded());
+        }
+        self.sync_ink();
+    }
+
+    fn charge(&mut self, cost: u64) -> Result<(), InstructionResult> {
+        if self.gas.record_cost(cost) {
+            Ok(())
+        } else {
+            Err(InstructionResult::OutOfGas)
+        }
+    }
+
+    fn write(&mut self, ptr: u32, data: &[u8]) -> Result<(), InstructionResult> {
+        self.memory
+            .write(&mut self.store, ptr as usize, data)
+            .map_err(|_| InstructionResult::InvalidFEOpcode)
+    }
+
+    fn ink_price(&self) -> u64 {
+        self.env().ink_price.max(1) as u64
+    }
+
+    /// Updates the gas with the ink consumed by the program.
+    fn sync_gas(&mut self) {
+        let ink_price = self.ink_price();
+        let ink_left = match self.ink_left.get(&self.store) {
+            Val::I64(ink) => ink as u64,
+            _ => unreachable!("Ink is an i64 global"),
+        };
+        self.ink_remainder = ink_left % ink_price;
+        let spent = self.gas.remaining().saturating_sub(ink_left / ink_price);
+        let _ = self.gas.record_cost(spent);
+    }
+
+    /// Updates the ink of the program with the remaining gas.
+    fn sync_ink(&mut self) {
+        let ink_left = self
+            .gas
+            .remaining()
+            .saturating_mul(self.ink_price())
+            .saturating_add(self.ink_remainder);
+        // Ink left never exceeds `i64::MAX` so the program can compare it as signed.
+        let ink_left = ink_left.min(i64::MAX as u64) as i64;
+        self.ink_left
+            .set(&mut self.store, Val::I64(ink_left))
+            .expect("Ink is a mutable i64 global");
+    }
+
+    /// Maps WASM trap to the instruction result, metering traps are reported by the status.
+    fn trap_result(&self, error: &Error) -> InstructionResult {
+        use wasmi::core::TrapCode;
+        match self.ink_status.get(&self.store) {
+            Val::I32(STATUS_OUT_OF_INK) => return InstructionResult::OutOfGas,
+            Val::I32(STATUS_STACK_OVERFLOW) => return InstructionResult::StackOverflow,
+            _ => {}
+        }
+        match error.as_trap_code() {
+            Some(TrapCode::OutOfFuel) => InstructionResult::OutOfGas,
+            Some(TrapCode::StackOverflow) => InstructionResult::StackOverflow,
+            _ => InstructionResult::InvalidFEOpcode,
+        }
+    }
+}
+
+/// Returns the static cost of the opcode in the gas schedule of the host.
+fn static_cost<H: Host + ?Sized>(host: &H, opcode: u8) -> u64 {
+    host.cfg().gas_schedule().static_costs[opcode as usize]
+}
+
+/// Returns the metering global exported by the instrumented program.
+fn meter_global(
+    instance: &Instance,
+    store: &Store<StylusData>,
+    name: &str,
+) -> Result<Global, ProgramError> {
+    instance
+        .get_global(store, name)
+        .ok_or(ProgramError::Instantiation)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        activation::{activation_slot, ARB_WASM_ADDRESS},
+        STYLUS_VERSION,
+    };
+    use bytecode::{Bytecode, StylusBytecode, StylusDictionary};
+    use database::CacheDB;
+    use interpreter::DummyHost;
+    use primitives::{TxKind, B256};
+    use revm::{
+        context::{BlockEnv, CfgEnv, TxEnv},
+        context_interface::result::{ExecutionResult, HaltReason, Output},
+        database_interface::EmptyDB,
+        handler::EthHandler,
+        state::AccountInfo,
+        Context, EvmExec, MainEvm,
+    };
+    use std::io::Write;
+
+    pub(crate) fn stylus_code(wat: &str) -> Bytecode {
+        let wasm = wat::parse_str(wat).unwrap();
+        let mut compressed = Vec::new();
+        {
+            let mut writer = brotli::CompressorWriter::new(&mut compressed, 4096, 11, 22);
+            writer.write_all(&wasm).unwrap();
+        }
+        Bytecode::Stylus(StylusBytecode::new(StylusDictionary::Empty, &compressed))
+    }
+
+    /// Marks the program as activated with the current version.
+    pub(crate) fn activate(db: &mut CacheDB<EmptyDB>, code: &Bytecode) {
+        let slot = activation_slot(code.hash_slow());
+        db.insert_account_storage(ARB_WASM_ADDRESS, slot, U256::from(STYLUS_VERSION))
+            .unwrap();
+    }
+
+    const PROGRAM: &str = r#"
+        (module
+            (import "vm_hooks" "read_args" (func $read_args (param i32)))
+            (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+            (import "vm_hooks" "storage_cache_bytes32" (func $cache (param i32 i32)))
+            (import "vm_hooks" "storage_flush_cache" (func $flush (param i32)))
+            (import "vm_hooks" "emit_log" (func $emit_log (param i32 i32 i32)))
+            (import "vm_hooks" "static_call_contract"
+                (func $static_call (param i32 i32 i32 i64 i32) (result i32)))
+            (import "vm_hooks" "read_return_data"
+                (func $read_return_data (param i32 i32 i32) (result i32)))
+            (memory (export "memory") 1)
+            ;; Address of the called EVM contract.
+            (data (i32.const 64) "\00\00\00\00\00\00\00\00\00\00\00\00\00\00\00\00\00\00\00\c0")
+            (func (export "user_entrypoint") (param $len i32) (result i32)
+                ;; Store args to slot zero.
+                (call $read_args (i32.const 0))
+                (call $cache (i32.const 32) (i32.const 0))
+                (call $flush (i32.const 0))
+                ;; Log args as topic.
+                (call $emit_log (i32.const 0) (i32.const 32) (i32.const 1))
+                ;; Return output of the EVM contract.
+                (drop (call $static_call
+                    (i32.const 64) (i32.const 0) (i32.const 0) (i64.const -1) (i32.const 96)))
+                (drop (call $read_return_data (i32.const 128) (i32.const 0) (i32.const 32)))
+                (call $write_result (i32.const 128) (i32.const 32))
+                (i32.const 0)))
+    "#;
+
+    #[test]
+    fn mixed_call_chain() {
+        let evm_caller = Address::with_last_byte(0xa0);
+        let program = Address::with_last_byte(0xb0);
+        let evm_callee = Address::with_last_byte(0xc0);
+
+        // Forwards call data to the program and returns its output.
+        let caller_code = [
+            opcode::CALLDATASIZE,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::CALLDATACOPY,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::CALLDATASIZE,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::PUSH1,
+            0xb0,
+            opcode::GAS,
+            opcode::CALL,
+            opcode::POP,
+            opcode::RETURNDATASIZE,
+            opcode::PUSH0,
+            opcode::PUSH0,
+            opcode::RETURNDATACOPY,
+            opcode::RETURNDATASIZE,
+            opcode::PUSH0,
+            opcode::RETURN,
+        ];
+        // Returns 42.
+        let callee_code = [
+            opcode::PUSH1,
+            0x2a,
+            opcode::PUSH0,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            0x20,
+            opcode::PUSH0,
+            opcode::RETURN,
+        ];
+
+        let mut db = CacheDB::<EmptyDB>::default();
+        for (address, code) in [
+            (evm_caller, Bytecode::new_raw(Bytes::from(caller_code))),
+            (program, stylus_code(PROGRAM)),
+            (evm_callee, Bytecode::new_raw(Bytes::from(callee_code))),
+        ] {
+            if code.is_stylus() {
+                activate(&mut db, &code);
+            }
+            let info = AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code);
+            db.insert_account_info(address, info);
+        }
+
+        let value = U256::from(0x1234);
+        let mut evm = MainEvm::new(
+            Context::builder()
+                .modify_cfg_chained(|cfg| cfg.stylus_version = STYLUS_VERSION)
+                .modify_tx_chained(|tx| {
+                    tx.kind = TxKind::Call(evm_caller);
+                    tx.data = value.to_be_bytes::<32>().into();
+                })
+                .with_db(db),
+            EthHandler::default(),
+        );
+        let result = evm.exec().unwrap();
+
+        let ExecutionResult::Success {
+            output: Output::Call(output),
+            logs,
+            ..
+        } = result.result
+        else {
+            panic!("Execution failed: {:?}", result.result);
+        };
+        assert_eq!(output[..], U256::from(42).to_be_bytes::<32>());
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].address, program);
+        assert_eq!(logs[0].topics(), [B256::from(value)]);
+        let slot = &result.state[&program].storage[&U256::ZERO];
+        assert_eq!(slot.present_value, value);
+    }
+
+    #[test]
+    fn metering_halts() {
+        let program = Address::with_last_byte(0xb0);
+        let run = |wat: &str| {
+            let code = stylus_code(wat);
+            let mut db = CacheDB::<EmptyDB>::default();
+            activate(&mut db, &code);
+            let info = AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code);
+            db.insert_account_info(program, info);
+            let mut evm = MainEvm::new(
+                Context::builder()
+                    .modify_cfg_chained(|cfg| cfg.stylus_version = STYLUS_VERSION)
+                    .modify_tx_chained(|tx| {
+                        tx.kind = TxKind::Call(program);
+                        tx.gas_limit = 1_000_000;
+                    })
+                    .with_db(db),
+                EthHandler::default(),
+            );
+            evm.exec().unwrap().result
+        };
+
+        let result = run(r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "user_entrypoint") (param i32) (result i32)
+                    (loop $loop (br $loop))
+                    (i32.const 0)))
+        "#);
+        assert!(
+            matches!(
+                result,
+                ExecutionResult::Halt {
+                    reason: HaltReason::OutOfGas(_),
+                    gas_used: 1_000_000,
+                }
+            ),
+            "{result:?}"
+        );
+
+        let result = run(r#"
+            (module
+                (memory (export "memory") 1)
+                (func $recurse (export "user_entrypoint") (param i32) (result i32)
+                    (call $recurse (local.get 0))))
+        "#);
+        assert!(
+            matches!(
+                result,
+                ExecutionResult::Halt {
+                    reason: HaltReason::StackOverflow,
+                    ..
+                }
+            ),
+            "{result:?}"
+        );
+
+        // Lengths beyond the memory trap before the host allocates the buffer.
+        let result = run(r#"
+            (module
+                (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+                (memory (export "memory") 1)
+                (func (export "user_entrypoint") (param i32) (result i32)
+                    (call $write_result (i32.const 0) (i32.const -1))
+                    (i32.const 0)))
+        "#);
+        assert!(!result.is_success(), "{result:?}");
+
+        // Reads within the memory are charged per copied word.
+        let result = run(r#"
+            (module
+                (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+                (memory (export "memory") 1)
+                (func (export "user_entrypoint") (param i32) (result i32)
+                    (call $write_result (i32.const 0) (i32.const 65536))
+                    (i32.const 0)))
+        "#);
+        assert!(result.is_success(), "{result:?}");
+        let small = run(r#"
+            (module
+                (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+                (memory (export "memory") 1)
+                (func (export "user_entrypoint") (param i32) (result i32)
+                    (call $write_result (i32.const 0) (i32.const 32))
+                    (i32.const 0)))
+        "#);
+        assert!(result.gas_used() >= small.gas_used() + 2047 * gas::COPY);
+    }
+
+    #[test]
+    fn disabled_stylus_is_legacy_code() {
+        let code = stylus_code(
+            r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "user_entrypoint") (param i32) (result i32)
+                    (i32.const 0)))
+            "#,
+        );
+        // Returns the program appended to the init code.
+        let program = code.original_bytes();
+        let len = (program.len() as u16).to_be_bytes();
+        let mut init_code = vec![
+            opcode::PUSH2,
+            len[0],
+            len[1],
+            opcode::PUSH1,
+            12,
+            opcode::PUSH0,
+            opcode::CODECOPY,
+            opcode::PUSH2,
+            len[0],
+            len[1],
+            opcode::PUSH0,
+            opcode::RETURN,
+        ];
+        init_code.extend_from_slice(&program);
+
+        let address = Address::with_last_byte(0xb0);
+        let mut db = CacheDB::<EmptyDB>::default();
+        activate(&mut db, &code);
+        let info = AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code);
+        db.insert_account_info(address, info);
+        let mut evm = MainEvm::new(Context::builder().with_db(db), EthHandler::default());
+        let mut run = |version: u16, kind: TxKind| {
+            evm.context.modify_cfg(|cfg| cfg.stylus_version = version);
+            evm.context.modify_tx(|tx| {
+                tx.kind = kind;
+                tx.data = init_code.clone().into();
+            });
+            evm.exec().unwrap()
+        };
+
+        // EIP-3541 rejects the program if Stylus is disabled, the halt is reported as a size
+        // limit like any other code starting with `0xEF`.
+        let result = run(0, TxKind::Create).result;
+        assert!(
+            matches!(
+                result,
+                ExecutionResult::Halt {
+                    reason: HaltReason::CreateContractSizeLimit,
+                    ..
+                }
+            ),
+            "{result:?}"
+        );
+        let result = run(STYLUS_VERSION, TxKind::Create);
+        let ExecutionResult::Success {
+            output: Output::Create(_, Some(created)),
+            ..
+        } = result.result
+        else {
+            panic!("Deployment failed: {:?}", result.result);
+        };
+        assert!(result.state[&created]
+            .info
+            .code
+            .as_ref()
+            .unwrap()
+            .is_stylus());
+
+        // Deployed programs are executed as legacy bytecode, halting on the `0xEF` opcode.
+        let result = run(0, TxKind::Call(address)).result;
+        assert!(
+            matches!(
+                result,
+                ExecutionResult::Halt {
+                    reason: HaltReason::OpcodeNotFound,
+                    ..
+                }
+            ),
+            "{result:?}"
+        );
+        let result = run(STYLUS_VERSION, TxKind::Call(address)).result;
+        assert!(result.is_success(), "{result:?}");
+    }
+
+    #[test]
+    fn state_machine_misuse() {
+        let code = stylus_code(
+            r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "user_entrypoint") (param i32) (result i32)
+                    (i32.const 0)))
+            "#,
+        );
+        let program = StylusProgram::new(code.stylus().unwrap()).unwrap();
+        let mut runtime =
+            StylusRuntime::new(&program, StylusEnv::default(), 1_000_000, SpecId::PRAGUE).unwrap();
+        let result = InterpreterResult::new(InstructionResult::Stop, Bytes::new(), Gas::new(0));
+
+        assert_eq!(
+            runtime.insert_call_outcome(CallOutcome::new(result.clone(), 0..0)),
+            Err(StylusError::NotWaitingForCall)
+        );
+        assert_eq!(
+            runtime.insert_create_outcome(CreateOutcome::new(result, None)),
+            Err(StylusError::NotWaitingForCreate)
+        );
+
+        let mut host = DummyHost::<BlockEnv, TxEnv, CfgEnv>::default();
+        assert!(runtime.run(&mut host).unwrap().is_return());
+        assert_eq!(runtime.run(&mut host), Err(StylusError::NotRunnable));
+    }
+}
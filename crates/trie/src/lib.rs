@@ -0,0 +1,19 @@
+//! Receipts, logs bloom and Merkle Patricia Trie roots.
+//!
+//! [`Receipt`] builds the typed receipts with their logs bloom, [`receipts_root`],
+//! [`transactions_root`] and [`state_root`] compute the block roots from scratch and
+//! [`StateTrie`] keeps the state trie in memory to update the state root incrementally.
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+
+pub mod receipt;
+pub mod root;
+pub mod state;
+pub mod trie;
+
+pub use receipt::{logs_bloom, logs_rlp_hash, Receipt};
+pub use root::{
+    bundle_state_root, ordered_root, receipts_root, state_root, storage_root, transactions_root,
+    TrieAccount, EMPTY_ROOT_HASH,
+};
+pub use state::StateTrie;
+pub use trie::{verify_proof, ProofError, Trie};
@@ -0,0 +1,140 @@
+//! Transaction receipts and logs bloom.
+use alloy_rlp::{BufMut, Encodable, Header};
+use revm::{
+    context_interface::result::{ExecutionResult, HaltReason},
+    primitives::{alloy_primitives::Bloom, keccak256, Log, B256},
+};
+use std::vec::Vec;
+
+/// Receipt of the transaction executed in the block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Receipt {
+    /// Type of the transaction.
+    pub tx_type: u8,
+    /// Whether the transaction succeeded.
+    pub success: bool,
+    /// Gas used by the transaction.
+    pub gas_used: u64,
+    /// Gas used by the transactions of the block up to and including this one.
+    pub cumulative_gas_used: u64,
+    /// Logs emitted by the transaction.
+    pub logs: Vec<Log>,
+    /// Bloom filter of the logs.
+    pub logs_bloom: Bloom,
+}
+
+impl Receipt {
+    /// Creates the receipt of the executed transaction.
+    pub fn new(
+        tx_type: u8,
+        result: &ExecutionResult<HaltReason>,
+        cumulative_gas_used: u64,
+    ) -> Self {
+        let logs = result.logs().to_vec();
+        Self {
+            tx_type,
+            success: result.is_success(),
+            gas_used: result.gas_used(),
+            cumulative_gas_used,
+            logs_bloom: logs_bloom(&logs),
+            logs,
+        }
+    }
+
+    /// Encodes the receipt as in the receipts trie.
+    ///
+    /// Legacy receipts are the RLP list of the status, cumulative gas used, logs bloom and
+    /// logs, typed receipts are prefixed with the transaction type as defined in EIP-2718.
+    /// Gas used by the transaction itself is not part of the encoding.
+    pub fn encode_2718(&self, out: &mut dyn BufMut) {
+        if self.tx_type != 0 {
+            out.put_u8(self.tx_type);
+        }
+        self.encode(out);
+    }
+
+    /// Returns the EIP-2718 encoding of the receipt.
+    pub fn encoded_2718(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.length() + 1);
+        self.encode_2718(&mut out);
+        out
+    }
+
+    fn payload_length(&self) -> usize {
+        self.success.length()
+            + self.cumulative_gas_used.length()
+            + self.logs_bloom.length()
+            + self.logs.length()
+    }
+}
+
+impl Encodable for Receipt {
+    fn encode(&self, out: &mut dyn BufMut) {
+        Header {
+            list: true,
+            payload_length: self.payload_length(),
+        }
+        .encode(out);
+        self.success.encode(out);
+        self.cumulative_gas_used.encode(out);
+        self.logs_bloom.encode(out);
+        self.logs.encode(out);
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        payload_length + alloy_rlp::length_of_length(payload_length)
+    }
+}
+
+/// Returns the 2048-bit bloom filter of the logs.
+pub fn logs_bloom<'a>(logs: impl IntoIterator<Item = &'a Log>) -> Bloom {
+    logs.into_iter().collect()
+}
+
+/// Returns the hash of the RLP-encoded list of the logs.
+pub fn logs_rlp_hash(logs: &[Log]) -> B256 {
+    let mut out = Vec::with_capacity(alloy_rlp::list_length(logs));
+    alloy_rlp::encode_list(logs, &mut out);
+    keccak256(&out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::{address, alloy_primitives::BloomInput, b256, bytes, LogData};
+
+    #[test]
+    fn bloom_and_encoding() {
+        let log = Log {
+            address: address!("1000000000000000000000000000000000000001"),
+            data: LogData::new_unchecked(vec![B256::repeat_byte(0x11)], bytes!("abcd")),
+        };
+        let mut receipt = Receipt {
+            tx_type: 0,
+            success: true,
+            gas_used: 21_000,
+            cumulative_gas_used: 42_000,
+            logs_bloom: logs_bloom([&log]),
+            logs: vec![log],
+        };
+        assert!(receipt
+            .logs_bloom
+            .contains_input(BloomInput::Raw(receipt.logs[0].address.as_slice())));
+        assert_eq!(
+            logs_rlp_hash(&[]),
+            b256!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347")
+        );
+
+        let legacy = receipt.encoded_2718();
+        assert_eq!(legacy.len(), receipt.length());
+        assert_eq!(&legacy[..6], &[0xf9, 0x01, 0x45, 0x01, 0x82, 0xa4]);
+
+        receipt.tx_type = 2;
+        receipt.success = false;
+        let typed = receipt.encoded_2718();
+        assert_eq!(typed[0], 2);
+        assert_eq!(typed[4], 0x80);
+        assert_eq!(typed.len(), legacy.len() + 1);
+    }
+}
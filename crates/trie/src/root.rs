@@ -0,0 +1,136 @@
+//! Roots of the receipts, transactions and state tries computed from scratch.
+use crate::receipt::Receipt;
+use alloy_rlp::{RlpEncodable, RlpMaxEncodedLen};
+use database::{BundleAccount, PlainAccount};
+use hash_db::Hasher;
+use plain_hasher::PlainHasher;
+use revm::{
+    primitives::{b256, keccak256, Address, B256, U256},
+    state::AccountInfo,
+};
+use triehash::{ordered_trie_root, sec_trie_root};
+
+/// Root of the empty trie, `keccak256(rlp(""))`.
+pub const EMPTY_ROOT_HASH: B256 =
+    b256!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+
+/// Account as stored in the state trie.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, RlpEncodable, RlpMaxEncodedLen)]
+pub struct TrieAccount {
+    /// Nonce of the account.
+    pub nonce: u64,
+    /// Balance of the account.
+    pub balance: U256,
+    /// Root of the storage trie of the account.
+    pub storage_root: B256,
+    /// Hash of the code of the account.
+    pub code_hash: B256,
+}
+
+impl TrieAccount {
+    /// Creates the trie account from the account info and its storage root.
+    pub fn new(info: &AccountInfo, storage_root: B256) -> Self {
+        Self {
+            nonce: info.nonce,
+            balance: info.balance,
+            storage_root,
+            code_hash: info.code_hash,
+        }
+    }
+}
+
+/// Returns the root of the trie keyed by the RLP-encoded index of the items.
+pub fn ordered_root<I>(items: I) -> B256
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    ordered_trie_root::<KeccakHasher, _>(items)
+}
+
+/// Returns the receipts root of the block.
+pub fn receipts_root(receipts: &[Receipt]) -> B256 {
+    ordered_root(receipts.iter().map(Receipt::encoded_2718))
+}
+
+/// Returns the transactions root of the block from the EIP-2718 encoded transactions.
+pub fn transactions_root<I>(transactions: I) -> B256
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    ordered_root(transactions)
+}
+
+/// Returns the root of the storage trie, zero slots are left out.
+pub fn storage_root<'a>(storage: impl IntoIterator<Item = (&'a U256, &'a U256)>) -> B256 {
+    sec_trie_root::<KeccakHasher, _, _, _>(
+        storage
+            .into_iter()
+            .filter(|(_, value)| !value.is_zero())
+            .map(|(slot, value)| {
+                (
+                    slot.to_be_bytes::<32>(),
+                    alloy_rlp::encode_fixed_size(value),
+                )
+            }),
+    )
+}
+
+/// Returns the state root of the accounts.
+pub fn state_root<'a>(accounts: impl IntoIterator<Item = (Address, &'a PlainAccount)>) -> B256 {
+    sec_trie_root::<KeccakHasher, _, _, _>(accounts.into_iter().map(|(address, account)| {
+        let account = TrieAccount::new(&account.info, storage_root(&account.storage));
+        (address, alloy_rlp::encode_fixed_size(&account))
+    }))
+}
+
+/// Returns the state root of the bundle.
+///
+/// The bundle has to contain the whole state, e.g. the bundle of the genesis block or of
+/// all blocks executed on top of the empty database. Destroyed accounts are left out.
+pub fn bundle_state_root<'a>(
+    accounts: impl IntoIterator<Item = (&'a Address, &'a BundleAccount)>,
+) -> B256 {
+    sec_trie_root::<KeccakHasher, _, _, _>(accounts.into_iter().filter_map(|(address, account)| {
+        let info = account.info.as_ref()?;
+        let storage = account
+            .storage
+            .iter()
+            .map(|(slot, value)| (slot, &value.present_value));
+        let account = TrieAccount::new(info, storage_root(storage));
+        Some((address, alloy_rlp::encode_fixed_size(&account)))
+    }))
+}
+
+/// Keccak-256 hasher of the trie nodes.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeccakHasher;
+
+impl Hasher for KeccakHasher {
+    type Out = B256;
+    type StdHasher = PlainHasher;
+    const LENGTH: usize = 32;
+
+    #[inline]
+    fn hash(x: &[u8]) -> Self::Out {
+        keccak256(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn empty_roots() {
+        assert_eq!(receipts_root(&[]), EMPTY_ROOT_HASH);
+        assert_eq!(transactions_root(Vec::<Vec<u8>>::new()), EMPTY_ROOT_HASH);
+        assert_eq!(
+            storage_root([(&U256::from(1), &U256::ZERO)]),
+            EMPTY_ROOT_HASH
+        );
+        assert_eq!(state_root([]), EMPTY_ROOT_HASH);
+    }
+}
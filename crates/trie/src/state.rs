@@ -0,0 +1,261 @@
+//! Incrementally updated state root.
+use crate::{root::TrieAccount, trie::Trie};
+use database::{BundleAccount, BundleState, ExecutionWitness, PlainAccount};
+use revm::{
+    primitives::{keccak256, Address, HashMap, B256, U256},
+    state::AccountInfo,
+};
+
+/// State trie keeping the account and storage tries in memory.
+///
+/// Created once from the whole state, then updated with the changes of every block. Only
+/// the storage tries of the changed accounts and the paths to the changed accounts are
+/// rehashed when computing the new root.
+#[derive(Clone, Debug, Default)]
+pub struct StateTrie {
+    accounts: Trie,
+    storages: HashMap<Address, Trie>,
+}
+
+impl StateTrie {
+    /// Creates the empty state trie.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the state trie from the whole state.
+    pub fn from_accounts<'a>(
+        accounts: impl IntoIterator<Item = (Address, &'a PlainAccount)>,
+    ) -> Self {
+        let mut trie = Self::new();
+        for (address, account) in accounts {
+            trie.update_account(address, Some(&account.info), false, &account.storage);
+        }
+        trie
+    }
+
+    /// Applies the changes of the bundle.
+    pub fn update_bundle(&mut self, bundle: &BundleState) {
+        for (address, account) in bundle.state() {
+            self.update_bundle_account(*address, account);
+        }
+    }
+
+    /// Applies the changes of the bundle account.
+    pub fn update_bundle_account(&mut self, address: Address, account: &BundleAccount) {
+        let storage = account
+            .storage
+            .iter()
+            .map(|(slot, value)| (*slot, value.present_value))
+            .collect::<HashMap<_, _>>();
+        self.update_account(
+            address,
+            account.info.as_ref(),
+            account.was_destroyed(),
+            &storage,
+        );
+    }
+
+    /// Sets the account info and updates its storage slots, zero values remove the slots.
+    ///
+    /// `None` info removes the account with its storage. If `wipe_storage` is set the old
+    /// storage is cleared before applying the slots, as for the selfdestructed accounts.
+    pub fn update_account(
+        &mut self,
+        address: Address,
+        info: Option<&AccountInfo>,
+        wipe_storage: bool,
+        storage: &HashMap<U256, U256>,
+    ) {
+        let key = keccak256(address);
+        let Some(info) = info else {
+            self.storages.remove(&address);
+            self.accounts.remove(key);
+            return;
+        };
+        if wipe_storage {
+            self.storages.remove(&address);
+        }
+        let trie = self.storages.entry(address).or_default();
+        for (slot, value) in storage {
+            let slot = keccak256(slot.to_be_bytes::<32>());
+            if value.is_zero() {
+                trie.remove(slot);
+            } else {
+                trie.insert(slot, alloy_rlp::encode(value));
+            }
+        }
+        let account = TrieAccount::new(info, trie.root());
+        self.accounts.insert(key, alloy_rlp::encode(account));
+    }
+
+    /// Returns the storage root of the account, `None` if the account does not exist.
+    pub fn storage_root(&mut self, address: Address) -> Option<B256> {
+        self.storages.get_mut(&address).map(Trie::root)
+    }
+
+    /// Adds the storage roots of the witness accounts and the storage trie nodes proving
+    /// their slots to the witness, see [`Trie::from_proof`].
+    pub fn prove_storage(&mut self, witness: &mut ExecutionWitness) {
+        for (address, info) in &witness.accounts {
+            let Some(trie) = info.as_ref().and_then(|_| self.storages.get_mut(address)) else {
+                continue;
+            };
+            let slots = witness
+                .storage
+                .get(address)
+                .into_iter()
+                .flat_map(|storage| {
+                    storage
+                        .keys()
+                        .map(|slot| keccak256(slot.to_be_bytes::<32>()))
+                });
+            let nodes = trie.proof_nodes(slots);
+            witness.storage_roots.insert(*address, trie.root());
+            witness.storage_nodes.insert(*address, nodes);
+        }
+    }
+
+    /// Returns the state root.
+    pub fn root(&mut self) -> B256 {
+        self.accounts.root()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::root::{state_root, EMPTY_ROOT_HASH};
+    use database::{states::bundle_state::BundleRetention, CacheState, State};
+    use revm::{
+        primitives::address,
+        state::{Account, EvmStorageSlot},
+        Database, DatabaseCommit,
+    };
+    use std::collections::BTreeMap;
+
+    fn account(balance: u64, storage: &[(u64, u64)]) -> PlainAccount {
+        PlainAccount {
+            info: AccountInfo::from_balance(U256::from(balance)),
+            storage: storage
+                .iter()
+                .map(|(slot, value)| (U256::from(*slot), U256::from(*value)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn incremental_root() {
+        let mut accounts = BTreeMap::new();
+        for i in 0..50u64 {
+            let storage = (0..i % 5)
+                .map(|slot| (slot, i + slot + 1))
+                .collect::<Vec<_>>();
+            accounts.insert(Address::with_last_byte(i as u8), account(i + 1, &storage));
+        }
+        let mut trie = StateTrie::from_accounts(accounts.iter().map(|(a, acc)| (*a, acc)));
+        assert_eq!(
+            trie.root(),
+            state_root(accounts.iter().map(|(a, acc)| (*a, acc)))
+        );
+        assert_eq!(
+            trie.storage_root(Address::with_last_byte(0)),
+            Some(EMPTY_ROOT_HASH)
+        );
+
+        // Changes a balance and a slot, clears a slot and creates an account.
+        let mut cache = CacheState::new(true);
+        for (address, account) in &accounts {
+            cache.insert_account_with_storage(
+                *address,
+                account.info.clone(),
+                account.storage.clone(),
+            );
+        }
+        let mut state = State::builder()
+            .with_cached_prestate(cache)
+            .with_bundle_update()
+            .build();
+        let changed = Address::with_last_byte(9);
+        let created = address!("1000000000000000000000000000000000000001");
+        let mut changes = HashMap::default();
+        for (address, balance, storage) in [
+            (changed, 100, vec![(0, 0), (1, 7)]),
+            (created, 5, vec![(3, 3)]),
+        ] {
+            let mut account = Account::from(state.basic(address).unwrap().unwrap_or_default());
+            account.info.balance = U256::from(balance);
+            for (slot, value) in storage {
+                account.storage.insert(
+                    U256::from(slot),
+                    EvmStorageSlot::new_changed(
+                        state.storage(address, U256::from(slot)).unwrap(),
+                        U256::from(value),
+                    ),
+                );
+            }
+            account.mark_touch();
+            changes.insert(address, account);
+        }
+        state.commit(changes);
+        state.merge_transitions(BundleRetention::PlainState);
+        let bundle = state.take_bundle();
+        trie.update_bundle(&bundle);
+
+        accounts.insert(changed, account(100, &[(1, 7), (2, 12), (3, 13)]));
+        accounts.insert(created, account(5, &[(3, 3)]));
+        assert_eq!(
+            trie.root(),
+            state_root(accounts.iter().map(|(a, acc)| (*a, acc)))
+        );
+
+        // Removes the account.
+        trie.update_account(changed, None, false, &HashMap::default());
+        accounts.remove(&changed);
+        assert_eq!(
+            trie.root(),
+            state_root(accounts.iter().map(|(a, acc)| (*a, acc)))
+        );
+        assert_eq!(trie.storage_root(changed), None);
+    }
+
+    #[test]
+    fn proves_witness_storage() {
+        let contract = Address::with_last_byte(1);
+        let missing = Address::with_last_byte(2);
+        let storage = (0..20).map(|slot| (slot, slot + 100)).collect::<Vec<_>>();
+        let accounts = BTreeMap::from([(contract, account(1, &storage))]);
+        let mut trie = StateTrie::from_accounts(accounts.iter().map(|(a, acc)| (*a, acc)));
+
+        let mut witness = ExecutionWitness::default();
+        witness
+            .accounts
+            .insert(contract, Some(AccountInfo::from_balance(U256::from(1))));
+        witness.accounts.insert(missing, None);
+        witness.storage.insert(
+            contract,
+            BTreeMap::from([
+                (U256::from(3), U256::from(103)),
+                (U256::from(50), U256::ZERO),
+            ]),
+        );
+        trie.prove_storage(&mut witness);
+
+        let root = trie.storage_root(contract).unwrap();
+        assert_eq!(witness.storage_roots, BTreeMap::from([(contract, root)]));
+        assert!(!witness.storage_nodes.contains_key(&missing));
+
+        // Witness slots can be updated in the trie created from the nodes.
+        let mut partial = Trie::from_proof(root, &witness.storage_nodes[&contract]).unwrap();
+        assert_eq!(partial.root(), root);
+        partial.remove(keccak256(U256::from(3).to_be_bytes::<32>()));
+        partial.insert(
+            keccak256(U256::from(50).to_be_bytes::<32>()),
+            alloy_rlp::encode(U256::from(1)),
+        );
+        let mut expected = account(1, &storage).storage;
+        expected.remove(&U256::from(3));
+        expected.insert(U256::from(50), U256::from(1));
+        assert_eq!(partial.root(), crate::storage_root(&expected));
+    }
+}
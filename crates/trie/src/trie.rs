@@ -0,0 +1,785 @@
+//! In-memory Merkle Patricia Trie with cached node hashes.
+use crate::root::EMPTY_ROOT_HASH;
+use alloy_rlp::{Encodable, Header, EMPTY_STRING_CODE};
+use core::{fmt, mem};
+use revm::primitives::{keccak256, Bytes, B256};
+use std::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+/// Merkle Patricia Trie with the 32-byte keys.
+///
+/// Nodes remember their encoding until a key below them changes, so computing the root
+/// after updating a few keys only hashes the nodes on the paths to the updated keys.
+///
+/// A trie created with [`Trie::from_proof`] only knows the nodes of the proof, the other
+/// subtries are known by their hash.
+#[derive(Clone, Debug, Default)]
+pub struct Trie {
+    root: Node,
+}
+
+impl Trie {
+    /// Creates the empty trie.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the trie has no keys.
+    pub fn is_empty(&self) -> bool {
+        matches!(self.root, Node::Empty)
+    }
+
+    /// Creates the trie with the root from the encoded nodes of [`Trie::proof_nodes`].
+    ///
+    /// Nodes are found by their hash, nodes that do not belong to the trie are ignored.
+    pub fn from_proof<'a>(
+        root: B256,
+        nodes: impl IntoIterator<Item = &'a Bytes>,
+    ) -> Result<Self, ProofError> {
+        if root == EMPTY_ROOT_HASH {
+            return Ok(Self::new());
+        }
+        let nodes: BTreeMap<B256, &[u8]> = nodes
+            .into_iter()
+            .map(|node| (keccak256(node), node.as_ref()))
+            .collect();
+        Ok(Self {
+            root: Node::decode_reference(Reference::Hash(root), &nodes)?,
+        })
+    }
+
+    /// Inserts the value at the key, empty values remove the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the trie does not know the nodes on the path of the key, see
+    /// [`Trie::try_insert`].
+    pub fn insert(&mut self, key: B256, value: Vec<u8>) {
+        self.try_insert(key, value)
+            .expect("trie knows the nodes on the path of the key");
+    }
+
+    /// Removes the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the trie does not know the nodes on the path of the key, see
+    /// [`Trie::try_remove`].
+    pub fn remove(&mut self, key: B256) {
+        self.try_remove(key)
+            .expect("trie knows the nodes on the path of the key");
+    }
+
+    /// Inserts the value at the key, empty values remove the key.
+    ///
+    /// Fails with [`ProofError::MissingNode`] if the trie does not know the nodes on the path
+    /// of the key, the trie may be partially updated then.
+    pub fn try_insert(&mut self, key: B256, value: Vec<u8>) -> Result<(), ProofError> {
+        if value.is_empty() {
+            return self.try_remove(key);
+        }
+        self.root.insert(&nibbles(&key), value)
+    }
+
+    /// Removes the key.
+    ///
+    /// Fails with [`ProofError::MissingNode`] if the trie does not know the nodes on the path
+    /// of the key or the node that replaces a branch left with a single child, the trie may
+    /// be partially updated then.
+    pub fn try_remove(&mut self, key: B256) -> Result<(), ProofError> {
+        self.root.remove(&nibbles(&key))
+    }
+
+    /// Returns the root hash, the hashes of unchanged nodes are reused.
+    pub fn root(&mut self) -> B256 {
+        match &mut self.root {
+            Node::Empty => EMPTY_ROOT_HASH,
+            Node::Hash(hash) => *hash,
+            root => {
+                let mut out = Vec::new();
+                root.encode(&mut out);
+                keccak256(out)
+            }
+        }
+    }
+
+    /// Returns the proof of the key, the encoded nodes on the path from the root to the key.
+    ///
+    /// Nodes embedded into their parents are not part of the proof. The proof of a missing
+    /// key ends at the node where its path leaves the trie, see [`verify_proof`].
+    pub fn proof(&mut self, key: B256) -> Vec<Bytes> {
+        let mut proof = Vec::new();
+        if !self.is_empty() {
+            self.root.proof(&nibbles(&key), true, &mut proof);
+        }
+        proof
+    }
+
+    /// Returns the encoded nodes needed to update the keys in the trie created from them with
+    /// [`Trie::from_proof`].
+    ///
+    /// Those are the nodes of the proofs of the keys and the children of the branches on their
+    /// paths that can replace the branch if the keys are removed.
+    pub fn proof_nodes(&mut self, keys: impl IntoIterator<Item = B256>) -> Vec<Bytes> {
+        let paths: Vec<Vec<u8>> = keys.into_iter().map(|key| nibbles(&key)).collect();
+        let paths: Vec<&[u8]> = paths.iter().map(Vec::as_slice).collect();
+        let mut nodes = BTreeSet::new();
+        if !self.is_empty() && !paths.is_empty() {
+            self.root.proof_nodes(&paths, true, &mut nodes);
+        }
+        nodes.into_iter().collect()
+    }
+}
+
+/// Error of [`verify_proof`] and of the tries created from proofs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofError {
+    /// Node is not valid RLP of a trie node.
+    InvalidNode,
+    /// Hash of the node does not match its reference.
+    HashMismatch,
+    /// Proof ends before the path of the key does.
+    MissingNode,
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNode => write!(f, "invalid trie node in proof"),
+            Self::HashMismatch => write!(f, "trie node does not match its hash"),
+            Self::MissingNode => write!(f, "trie node missing in proof"),
+        }
+    }
+}
+
+impl core::error::Error for ProofError {}
+
+/// Verifies the [`Trie::proof`] of the key against the root.
+///
+/// Returns the value of the key or `None` if the proof shows that the key is not in the trie.
+pub fn verify_proof(root: B256, key: B256, proof: &[Bytes]) -> Result<Option<Vec<u8>>, ProofError> {
+    if root == EMPTY_ROOT_HASH && proof.is_empty() {
+        return Ok(None);
+    }
+    let path = nibbles(&key);
+    let mut path = path.as_slice();
+    let mut proof = proof.iter();
+    let mut reference = Reference::Hash(root);
+    loop {
+        let node = match reference {
+            Reference::Empty => return Ok(None),
+            Reference::Hash(hash) => {
+                let node = proof.next().ok_or(ProofError::MissingNode)?;
+                if keccak256(node) != hash {
+                    return Err(ProofError::HashMismatch);
+                }
+                node.as_ref()
+            }
+            Reference::Embedded(node) => node,
+        };
+        let items = list_items(node).ok_or(ProofError::InvalidNode)?;
+        match items.as_slice() {
+            [branch @ .., _] if branch.len() == 16 => {
+                let (&nibble, rest) = path.split_first().ok_or(ProofError::InvalidNode)?;
+                path = rest;
+                reference = Reference::decode(branch[nibble as usize])?;
+            }
+            [key, child] => {
+                let (key, leaf) = decode_hex_prefix(decode_string(key)?)?;
+                if leaf {
+                    if key != path {
+                        return Ok(None);
+                    }
+                    return decode_string(child).map(|value| Some(value.to_vec()));
+                }
+                let Some(rest) = path.strip_prefix(key.as_slice()) else {
+                    return Ok(None);
+                };
+                path = rest;
+                reference = Reference::decode(child)?;
+            }
+            _ => return Err(ProofError::InvalidNode),
+        }
+    }
+}
+
+/// Reference to a child node in an encoded node.
+enum Reference<'a> {
+    Empty,
+    Hash(B256),
+    /// Encoding of a node shorter than 32 bytes.
+    Embedded(&'a [u8]),
+}
+
+impl<'a> Reference<'a> {
+    fn decode(item: &'a [u8]) -> Result<Self, ProofError> {
+        match item {
+            [EMPTY_STRING_CODE] => Ok(Self::Empty),
+            [first, ..] if *first >= alloy_rlp::EMPTY_LIST_CODE => Ok(Self::Embedded(item)),
+            _ => {
+                let hash = decode_string(item)?;
+                B256::try_from(hash)
+                    .map(Self::Hash)
+                    .map_err(|_| ProofError::InvalidNode)
+            }
+        }
+    }
+}
+
+/// Splits the RLP list into the encodings of its items.
+fn list_items(mut node: &[u8]) -> Option<Vec<&[u8]>> {
+    let header = Header::decode(&mut node).ok()?;
+    if !header.list || header.payload_length != node.len() {
+        return None;
+    }
+    let mut items = Vec::new();
+    while !node.is_empty() {
+        let item = node;
+        let header = Header::decode(&mut node).ok()?;
+        let length = item.len() - node.len() + header.payload_length;
+        items.push(item.get(..length)?);
+        node = &item[length..];
+    }
+    Some(items)
+}
+
+/// Returns the payload of the RLP string.
+fn decode_string(mut item: &[u8]) -> Result<&[u8], ProofError> {
+    Header::decode_bytes(&mut item, false).map_err(|_| ProofError::InvalidNode)
+}
+
+/// Decodes the [`hex_prefix`] encoding into the nibbles and the leaf flag.
+fn decode_hex_prefix(encoded: &[u8]) -> Result<(Vec<u8>, bool), ProofError> {
+    let (&first, rest) = encoded.split_first().ok_or(ProofError::InvalidNode)?;
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    if first & 0x10 != 0 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(rest.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]));
+    Ok((nibbles, first & 0x20 != 0))
+}
+
+/// Node of the trie.
+///
+/// Keys have the same length, so the branches never hold values and the leaves are never
+/// on the path to other leaves.
+#[derive(Clone, Debug, Default)]
+enum Node {
+    #[default]
+    Empty,
+    Leaf {
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Extension {
+        key: Vec<u8>,
+        child: Box<Node>,
+        cache: Option<Vec<u8>>,
+    },
+    Branch {
+        children: Box<[Node; 16]>,
+        cache: Option<Vec<u8>>,
+    },
+    /// Node known only by the hash of its encoding, which is at least 32 bytes long.
+    Hash(B256),
+}
+
+impl Node {
+    /// Decodes the referenced node, nodes missing in `nodes` are kept as [`Node::Hash`].
+    fn decode_reference(
+        reference: Reference<'_>,
+        nodes: &BTreeMap<B256, &[u8]>,
+    ) -> Result<Self, ProofError> {
+        let encoded = match reference {
+            Reference::Empty => return Ok(Node::Empty),
+            Reference::Hash(hash) => match nodes.get(&hash) {
+                Some(encoded) => *encoded,
+                None => return Ok(Node::Hash(hash)),
+            },
+            Reference::Embedded(encoded) => encoded,
+        };
+        let items = list_items(encoded).ok_or(ProofError::InvalidNode)?;
+        match items.as_slice() {
+            [branch @ .., _] if branch.len() == 16 => {
+                let mut children = Box::<[Node; 16]>::default();
+                for (child, item) in children.iter_mut().zip(branch) {
+                    *child = Self::decode_reference(Reference::decode(item)?, nodes)?;
+                }
+                Ok(Node::Branch {
+                    children,
+                    cache: Some(encoded.to_vec()),
+                })
+            }
+            [key, child] => {
+                let (key, leaf) = decode_hex_prefix(decode_string(key)?)?;
+                if leaf {
+                    return Ok(Node::Leaf {
+                        key,
+                        value: decode_string(child)?.to_vec(),
+                    });
+                }
+                Ok(Node::Extension {
+                    key,
+                    child: Box::new(Self::decode_reference(Reference::decode(child)?, nodes)?),
+                    cache: Some(encoded.to_vec()),
+                })
+            }
+            _ => Err(ProofError::InvalidNode),
+        }
+    }
+
+    fn insert(&mut self, path: &[u8], value: Vec<u8>) -> Result<(), ProofError> {
+        match self {
+            Node::Empty => {
+                *self = Node::Leaf {
+                    key: path.to_vec(),
+                    value,
+                }
+            }
+            Node::Leaf { key, value: old } => {
+                if key == path {
+                    *old = value;
+                    return Ok(());
+                }
+                let common = common_prefix(key, path);
+                let mut children = Box::<[Node; 16]>::default();
+                children[key[common] as usize] = Node::Leaf {
+                    key: key[common + 1..].to_vec(),
+                    value: mem::take(old),
+                };
+                children[path[common] as usize] = Node::Leaf {
+                    key: path[common + 1..].to_vec(),
+                    value,
+                };
+                *self = Node::extension(path[..common].to_vec(), Node::branch(children));
+            }
+            Node::Extension { key, child, cache } => {
+                let common = common_prefix(key, path);
+                if common == key.len() {
+                    *cache = None;
+                    return child.insert(&path[common..], value);
+                }
+                let mut children = Box::<[Node; 16]>::default();
+                children[key[common] as usize] =
+                    Node::extension(key[common + 1..].to_vec(), mem::take(child));
+                children[path[common] as usize] = Node::Leaf {
+                    key: path[common + 1..].to_vec(),
+                    value,
+                };
+                *self = Node::extension(path[..common].to_vec(), Node::branch(children));
+            }
+            Node::Branch { children, cache } => {
+                *cache = None;
+                return children[path[0] as usize].insert(&path[1..], value);
+            }
+            Node::Hash(_) => return Err(ProofError::MissingNode),
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, path: &[u8]) -> Result<(), ProofError> {
+        match self {
+            Node::Empty => {}
+            Node::Leaf { key, .. } => {
+                if key == path {
+                    *self = Node::Empty;
+                }
+            }
+            Node::Extension { key, child, cache } => {
+                let Some(rest) = path.strip_prefix(key.as_slice()) else {
+                    return Ok(());
+                };
+                *cache = None;
+                child.remove(rest)?;
+                let key = mem::take(key);
+                let child = mem::take(child);
+                *self = Node::extension(key, *child);
+            }
+            Node::Branch { children, cache } => {
+                *cache = None;
+                children[path[0] as usize].remove(&path[1..])?;
+                let mut remaining = children
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, child)| !matches!(child, Node::Empty));
+                let (Some((index, _)), None) = (remaining.next(), remaining.next()) else {
+                    return Ok(());
+                };
+                // The child is merged with the key of the branch, unless it is a branch.
+                if matches!(children[index], Node::Hash(_)) {
+                    return Err(ProofError::MissingNode);
+                }
+                let child = mem::take(&mut children[index]);
+                *self = Node::extension(vec![index as u8], child);
+            }
+            Node::Hash(_) => return Err(ProofError::MissingNode),
+        }
+        Ok(())
+    }
+
+    /// Creates the node with the key prefixed to the child, merging it into the child if
+    /// it is a leaf or an extension.
+    fn extension(mut prefix: Vec<u8>, child: Node) -> Node {
+        if prefix.is_empty() {
+            return child;
+        }
+        match child {
+            Node::Leaf { key, value } => {
+                prefix.extend_from_slice(&key);
+                Node::Leaf { key: prefix, value }
+            }
+            Node::Extension { key, child, .. } => {
+                prefix.extend_from_slice(&key);
+                Node::Extension {
+                    key: prefix,
+                    child,
+                    cache: None,
+                }
+            }
+            child => Node::Extension {
+                key: prefix,
+                child: Box::new(child),
+                cache: None,
+            },
+        }
+    }
+
+    fn branch(children: Box<[Node; 16]>) -> Node {
+        Node::Branch {
+            children,
+            cache: None,
+        }
+    }
+
+    /// Appends the RLP encoding of the node.
+    fn encode(&mut self, out: &mut Vec<u8>) {
+        match self {
+            Node::Empty => out.push(EMPTY_STRING_CODE),
+            Node::Leaf { key, value } => {
+                let key = hex_prefix(key, true);
+                let payload_length = key.as_slice().length() + value.as_slice().length();
+                Header {
+                    list: true,
+                    payload_length,
+                }
+                .encode(out);
+                key.as_slice().encode(out);
+                value.as_slice().encode(out);
+            }
+            Node::Extension { key, child, cache } => {
+                let encoded = cache.get_or_insert_with(|| {
+                    let key = hex_prefix(key, false);
+                    let mut payload = Vec::new();
+                    key.as_slice().encode(&mut payload);
+                    child.encode_reference(&mut payload);
+                    list(payload)
+                });
+                out.extend_from_slice(encoded);
+            }
+            Node::Branch { children, cache } => {
+                let encoded = cache.get_or_insert_with(|| {
+                    let mut payload = Vec::new();
+                    for child in children.iter_mut() {
+                        child.encode_reference(&mut payload);
+                    }
+                    payload.push(EMPTY_STRING_CODE);
+                    list(payload)
+                });
+                out.extend_from_slice(encoded);
+            }
+            Node::Hash(_) => unreachable!("hashed nodes are only referenced"),
+        }
+    }
+
+    /// Appends the encodings of the nodes on the path, the node itself is skipped if it is
+    /// embedded into its parent.
+    fn proof(&mut self, path: &[u8], is_root: bool, proof: &mut Vec<Bytes>) {
+        if let Node::Hash(_) = self {
+            return;
+        }
+        let mut encoded = Vec::new();
+        self.encode(&mut encoded);
+        if is_root || encoded.len() >= 32 {
+            proof.push(encoded.into());
+        }
+        match self {
+            Node::Extension { key, child, .. } => {
+                if let Some(rest) = path.strip_prefix(key.as_slice()) {
+                    child.proof(rest, false, proof);
+                }
+            }
+            Node::Branch { children, .. } => {
+                children[path[0] as usize].proof(&path[1..], false, proof)
+            }
+            Node::Empty | Node::Leaf { .. } | Node::Hash(_) => {}
+        }
+    }
+
+    /// Inserts the encodings of the nodes on the paths and of the children of the branches
+    /// that can replace them, see [`Trie::proof_nodes`].
+    fn proof_nodes(&mut self, paths: &[&[u8]], is_root: bool, nodes: &mut BTreeSet<Bytes>) {
+        if let Node::Hash(_) = self {
+            return;
+        }
+        let mut encoded = Vec::new();
+        self.encode(&mut encoded);
+        if is_root || encoded.len() >= 32 {
+            nodes.insert(encoded.into());
+        }
+        match self {
+            Node::Extension { key, child, .. } => {
+                let rest: Vec<&[u8]> = paths
+                    .iter()
+                    .filter_map(|path| path.strip_prefix(key.as_slice()))
+                    .collect();
+                if !rest.is_empty() {
+                    child.proof_nodes(&rest, false, nodes);
+                }
+            }
+            Node::Branch { children, .. } => {
+                let mut off_path = Vec::new();
+                for (nibble, child) in children.iter_mut().enumerate() {
+                    let rest: Vec<&[u8]> = paths
+                        .iter()
+                        .filter(|path| path[0] as usize == nibble)
+                        .map(|path| &path[1..])
+                        .collect();
+                    if !rest.is_empty() {
+                        child.proof_nodes(&rest, false, nodes);
+                    } else if !matches!(child, Node::Empty) {
+                        off_path.push(child);
+                    }
+                }
+                // Branch is replaced by its last child if the keys on the paths are removed.
+                if let [child] = off_path.as_mut_slice() {
+                    child.proof_nodes(&[], false, nodes);
+                }
+            }
+            Node::Empty | Node::Leaf { .. } | Node::Hash(_) => {}
+        }
+    }
+
+    /// Appends the reference to the node, the node itself if its encoding is shorter than
+    /// 32 bytes and the hash of the encoding otherwise.
+    fn encode_reference(&mut self, out: &mut Vec<u8>) {
+        if let Node::Hash(hash) = self {
+            hash.encode(out);
+            return;
+        }
+        let mut encoded = Vec::new();
+        self.encode(&mut encoded);
+        if encoded.len() < 32 {
+            out.extend_from_slice(&encoded);
+        } else {
+            keccak256(encoded).encode(out);
+        }
+    }
+}
+
+/// Returns the RLP list with the encoded payload.
+fn list(payload: Vec<u8>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(payload.len() + 3);
+    Header {
+        list: true,
+        payload_length: payload.len(),
+    }
+    .encode(&mut out);
+    out.extend_from_slice(&payload);
+    out
+}
+
+/// Splits the key into nibbles.
+fn nibbles(key: &B256) -> Vec<u8> {
+    key.iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// Hex-prefix encoding of the nibbles, see Appendix C of the Yellow Paper.
+fn hex_prefix(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 0x20 } else { 0x00 };
+    let mut out = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        out.push(flag | 0x10 | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        out.push(flag);
+        nibbles
+    };
+    out.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::root::KeccakHasher;
+    use triehash::trie_root;
+
+    fn key(i: u64) -> B256 {
+        keccak256(i.to_be_bytes())
+    }
+
+    fn value(i: u64) -> Vec<u8> {
+        // Short values make the leaves embedded into their parents.
+        vec![i as u8; 1 + (i % 40) as usize]
+    }
+
+    #[test]
+    fn matches_triehash() {
+        let mut trie = Trie::new();
+        assert_eq!(trie.root(), EMPTY_ROOT_HASH);
+
+        let mut expected = std::collections::BTreeMap::new();
+        for i in 0..300 {
+            trie.insert(key(i), value(i));
+            expected.insert(key(i), value(i));
+            if i % 37 == 0 {
+                assert_eq!(
+                    trie.root(),
+                    trie_root::<KeccakHasher, _, _, _>(expected.clone())
+                );
+            }
+        }
+        assert_eq!(
+            trie.root(),
+            trie_root::<KeccakHasher, _, _, _>(expected.clone())
+        );
+
+        for i in (0..300).step_by(3) {
+            trie.remove(key(i));
+            expected.remove(&key(i));
+            trie.insert(key(i + 1), value(i + 7));
+            expected.insert(key(i + 1), value(i + 7));
+            if i % 31 == 0 {
+                assert_eq!(
+                    trie.root(),
+                    trie_root::<KeccakHasher, _, _, _>(expected.clone())
+                );
+            }
+        }
+        assert_eq!(
+            trie.root(),
+            trie_root::<KeccakHasher, _, _, _>(expected.clone())
+        );
+
+        for i in 0..301 {
+            trie.insert(key(i), Vec::new());
+        }
+        assert!(trie.is_empty());
+        assert_eq!(trie.root(), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn proofs() {
+        let mut trie = Trie::new();
+        assert_eq!(
+            verify_proof(trie.root(), key(0), &trie.proof(key(0))),
+            Ok(None)
+        );
+
+        for i in 0..200 {
+            trie.insert(key(i), value(i));
+        }
+        let root = trie.root();
+        for i in 0..250 {
+            let proof = trie.proof(key(i));
+            let expected = (i < 200).then(|| value(i));
+            assert_eq!(verify_proof(root, key(i), &proof), Ok(expected), "key {i}");
+        }
+
+        // Proof of another key or against another root does not verify.
+        let proof = trie.proof(key(1));
+        assert_ne!(verify_proof(root, key(2), &proof), Ok(Some(value(1))));
+        assert_eq!(
+            verify_proof(keccak256(root), key(1), &proof),
+            Err(ProofError::HashMismatch)
+        );
+        assert_eq!(
+            verify_proof(root, key(1), &proof[..1]),
+            Err(ProofError::MissingNode)
+        );
+    }
+
+    #[test]
+    fn partial_trie_from_proof() {
+        let mut trie = Trie::new();
+        for i in 0..200 {
+            trie.insert(key(i), value(i));
+        }
+        let root = trie.root();
+
+        // Existing keys that are updated or removed and new keys.
+        let keys = [3, 17, 42, 99, 150, 210, 250];
+        let nodes = trie.proof_nodes(keys.map(key));
+        let mut partial = Trie::from_proof(root, &nodes).unwrap();
+        assert_eq!(partial.root(), root);
+
+        for i in keys {
+            if i % 2 == 0 {
+                trie.insert(key(i), value(i + 1));
+                partial.try_insert(key(i), value(i + 1)).unwrap();
+            } else {
+                trie.remove(key(i));
+                partial.try_remove(key(i)).unwrap();
+            }
+            assert_eq!(partial.root(), trie.root(), "key {i}");
+        }
+        for i in keys {
+            assert_eq!(partial.proof(key(i)), trie.proof(key(i)), "key {i}");
+        }
+
+        // Keys outside of the proof can not be updated.
+        assert_eq!(
+            partial.try_insert(key(5), value(5)),
+            Err(ProofError::MissingNode)
+        );
+        assert_eq!(partial.try_remove(key(5)), Err(ProofError::MissingNode));
+
+        assert!(Trie::from_proof(EMPTY_ROOT_HASH, &[]).unwrap().is_empty());
+        assert_eq!(
+            Trie::from_proof(root, &[Bytes::from_static(&[0x80; 40])])
+                .unwrap()
+                .try_insert(key(1), value(1)),
+            Err(ProofError::MissingNode)
+        );
+    }
+
+    #[test]
+    fn removing_all_keys_of_a_partial_trie() {
+        let mut trie = Trie::new();
+        // Long values make the remaining leaf hashed into the branch.
+        for i in 0..3 {
+            trie.insert(key(i), value(39 + 40 * i));
+        }
+        let root = trie.root();
+
+        let nodes = trie.proof_nodes([key(0), key(1)]);
+        let mut partial = Trie::from_proof(root, &nodes).unwrap();
+        partial.try_remove(key(0)).unwrap();
+        partial.try_remove(key(1)).unwrap();
+        trie.remove(key(0));
+        trie.remove(key(1));
+        assert_eq!(partial.root(), trie.root());
+        assert_eq!(
+            partial.root(),
+            trie_root::<KeccakHasher, _, _, _>([(key(2), value(119))])
+        );
+    }
+
+    #[test]
+    fn hex_prefix_encoding() {
+        assert_eq!(hex_prefix(&[1, 2, 3], false), [0x11, 0x23]);
+        assert_eq!(hex_prefix(&[1, 2], true), [0x20, 0x12]);
+        assert_eq!(hex_prefix(&[], true), [0x20]);
+        assert_eq!(decode_hex_prefix(&[0x11, 0x23]), Ok((vec![1, 2, 3], false)));
+        assert_eq!(decode_hex_prefix(&[0x20, 0x12]), Ok((vec![1, 2], true)));
+    }
+}